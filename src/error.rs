@@ -3,6 +3,7 @@ use thiserror::Error;
 use solana_program::program_error::ProgramError;
 
 #[derive(Error, Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum GauntletError {
     /// Invalid instruction data passed in.
     #[error("Failed to unpack instruction data")]
@@ -68,6 +69,78 @@ pub enum GauntletError {
     InvalidWithdrawAmount,
     #[error("Wrong program id")]
     InvalidProgramId,
+    #[error("Strategy is not accepting new deposits")]
+    DepositsClosed,
+    #[error("Token account is frozen")]
+    FrozenTokenAccount,
+    #[error("Math overflow")]
+    MathOverflow,
+    #[error("Deposit amount is below the vault minimum")]
+    DepositTooSmall,
+    #[error("Swap instruction does not match the strategy's routing mode")]
+    WrongRoutingMode,
+    #[error("Signer is not an allowed keeper")]
+    NotKeeper,
+    #[error("Gas reimbursement share is above the allowed maximum")]
+    GasReimbursementTooLarge,
+    #[error("Harvest called before the vault's minimum harvest interval elapsed")]
+    HarvestTooSoon,
+    #[error("Admin nonce does not match the gauntlet's current nonce")]
+    StaleNonce,
+    #[error("Swap route spent more than the allotted input amount")]
+    RouteOverspent,
+    #[error("Gauntlet is paused")]
+    VaultPaused,
+    #[error("Swap output deviates from the oracle price beyond the allowed slippage")]
+    PriceDeviation,
+    #[error("Token balance change does not match the tracked amount")]
+    AccountingDesync,
+    #[error("Vault is not configured for compounding")]
+    CompoundingDisabled,
+    #[error("A harvest is still being distributed, finish it before changing deposits")]
+    HarvestInProgress,
+    #[error("Strategy still has rewards or usdc waiting to be swapped")]
+    SwapInFlight,
+    #[error("Deposit would exceed the strategy deposit cap")]
+    DepositCapExceeded,
+    #[error("Raydium state account size does not match the staking user info layout")]
+    RaydiumStateAccountSizeMismatch,
+    #[error("Account still holds deposits")]
+    AccountNotEmpty,
+    #[error("Strategy is retired")]
+    StrategyRetired,
+    #[error("Pending reward exceeds the vault cap, swaps are not keeping up")]
+    PendingRewardOverflow,
+    #[error("Created account is not rent exempt")]
+    NotRentExempt,
+    #[error("Withdrawals from this vault are disabled")]
+    WithdrawalsClosed,
+    #[error("Withdraw is not allowed until the deposit cooldown has passed")]
+    WithdrawCooldown,
+    #[error("Token account is already used by another vault")]
+    TokenAccountAlreadyUsed,
+    #[error("Instruction targets an unsupported ABI version")]
+    UnsupportedAbiVersion,
+    #[error("Keeper list is full")]
+    KeeperListFull,
+    #[error("Keeper is already in the keeper list")]
+    KeeperAlreadyAdded,
+    #[error("Keeper is not in the keeper list")]
+    KeeperNotFound,
+    #[error("Strategy is paused")]
+    StrategyPaused,
+    #[error("Gauntlet already tracks the maximum number of mints")]
+    TrackedMintListFull,
+    #[error("Price attestation is missing, malformed or expired")]
+    InvalidPriceAttestation,
+    #[error("Strategy slot is outside the vault strategy account capacity")]
+    VaultStrategyCapacityExceeded,
+    #[error("Gauntlet already has the maximum number of vaults")]
+    MaxVaultsReached,
+    #[error("Slippage is above 100%")]
+    InvalidSlippage,
+    #[error("User account is already in use by another instruction")]
+    UserBusy,
 }
 
 impl From<GauntletError> for ProgramError {
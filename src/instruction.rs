@@ -1,18 +1,36 @@
-use crate::{error::GauntletError, state::Fees};
-use solana_program::program_error::ProgramError;
+use crate::{
+    error::GauntletError,
+    state::{validate_keeper_fee, Fees, VaultConfig, BPS_DENOMINATOR},
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
 use std::convert::TryInto;
 
+#[non_exhaustive]
 pub enum DepositType {
     RAYDIUM,
     RAYDIUM_V4,
 }
+#[non_exhaustive]
 pub enum WithdrawType {
     RAYDIUM,
     RAYDIUM_V4,
 }
+#[non_exhaustive]
 pub enum SwapType {
     RAYDIUM,
+    ORCA,
+    /// client supplied jupiter route, only for SwapFarmRewardToUsdc
+    JUPITER,
 }
+/// CollectFees 가 fee state account 를 어떤 state 로 읽을지
+#[non_exhaustive]
+pub enum FeeSource {
+    /// withdraw / deposit fee account
+    VAULT,
+    /// performance / strategist fee account
+    STRATEGY,
+}
+#[derive(PartialEq, Clone, Copy)]
 pub enum StrategyType {
     RAY,
     RAYDIUM_LP,
@@ -32,20 +50,31 @@ pub enum GauntletInstruction {
     /// 2. `[writable]` The account to store vault state that not initiialized
     /// 3. `[writable]` The account to store vault strategy state that not initiialized
     /// 4. `[]` deposit token account
-    /// 5. `[]` withdraw fee token account
+    /// 5. `[writable]` withdraw fee token account, owned by admin and handed over to the pda
     /// 6. `[]` token program account
     /// 7. `[]` farm reward token account
-    /// 8. `[]` farm second reward token account // 없으면 skip
+    /// 8. `[writable]` deposit fee token account, owned by admin and handed over to the pda
+    /// 9. `[]` farm second reward token account // 없으면 skip
     InitVault {
+        /// min_fee_enabled is an optional byte after admin_nonce, true (the v1 behaviour) when missing
         fees: Fees,
+        config: VaultConfig,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
     },
 
     /// 0. `[signer]` The account of admin
     /// 1. `[writeable]` the account to store gauntlet state
     /// 2. `[writeable]` the account to store strategy state that not initiialized
     /// 3. `[]` strategy token account
-    /// 4. `[]` performance fee token account
-    InitStrategy {},
+    /// 4. `[writable]` performance fee token account, owned by admin and handed over to the pda
+    InitStrategy {
+        strategy_type: StrategyType,
+        /// most deposit token(LP) all vaults together can route into the strategy, 0 means unlimited
+        deposit_cap: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
 
     /// 0. `[signer]` The account of vault admin
     /// 1. `[writable]` The account of gauntlet state
@@ -63,6 +92,8 @@ pub enum GauntletInstruction {
     UpdateVaultStrategy {
         availability: bool,
         needs_usdc_pool: bool,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
     },
 
     /// Deposit
@@ -82,9 +113,19 @@ pub enum GauntletInstruction {
     /// 13. `[writable or read]` swap_reward_to_usdc_accounts: accounts used by Radium (swap) (used to swap first reward token)
     /// 14. '[writable or read] [option]` swap_reward_b_to_usdc_accounts: accounts used by Radium (used to swap second reward token)
     /// 15. `[writable or read]` swap_usdc_to_strategy_accounts: accounts used by Radium (used to swap usdc to strategy token)
+    /// 16. `[]` deposit_token_mint: vault.deposit_token_mint, the LP moves with transfer_checked through vault.token_program
+    /// 17. `[writable] [option]` deposit_fee_account: vault deposit fee token account, passed when with_deposit_fee_account
+    /// 18. `[writable] [option]` referral_token_account: LP token account of the referrer, passed when with_referral_account
+    /// 19. `[] [option]` system_program: passed when create_user (depositor must be writable)
     Deposit {
         amount: u64,
         deposit_type: DepositType,
+        /// deposit_fee_account is passed, required when the vault charges a deposit fee
+        with_deposit_fee_account: bool,
+        /// referral_token_account is passed, receives a cut of the deposit fee
+        with_referral_account: bool,
+        /// creates depositor_user_account when it does not exist yet, system_program is passed
+        create_user: bool,
     },
     /// Harvest
     /// 0. `[]` gauntlet_account: The account to store gauntlet state
@@ -98,8 +139,14 @@ pub enum GauntletInstruction {
     /// 8. `[writable or read]` swap_reward_to_usdc_accounts: accounts used by Radium (used to swap first reward token to usdc)
     /// 9. '[writable or read] [option]` swap_reward_b_to_usdc_accounts: accounts used by Radium (used to swap second reward token to usdc)
     /// 10. `[writable or read]` swap_usdc_to_strategy_accounts: accounts used by Radium (used to swap usdc to strategy token)
+    ///
+    /// keeper_reward_token_account `[writable] [option]` and reward_token_mint `[] [option]` follow the
+    /// harvest accounts: the keeper's reward token account (owned by the vault's token program) and the
+    /// vault reward mint, required when the vault keeper fee is set
     Harvest {
         deposit_type: DepositType,
+        /// strategies distributed per call, the next call resumes from vault.harvest_cursor. 0 means all
+        max_strategies: u8,
     },
 
     /// Withdraw
@@ -122,53 +169,564 @@ pub enum GauntletInstruction {
     /// 15. `[writable or read]` swap_reward_to_usdc_accounts: accounts used by Radium (swap) (used to swap first reward token)
     /// 16. '[writable or read] [option]` swap_reward_b_to_usdc_accounts: accounts used by Radium (used to swap second reward token)
     /// 17. `[writable or read]` swap_usdc_to_strategy_accounts: accounts used by Radium (used to swap usdc to strategy token)
+    ///     followed by `[]` deposit_token_mint, `[]` strategy_token_mint and `[]` the token program of the strategy token account
+    /// 18. `[writable or read] [option]` gas_reimbursement_swap_accounts: accounts used by Radium (swap strategy token to the withdrawer's wSOL account),
+    ///     required when gas_reimbursement_bps > 0. The wSOL account is closed into the withdrawer afterwards
+    /// 19. `[writable] [option]` recipient_deposit_token_account: token(LP) account receiving the principal, required when to_recipient
+    /// 20. `[writable] [option]` recipient_reward_token_account: token(ex. BTC) account receiving the reward, required when to_recipient
+    /// 21. `[writable] [option]` strategist_fee_account: strategy.strategist_fee_account, required when the strategy splits its performance fee
     Withdraw {
         amount: u64,
         reward_amount: u64,
         withdraw_type: WithdrawType,
+        /// share of the claimed reward (in bps, capped by MAX_GAS_REIMBURSEMENT_BPS) paid out as native SOL
+        gas_reimbursement_bps: u64,
+        /// send principal and reward to the recipient accounts instead of the withdrawer's own
+        to_recipient: bool,
     },
+    /// With SwapType::JUPITER the swap accounts are jupiter program, gauntlet signer (pda),
+    /// vault reward token account, gauntlet usdc token account, then the route accounts
+    /// When max_slippage_bps > 0, three oracle accounts follow the strategy state account:
+    /// pyth price account (reward token / USD), reward token mint, usdc mint
+    /// Passing the instructions sysvar instead of the pyth account reads a keeper signed
+    /// PriceAttestation from the ed25519 instruction right before this one
+    /// Logs the realized amounts as `swap_result: in <amount> out <amount>`, solana-program 1.7.14
+    /// has no set_return_data so keepers read them from the transaction log
     SwapFarmRewardToUsdc {
         swap_type: SwapType,
+        /// allowed shortfall of the realized usdc against the oracle price, 0 skips the oracle check
+        max_slippage_bps: u64,
+        /// jupiter route instruction data, empty for the other swap types
+        route_data: Vec<u8>,
     },
-    SwapUsdcToStrategyToken {
-        swap_type: SwapType,
+    /// When the strategy token is the vault's deposit LP, the swapped LP is re-staked as principal
+    /// and the raydium deposit accounts (11, or 13 for v4) and the deposit LP mint must follow the swap accounts
+    /// Logs the realized amounts as `swap_result: in <amount> out <amount>`, like SwapFarmRewardToUsdc
+    SwapUsdcToStrategyToken { swap_type: SwapType },
+    /// When the strategy token is the vault's deposit LP, the swapped LP is re-staked as principal
+    /// and the raydium deposit accounts (11, or 13 for v4) and the deposit LP mint must follow the swap accounts
+    /// A RAY strategy whose strategy token is the reward being swapped takes, instead of the swap
+    /// accounts, `[writable]` vault reward token, `[writable]` strategy token, `[]` authority,
+    /// `[]` vault.token_program, `[]` reward mint
+    /// Logs the realized amounts as `swap_result: in <amount> out <amount>`, like SwapFarmRewardToUsdc
+    SwapFarmRewardToStrategyToken { swap_type: SwapType },
+    /// Create and initialize the user state pda, a no-op when it already exists
+    /// 0. `[signer]` The account of depositor
+    /// 1. `[]` The account of vault state
+    /// 2. `[]` The account of strategy state
+    /// 3. `[writable]` user state account (pda of vault, depositor, strategy)
+    /// 4. `[]` system program account
+    CreateUserAccount {},
+
+    /// Open or close a strategy to new deposits without changing its availability
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault strategy state
+    /// 3. `[]` The account of vault state
+    /// 4. `[]` The account of strategy state
+    SetAcceptingDeposits {
+        accepting_deposits: bool,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
     },
-    SwapFarmRewardToStrategyToken {
-        swap_type: SwapType,
+
+    /// Sweep stray tokens (airdrops, dust) out of a pda owned token account
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` source token account (token account owned by pda)
+    /// 3. `[writable]` destination token account
+    /// 4. `[]` gauntlet signer account (pda)
+    /// 5. `[]` token program account, spl-token or Token-2022 (owner of the source account)
+    ///
+    /// Fails with WrongTokenAccount if the source holds a mint tracked by the gauntlet
+    SweepTokens {
+        amount: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Allow a keeper to call harvest and the swap instructions
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[]` keeper account
+    AddKeeper {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Remove a keeper from the allow-list
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[]` keeper account
+    RemoveKeeper {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Create the raydium staking state pda of a vault, must run after InitVault
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    /// 3. `[]` The account of vault strategy state
+    /// 4. `[writable]` vault raydium state account (pda of gauntlet, vault, vault strategy)
+    /// 5. `[]` raydium staking program
+    /// 6. `[]` system program account
+    InitVaultRaydiumState {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Read-only, logs the packed GauntletConfig as `gauntlet_config: <hex>`
+    /// 0. `[]` The account of gauntlet state
+    GetConfig {},
+
+    /// Emergency switch for every vault, withdraw stays open while paused and skips the user status check
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    SetGlobalPause {
+        paused: bool,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Put a user stuck in the middle of the keeper flow back to READY (IDLE without a balance),
+    /// balances are untouched
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[]` The account of vault state
+    /// 3. `[writable]` user state account
+    ResetUserStatus {
+        /// owner of the user state account
+        user: Pubkey,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Pre-flight check before opening a vault, fails with the first misconfiguration found.
+    /// Read-only, so it does not consume the admin nonce
+    /// 0. `[signer]` The account of admin
+    /// 1. `[]` The account of gauntlet state
+    /// 2. `[]` The account of vault state
+    /// 3. `[]` The account of vault strategy state
+    /// 4. `[]` withdraw fee token account
+    /// 5. `[]` deposit fee token account
+    /// 6. `[]` every strategy state account, ordered by strategy index
+    ValidateVaultReady {},
+
+    /// Compounding vault 전용, strategy 에 쌓인 reward 의 절반을 usdc 로 바꾼 뒤
+    /// reward/usdc LP 를 만들어 다시 staking 하고 해당 strategy 예치자들의 원금으로 합산
+    /// (LP 는 Vault.compounded_per_shares 로 배분되어 strategy token reward 와 섞이지 않음)
+    /// 0. `[signer]` keeper
+    /// 1. `[]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    /// 3. `[]` The account of vault strategy state
+    /// 4. `[writable]` The account of strategy state
+    /// 5. `[writable or read]` swap_reward_to_usdc_accounts: raydium swap accounts (vault reward -> gauntlet usdc)
+    /// 6. `[writable or read]` add_liquidity_accounts: raydium amm deposit accounts (lp goes to vault deposit token account)
+    /// 7. `[writable or read]` restake_accounts: raydium staking deposit accounts
+    Compound { deposit_type: DepositType },
+
+    /// Read-only, logs the packed VaultTvl as `vault_tvl: <hex>`
+    /// 0. `[]` The account of vault state
+    /// 1. `[]` The account of vault strategy state
+    GetVaultTvl {},
+
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of strategy state
+    SetStrategyDepositCap {
+        /// most deposit token(LP) all vaults together can route into the strategy, 0 means unlimited
+        deposit_cap: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Retires an empty strategy for good, it can't be made available again
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of strategy state
+    /// 3. `[writable]` vault state accounts followed by their vault strategy state accounts,
+    ///    one pair per vault (gauntlet.vaults_len pairs, vault 0 first)
+    RetireStrategy {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Rotates the vault's fee token accounts, both must hold the deposit token(LP)
+    /// new accounts are owned by admin and handed over to the pda
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    /// 3. `[writable]` new withdraw fee token account
+    /// 4. `[writable]` new deposit fee token account
+    /// 5. `[]` the vault's token program account
+    SetVaultFeeAccounts {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Rotates the strategy's performance fee token account, it must hold the strategy token
+    /// a new account is owned by admin and handed over to the pda
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of strategy state
+    /// 3. `[writable]` new performance fee token account
+    /// 4. `[]` token program account of the strategy token
+    SetStrategyFeeAccount {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Read-only, logs how a harvest of reward_amount would be split as `harvest_preview: <hex>`,
+    /// one u64 LE per strategy index
+    /// 0. `[]` The account of vault state
+    /// 1. `[]` The account of vault strategy state
+    GetHarvestPreview { reward_amount: u64 },
+
+    /// Toggles vault deposits/withdrawals without touching harvest and the swaps
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    SetVaultFlags {
+        deposits_enabled: bool,
+        withdrawals_enabled: bool,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Sets the keeper bounty taken from harvested reward a, capped at MAX_KEEPER_FEE_BPS
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    SetVaultKeeperFee {
+        /// 0/0 disables the bounty
+        keeper_fee_numerator: u64,
+        keeper_fee_denominator: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Sets how long a user must wait after a deposit before withdrawing
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    SetVaultWithdrawCooldown {
+        /// 0 disables the cooldown
+        withdraw_cooldown_secs: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Read-only, logs the packed UserPosition as `user_position: <hex>`
+    /// 0. `[]` The account of user state
+    /// 1. `[]` The account of vault state
+    /// 2. `[]` The account of vault strategy state
+    /// 3. `[]` The account of strategy state
+    GetUserPosition {},
+
+    /// Splits the strategy's performance fee with a strategist
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of strategy state
+    /// 3. `[writable]` strategist fee token account, must hold the strategy token,
+    ///    a new account is owned by admin and handed over to the pda
+    /// 4. `[]` token program account of the strategy token
+    SetStrategistFee {
+        /// share of the performance fee sent to the strategist, at most BPS_DENOMINATOR (100%)
+        strategist_fee_split_bps: u16,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Repairs the vault's total_deposit_amount as the sum of available strategy deposit amounts
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[]` The account of vault strategy state
+    /// 3. `[writable]` The account of vault state
+    RecomputeVaultTotal {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Sets the share of LP gains restaked as principal, the rest stays claimable as reward.
+    /// Fails with SwapInFlight while harvested rewards are still waiting to be swapped
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    SetVaultCompoundRatio {
+        /// at most BPS_DENOMINATOR (100%), 0 leaves every gain claimable
+        compound_ratio_bps: u16,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Moves collected fees out of a pda owned fee token account
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[]` vault state account (withdraw / deposit fee) or strategy state account
+    ///    (performance / strategist fee), as given by fee_source
+    /// 3. `[writable]` fee token account (token account owned by pda)
+    /// 4. `[writable]` destination token account, same mint as the fee token account
+    /// 5. `[]` gauntlet signer account (pda)
+    /// 6. `[]` token program account that owns the fee token account
+    CollectFees {
+        amount: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+        fee_source: FeeSource,
+    },
+
+    /// Moves the vault strategy state into a larger account, existing slots are kept and the
+    /// new ones start unavailable. solana-program 1.7 has no realloc, so the admin creates the
+    /// new account (owned by this program, rent exempt, sized for new_capacity) and the old one
+    /// is closed to the admin
+    /// 0. `[signer, writable]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[]` The account of vault state
+    /// 3. `[writable]` The account of current vault strategy state
+    /// 4. `[writable]` The account of new vault strategy state
+    GrowVaultStrategy {
+        /// strategy slots of the new account, larger than the current capacity
+        new_capacity: u8,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Rewrites a v1 vault (VAULT_V1_LEN bytes) and its v1 vault strategy in the current layout.
+    /// solana-program 1.7 has no realloc, so the admin creates the new accounts (owned by this
+    /// program, rent exempt, Vault::LEN and VaultStrategy::packed_len bytes), the vault token
+    /// accounts are handed from the v1 authority to this gauntlet's authority and the v1 accounts
+    /// are closed to the admin. Run after MigrateGauntlet, then MigrateVaultStake and MigrateUser
+    /// 0. `[signer, writable]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of v1 vault state
+    /// 3. `[writable]` The account of new vault state
+    /// 4. `[writable]` The account of v1 vault strategy state
+    /// 5. `[writable]` The account of new vault strategy state
+    /// 6. `[]` v1 authority, seeds = [b"glt"]
+    /// 7. `[]` spl token program
+    /// 8. `[writable]` vault deposit token account
+    /// 9. `[writable]` vault farm reward token account
+    /// 10. `[writable]` vault farm second reward token account, only when the v1 vault has one
+    MigrateVault {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Rewrites a v1 gauntlet (GAUNTLET_V1_LEN bytes) into a new account the admin created
+    /// (owned by this program, rent exempt, Gauntlet::LEN bytes) and hands the usdc token account
+    /// from the v1 authority to the new gauntlet's authority. Signed by the v1 admin
+    /// 0. `[signer, writable]` The account of admin
+    /// 1. `[writable]` The account of v1 gauntlet state
+    /// 2. `[writable]` The account of new gauntlet state
+    /// 3. `[]` v1 authority, seeds = [b"glt"]
+    /// 4. `[writable]` gauntlet usdc token account
+    /// 5. `[]` spl token program
+    MigrateGauntlet {},
+
+    /// Rewrites a v1 strategy (STRATEGY_V1_LEN bytes) of the gauntlet's v1 gauntlet into a new
+    /// account (Strategy::LEN bytes) and hands its strategy token account to the gauntlet authority
+    /// 0. `[signer, writable]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of v1 strategy state
+    /// 3. `[writable]` The account of new strategy state
+    /// 4. `[]` v1 authority, seeds = [b"glt"]
+    /// 5. `[writable]` strategy token account
+    /// 6. `[]` spl token program
+    MigrateStrategy {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Moves the farm stake of a migrated vault from the v1 ledger (owned by the v1 authority) to a
+    /// new raydium state account owned by the gauntlet authority. The reward paid while unstaking
+    /// is distributed to the strategies like a harvest
+    /// 0. `[signer, writable]` The account of admin, pays the new raydium state account
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    /// 3. `[]` The account of vault strategy state
+    /// 4. `[]` v1 authority, seeds = [b"glt"]
+    /// 5. `[writable]` new raydium state account, seeds = [gauntlet, vault, vault strategy]
+    /// 6. `[]` system program
+    /// 7. ~ raydium staking accounts (11, 13 for v4) with the v1 ledger and the gauntlet authority
+    MigrateVaultStake {
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
+    },
+
+    /// Moves a v1 user (USER_V1_LEN bytes) to the user PDA of the migrated vault and strategy.
+    /// Anyone can run it, the payer funds the new PDA and the v1 rent goes back to the user
+    /// 0. `[signer, writable]` payer
+    /// 1. `[]` The account of migrated vault state
+    /// 2. `[]` The account of migrated strategy state
+    /// 3. `[writable]` The account of v1 user state
+    /// 4. `[writable]` new user state pda, seeds = [vault, user, strategy]
+    /// 5. `[writable]` user wallet
+    /// 6. `[]` system program
+    MigrateUser {},
+
+    /// Sets the most reward a strategy may have waiting to be swapped before harvest fails
+    /// 0. `[signer]` The account of admin
+    /// 1. `[writable]` The account of gauntlet state
+    /// 2. `[writable]` The account of vault state
+    SetVaultMaxPendingReward {
+        /// 0 disables the cap
+        max_pending_reward: u64,
+        /// must equal the gauntlet's current admin_nonce
+        admin_nonce: u64,
     },
-    CreateUserAccount {},
+}
+
+/// tag 의 최상위 bit 는 ABI version, 나머지 7 bit 가 instruction
+/// 현재 layout 은 version 0 이라 기존 client 의 tag 는 그대로 유효함
+pub const ABI_VERSION: u8 = 0;
+pub const ABI_VERSION_SHIFT: u8 = 7;
+pub const INSTRUCTION_TAG_MASK: u8 = (1 << ABI_VERSION_SHIFT) - 1;
+
+/// anchor 식 instruction 이름, index 가 1 byte tag 와 같음
+/// discriminator 는 sha256("global:<name>") 의 앞 8 byte
+#[cfg(feature = "anchor-discriminator")]
+pub const ANCHOR_INSTRUCTION_NAMES: [&str; 42] = [
+    "init_gauntlet",
+    "init_vault",
+    "init_strategy",
+    "update_vault_strategy",
+    "deposit",
+    "harvest",
+    "withdraw",
+    "swap_farm_reward_to_usdc",
+    "swap_usdc_to_strategy_token",
+    "swap_farm_reward_to_strategy_token",
+    "create_user_account",
+    "set_accepting_deposits",
+    "sweep_tokens",
+    "add_keeper",
+    "remove_keeper",
+    "init_vault_raydium_state",
+    "get_config",
+    "set_global_pause",
+    "reset_user_status",
+    "validate_vault_ready",
+    "compound",
+    "get_vault_tvl",
+    "set_strategy_deposit_cap",
+    "retire_strategy",
+    "set_vault_fee_accounts",
+    "set_strategy_fee_account",
+    "get_harvest_preview",
+    "set_vault_flags",
+    "set_vault_keeper_fee",
+    "set_vault_withdraw_cooldown",
+    "get_user_position",
+    "set_strategist_fee",
+    "recompute_vault_total",
+    "set_vault_compound_ratio",
+    "collect_fees",
+    "grow_vault_strategy",
+    "migrate_vault",
+    "migrate_gauntlet",
+    "migrate_strategy",
+    "migrate_vault_stake",
+    "migrate_user",
+    "set_vault_max_pending_reward",
+];
+
+#[cfg(feature = "anchor-discriminator")]
+pub fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let hash = solana_program::hash::hash(format!("global:{}", name).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+    discriminator
 }
 
 impl GauntletInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (&tag, rest) = input
-            .split_first()
-            .ok_or(GauntletError::InstructionUnpackError)?;
-        Ok(match tag {
+        #[cfg(feature = "anchor-discriminator")]
+        let (tag, rest) = Self::unpack_tag(input)?;
+        #[cfg(not(feature = "anchor-discriminator"))]
+        let (tag, rest) = Self::unpack_u8(input)?;
+        if tag >> ABI_VERSION_SHIFT != ABI_VERSION {
+            return Err(GauntletError::UnsupportedAbiVersion.into());
+        }
+        Ok(match tag & INSTRUCTION_TAG_MASK {
             0 => Self::InitGauntlet {},
             1 => {
                 let (performance_fee_numerator, _rest) = Self::unpack_u64(rest)?;
                 let (performance_fee_denominator, _rest) = Self::unpack_u64(_rest)?;
                 let (withdrawal_fee_numerator, _rest) = Self::unpack_u64(_rest)?;
                 let (withdrawal_fee_denominator, _rest) = Self::unpack_u64(_rest)?;
+                let (deposit_fee_numerator, _rest) = Self::unpack_u64(_rest)?;
+                let (deposit_fee_denominator, _rest) = Self::unpack_u64(_rest)?;
+                let (referral_fee_numerator, _rest) = Self::unpack_u64(_rest)?;
+                let (referral_fee_denominator, _rest) = Self::unpack_u64(_rest)?;
+                let (min_deposit, _rest) = Self::unpack_u64(_rest)?;
+                let (min_harvest_interval, _rest) = Self::unpack_u64(_rest)?;
+                let (swap_slippage_bps, _rest) = Self::unpack_u64(_rest)?;
+                let (max_swap_usdc_per_tx, _rest) = Self::unpack_u64(_rest)?;
+                let (compounding, _rest) = Self::unpack_bool(_rest)?;
+                let (min_swap_output, _rest) = Self::unpack_u64(_rest)?;
+                let (max_pending_reward, _rest) = Self::unpack_u64(_rest)?;
+                let config = VaultConfig {
+                    min_deposit,
+                    min_harvest_interval,
+                    swap_slippage_bps,
+                    max_swap_usdc_per_tx,
+                    compounding,
+                    min_swap_output,
+                    max_pending_reward,
+                };
+
+                VaultConfig::validate(&config)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                // 없으면 v1 처럼 최소 fee 1 을 받음
+                let min_fee_enabled = match _rest.is_empty() {
+                    true => true,
+                    false => Self::unpack_bool(_rest)?.0,
+                };
                 let fees = Fees {
                     performance_fee_numerator,
                     performance_fee_denominator,
                     withdrawal_fee_numerator,
                     withdrawal_fee_denominator,
+                    deposit_fee_numerator,
+                    deposit_fee_denominator,
+                    referral_fee_numerator,
+                    referral_fee_denominator,
+                    min_fee_enabled,
                 };
 
                 Fees::validate(&fees)?;
 
-                Self::InitVault { fees }
+                Self::InitVault {
+                    fees,
+                    config,
+                    admin_nonce,
+                }
+            }
+            2 => {
+                let (&strategy_type, _rest) = rest
+                    .split_first()
+                    .ok_or(GauntletError::InstructionUnpackError)?;
+                let (deposit_cap, _rest) = Self::unpack_u64(_rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::InitStrategy {
+                    strategy_type: match strategy_type {
+                        0 => StrategyType::RAY,
+                        1 => StrategyType::RAYDIUM_LP,
+                        _ => return Err(GauntletError::InstructionUnpackError.into()),
+                    },
+                    deposit_cap,
+                    admin_nonce,
+                }
             }
-            2 => Self::InitStrategy {},
             3 => {
                 let (availability, rest) = Self::unpack_bool(rest)?;
                 let (needs_usdc_pool, _rest) = Self::unpack_bool(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
                 Self::UpdateVaultStrategy {
                     availability,
                     needs_usdc_pool,
+                    admin_nonce,
                 }
             }
             4 => {
@@ -176,6 +734,10 @@ impl GauntletInstruction {
                 let (&deposit_type, _rest) = _rest
                     .split_first()
                     .ok_or(GauntletError::InstructionUnpackError)?;
+                // 없으면 optional account 를 넘기지 않은 것
+                let (with_deposit_fee_account, _rest) = Self::unpack_optional_bool(_rest)?;
+                let (with_referral_account, _rest) = Self::unpack_optional_bool(_rest)?;
+                let (create_user, _rest) = Self::unpack_optional_bool(_rest)?;
                 Self::Deposit {
                     amount,
                     deposit_type: match deposit_type {
@@ -183,6 +745,9 @@ impl GauntletInstruction {
                         1 => DepositType::RAYDIUM_V4,
                         _ => return Err(GauntletError::InstructionUnpackError.into()),
                     },
+                    with_deposit_fee_account,
+                    with_referral_account,
+                    create_user,
                 }
             }
             5 => {
@@ -191,6 +756,17 @@ impl GauntletInstruction {
                 let (&withdraw_type, _rest) = _rest
                     .split_first()
                     .ok_or(GauntletError::InstructionUnpackError)?;
+                // 없으면 gas reimbursement 하지 않음
+                let (gas_reimbursement_bps, _rest) = match _rest.is_empty() {
+                    true => (0, _rest),
+                    false => Self::unpack_u64(_rest)?,
+                };
+                // 없으면 본인 계정으로 출금
+                let to_recipient = match _rest.first() {
+                    None | Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(GauntletError::InstructionUnpackError.into()),
+                };
                 Self::Withdraw {
                     amount,
                     reward_amount,
@@ -199,29 +775,42 @@ impl GauntletInstruction {
                         1 => WithdrawType::RAYDIUM_V4,
                         _ => return Err(GauntletError::InstructionUnpackError.into()),
                     },
+                    gas_reimbursement_bps,
+                    to_recipient,
                 }
             }
             6 => {
                 let (&deposit_type, _rest) = rest
                     .split_first()
                     .ok_or(GauntletError::InstructionUnpackError)?;
+                // 없으면 한번에 모든 strategy 에 배분
+                let max_strategies = match _rest.is_empty() {
+                    true => 0,
+                    false => Self::unpack_u8(_rest)?.0,
+                };
                 Self::Harvest {
                     deposit_type: match deposit_type {
                         0 => DepositType::RAYDIUM,
                         1 => DepositType::RAYDIUM_V4,
                         _ => return Err(GauntletError::InstructionUnpackError.into()),
                     },
+                    max_strategies,
                 }
             }
             7 => {
                 let (&swap_type, _rest) = rest
                     .split_first()
                     .ok_or(GauntletError::InstructionUnpackError)?;
+                let (max_slippage_bps, _rest) = Self::unpack_u64(_rest)?;
                 Self::SwapFarmRewardToUsdc {
                     swap_type: match swap_type {
                         0 => SwapType::RAYDIUM,
+                        1 => SwapType::ORCA,
+                        2 => SwapType::JUPITER,
                         _ => return Err(GauntletError::InstructionUnpackError.into()),
                     },
+                    max_slippage_bps,
+                    route_data: _rest.to_vec(),
                 }
             }
             8 => {
@@ -231,6 +820,7 @@ impl GauntletInstruction {
                 Self::SwapUsdcToStrategyToken {
                     swap_type: match swap_type {
                         0 => SwapType::RAYDIUM,
+                        1 => SwapType::ORCA,
                         _ => return Err(GauntletError::InstructionUnpackError.into()),
                     },
                 }
@@ -242,11 +832,194 @@ impl GauntletInstruction {
                 Self::SwapFarmRewardToStrategyToken {
                     swap_type: match swap_type {
                         0 => SwapType::RAYDIUM,
+                        1 => SwapType::ORCA,
                         _ => return Err(GauntletError::InstructionUnpackError.into()),
                     },
                 }
             }
             10 => Self::CreateUserAccount {},
+            11 => {
+                let (accepting_deposits, _rest) = Self::unpack_bool(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetAcceptingDeposits {
+                    accepting_deposits,
+                    admin_nonce,
+                }
+            }
+            12 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SweepTokens {
+                    amount,
+                    admin_nonce,
+                }
+            }
+            13 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::AddKeeper { admin_nonce }
+            }
+            14 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::RemoveKeeper { admin_nonce }
+            }
+            15 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::InitVaultRaydiumState { admin_nonce }
+            }
+            16 => Self::GetConfig {},
+            17 => {
+                let (paused, _rest) = Self::unpack_bool(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetGlobalPause {
+                    paused,
+                    admin_nonce,
+                }
+            }
+            18 => {
+                let (user, _rest) = Self::unpack_pubkey(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::ResetUserStatus { user, admin_nonce }
+            }
+            19 => Self::ValidateVaultReady {},
+            20 => {
+                let (&deposit_type, _rest) = rest
+                    .split_first()
+                    .ok_or(GauntletError::InstructionUnpackError)?;
+                Self::Compound {
+                    deposit_type: match deposit_type {
+                        0 => DepositType::RAYDIUM,
+                        1 => DepositType::RAYDIUM_V4,
+                        _ => return Err(GauntletError::InstructionUnpackError.into()),
+                    },
+                }
+            }
+            21 => Self::GetVaultTvl {},
+            22 => {
+                let (deposit_cap, _rest) = Self::unpack_u64(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetStrategyDepositCap {
+                    deposit_cap,
+                    admin_nonce,
+                }
+            }
+            23 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::RetireStrategy { admin_nonce }
+            }
+            24 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::SetVaultFeeAccounts { admin_nonce }
+            }
+            25 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::SetStrategyFeeAccount { admin_nonce }
+            }
+            26 => {
+                let (reward_amount, _rest) = Self::unpack_u64(rest)?;
+                Self::GetHarvestPreview { reward_amount }
+            }
+            27 => {
+                let (deposits_enabled, _rest) = Self::unpack_bool(rest)?;
+                let (withdrawals_enabled, _rest) = Self::unpack_bool(_rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetVaultFlags {
+                    deposits_enabled,
+                    withdrawals_enabled,
+                    admin_nonce,
+                }
+            }
+            28 => {
+                let (keeper_fee_numerator, _rest) = Self::unpack_u64(rest)?;
+                let (keeper_fee_denominator, _rest) = Self::unpack_u64(_rest)?;
+                validate_keeper_fee(keeper_fee_numerator, keeper_fee_denominator)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetVaultKeeperFee {
+                    keeper_fee_numerator,
+                    keeper_fee_denominator,
+                    admin_nonce,
+                }
+            }
+            29 => {
+                let (withdraw_cooldown_secs, _rest) = Self::unpack_u64(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetVaultWithdrawCooldown {
+                    withdraw_cooldown_secs,
+                    admin_nonce,
+                }
+            }
+            30 => Self::GetUserPosition {},
+            31 => {
+                let (strategist_fee_split_bps, _rest) = Self::unpack_u16(rest)?;
+                if strategist_fee_split_bps as u64 > BPS_DENOMINATOR {
+                    return Err(GauntletError::InvalidFee.into());
+                }
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetStrategistFee {
+                    strategist_fee_split_bps,
+                    admin_nonce,
+                }
+            }
+            32 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::RecomputeVaultTotal { admin_nonce }
+            }
+            33 => {
+                let (compound_ratio_bps, _rest) = Self::unpack_u16(rest)?;
+                if compound_ratio_bps as u64 > BPS_DENOMINATOR {
+                    return Err(GauntletError::InvalidFee.into());
+                }
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetVaultCompoundRatio {
+                    compound_ratio_bps,
+                    admin_nonce,
+                }
+            }
+            34 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                let (&fee_source, _rest) = _rest
+                    .split_first()
+                    .ok_or(GauntletError::InstructionUnpackError)?;
+                Self::CollectFees {
+                    amount,
+                    admin_nonce,
+                    fee_source: match fee_source {
+                        0 => FeeSource::VAULT,
+                        1 => FeeSource::STRATEGY,
+                        _ => return Err(GauntletError::InstructionUnpackError.into()),
+                    },
+                }
+            }
+            35 => {
+                let (new_capacity, _rest) = Self::unpack_u8(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::GrowVaultStrategy {
+                    new_capacity,
+                    admin_nonce,
+                }
+            }
+            36 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::MigrateVault { admin_nonce }
+            }
+            37 => Self::MigrateGauntlet {},
+            38 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::MigrateStrategy { admin_nonce }
+            }
+            39 => {
+                let (admin_nonce, _rest) = Self::unpack_u64(rest)?;
+                Self::MigrateVaultStake { admin_nonce }
+            }
+            40 => Self::MigrateUser {},
+            41 => {
+                let (max_pending_reward, _rest) = Self::unpack_u64(rest)?;
+                let (admin_nonce, _rest) = Self::unpack_u64(_rest)?;
+                Self::SetVaultMaxPendingReward {
+                    max_pending_reward,
+                    admin_nonce,
+                }
+            }
             _ => return Err(GauntletError::InstructionUnpackError.into()),
         })
     }
@@ -267,6 +1040,31 @@ impl GauntletInstruction {
         }
     }
 
+    fn unpack_optional_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+        match input.is_empty() {
+            true => Ok((false, input)),
+            false => Self::unpack_bool(input),
+        }
+    }
+
+    /// 앞 8 byte 가 anchor discriminator 면 해당 tag 로, 아니면 기존 1 byte tag 로 읽음
+    #[cfg(feature = "anchor-discriminator")]
+    fn unpack_tag(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
+        if input.len() >= 8 {
+            let (discriminator, rest) = input.split_at(8);
+            if let Some(tag) = ANCHOR_INSTRUCTION_NAMES
+                .iter()
+                .position(|name| anchor_discriminator(name) == discriminator)
+            {
+                return Ok((tag as u8, rest));
+            }
+        }
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(GauntletError::InstructionUnpackError)?;
+        Ok((tag, rest))
+    }
+
     fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
         if input.is_empty() {
             return Err(GauntletError::InstructionUnpackError.into());
@@ -280,6 +1078,27 @@ impl GauntletInstruction {
         Ok((value, rest))
     }
 
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() < 2 {
+            return Err(GauntletError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(2);
+        let value = bytes
+            .get(..2)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u16::from_le_bytes)
+            .ok_or(GauntletError::InstructionUnpackError)?;
+        Ok((value, rest))
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() < 32 {
+            return Err(GauntletError::InstructionUnpackError.into());
+        }
+        let (key, rest) = input.split_at(32);
+        Ok((Pubkey::new(key), rest))
+    }
+
     fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
         if input.len() < 8 {
             return Err(GauntletError::InstructionUnpackError.into());
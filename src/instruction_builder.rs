@@ -0,0 +1,1262 @@
+//! Client side helpers that build gauntlet instructions with the accounts in the order
+//! the processor reads them. Only compiled with the `client` feature.
+use crate::{
+    instruction::{DepositType, FeeSource, StrategyType, SwapType, WithdrawType},
+    oracle::{
+        PriceAttestation, ED25519_CURRENT_INSTRUCTION, ED25519_OFFSETS_LEN, ED25519_OFFSETS_START,
+    },
+    state::{Fees, VaultConfig},
+    utils::{
+        find_authority_address, find_legacy_authority_address, find_user_address,
+        ED25519_PROGRAM_ID,
+    },
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+
+pub struct DepositAccounts {
+    pub depositor: Pubkey,
+    pub depositor_user_state: Pubkey,
+    pub depositor_deposit_token: Pubkey,
+    pub gauntlet_state: Pubkey,
+    pub vault_state: Pubkey,
+    pub vault_strategy_state: Pubkey,
+    pub strategy_state: Pubkey,
+    /// raydium deposit accounts, 11 (13 for v4)
+    pub deposit_accounts: Vec<AccountMeta>,
+    /// vault.deposit_token_mint, LP moves with transfer_checked
+    pub deposit_token_mint: Pubkey,
+    /// required when the vault charges a deposit fee
+    pub deposit_fee_token: Option<Pubkey>,
+    /// receives a cut of the deposit fee
+    pub referral_token: Option<Pubkey>,
+    /// creates depositor_user_state in the same instruction, depositor pays the rent
+    pub create_user: bool,
+}
+
+pub struct WithdrawAccounts {
+    pub withdrawer: Pubkey,
+    pub withdrawer_user_state: Pubkey,
+    pub withdrawer_deposit_token: Pubkey,
+    pub withdrawer_reward_token: Pubkey,
+    pub gauntlet_state: Pubkey,
+    pub vault_state: Pubkey,
+    pub vault_strategy_state: Pubkey,
+    pub strategy_state: Pubkey,
+    pub strategy_token: Pubkey,
+    pub withdraw_fee_token: Pubkey,
+    pub performance_fee_token: Pubkey,
+    /// raydium withdraw accounts, 11 (13 for v4)
+    pub withdraw_accounts: Vec<AccountMeta>,
+    /// vault.deposit_token_mint
+    pub deposit_token_mint: Pubkey,
+    /// strategy.strategy_token_mint
+    pub strategy_token_mint: Pubkey,
+    /// token program owning strategy_token, spl-token or Token-2022
+    pub strategy_token_program: Pubkey,
+    /// raydium swap accounts (19), required when gas_reimbursement_bps > 0
+    pub gas_reimbursement_swap_accounts: Vec<AccountMeta>,
+    /// (deposit token, reward token) accounts to withdraw into instead of the withdrawer's own
+    pub recipient_tokens: Option<(Pubkey, Pubkey)>,
+    /// strategy.strategist_fee_account, required when the strategy splits its performance fee
+    pub strategist_fee_token: Option<Pubkey>,
+}
+
+pub struct HarvestAccounts {
+    pub harvestor: Pubkey,
+    pub gauntlet_state: Pubkey,
+    pub harvestor_user_state: Pubkey,
+    pub vault_state: Pubkey,
+    pub vault_strategy_state: Pubkey,
+    /// raydium harvest accounts, 11 (13 for v4)
+    pub harvest_accounts: Vec<AccountMeta>,
+    /// keeper's reward token account and the vault reward mint, required when the vault keeper fee is set
+    pub keeper_reward_token: Option<(Pubkey, Pubkey)>,
+}
+
+/// accounts shared by the three swap instructions
+pub struct SwapAccounts {
+    pub swaper: Pubkey,
+    pub gauntlet_state: Pubkey,
+    pub swaper_user_state: Pubkey,
+    pub vault_state: Pubkey,
+    pub vault_strategy_state: Pubkey,
+    pub strategy_state: Pubkey,
+    /// pyth price account (or the instructions sysvar with a price_attestation instruction before),
+    /// reward token mint, usdc mint. only for swap_farm_reward_to_usdc and required when
+    /// max_slippage_bps > 0
+    pub oracle_accounts: Vec<AccountMeta>,
+    /// raydium (19), orca (12) or jupiter route accounts. a RAY strategy taking the reward as is
+    /// only needs (vault reward token, strategy token, authority, token program, reward mint)
+    pub swap_accounts: Vec<AccountMeta>,
+    /// raydium deposit accounts followed by the deposit LP mint, used to re-stake when the
+    /// strategy token is the deposit LP
+    pub restake_accounts: Vec<AccountMeta>,
+}
+
+fn deposit_type_tag(deposit_type: &DepositType) -> u8 {
+    match deposit_type {
+        DepositType::RAYDIUM => 0,
+        DepositType::RAYDIUM_V4 => 1,
+    }
+}
+
+fn withdraw_type_tag(withdraw_type: &WithdrawType) -> u8 {
+    match withdraw_type {
+        WithdrawType::RAYDIUM => 0,
+        WithdrawType::RAYDIUM_V4 => 1,
+    }
+}
+
+fn swap_type_tag(swap_type: &SwapType) -> u8 {
+    match swap_type {
+        SwapType::RAYDIUM => 0,
+        SwapType::ORCA => 1,
+        SwapType::JUPITER => 2,
+    }
+}
+
+pub fn deposit(
+    program_id: &Pubkey,
+    accounts: DepositAccounts,
+    amount: u64,
+    deposit_type: DepositType,
+) -> Instruction {
+    let mut data = vec![4];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(deposit_type_tag(&deposit_type));
+    data.push(accounts.deposit_fee_token.is_some() as u8);
+    data.push(accounts.referral_token.is_some() as u8);
+    data.push(accounts.create_user as u8);
+
+    let mut metas = vec![
+        match accounts.create_user {
+            true => AccountMeta::new(accounts.depositor, true),
+            false => AccountMeta::new_readonly(accounts.depositor, true),
+        },
+        AccountMeta::new(accounts.depositor_user_state, false),
+        AccountMeta::new(accounts.depositor_deposit_token, false),
+        AccountMeta::new_readonly(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new(accounts.vault_strategy_state, false),
+        AccountMeta::new(accounts.strategy_state, false),
+    ];
+    metas.extend(accounts.deposit_accounts);
+    metas.push(AccountMeta::new_readonly(
+        accounts.deposit_token_mint,
+        false,
+    ));
+    if let Some(deposit_fee_token) = accounts.deposit_fee_token {
+        metas.push(AccountMeta::new(deposit_fee_token, false));
+    }
+    if let Some(referral_token) = accounts.referral_token {
+        metas.push(AccountMeta::new(referral_token, false));
+    }
+    if accounts.create_user {
+        metas.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+pub fn withdraw(
+    program_id: &Pubkey,
+    accounts: WithdrawAccounts,
+    amount: u64,
+    reward_amount: u64,
+    withdraw_type: WithdrawType,
+    gas_reimbursement_bps: u64,
+) -> Instruction {
+    let mut data = vec![5];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&reward_amount.to_le_bytes());
+    data.push(withdraw_type_tag(&withdraw_type));
+    data.extend_from_slice(&gas_reimbursement_bps.to_le_bytes());
+    data.push(accounts.recipient_tokens.is_some() as u8);
+
+    let mut metas = vec![
+        AccountMeta::new(accounts.withdrawer, true),
+        AccountMeta::new(accounts.withdrawer_user_state, false),
+        AccountMeta::new(accounts.withdrawer_deposit_token, false),
+        AccountMeta::new(accounts.withdrawer_reward_token, false),
+        AccountMeta::new_readonly(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new(accounts.vault_strategy_state, false),
+        AccountMeta::new(accounts.strategy_state, false),
+        AccountMeta::new(accounts.strategy_token, false),
+        AccountMeta::new(accounts.withdraw_fee_token, false),
+        AccountMeta::new(accounts.performance_fee_token, false),
+    ];
+    metas.extend(accounts.withdraw_accounts);
+    metas.push(AccountMeta::new_readonly(
+        accounts.deposit_token_mint,
+        false,
+    ));
+    metas.push(AccountMeta::new_readonly(
+        accounts.strategy_token_mint,
+        false,
+    ));
+    metas.push(AccountMeta::new_readonly(
+        accounts.strategy_token_program,
+        false,
+    ));
+    if gas_reimbursement_bps > 0 {
+        metas.extend(accounts.gas_reimbursement_swap_accounts);
+    }
+    if let Some((recipient_deposit_token, recipient_reward_token)) = accounts.recipient_tokens {
+        metas.push(AccountMeta::new(recipient_deposit_token, false));
+        metas.push(AccountMeta::new(recipient_reward_token, false));
+    }
+    if let Some(strategist_fee_token) = accounts.strategist_fee_token {
+        metas.push(AccountMeta::new(strategist_fee_token, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+pub fn harvest(
+    program_id: &Pubkey,
+    accounts: HarvestAccounts,
+    deposit_type: DepositType,
+    max_strategies: u8,
+) -> Instruction {
+    let data = vec![6, deposit_type_tag(&deposit_type), max_strategies];
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.harvestor, true),
+        AccountMeta::new_readonly(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.harvestor_user_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new_readonly(accounts.vault_strategy_state, false),
+    ];
+    metas.extend(accounts.harvest_accounts);
+    if let Some((keeper_reward_token, reward_token_mint)) = accounts.keeper_reward_token {
+        metas.push(AccountMeta::new(keeper_reward_token, false));
+        metas.push(AccountMeta::new_readonly(reward_token_mint, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+fn swap_metas(accounts: SwapAccounts) -> Vec<AccountMeta> {
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.swaper, true),
+        AccountMeta::new_readonly(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.swaper_user_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new(accounts.vault_strategy_state, false),
+        AccountMeta::new(accounts.strategy_state, false),
+    ];
+    metas.extend(accounts.oracle_accounts);
+    metas.extend(accounts.swap_accounts);
+    metas.extend(accounts.restake_accounts);
+    metas
+}
+
+pub fn swap_farm_reward_to_usdc(
+    program_id: &Pubkey,
+    accounts: SwapAccounts,
+    swap_type: SwapType,
+    max_slippage_bps: u64,
+    route_data: &[u8],
+) -> Instruction {
+    let mut data = vec![7, swap_type_tag(&swap_type)];
+    data.extend_from_slice(&max_slippage_bps.to_le_bytes());
+    data.extend_from_slice(route_data);
+    Instruction {
+        program_id: *program_id,
+        accounts: swap_metas(accounts),
+        data,
+    }
+}
+
+pub fn swap_usdc_to_strategy_token(
+    program_id: &Pubkey,
+    accounts: SwapAccounts,
+    swap_type: SwapType,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: swap_metas(accounts),
+        data: vec![8, swap_type_tag(&swap_type)],
+    }
+}
+
+pub fn swap_farm_reward_to_strategy_token(
+    program_id: &Pubkey,
+    accounts: SwapAccounts,
+    swap_type: SwapType,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: swap_metas(accounts),
+        data: vec![9, swap_type_tag(&swap_type)],
+    }
+}
+
+/// ed25519 precompile instruction verifying `signature` of `signer` over the packed attestation,
+/// must come right before swap_farm_reward_to_usdc
+pub fn price_attestation(
+    signer: &Pubkey,
+    signature: &[u8; 64],
+    attestation: &PriceAttestation,
+) -> Instruction {
+    let public_key_offset = ED25519_OFFSETS_START + ED25519_OFFSETS_LEN;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + 64;
+    let mut data = vec![1, 0];
+    for value in [
+        signature_offset as u16,
+        ED25519_CURRENT_INSTRUCTION,
+        public_key_offset as u16,
+        ED25519_CURRENT_INSTRUCTION,
+        message_data_offset as u16,
+        PriceAttestation::LEN as u16,
+        ED25519_CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    let mut message = [0; PriceAttestation::LEN];
+    attestation.pack_into_slice(&mut message);
+    data.extend_from_slice(&message);
+    Instruction {
+        program_id: ED25519_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// signer and gauntlet state, the first two accounts of every admin instruction
+pub struct AdminAccounts {
+    pub admin: Pubkey,
+    pub gauntlet_state: Pubkey,
+}
+
+/// vault strategy, vault and strategy state, in the order UpdateVaultStrategy and
+/// SetAcceptingDeposits read them
+pub struct VaultStrategyAccounts {
+    pub vault_strategy_state: Pubkey,
+    pub vault_state: Pubkey,
+    pub strategy_state: Pubkey,
+}
+
+pub struct InitVaultAccounts {
+    pub admin: Pubkey,
+    pub gauntlet_state: Pubkey,
+    /// uninitialized, owned by the program
+    pub vault_state: Pubkey,
+    /// uninitialized, owned by the program
+    pub vault_strategy_state: Pubkey,
+    /// owned by the admin, handed over to the gauntlet authority
+    pub deposit_token: Pubkey,
+    pub withdraw_fee_token: Pubkey,
+    pub reward_token: Pubkey,
+    pub deposit_fee_token: Pubkey,
+    /// farms paying a second reward only
+    pub reward_b_token: Option<Pubkey>,
+    /// spl-token or Token-2022, owns every vault token account and is stored as vault.token_program
+    pub token_program: Pubkey,
+}
+
+pub struct InitStrategyAccounts {
+    pub admin: Pubkey,
+    pub gauntlet_state: Pubkey,
+    /// uninitialized, owned by the program
+    pub strategy_state: Pubkey,
+    /// owned by the admin, handed over to the gauntlet authority
+    pub strategy_token: Pubkey,
+    pub performance_fee_token: Pubkey,
+    /// spl-token or Token-2022, owner of strategy_token
+    pub token_program: Pubkey,
+}
+
+pub struct CompoundAccounts {
+    pub keeper: Pubkey,
+    pub gauntlet_state: Pubkey,
+    pub vault_state: Pubkey,
+    pub vault_strategy_state: Pubkey,
+    pub strategy_state: Pubkey,
+    /// raydium swap accounts (19), vault reward -> gauntlet usdc
+    pub swap_accounts: Vec<AccountMeta>,
+    /// raydium amm deposit accounts (14)
+    pub add_liquidity_accounts: Vec<AccountMeta>,
+    /// raydium staking deposit accounts, 11 (13 for v4)
+    pub restake_accounts: Vec<AccountMeta>,
+}
+
+pub struct MigrateVaultAccounts {
+    pub admin: Pubkey,
+    pub gauntlet_state: Pubkey,
+    pub v1_vault_state: Pubkey,
+    /// Vault::LEN bytes, owned by the program
+    pub vault_state: Pubkey,
+    pub v1_vault_strategy_state: Pubkey,
+    /// VaultStrategy::packed_len(capacity) bytes, owned by the program
+    pub vault_strategy_state: Pubkey,
+    pub deposit_token: Pubkey,
+    pub reward_token: Pubkey,
+    /// only when the v1 vault has a second reward
+    pub reward_b_token: Option<Pubkey>,
+}
+
+pub struct MigrateUserAccounts {
+    pub payer: Pubkey,
+    pub vault_state: Pubkey,
+    pub strategy_state: Pubkey,
+    pub v1_user_state: Pubkey,
+    /// receives the rent of the closed v1 user account
+    pub user: Pubkey,
+}
+
+pub struct CollectFeesAccounts {
+    /// the vault (withdraw / deposit fee) or strategy (performance / strategist fee) owning fee_token
+    pub fee_state: Pubkey,
+    /// owned by the gauntlet authority
+    pub fee_token: Pubkey,
+    pub destination_token: Pubkey,
+    /// spl-token or Token-2022, owner of fee_token
+    pub token_program: Pubkey,
+}
+
+fn admin_metas(admin: &AdminAccounts) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(admin.admin, true),
+        AccountMeta::new(admin.gauntlet_state, false),
+    ]
+}
+
+fn admin_instruction(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+) -> Instruction {
+    let mut metas = admin_metas(admin);
+    metas.extend(accounts);
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+fn strategy_type_tag(strategy_type: &StrategyType) -> u8 {
+    match strategy_type {
+        StrategyType::RAY => 0,
+        StrategyType::RAYDIUM_LP => 1,
+    }
+}
+
+pub fn init_gauntlet(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    gauntlet_state: &Pubkey,
+    usdc_token: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*gauntlet_state, false),
+            AccountMeta::new(*usdc_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![0],
+    }
+}
+
+pub fn init_vault(
+    program_id: &Pubkey,
+    accounts: InitVaultAccounts,
+    fees: &Fees,
+    config: &VaultConfig,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![1];
+    for value in [
+        fees.performance_fee_numerator,
+        fees.performance_fee_denominator,
+        fees.withdrawal_fee_numerator,
+        fees.withdrawal_fee_denominator,
+        fees.deposit_fee_numerator,
+        fees.deposit_fee_denominator,
+        fees.referral_fee_numerator,
+        fees.referral_fee_denominator,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for value in [
+        config.min_deposit,
+        config.min_harvest_interval,
+        config.swap_slippage_bps,
+        config.max_swap_usdc_per_tx,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.push(config.compounding as u8);
+    data.extend_from_slice(&config.min_swap_output.to_le_bytes());
+    data.extend_from_slice(&config.max_pending_reward.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    data.push(fees.min_fee_enabled as u8);
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.admin, true),
+        AccountMeta::new(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new(accounts.vault_strategy_state, false),
+        AccountMeta::new(accounts.deposit_token, false),
+        AccountMeta::new(accounts.withdraw_fee_token, false),
+        AccountMeta::new_readonly(accounts.token_program, false),
+        AccountMeta::new(accounts.reward_token, false),
+        AccountMeta::new(accounts.deposit_fee_token, false),
+    ];
+    if let Some(reward_b_token) = accounts.reward_b_token {
+        metas.push(AccountMeta::new(reward_b_token, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+pub fn init_strategy(
+    program_id: &Pubkey,
+    accounts: InitStrategyAccounts,
+    strategy_type: StrategyType,
+    deposit_cap: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![2, strategy_type_tag(&strategy_type)];
+    data.extend_from_slice(&deposit_cap.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.admin, true),
+            AccountMeta::new(accounts.gauntlet_state, false),
+            AccountMeta::new(accounts.strategy_state, false),
+            AccountMeta::new(accounts.strategy_token, false),
+            AccountMeta::new(accounts.performance_fee_token, false),
+            AccountMeta::new_readonly(accounts.token_program, false),
+        ],
+        data,
+    }
+}
+
+pub fn update_vault_strategy(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    accounts: &VaultStrategyAccounts,
+    availability: bool,
+    needs_usdc_pool: bool,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![3, availability as u8, needs_usdc_pool as u8];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(accounts.vault_strategy_state, false),
+            AccountMeta::new(accounts.vault_state, false),
+            AccountMeta::new_readonly(accounts.strategy_state, false),
+        ],
+        data,
+    )
+}
+
+/// the user state address is derived from the vault, depositor and strategy
+pub fn create_user_account(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    vault_state: &Pubkey,
+    strategy_state: &Pubkey,
+) -> Instruction {
+    let (user_state, _) = find_user_address(vault_state, depositor, strategy_state, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new_readonly(*vault_state, false),
+            AccountMeta::new_readonly(*strategy_state, false),
+            AccountMeta::new(user_state, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![10],
+    }
+}
+
+pub fn set_accepting_deposits(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    accounts: &VaultStrategyAccounts,
+    accepting_deposits: bool,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![11, accepting_deposits as u8];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(accounts.vault_strategy_state, false),
+            AccountMeta::new_readonly(accounts.vault_state, false),
+            AccountMeta::new_readonly(accounts.strategy_state, false),
+        ],
+        data,
+    )
+}
+
+/// `token_program` is the owner of the source account, spl-token or Token-2022
+pub fn sweep_tokens(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    source_token: &Pubkey,
+    destination_token: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let (authority, _) = find_authority_address(&admin.gauntlet_state, program_id);
+    let mut data = vec![12];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(*source_token, false),
+            AccountMeta::new(*destination_token, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    )
+}
+
+pub fn add_keeper(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    keeper: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![13];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new_readonly(*keeper, false)],
+        data,
+    )
+}
+
+pub fn remove_keeper(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    keeper: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![14];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new_readonly(*keeper, false)],
+        data,
+    )
+}
+
+pub fn init_vault_raydium_state(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    vault_strategy_state: &Pubkey,
+    vault_raydium_state: &Pubkey,
+    staking_program_id: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![15];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(*vault_state, false),
+            AccountMeta::new_readonly(*vault_strategy_state, false),
+            AccountMeta::new(*vault_raydium_state, false),
+            AccountMeta::new_readonly(*staking_program_id, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    )
+}
+
+pub fn get_config(program_id: &Pubkey, gauntlet_state: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*gauntlet_state, false)],
+        data: vec![16],
+    }
+}
+
+pub fn set_global_pause(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    paused: bool,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![17, paused as u8];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(program_id, admin, vec![], data)
+}
+
+/// `user` is the owner of `user_state`
+pub fn reset_user_status(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    user_state: &Pubkey,
+    user: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![18];
+    data.extend_from_slice(user.as_ref());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new_readonly(*vault_state, false),
+            AccountMeta::new(*user_state, false),
+        ],
+        data,
+    )
+}
+
+/// `strategy_states` are every strategy of the gauntlet, ordered by strategy index
+pub fn validate_vault_ready(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    vault_strategy_state: &Pubkey,
+    withdraw_fee_token: &Pubkey,
+    deposit_fee_token: &Pubkey,
+    strategy_states: &[Pubkey],
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new_readonly(admin.admin, true),
+        AccountMeta::new_readonly(admin.gauntlet_state, false),
+        AccountMeta::new_readonly(*vault_state, false),
+        AccountMeta::new_readonly(*vault_strategy_state, false),
+        AccountMeta::new_readonly(*withdraw_fee_token, false),
+        AccountMeta::new_readonly(*deposit_fee_token, false),
+    ];
+    for strategy_state in strategy_states {
+        metas.push(AccountMeta::new_readonly(*strategy_state, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data: vec![19],
+    }
+}
+
+pub fn compound(
+    program_id: &Pubkey,
+    accounts: CompoundAccounts,
+    deposit_type: DepositType,
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.keeper, true),
+        AccountMeta::new_readonly(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new_readonly(accounts.vault_strategy_state, false),
+        AccountMeta::new(accounts.strategy_state, false),
+    ];
+    metas.extend(accounts.swap_accounts);
+    metas.extend(accounts.add_liquidity_accounts);
+    metas.extend(accounts.restake_accounts);
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data: vec![20, deposit_type_tag(&deposit_type)],
+    }
+}
+
+pub fn get_vault_tvl(
+    program_id: &Pubkey,
+    vault_state: &Pubkey,
+    vault_strategy_state: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*vault_state, false),
+            AccountMeta::new_readonly(*vault_strategy_state, false),
+        ],
+        data: vec![21],
+    }
+}
+
+pub fn set_strategy_deposit_cap(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    strategy_state: &Pubkey,
+    deposit_cap: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![22];
+    data.extend_from_slice(&deposit_cap.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new(*strategy_state, false)],
+        data,
+    )
+}
+
+/// `vault_states` and `vault_strategy_states` cover every vault of the gauntlet, vault 0 first
+pub fn retire_strategy(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    strategy_state: &Pubkey,
+    vault_states: &[Pubkey],
+    vault_strategy_states: &[Pubkey],
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![23];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    let mut metas = vec![AccountMeta::new(*strategy_state, false)];
+    for state in vault_states.iter().chain(vault_strategy_states) {
+        metas.push(AccountMeta::new(*state, false));
+    }
+    admin_instruction(program_id, admin, metas, data)
+}
+
+pub fn set_vault_fee_accounts(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    withdraw_fee_token: &Pubkey,
+    deposit_fee_token: &Pubkey,
+    token_program: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![24];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(*vault_state, false),
+            AccountMeta::new(*withdraw_fee_token, false),
+            AccountMeta::new(*deposit_fee_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    )
+}
+
+pub fn set_strategy_fee_account(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    strategy_state: &Pubkey,
+    performance_fee_token: &Pubkey,
+    token_program: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![25];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(*strategy_state, false),
+            AccountMeta::new(*performance_fee_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    )
+}
+
+pub fn get_harvest_preview(
+    program_id: &Pubkey,
+    vault_state: &Pubkey,
+    vault_strategy_state: &Pubkey,
+    reward_amount: u64,
+) -> Instruction {
+    let mut data = vec![26];
+    data.extend_from_slice(&reward_amount.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*vault_state, false),
+            AccountMeta::new_readonly(*vault_strategy_state, false),
+        ],
+        data,
+    }
+}
+
+pub fn set_vault_flags(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    deposits_enabled: bool,
+    withdrawals_enabled: bool,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![27, deposits_enabled as u8, withdrawals_enabled as u8];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new(*vault_state, false)],
+        data,
+    )
+}
+
+pub fn set_vault_keeper_fee(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    keeper_fee_numerator: u64,
+    keeper_fee_denominator: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![28];
+    data.extend_from_slice(&keeper_fee_numerator.to_le_bytes());
+    data.extend_from_slice(&keeper_fee_denominator.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new(*vault_state, false)],
+        data,
+    )
+}
+
+pub fn set_vault_withdraw_cooldown(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    withdraw_cooldown_secs: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![29];
+    data.extend_from_slice(&withdraw_cooldown_secs.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new(*vault_state, false)],
+        data,
+    )
+}
+
+pub fn set_vault_max_pending_reward(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    max_pending_reward: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![41];
+    data.extend_from_slice(&max_pending_reward.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new(*vault_state, false)],
+        data,
+    )
+}
+
+pub fn get_user_position(
+    program_id: &Pubkey,
+    user_state: &Pubkey,
+    vault_strategy: &VaultStrategyAccounts,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*user_state, false),
+            AccountMeta::new_readonly(vault_strategy.vault_state, false),
+            AccountMeta::new_readonly(vault_strategy.vault_strategy_state, false),
+            AccountMeta::new_readonly(vault_strategy.strategy_state, false),
+        ],
+        data: vec![30],
+    }
+}
+
+pub fn set_strategist_fee(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    strategy_state: &Pubkey,
+    strategist_fee_token: &Pubkey,
+    token_program: &Pubkey,
+    strategist_fee_split_bps: u16,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![31];
+    data.extend_from_slice(&strategist_fee_split_bps.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new(*strategy_state, false),
+            AccountMeta::new(*strategist_fee_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    )
+}
+
+pub fn recompute_vault_total(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_strategy_state: &Pubkey,
+    vault_state: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![32];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new_readonly(*vault_strategy_state, false),
+            AccountMeta::new(*vault_state, false),
+        ],
+        data,
+    )
+}
+
+pub fn set_vault_compound_ratio(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    compound_ratio_bps: u16,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![33];
+    data.extend_from_slice(&compound_ratio_bps.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    admin_instruction(
+        program_id,
+        admin,
+        vec![AccountMeta::new(*vault_state, false)],
+        data,
+    )
+}
+
+/// `fee_source` tells whether `accounts.fee_state` is a vault or a strategy
+pub fn collect_fees(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    accounts: &CollectFeesAccounts,
+    fee_source: FeeSource,
+    amount: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let (authority, _) = find_authority_address(&admin.gauntlet_state, program_id);
+    let mut data = vec![34];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    data.push(match fee_source {
+        FeeSource::VAULT => 0,
+        FeeSource::STRATEGY => 1,
+    });
+    admin_instruction(
+        program_id,
+        admin,
+        vec![
+            AccountMeta::new_readonly(accounts.fee_state, false),
+            AccountMeta::new(accounts.fee_token, false),
+            AccountMeta::new(accounts.destination_token, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(accounts.token_program, false),
+        ],
+        data,
+    )
+}
+
+/// the admin receives the rent of the closed vault strategy account
+pub fn grow_vault_strategy(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    vault_strategy_state: &Pubkey,
+    new_vault_strategy_state: &Pubkey,
+    new_capacity: u8,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![35, new_capacity];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(admin.admin, true),
+            AccountMeta::new(admin.gauntlet_state, false),
+            AccountMeta::new_readonly(*vault_state, false),
+            AccountMeta::new(*vault_strategy_state, false),
+            AccountMeta::new(*new_vault_strategy_state, false),
+        ],
+        data,
+    }
+}
+
+/// the admin receives the rent of the closed v1 vault and vault strategy accounts
+pub fn migrate_vault(
+    program_id: &Pubkey,
+    accounts: MigrateVaultAccounts,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![36];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    let mut metas = vec![
+        AccountMeta::new(accounts.admin, true),
+        AccountMeta::new(accounts.gauntlet_state, false),
+        AccountMeta::new(accounts.v1_vault_state, false),
+        AccountMeta::new(accounts.vault_state, false),
+        AccountMeta::new(accounts.v1_vault_strategy_state, false),
+        AccountMeta::new(accounts.vault_strategy_state, false),
+        AccountMeta::new_readonly(find_legacy_authority_address(program_id).0, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(accounts.deposit_token, false),
+        AccountMeta::new(accounts.reward_token, false),
+    ];
+    if let Some(reward_b_token) = accounts.reward_b_token {
+        metas.push(AccountMeta::new(reward_b_token, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+/// signed by the v1 admin, who receives the rent of the closed v1 gauntlet account
+pub fn migrate_gauntlet(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    v1_gauntlet_state: &Pubkey,
+    gauntlet_state: &Pubkey,
+    usdc_token: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*v1_gauntlet_state, false),
+            AccountMeta::new(*gauntlet_state, false),
+            AccountMeta::new_readonly(find_legacy_authority_address(program_id).0, false),
+            AccountMeta::new(*usdc_token, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: vec![37],
+    }
+}
+
+/// the admin receives the rent of the closed v1 strategy account
+pub fn migrate_strategy(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    v1_strategy_state: &Pubkey,
+    strategy_state: &Pubkey,
+    strategy_token: &Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![38];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(admin.admin, true),
+            AccountMeta::new(admin.gauntlet_state, false),
+            AccountMeta::new(*v1_strategy_state, false),
+            AccountMeta::new(*strategy_state, false),
+            AccountMeta::new_readonly(find_legacy_authority_address(program_id).0, false),
+            AccountMeta::new(*strategy_token, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data,
+    }
+}
+
+/// `staking_accounts` are the raydium deposit accounts (11, 13 for v4) with the v1 ledger
+/// as user info account and the gauntlet authority as owner
+pub fn migrate_vault_stake(
+    program_id: &Pubkey,
+    admin: &AdminAccounts,
+    vault_state: &Pubkey,
+    vault_strategy_state: &Pubkey,
+    staking_accounts: Vec<AccountMeta>,
+    admin_nonce: u64,
+) -> Instruction {
+    let mut data = vec![39];
+    data.extend_from_slice(&admin_nonce.to_le_bytes());
+    let (vault_raydium_state, _bump_seed) = Pubkey::find_program_address(
+        &[
+            &admin.gauntlet_state.to_bytes(),
+            &vault_state.to_bytes(),
+            &vault_strategy_state.to_bytes(),
+        ],
+        program_id,
+    );
+    let mut metas = vec![
+        AccountMeta::new(admin.admin, true),
+        AccountMeta::new(admin.gauntlet_state, false),
+        AccountMeta::new(*vault_state, false),
+        AccountMeta::new_readonly(*vault_strategy_state, false),
+        AccountMeta::new_readonly(find_legacy_authority_address(program_id).0, false),
+        AccountMeta::new(vault_raydium_state, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    metas.extend(staking_accounts);
+    Instruction {
+        program_id: *program_id,
+        accounts: metas,
+        data,
+    }
+}
+
+pub fn migrate_user(program_id: &Pubkey, accounts: MigrateUserAccounts) -> Instruction {
+    let (user_state, _bump_seed) = find_user_address(
+        &accounts.vault_state,
+        &accounts.user,
+        &accounts.strategy_state,
+        program_id,
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(accounts.payer, true),
+            AccountMeta::new_readonly(accounts.vault_state, false),
+            AccountMeta::new_readonly(accounts.strategy_state, false),
+            AccountMeta::new(accounts.v1_user_state, false),
+            AccountMeta::new(user_state, false),
+            AccountMeta::new(accounts.user, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![40],
+    }
+}
@@ -0,0 +1,87 @@
+use crate::error::GauntletError;
+use crate::utils::{check_jupiter_program_id, TOKEN_2022_PROGRAM_ID};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Account;
+
+/// index of the first route account, the accounts before it are read by the adapter
+pub const JUPITER_ROUTE_ACCOUNTS_OFFSET: usize = 4;
+
+pub struct Jupiter;
+impl Jupiter {
+    /// accounts: jupiter program, token authority(pda), source token account, dest token account,
+    /// route accounts (passed to jupiter in the given order)
+    ///
+    /// route_data 는 client 가 jupiter api 로 만든 instruction data 를 그대로 전달
+    pub fn route_swap(
+        accounts: &[AccountInfo],
+        route_data: &[u8],
+        max_amount_in: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if accounts.len() < JUPITER_ROUTE_ACCOUNTS_OFFSET {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let jupiter_program_id = &accounts[0];
+        let token_authority = &accounts[1];
+        let source_token_account = &accounts[2];
+        let dest_token_account = &accounts[3];
+        check_jupiter_program_id(jupiter_program_id)?;
+
+        // route 에는 pda 서명이 붙으므로 source / dest 외의 pda 소유 token account 는 다른 vault 몫이 빠져나갈 수 있음
+        for account in &accounts[JUPITER_ROUTE_ACCOUNTS_OFFSET..] {
+            if account.key != source_token_account.key
+                && account.key != dest_token_account.key
+                && is_token_account_owned_by(account, token_authority.key)
+            {
+                return Err(GauntletError::InvalidAccount.into());
+            }
+        }
+
+        let before_source_amount = Account::unpack(&source_token_account.data.borrow())?.amount;
+        let swap_ix = Instruction {
+            program_id: *jupiter_program_id.key,
+            accounts: accounts[JUPITER_ROUTE_ACCOUNTS_OFFSET..]
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: *account.key,
+                    is_signer: account.is_signer || account.key == token_authority.key,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: route_data.to_vec(),
+        };
+        invoke_signed(&swap_ix, accounts, signer_seeds)?;
+
+        // route 가 다른 strategy 몫의 token 까지 쓰지 않았는지 확인
+        let after_source_amount = Account::unpack(&source_token_account.data.borrow())?.amount;
+        let spent_amount = before_source_amount
+            .checked_sub(after_source_amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        if spent_amount > max_amount_in {
+            return Err(GauntletError::RouteOverspent.into());
+        }
+        Ok(())
+    }
+}
+
+/// legacy / Token-2022 token account 중 owner 가 `authority` 인 것
+fn is_token_account_owned_by(account: &AccountInfo, authority: &Pubkey) -> bool {
+    if *account.owner != spl_token::id() && *account.owner != TOKEN_2022_PROGRAM_ID {
+        return false;
+    }
+    let data = account.data.borrow();
+    // Token-2022 extension 은 base account 뒤에 붙으므로 앞부분만 봄
+    data.len() >= Account::LEN
+        && matches!(
+            Account::unpack_from_slice(&data[..Account::LEN]),
+            Ok(token_account) if token_account.owner == *authority
+        )
+}
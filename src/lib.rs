@@ -3,6 +3,11 @@
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
+#[cfg(feature = "client")]
+pub mod instruction_builder;
+pub mod jupiter;
+pub mod oracle;
+pub mod orca;
 pub mod processor;
 pub mod raydium;
 pub mod state;
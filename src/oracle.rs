@@ -0,0 +1,237 @@
+use crate::error::GauntletError;
+use crate::state::MAX_SLIPPAGE_BPS;
+use crate::utils::{check_pyth_program_id, ED25519_PROGRAM_ID};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+    pubkey::Pubkey,
+    sysvar::instructions::{load_current_index, load_instruction_at},
+};
+use spl_token::state::Mint;
+
+/// magic number at the start of every pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+/// offset of `expo` in the pyth price account data
+pub const PYTH_EXPO_OFFSET: usize = 20;
+/// offset of the aggregate price (price, conf, status, corp_act, pub_slot)
+pub const PYTH_AGG_PRICE_OFFSET: usize = 208;
+pub const PYTH_AGG_STATUS_OFFSET: usize = 224;
+pub const PYTH_AGG_PUB_SLOT_OFFSET: usize = 232;
+/// pyth aggregate status `Trading`
+pub const PYTH_STATUS_TRADING: u32 = 1;
+/// oracle price older than this many slots is treated as stale
+pub const MAX_ORACLE_SLOT_AGE: u64 = 25;
+/// ed25519 instruction data: signature count (u8), padding (u8), then 7 u16 offsets per signature
+pub const ED25519_OFFSETS_START: usize = 2;
+pub const ED25519_OFFSETS_LEN: usize = 14;
+/// instruction index in the ed25519 offsets that points at the ed25519 instruction itself
+pub const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Reference price of the swap source token in USD, used to bound the realized USDC output
+pub struct OraclePriceCheck {
+    pub price: u64,
+    pub expo: i32,
+    pub source_decimals: u8,
+    pub dest_decimals: u8,
+    pub max_slippage_bps: u64,
+}
+
+impl OraclePriceCheck {
+    /// accounts: pyth price account (source token / USD), source token mint, dest(usdc) mint
+    pub fn load(
+        price_account: &AccountInfo,
+        source_mint: &AccountInfo,
+        dest_mint: &AccountInfo,
+        max_slippage_bps: u64,
+        clock: &Clock,
+    ) -> Result<Self, ProgramError> {
+        if max_slippage_bps > MAX_SLIPPAGE_BPS {
+            return Err(GauntletError::InvalidSlippage.into());
+        }
+        check_pyth_program_id(price_account.owner)?;
+
+        let data = price_account.data.borrow();
+        if data.len() < PYTH_AGG_PUB_SLOT_OFFSET + 8
+            || u32::from_le_bytes(*array_ref![data, 0, 4]) != PYTH_MAGIC
+        {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let expo = i32::from_le_bytes(*array_ref![data, PYTH_EXPO_OFFSET, 4]);
+        let price = i64::from_le_bytes(*array_ref![data, PYTH_AGG_PRICE_OFFSET, 8]);
+        let status = u32::from_le_bytes(*array_ref![data, PYTH_AGG_STATUS_OFFSET, 4]);
+        let pub_slot = u64::from_le_bytes(*array_ref![data, PYTH_AGG_PUB_SLOT_OFFSET, 8]);
+        drop(data);
+
+        // 거래 중이 아니거나 오래된 가격은 신뢰할 수 없음
+        if status != PYTH_STATUS_TRADING
+            || price <= 0
+            || clock.slot.saturating_sub(pub_slot) > MAX_ORACLE_SLOT_AGE
+        {
+            return Err(GauntletError::PriceDeviation.into());
+        }
+
+        Ok(OraclePriceCheck {
+            price: price as u64,
+            expo,
+            source_decimals: Mint::unpack(&source_mint.data.borrow())?.decimals,
+            dest_decimals: Mint::unpack(&dest_mint.data.borrow())?.decimals,
+            max_slippage_bps,
+        })
+    }
+
+    /// keeper 가 서명한 가격으로 pyth 가격과 같은 check 를 만듦, mint 는 서명된 것과 같아야 함
+    pub fn from_attestation(
+        attestation: &PriceAttestation,
+        source_mint: &AccountInfo,
+        dest_mint: &AccountInfo,
+        max_slippage_bps: u64,
+        clock: &Clock,
+    ) -> Result<Self, ProgramError> {
+        if max_slippage_bps > MAX_SLIPPAGE_BPS {
+            return Err(GauntletError::InvalidSlippage.into());
+        }
+        if attestation.source_mint != *source_mint.key
+            || attestation.dest_mint != *dest_mint.key
+            || attestation.price == 0
+            || clock.unix_timestamp > attestation.expires_at
+        {
+            return Err(GauntletError::InvalidPriceAttestation.into());
+        }
+
+        Ok(OraclePriceCheck {
+            price: attestation.price,
+            expo: attestation.expo,
+            source_decimals: Mint::unpack(&source_mint.data.borrow())?.decimals,
+            dest_decimals: Mint::unpack(&dest_mint.data.borrow())?.decimals,
+            max_slippage_bps,
+        })
+    }
+
+    /// oracle 가격으로 계산한 예상 output 에서 slippage 만큼 뺀 최소 output
+    pub fn min_amount_out(&self, amount_in: u64) -> Result<u64, ProgramError> {
+        let exponent = self.expo + self.dest_decimals as i32 - self.source_decimals as i32;
+        let scale = 10u128
+            .checked_pow(exponent.unsigned_abs())
+            .ok_or(GauntletError::MathOverflow)?;
+        let value = (amount_in as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(GauntletError::MathOverflow)?;
+        let expected = if exponent >= 0 {
+            value.checked_mul(scale)
+        } else {
+            value.checked_div(scale)
+        }
+        .ok_or(GauntletError::MathOverflow)?;
+        let min_out = expected
+            .checked_mul((MAX_SLIPPAGE_BPS - self.max_slippage_bps) as u128)
+            .ok_or(GauntletError::MathOverflow)?
+            / MAX_SLIPPAGE_BPS as u128;
+        Ok(min_out.min(u64::MAX as u128) as u64)
+    }
+}
+
+/// Reference price signed off-chain by a keeper, the message of an ed25519 precompile instruction
+pub struct PriceAttestation {
+    /// swap source token mint
+    pub source_mint: Pubkey,
+    /// swap destination (usdc) mint
+    pub dest_mint: Pubkey,
+    /// dest token per source token as price * 10^expo, in whole tokens like pyth
+    pub price: u64,
+    pub expo: i32,
+    /// unix timestamp after which the attestation is rejected
+    pub expires_at: i64,
+}
+
+impl PriceAttestation {
+    /// Reads the attestation signed in the ed25519 instruction right before the current one,
+    /// returns it with the key that signed it. The precompile already failed the transaction
+    /// if the signature does not verify, so only the signed pubkey and message are read here
+    pub fn load(instructions_sysvar: &AccountInfo) -> Result<(Pubkey, Self), ProgramError> {
+        if !solana_program::sysvar::instructions::check_id(instructions_sysvar.key) {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let data = instructions_sysvar.data.borrow();
+        let ed25519_index = (load_current_index(&data) as usize)
+            .checked_sub(1)
+            .ok_or(GauntletError::InvalidPriceAttestation)?;
+        let instruction = load_instruction_at(ed25519_index, &data)
+            .map_err(|_| GauntletError::InvalidPriceAttestation)?;
+        if instruction.program_id != ED25519_PROGRAM_ID
+            || instruction.data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_LEN
+            || instruction.data[0] != 1
+        {
+            return Err(GauntletError::InvalidPriceAttestation.into());
+        }
+
+        let offsets = array_ref![instruction.data, ED25519_OFFSETS_START, ED25519_OFFSETS_LEN];
+        let (
+            _signature_offset,
+            signature_instruction_index,
+            public_key_offset,
+            public_key_instruction_index,
+            message_data_offset,
+            message_data_size,
+            message_instruction_index,
+        ) = array_refs![offsets, 2, 2, 2, 2, 2, 2, 2];
+        // 다른 instruction 의 data 를 가리키면 여기서 읽는 key / message 가 검증된 것과 다를 수 있음
+        if [
+            signature_instruction_index,
+            public_key_instruction_index,
+            message_instruction_index,
+        ]
+        .iter()
+        .any(|index| u16::from_le_bytes(**index) != ED25519_CURRENT_INSTRUCTION)
+            || u16::from_le_bytes(*message_data_size) as usize != PriceAttestation::LEN
+        {
+            return Err(GauntletError::InvalidPriceAttestation.into());
+        }
+
+        let public_key_offset = u16::from_le_bytes(*public_key_offset) as usize;
+        let message_data_offset = u16::from_le_bytes(*message_data_offset) as usize;
+        let public_key = instruction
+            .data
+            .get(public_key_offset..public_key_offset + 32)
+            .ok_or(GauntletError::InvalidPriceAttestation)?;
+        let message = instruction
+            .data
+            .get(message_data_offset..message_data_offset + PriceAttestation::LEN)
+            .ok_or(GauntletError::InvalidPriceAttestation)?;
+        Ok((
+            Pubkey::new(public_key),
+            PriceAttestation::unpack_from_slice(message)?,
+        ))
+    }
+}
+
+impl Sealed for PriceAttestation {}
+impl Pack for PriceAttestation {
+    const LEN: usize = 32 + 32 + 8 + 4 + 8; // 84
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, PriceAttestation::LEN];
+        let (source_mint, dest_mint, price, expo, expires_at) =
+            mut_array_refs![output, 32, 32, 8, 4, 8];
+
+        source_mint.copy_from_slice(self.source_mint.as_ref());
+        dest_mint.copy_from_slice(self.dest_mint.as_ref());
+        *price = self.price.to_le_bytes();
+        *expo = self.expo.to_le_bytes();
+        *expires_at = self.expires_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, PriceAttestation::LEN];
+        let (source_mint, dest_mint, price, expo, expires_at) = array_refs![input, 32, 32, 8, 4, 8];
+
+        Ok(Self {
+            source_mint: Pubkey::new_from_array(*source_mint),
+            dest_mint: Pubkey::new_from_array(*dest_mint),
+            price: u64::from_le_bytes(*price),
+            expo: i32::from_le_bytes(*expo),
+            expires_at: i64::from_le_bytes(*expires_at),
+        })
+    }
+}
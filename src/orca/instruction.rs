@@ -0,0 +1,99 @@
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::mem::size_of;
+
+/// anchor discriminator of whirlpool `swap` (sighash("global:swap"))
+pub const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+/// sqrt price limits of whirlpool (no price limit)
+pub const MIN_SQRT_PRICE_X64: u128 = 4295048016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79226673515401279992447579055;
+
+/// accounts of a whirlpool swap, token a/b in whirlpool order
+pub struct SwapAccounts<'a> {
+    pub whirlpool_program_id: &'a Pubkey,
+    pub token_program_id: &'a Pubkey,
+    pub token_authority: &'a Pubkey,
+    pub whirlpool: &'a Pubkey,
+    pub token_owner_account_a: &'a Pubkey,
+    pub token_vault_a: &'a Pubkey,
+    pub token_owner_account_b: &'a Pubkey,
+    pub token_vault_b: &'a Pubkey,
+    pub tick_array_0: &'a Pubkey,
+    pub tick_array_1: &'a Pubkey,
+    pub tick_array_2: &'a Pubkey,
+    pub oracle: &'a Pubkey,
+}
+
+pub enum OrcaInstruction {
+    Swap {
+        amount: u64,
+        other_amount_threshold: u64,
+        sqrt_price_limit: u128,
+        amount_specified_is_input: bool,
+        a_to_b: bool,
+    },
+}
+
+impl OrcaInstruction {
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>() + 8);
+        match self {
+            OrcaInstruction::Swap {
+                amount,
+                other_amount_threshold,
+                sqrt_price_limit,
+                amount_specified_is_input,
+                a_to_b,
+            } => {
+                buf.extend_from_slice(&SWAP_DISCRIMINATOR);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&other_amount_threshold.to_le_bytes());
+                buf.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+                buf.push(*amount_specified_is_input as u8);
+                buf.push(*a_to_b as u8);
+            }
+        };
+        buf
+    }
+    pub fn swap(
+        swap_accounts: &SwapAccounts,
+        amount: u64,
+        other_amount_threshold: u64,
+        a_to_b: bool,
+    ) -> Result<Instruction, ProgramError> {
+        let sqrt_price_limit = match a_to_b {
+            true => MIN_SQRT_PRICE_X64,
+            false => MAX_SQRT_PRICE_X64,
+        };
+        let data = OrcaInstruction::Swap {
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit,
+            amount_specified_is_input: true,
+            a_to_b,
+        }
+        .pack();
+        let accounts = vec![
+            AccountMeta::new_readonly(*swap_accounts.token_program_id, false),
+            AccountMeta::new_readonly(*swap_accounts.token_authority, true),
+            AccountMeta::new(*swap_accounts.whirlpool, false),
+            AccountMeta::new(*swap_accounts.token_owner_account_a, false),
+            AccountMeta::new(*swap_accounts.token_vault_a, false),
+            AccountMeta::new(*swap_accounts.token_owner_account_b, false),
+            AccountMeta::new(*swap_accounts.token_vault_b, false),
+            AccountMeta::new(*swap_accounts.tick_array_0, false),
+            AccountMeta::new(*swap_accounts.tick_array_1, false),
+            AccountMeta::new(*swap_accounts.tick_array_2, false),
+            AccountMeta::new_readonly(*swap_accounts.oracle, false),
+        ];
+
+        Ok(Instruction {
+            program_id: *swap_accounts.whirlpool_program_id,
+            accounts,
+            data,
+        })
+    }
+}
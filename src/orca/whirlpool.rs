@@ -0,0 +1,170 @@
+use crate::error::GauntletError;
+use crate::orca::instruction::{OrcaInstruction, SwapAccounts};
+use crate::utils::check_whirlpool_program_id;
+use arrayref::array_ref;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Account;
+
+/// offset of `fee_rate` (u16, hundredths of a bps) in the whirlpool account data
+pub const WHIRLPOOL_FEE_RATE_OFFSET: usize = 45;
+/// offset of `sqrt_price` (u128, Q64.64) in the whirlpool account data
+pub const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65;
+/// offset of `token_mint_a` in the whirlpool account data
+pub const WHIRLPOOL_TOKEN_MINT_A_OFFSET: usize = 101;
+/// fee_rate 가 1_000_000 이면 100%
+const WHIRLPOOL_FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+pub struct Orca;
+impl Orca {
+    /// accounts: whirlpool program, token program, token authority(pda), whirlpool,
+    /// source token account, source token vault, dest token account, dest token vault,
+    /// tick array 0, tick array 1, tick array 2, oracle
+    pub fn whirlpool_swap(
+        accounts: &[AccountInfo],
+        amount_in: u64,
+        amount_out: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let whirlpool_program_id = next_account_info(account_info_iter)?;
+        let token_program_id = next_account_info(account_info_iter)?;
+        let token_authority = next_account_info(account_info_iter)?;
+        let whirlpool = next_account_info(account_info_iter)?;
+        let user_source_token_account = next_account_info(account_info_iter)?;
+        let source_token_vault = next_account_info(account_info_iter)?;
+        let user_dest_token_account = next_account_info(account_info_iter)?;
+        let dest_token_vault = next_account_info(account_info_iter)?;
+        let tick_array_0 = next_account_info(account_info_iter)?;
+        let tick_array_1 = next_account_info(account_info_iter)?;
+        let tick_array_2 = next_account_info(account_info_iter)?;
+        let oracle = next_account_info(account_info_iter)?;
+        check_whirlpool_program_id(whirlpool_program_id)?;
+
+        // whirlpool은 token a/b 순서로 account를 받기 때문에 source mint로 방향을 결정
+        let source_mint = Account::unpack(&user_source_token_account.data.borrow())?.mint;
+        let whirlpool_data = whirlpool.data.borrow();
+        if whirlpool_data.len() < WHIRLPOOL_TOKEN_MINT_A_OFFSET + 32 {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let token_mint_a = Pubkey::new_from_array(*array_ref![
+            whirlpool_data,
+            WHIRLPOOL_TOKEN_MINT_A_OFFSET,
+            32
+        ]);
+        drop(whirlpool_data);
+        let a_to_b = source_mint == token_mint_a;
+        let (token_owner_account_a, token_vault_a, token_owner_account_b, token_vault_b) =
+            match a_to_b {
+                true => (
+                    user_source_token_account,
+                    source_token_vault,
+                    user_dest_token_account,
+                    dest_token_vault,
+                ),
+                false => (
+                    user_dest_token_account,
+                    dest_token_vault,
+                    user_source_token_account,
+                    source_token_vault,
+                ),
+            };
+        let swap_ix = OrcaInstruction::swap(
+            &SwapAccounts {
+                whirlpool_program_id: whirlpool_program_id.key,
+                token_program_id: token_program_id.key,
+                token_authority: token_authority.key,
+                whirlpool: whirlpool.key,
+                token_owner_account_a: token_owner_account_a.key,
+                token_vault_a: token_vault_a.key,
+                token_owner_account_b: token_owner_account_b.key,
+                token_vault_b: token_vault_b.key,
+                tick_array_0: tick_array_0.key,
+                tick_array_1: tick_array_1.key,
+                tick_array_2: tick_array_2.key,
+                oracle: oracle.key,
+            },
+            amount_in,
+            amount_out,
+            a_to_b,
+        )?;
+        invoke_signed(&swap_ix, accounts, signer_seeds)?;
+        Ok(())
+    }
+
+    /// whirlpool 의 현재 sqrt price 로 계산한 swap 예상 수령량, tick 을 넘는 price impact 는 포함하지 않음
+    /// accounts 는 whirlpool_swap 과 동일
+    pub fn whirlpool_estimate_out(
+        accounts: &[AccountInfo],
+        amount_in: u64,
+    ) -> Result<u64, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let whirlpool_program_id = next_account_info(account_info_iter)?;
+        let _token_program_id = next_account_info(account_info_iter)?;
+        let _token_authority = next_account_info(account_info_iter)?;
+        let whirlpool = next_account_info(account_info_iter)?;
+        let user_source_token_account = next_account_info(account_info_iter)?;
+        check_whirlpool_program_id(whirlpool_program_id)?;
+        // 다른 program 의 account 로 가격을 속이지 못하도록 whirlpool program 소유만 읽음
+        if whirlpool.owner != whirlpool_program_id.key {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        let source_mint = Account::unpack(&user_source_token_account.data.borrow())?.mint;
+        let whirlpool_data = whirlpool.data.borrow();
+        if whirlpool_data.len() < WHIRLPOOL_TOKEN_MINT_A_OFFSET + 32 {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let fee_rate =
+            u16::from_le_bytes(*array_ref![whirlpool_data, WHIRLPOOL_FEE_RATE_OFFSET, 2]);
+        let sqrt_price =
+            u128::from_le_bytes(*array_ref![whirlpool_data, WHIRLPOOL_SQRT_PRICE_OFFSET, 16]);
+        let token_mint_a = Pubkey::new_from_array(*array_ref![
+            whirlpool_data,
+            WHIRLPOOL_TOKEN_MINT_A_OFFSET,
+            32
+        ]);
+        drop(whirlpool_data);
+        if sqrt_price == 0 || fee_rate as u128 > WHIRLPOOL_FEE_RATE_DENOMINATOR {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        let amount_in_after_fee = (amount_in as u128)
+            .checked_mul(WHIRLPOOL_FEE_RATE_DENOMINATOR - fee_rate as u128)
+            .ok_or(GauntletError::MathOverflow)?
+            / WHIRLPOOL_FEE_RATE_DENOMINATOR;
+        // price(b per a) = (sqrt_price / 2^64)^2
+        let amount_out = if source_mint == token_mint_a {
+            mul_shr_64(mul_shr_64(amount_in_after_fee, sqrt_price)?, sqrt_price)?
+        } else {
+            // amount_in_after_fee < 2^64 이므로 첫 shift 는 넘치지 않음
+            let once = (amount_in_after_fee << 64) / sqrt_price;
+            once.checked_mul(1 << 64)
+                .ok_or(GauntletError::MathOverflow)?
+                / sqrt_price
+        };
+        if amount_out > u64::MAX as u128 {
+            return Err(GauntletError::MathOverflow.into());
+        }
+        Ok(amount_out as u64)
+    }
+}
+
+/// value * q64 / 2^64, q64 를 상위 / 하위 64 bit 로 나눠 u128 안에서 계산
+fn mul_shr_64(value: u128, q64: u128) -> Result<u128, ProgramError> {
+    let high = value
+        .checked_mul(q64 >> 64)
+        .ok_or(GauntletError::MathOverflow)?;
+    let low = value
+        .checked_mul(q64 & u64::MAX as u128)
+        .ok_or(GauntletError::MathOverflow)?
+        >> 64;
+    high.checked_add(low)
+        .ok_or_else(|| GauntletError::MathOverflow.into())
+}
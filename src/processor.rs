@@ -4,26 +4,62 @@ use solana_program::{
     account_info::{next_account_info, next_account_infos, AccountInfo},
     clock::{Clock, UnixTimestamp},
     entrypoint::ProgramResult,
+    msg,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::Sysvar,
+    sysvar::{self, rent::Rent, Sysvar},
 };
 
-use spl_token::state::Account;
-
 use crate::{
     error::GauntletError,
-    instruction::{DepositType, GauntletInstruction, SwapType, WithdrawType},
-    raydium::raydium::Raydium,
-    state::{Fees, Gauntlet, Status, Strategy, User, Vault, VaultStrategy},
+    instruction::{
+        DepositType, FeeSource, GauntletInstruction, StrategyType, SwapType, WithdrawType,
+    },
+    jupiter::{Jupiter, JUPITER_ROUTE_ACCOUNTS_OFFSET},
+    oracle::{OraclePriceCheck, PriceAttestation},
+    orca::whirlpool::Orca,
+    raydium::raydium::{Raydium, RAYDIUM_SWAP_FEE_BPS},
+    state::{
+        distribute, next_swap_status, Fees, Gauntlet, GauntletConfig, Status, Strategy,
+        StrategyRef, TryFromAccount, User, UserPosition, Vault, VaultConfig, VaultRef,
+        VaultStrategy, VaultTvl, BPS_DENOMINATOR, HARVEST_FAILURE_WARN_THRESHOLD,
+        MAX_GAS_REIMBURSEMENT_BPS, MAX_KEEPERS, MAX_NUMBER_OF_STRATEGY, MAX_NUMBER_OF_VAULTS,
+        MAX_SLIPPAGE_BPS, USER_STATUS_HARVESTED, USER_STATUS_IDLE, USER_STATUS_READY,
+        USER_STATUS_REWARDS_SWAPPED, USER_STATUS_REWARD_A_SWAPPED, VAULT_LAYOUT_VERSION,
+    },
     utils::{
-        change_token_account_owner, create_pda_account, transfer_token, transfer_token_signed,
+        change_token_account_owner, change_token_account_owner_signed, check_account_owner,
+        check_any_token_program_id, check_pool_program_id, check_serum_program_id,
+        check_staking_program_id, check_system_program_id, check_token_program_id, checked_index,
+        close_program_account, close_token_account, create_pda_account, find_authority_address,
+        find_legacy_authority_address, find_user_address, transfer_token_checked,
+        transfer_token_signed, unpack_token_account, AUTHORITY_SEED,
     },
 };
 
 pub struct Processor;
 
+/// reward 를 swap 없이 옮기는 RAY strategy 가 받는 account 수
+/// vault reward token, strategy token, authority, token program, reward mint
+pub const REWARD_TRANSFER_ACCOUNT_COUNT: usize = 5;
+
+/// keeper 가 넘긴 swap 경로, route_data 는 JUPITER 일 때만, price_check 는 oracle 이 주어졌을 때만 씀
+struct SwapRoute<'r, 'a> {
+    accounts: &'r [AccountInfo<'a>],
+    swap_type: &'r SwapType,
+    route_data: &'r [u8],
+    price_check: &'r Option<OraclePriceCheck>,
+}
+
+/// strategy token 으로 swap 하는 경로와, compounding 몫을 다시 staking 할 account
+struct StrategyTokenSwap<'r, 'a> {
+    strategy_token_account: &'r AccountInfo<'a>,
+    accounts: &'r [AccountInfo<'a>],
+    swap_type: &'r SwapType,
+    restake_accounts: &'r [AccountInfo<'a>],
+}
+
 impl Processor {
     pub fn process(
         program_id: &Pubkey,
@@ -33,46 +69,251 @@ impl Processor {
         let instructions = GauntletInstruction::unpack(instruction_data)?;
         match instructions {
             GauntletInstruction::InitGauntlet {} => Self::init_gauntlet(accounts, program_id),
-            GauntletInstruction::InitVault { fees } => Self::init_vault(accounts, fees, program_id),
-            GauntletInstruction::InitStrategy {} => Self::init_strategy(accounts, program_id),
+            GauntletInstruction::InitVault {
+                fees,
+                config,
+                admin_nonce,
+            } => Self::init_vault(accounts, fees, config, admin_nonce, program_id),
+            GauntletInstruction::InitStrategy {
+                strategy_type,
+                deposit_cap,
+                admin_nonce,
+            } => Self::init_strategy(
+                accounts,
+                strategy_type,
+                deposit_cap,
+                admin_nonce,
+                program_id,
+            ),
             GauntletInstruction::UpdateVaultStrategy {
                 availability,
                 needs_usdc_pool,
-            } => Self::update_vault_strategy(accounts, availability, needs_usdc_pool),
+                admin_nonce,
+            } => Self::update_vault_strategy(
+                accounts,
+                availability,
+                needs_usdc_pool,
+                admin_nonce,
+                program_id,
+            ),
             GauntletInstruction::Deposit {
                 amount,
                 deposit_type,
-            } => Self::deposit(accounts, amount, deposit_type),
-            GauntletInstruction::Harvest { deposit_type } => Self::harvest(accounts, deposit_type),
-            GauntletInstruction::SwapFarmRewardToUsdc { swap_type } => {
-                Self::swap_farm_reward_to_usdc(accounts, swap_type)
-            }
+                with_deposit_fee_account,
+                with_referral_account,
+                create_user,
+            } => Self::with_user_lock(accounts.get(1), program_id, || {
+                Self::deposit(
+                    accounts,
+                    amount,
+                    deposit_type,
+                    with_deposit_fee_account,
+                    with_referral_account,
+                    create_user,
+                    program_id,
+                )
+            }),
+            GauntletInstruction::Harvest {
+                deposit_type,
+                max_strategies,
+            } => Self::with_user_lock(accounts.get(2), program_id, || {
+                Self::harvest(accounts, deposit_type, max_strategies, program_id)
+            }),
+            GauntletInstruction::SwapFarmRewardToUsdc {
+                swap_type,
+                max_slippage_bps,
+                route_data,
+            } => Self::with_user_lock(accounts.get(2), program_id, || {
+                Self::swap_farm_reward_to_usdc(accounts, swap_type, max_slippage_bps, &route_data)
+            }),
             GauntletInstruction::SwapUsdcToStrategyToken { swap_type } => {
-                Self::swap_usdc_to_strategy_token(accounts, swap_type)
+                Self::with_user_lock(accounts.get(2), program_id, || {
+                    Self::swap_usdc_to_strategy_token(accounts, swap_type)
+                })
             }
             GauntletInstruction::SwapFarmRewardToStrategyToken { swap_type } => {
-                Self::swap_reward_to_strategy_token(accounts, swap_type)
+                Self::with_user_lock(accounts.get(2), program_id, || {
+                    Self::swap_reward_to_strategy_token(accounts, swap_type, program_id)
+                })
             }
             GauntletInstruction::Withdraw {
                 amount,
                 reward_amount,
                 withdraw_type,
-            } => Self::withdraw(accounts, amount, reward_amount, withdraw_type),
+                gas_reimbursement_bps,
+                to_recipient,
+            } => Self::with_user_lock(accounts.get(1), program_id, || {
+                Self::withdraw(
+                    accounts,
+                    amount,
+                    reward_amount,
+                    withdraw_type,
+                    gas_reimbursement_bps,
+                    to_recipient,
+                )
+            }),
             GauntletInstruction::CreateUserAccount {} => {
                 Self::create_user_account(accounts, program_id)
             }
+            GauntletInstruction::SetAcceptingDeposits {
+                accepting_deposits,
+                admin_nonce,
+            } => {
+                Self::set_accepting_deposits(accounts, accepting_deposits, admin_nonce, program_id)
+            }
+            GauntletInstruction::SweepTokens {
+                amount,
+                admin_nonce,
+            } => Self::sweep_tokens(accounts, amount, admin_nonce, program_id),
+            GauntletInstruction::AddKeeper { admin_nonce } => {
+                Self::add_keeper(accounts, admin_nonce)
+            }
+            GauntletInstruction::InitVaultRaydiumState { admin_nonce } => {
+                Self::init_vault_raydium_state(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::RemoveKeeper { admin_nonce } => {
+                Self::remove_keeper(accounts, admin_nonce)
+            }
+            GauntletInstruction::GetConfig {} => Self::get_config(accounts, program_id),
+            GauntletInstruction::SetGlobalPause {
+                paused,
+                admin_nonce,
+            } => Self::set_global_pause(accounts, paused, admin_nonce),
+            GauntletInstruction::ResetUserStatus { user, admin_nonce } => {
+                Self::reset_user_status(accounts, user, admin_nonce, program_id)
+            }
+            GauntletInstruction::ValidateVaultReady {} => Self::validate_vault_ready(accounts),
+            GauntletInstruction::Compound { deposit_type } => {
+                Self::compound(accounts, deposit_type)
+            }
+            GauntletInstruction::GetVaultTvl {} => Self::get_vault_tvl(accounts, program_id),
+            GauntletInstruction::SetStrategyDepositCap {
+                deposit_cap,
+                admin_nonce,
+            } => Self::set_strategy_deposit_cap(accounts, deposit_cap, admin_nonce),
+            GauntletInstruction::RetireStrategy { admin_nonce } => {
+                Self::retire_strategy(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::SetVaultFeeAccounts { admin_nonce } => {
+                Self::set_vault_fee_accounts(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::SetStrategyFeeAccount { admin_nonce } => {
+                Self::set_strategy_fee_account(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::GetHarvestPreview { reward_amount } => {
+                Self::get_harvest_preview(accounts, reward_amount, program_id)
+            }
+            GauntletInstruction::SetVaultKeeperFee {
+                keeper_fee_numerator,
+                keeper_fee_denominator,
+                admin_nonce,
+            } => Self::set_vault_keeper_fee(
+                accounts,
+                keeper_fee_numerator,
+                keeper_fee_denominator,
+                admin_nonce,
+            ),
+            GauntletInstruction::SetVaultWithdrawCooldown {
+                withdraw_cooldown_secs,
+                admin_nonce,
+            } => Self::set_vault_withdraw_cooldown(accounts, withdraw_cooldown_secs, admin_nonce),
+            GauntletInstruction::GetUserPosition {} => {
+                Self::get_user_position(accounts, program_id)
+            }
+            GauntletInstruction::SetStrategistFee {
+                strategist_fee_split_bps,
+                admin_nonce,
+            } => Self::set_strategist_fee(
+                accounts,
+                strategist_fee_split_bps,
+                admin_nonce,
+                program_id,
+            ),
+            GauntletInstruction::SetVaultFlags {
+                deposits_enabled,
+                withdrawals_enabled,
+                admin_nonce,
+            } => {
+                Self::set_vault_flags(accounts, deposits_enabled, withdrawals_enabled, admin_nonce)
+            }
+            GauntletInstruction::RecomputeVaultTotal { admin_nonce } => {
+                Self::recompute_vault_total(accounts, admin_nonce)
+            }
+            GauntletInstruction::SetVaultCompoundRatio {
+                compound_ratio_bps,
+                admin_nonce,
+            } => Self::set_vault_compound_ratio(accounts, compound_ratio_bps, admin_nonce),
+            GauntletInstruction::CollectFees {
+                amount,
+                admin_nonce,
+                fee_source,
+            } => Self::collect_fees(accounts, amount, admin_nonce, fee_source, program_id),
+            GauntletInstruction::GrowVaultStrategy {
+                new_capacity,
+                admin_nonce,
+            } => Self::grow_vault_strategy(accounts, new_capacity, admin_nonce, program_id),
+            GauntletInstruction::MigrateVault { admin_nonce } => {
+                Self::migrate_vault(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::MigrateGauntlet {} => Self::migrate_gauntlet(accounts, program_id),
+            GauntletInstruction::MigrateStrategy { admin_nonce } => {
+                Self::migrate_strategy(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::MigrateVaultStake { admin_nonce } => {
+                Self::migrate_vault_stake(accounts, admin_nonce, program_id)
+            }
+            GauntletInstruction::MigrateUser {} => Self::migrate_user(accounts, program_id),
+            GauntletInstruction::SetVaultMaxPendingReward {
+                max_pending_reward,
+                admin_nonce,
+            } => Self::set_vault_max_pending_reward(accounts, max_pending_reward, admin_nonce),
         }
     }
+    /// user status 를 진행시키는 instruction 을 user account lock 안에서 실행
+    /// 같은 user account 로 재진입하면 UserBusy, 실패하면 tx 가 revert 되므로 lock 이 남지 않음
+    fn with_user_lock(
+        user_state_account: Option<&AccountInfo>,
+        program_id: &Pubkey,
+        instruction: impl FnOnce() -> ProgramResult,
+    ) -> ProgramResult {
+        let user_state_account = user_state_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        // lock 을 기록하기 전에 이 program 의 user PDA 인지 확인
+        // 아직 만들거나 초기화하지 않은 user 는 instruction 안에서 seed 를 확인함
+        if user_state_account.data_len() == User::LEN {
+            check_account_owner(user_state_account, program_id)?;
+            let user_info = User::unpack_unchecked(&user_state_account.data.borrow())?;
+            if user_info.is_initialized {
+                let (user_address, _bump_seed) = find_user_address(
+                    &user_info.vault_account,
+                    &user_info.user,
+                    &user_info.strategy_account,
+                    program_id,
+                );
+                if *user_state_account.key != user_address {
+                    return Err(ProgramError::InvalidSeeds);
+                }
+            }
+        }
+        User::acquire_lock(&mut user_state_account.data.borrow_mut())?;
+        instruction()?;
+        User::release_lock(&mut user_state_account.data.borrow_mut());
+        Ok(())
+    }
+
     fn init_gauntlet(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let initializer = next_account_info(account_info_iter)?;
         let gauntlet_state_account = next_account_info(account_info_iter)?;
         let usdc_token_account = next_account_info(account_info_iter)?;
-        let _token_program_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
 
         if !initializer.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        check_any_token_program_id(token_program_account)?;
+        if usdc_token_account.owner != token_program_account.key {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
 
         let mut gauntlet_info = Gauntlet::unpack_unchecked(&gauntlet_state_account.data.borrow())?;
 
@@ -80,17 +321,31 @@ impl Processor {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
 
-        gauntlet_info = Gauntlet::init(*initializer.key, *usdc_token_account.key);
+        let (pda, bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        gauntlet_info = Gauntlet::init(*initializer.key, *usdc_token_account.key, bump_seed);
+        gauntlet_info.token_program = *token_program_account.key;
+        let usdc_token_account_info = unpack_token_account(&usdc_token_account.data.borrow())?;
+        gauntlet_info.track_mint(usdc_token_account_info.mint)?;
 
         Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
 
-        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"glt"], program_id); // TODO change
-        change_token_account_owner(usdc_token_account, initializer, &pda)?;
+        change_token_account_owner(
+            token_program_account.key,
+            usdc_token_account,
+            initializer,
+            &pda,
+        )?;
 
         Ok(())
     }
 
-    fn init_vault(accounts: &[AccountInfo], fees: Fees, program_id: &Pubkey) -> ProgramResult {
+    fn init_vault(
+        accounts: &[AccountInfo],
+        fees: Fees,
+        config: VaultConfig,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let initializer = next_account_info(account_info_iter)?;
         let gauntlet_state_account = next_account_info(account_info_iter)?;
@@ -98,59 +353,244 @@ impl Processor {
         let vault_strategy_account = next_account_info(account_info_iter)?;
         let deposit_token_account = next_account_info(account_info_iter)?;
         let withdraw_fee_token_account = next_account_info(account_info_iter)?;
-        let vault_raydium_state_account = next_account_info(account_info_iter)?;
-        let raydium_staking_program = next_account_info(account_info_iter)?;
-        let _token_program_account = next_account_info(account_info_iter)?;
-        let system_program_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
         let farm_reward_token_account = next_account_info(account_info_iter)?;
+        let deposit_fee_token_account = next_account_info(account_info_iter)?;
         let mut farm_second_reward_token_account: Option<&AccountInfo> = None;
 
-        if accounts.len() > 11 {
+        if accounts.len() > 9 {
             farm_second_reward_token_account = Some(next_account_info(account_info_iter)?);
         }
 
         if !initializer.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        check_any_token_program_id(token_program_account)?;
 
         let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
 
         if gauntlet_info.admin != *initializer.key {
             return Err(GauntletError::NotAdmin.into());
         }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
 
+        // 쓰기 전에 두 account 모두 확인해야 vault 만 초기화된 채로 남지 않음
         let mut vault_info = Vault::unpack_unchecked(&vault_state_account.data.borrow())?;
+        let vault_strategy_info =
+            VaultStrategy::unpack_unchecked(&vault_strategy_account.data.borrow())?;
 
-        if vault_info.is_initialized() {
+        if vault_info.is_initialized() || vault_strategy_info.is_initialized() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
+        if gauntlet_info.vaults_len as usize >= MAX_NUMBER_OF_VAULTS {
+            return Err(GauntletError::MaxVaultsReached.into());
+        }
+
+        // fee account 가 deposit/reward account 와 겹치면 fee 가 원금/reward 회계에 섞임
+        let mut vault_token_accounts =
+            vec![deposit_token_account.key, farm_reward_token_account.key];
+        if let Some(farm_second_reward_token_account) = farm_second_reward_token_account {
+            vault_token_accounts.push(farm_second_reward_token_account.key);
+        }
+        for (i, key) in vault_token_accounts.iter().enumerate() {
+            if vault_token_accounts[..i].contains(key)
+                || *key == withdraw_fee_token_account.key
+                || *key == deposit_fee_token_account.key
+            {
+                return Err(GauntletError::InvalidAccount.into());
+            }
+        }
+        // vault 의 token 은 모두 vault.token_program 으로 옮기므로 같은 program 소유여야 함
+        let mut vault_token_account_infos = vec![deposit_token_account, farm_reward_token_account];
+        vault_token_account_infos.extend(farm_second_reward_token_account);
+        if vault_token_account_infos
+            .iter()
+            .any(|token_account| token_account.owner != token_program_account.key)
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
 
         let farm_reward_token_account_info =
-            Account::unpack(&farm_reward_token_account.data.borrow())?;
+            unpack_token_account(&farm_reward_token_account.data.borrow())?;
+        let deposit_token_account_info =
+            unpack_token_account(&deposit_token_account.data.borrow())?;
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        // 이미 pda 소유면 다른 vault 가 쓰고 있는 account, 같이 쓰면 total_deposit_amount 가 섞임
+        if deposit_token_account_info.owner == pda {
+            return Err(GauntletError::TokenAccountAlreadyUsed.into());
+        }
+        if deposit_token_account_info.owner != *initializer.key {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
         vault_info.is_initialized = true;
         vault_info.index = gauntlet_info.vaults_len;
         vault_info.status = Status::default();
         vault_info.fees = fees;
+        vault_info.min_deposit = config.min_deposit;
+        vault_info.min_harvest_interval = config.min_harvest_interval;
+        vault_info.swap_slippage_bps = config.swap_slippage_bps;
+        vault_info.max_swap_usdc_per_tx = config.max_swap_usdc_per_tx;
+        vault_info.compounding = config.compounding;
+        vault_info.min_swap_output = config.min_swap_output;
+        vault_info.max_pending_reward = config.max_pending_reward;
+        vault_info.has_second_reward = farm_second_reward_token_account.is_some();
+        vault_info.deposits_enabled = true;
+        vault_info.withdrawals_enabled = true;
+        // LP strategy token 은 기본으로 전부 재예치
+        vault_info.compound_ratio_bps = BPS_DENOMINATOR as u16;
+        vault_info.layout_version = VAULT_LAYOUT_VERSION;
         vault_info.gauntlet_state_account = *gauntlet_state_account.key;
         vault_info.deposit_token_account = *deposit_token_account.key;
+        vault_info.deposit_token_mint = deposit_token_account_info.mint;
         vault_info.reward_token_account = *farm_reward_token_account.key;
-        gauntlet_info.vaults_len = gauntlet_info.vaults_len.checked_add(1).unwrap();
+        vault_info.reward_token_mint = farm_reward_token_account_info.mint;
+        vault_info.token_program = *token_program_account.key;
+        gauntlet_info.vaults_len = gauntlet_info
+            .vaults_len
+            .checked_add(1)
+            .ok_or(GauntletError::MathOverflow)?;
 
         if farm_second_reward_token_account.is_some() {
             let farm_second_reward_token_account_unwrapped =
                 farm_second_reward_token_account.unwrap();
             let farm_second_reward_token_account_info =
-                Account::unpack(&farm_second_reward_token_account_unwrapped.data.borrow())?;
+                unpack_token_account(&farm_second_reward_token_account_unwrapped.data.borrow())?;
             if farm_second_reward_token_account_info.mint == farm_reward_token_account_info.mint {
                 // reward token 과 reward token b 가 같으면 에러
                 return Err(GauntletError::DuplicateFarmRewardToken.into());
             } else {
                 vault_info.reward_token_b_account = *farm_second_reward_token_account_unwrapped.key;
+                vault_info.reward_token_b_mint = farm_second_reward_token_account_info.mint;
             }
         }
+        gauntlet_info.track_mint(vault_info.deposit_token_mint)?;
+        gauntlet_info.track_mint(vault_info.reward_token_mint)?;
+        gauntlet_info.track_mint(vault_info.reward_token_b_mint)?;
         vault_info.withdraw_fee_account = *withdraw_fee_token_account.key;
+        vault_info.deposit_fee_account = *deposit_fee_token_account.key;
         vault_info.last_reward_update_time = 0;
         vault_info.total_deposit_amount = 0;
+        // raydium state account 는 InitVaultRaydiumState 에서 생성
+        vault_info.raydium_state_account = Pubkey::default();
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+
+        VaultStrategy::pack(
+            VaultStrategy::init(
+                *vault_state_account.key,
+                VaultStrategy::capacity_of(vault_strategy_account.data_len())?,
+            ),
+            &mut vault_strategy_account.data.borrow_mut(),
+        )?;
+
+        change_token_account_owner(
+            token_program_account.key,
+            deposit_token_account,
+            initializer,
+            &pda,
+        )?;
+
+        change_token_account_owner(
+            token_program_account.key,
+            farm_reward_token_account,
+            initializer,
+            &pda,
+        )?;
+
+        if farm_second_reward_token_account.is_some() {
+            change_token_account_owner(
+                token_program_account.key,
+                farm_second_reward_token_account.unwrap(),
+                initializer,
+                &pda,
+            )?;
+        }
+
+        // fee 는 collect_fees 로만 꺼낼 수 있게 pda 소유로 넘김, 두 fee account 가 같으면 한번만 넘어감
+        Self::take_fee_account_ownership(
+            token_program_account,
+            withdraw_fee_token_account,
+            initializer,
+            &pda,
+            &[],
+        )?;
+        Self::take_fee_account_ownership(
+            token_program_account,
+            deposit_fee_token_account,
+            initializer,
+            &pda,
+            &[*withdraw_fee_token_account.key],
+        )?;
+
+        Ok(())
+    }
+
+    /// fee token account 를 admin 에서 pda 소유로 넘김
+    /// 이미 pda 소유인 account 는 `registered_fee_accounts` 에 있을 때만 그대로 씀
+    /// (다른 vault 의 deposit account 같은 pda 소유 account 를 fee account 로 등록해서 꺼내가지 못하게)
+    fn take_fee_account_ownership<'a>(
+        token_program_account: &AccountInfo,
+        fee_token_account: &AccountInfo<'a>,
+        admin: &AccountInfo<'a>,
+        pda: &Pubkey,
+        registered_fee_accounts: &[Pubkey],
+    ) -> ProgramResult {
+        if fee_token_account.owner != token_program_account.key {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+        let fee_token_owner = unpack_token_account(&fee_token_account.data.borrow())?.owner;
+        if fee_token_owner == *pda && registered_fee_accounts.contains(fee_token_account.key) {
+            return Ok(());
+        }
+        if fee_token_owner != *admin.key {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+        change_token_account_owner(token_program_account.key, fee_token_account, admin, pda)
+    }
+
+    fn init_vault_raydium_state(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_account = next_account_info(account_info_iter)?;
+        let vault_raydium_state_account = next_account_info(account_info_iter)?;
+        let raydium_staking_program = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_system_program_id(system_program_account)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        // init_vault 가 먼저 실행되어야 함
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        if vault_info.raydium_state_account != Pubkey::default() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let vault_strategy_info = VaultStrategy::unpack(&vault_strategy_account.data.borrow())?;
+
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
         let (_pda, _seed) = Pubkey::find_program_address(
             &[
                 &gauntlet_state_account.key.to_bytes(),
@@ -163,12 +603,10 @@ impl Processor {
             return Err(ProgramError::InvalidSeeds);
         }
         // create raydium state account
-        let data_size = match farm_second_reward_token_account.is_some() {
-            true => 96,
-            false => 88,
-        };
+        let data_size =
+            Raydium::user_info_len(vault_info.reward_token_b_account != Pubkey::default());
         create_pda_account(
-            initializer,
+            admin,
             data_size,
             raydium_staking_program.key,
             system_program_account,
@@ -180,60 +618,46 @@ impl Processor {
                 &[_seed],
             ],
         )?;
+        if vault_raydium_state_account.data_len() != data_size {
+            return Err(GauntletError::RaydiumStateAccountSizeMismatch.into());
+        }
 
         vault_info.raydium_state_account = *vault_raydium_state_account.key;
         Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
-        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
-
-        let mut vault_strategy_info =
-            VaultStrategy::unpack_unchecked(&vault_strategy_account.data.borrow())?;
-
-        if vault_strategy_info.is_initialized() {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
-
-        vault_strategy_info = VaultStrategy::init(*vault_state_account.key);
-
-        VaultStrategy::pack(
-            vault_strategy_info,
-            &mut vault_strategy_account.data.borrow_mut(),
-        )?;
-
-        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"glt"], program_id);
-
-        change_token_account_owner(deposit_token_account, initializer, &pda)?;
-
-        change_token_account_owner(farm_reward_token_account, initializer, &pda)?;
-
-        if farm_second_reward_token_account.is_some() {
-            change_token_account_owner(
-                farm_second_reward_token_account.unwrap(),
-                initializer,
-                &pda,
-            )?;
-        }
 
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
         Ok(())
     }
 
-    fn init_strategy(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    fn init_strategy(
+        accounts: &[AccountInfo],
+        strategy_type: StrategyType,
+        deposit_cap: u64,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let admin = next_account_info(account_info_iter)?;
         let gauntlet_state_account = next_account_info(account_info_iter)?;
         let strategy_state_account = next_account_info(account_info_iter)?;
         let strategy_token_account = next_account_info(account_info_iter)?;
         let performance_fee_token_account = next_account_info(account_info_iter)?;
-        let _token_program_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
 
         if !admin.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        check_any_token_program_id(token_program_account)?;
+        if strategy_token_account.owner != token_program_account.key {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
 
         let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
 
         if gauntlet_info.admin != *admin.key {
             return Err(GauntletError::NotAdmin.into());
         }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
 
         let mut strategy_info = Strategy::unpack_unchecked(&strategy_state_account.data.borrow())?;
 
@@ -241,29 +665,65 @@ impl Processor {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
 
+        if gauntlet_info.strategies_len as usize >= MAX_NUMBER_OF_STRATEGY {
+            return Err(GauntletError::StrategyIdSizeError.into());
+        }
+
+        if performance_fee_token_account.key == strategy_token_account.key {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        let strategy_token_account_info =
+            unpack_token_account(&strategy_token_account.data.borrow())?;
+
         strategy_info = Strategy::init(
             gauntlet_info.strategies_len,
             *gauntlet_state_account.key,
             *admin.key,
             *performance_fee_token_account.key,
             *strategy_token_account.key,
+            strategy_type,
+            strategy_token_account_info.mint,
         );
-        gauntlet_info.strategies_len = gauntlet_info.strategies_len.checked_add(1).unwrap();
+        strategy_info.deposit_cap = deposit_cap;
+        gauntlet_info.track_mint(strategy_token_account_info.mint)?;
+        gauntlet_info.strategies_len = gauntlet_info
+            .strategies_len
+            .checked_add(1)
+            .ok_or(GauntletError::MathOverflow)?;
 
         Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
         Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
 
-        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"glt"], program_id); // TODO CHANGE
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
 
-        change_token_account_owner(strategy_token_account, admin, &pda)?;
+        change_token_account_owner(
+            token_program_account.key,
+            strategy_token_account,
+            admin,
+            &pda,
+        )?;
+        Self::take_fee_account_ownership(
+            token_program_account,
+            performance_fee_token_account,
+            admin,
+            &pda,
+            &[],
+        )?;
 
         Ok(())
     }
 
+    /// availability 가 false 인 동안 accumulated_reward_per_shares 는 움직이지 않음
+    /// (harvest 분배에서 빠지고 swap / deposit 도 막힘) 따라서 다시 켤 때 reward_debt 를 다시 잡을 필요가 없고
+    /// 꺼져 있던 기간에 대해서는 어떤 user 도 reward 를 더 받거나 잃지 않음
+    /// 꺼지기 전에 harvest 된 reward_token_remain / usdc 잔량은 다시 켜진 뒤 swap 될 때 그 시점 예치자에게 분배됨
     fn update_vault_strategy(
         accounts: &[AccountInfo],
         availability: bool,
         needs_usdc_pool: bool,
+        admin_nonce: u64,
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let admin = next_account_info(account_info_iter)?;
@@ -276,52 +736,1936 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        check_account_owner(gauntlet_state_account, program_id)?;
+        check_account_owner(vault_strategy_state_account, program_id)?;
+        check_account_owner(vault_state_account, program_id)?;
+        check_account_owner(strategy_state_account, program_id)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+        let mut vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        let strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+
+        if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        if *vault_state_account.key != vault_strategy_info.vault_account {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        if *gauntlet_state_account.key != strategy_info.gauntlet_state_account {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
+        if strategy_index >= vault_strategy_info.capacity {
+            return Err(GauntletError::VaultStrategyCapacityExceeded.into());
+        }
+        if availability && strategy_info.status == Status::RETIRED {
+            return Err(GauntletError::StrategyRetired.into());
+        }
+        if vault_strategy_info.needs_usdc_pools[strategy_index] != needs_usdc_pool
+            && (vault_info.harvest_cursor != 0
+                || vault_info.reward_token_remain_amounts[strategy_index] > 0
+                || vault_info.reward_token_b_remain_amounts[strategy_index] > 0
+                || vault_info.usdc_token_amounts[strategy_index] > 0)
+        {
+            // swap 도중에 경로가 바뀌면 남은 reward/usdc 와 진행 중인 user 가 갈 곳이 없어짐
+            return Err(GauntletError::SwapInFlight.into());
+        }
+        vault_strategy_info.needs_usdc_pools[strategy_index] = needs_usdc_pool;
+        // strategy token 이 vault 의 deposit LP 면 첫 재예치 전부터 compounding 으로 표시
+        vault_strategy_info.compounding[strategy_index] =
+            strategy_info.strategy_token_mint == vault_info.deposit_token_mint;
+        let availability_changed =
+            vault_strategy_info.availabilities[strategy_index] != availability;
+        vault_strategy_info.availabilities[strategy_index] = availability;
+        if availability_changed && availability {
+            msg!(
+                "Strategy {} re-enabled, accumulated reward per share carried over: {}",
+                strategy_index,
+                vault_info.accumulated_reward_per_shares[strategy_index]
+            );
+        }
+        // 같은 값으로 다시 호출되면 total 을 두번 더하거나 빼게 되므로 flag 가 바뀔 때만 반영
+        if availability_changed && vault_info.deposit_amounts[strategy_index] > 0 {
+            // flag 에 따라서 valid 한 total deposit amount를 설정해줌
+            if availability {
+                vault_info.total_deposit_amount = vault_info
+                    .total_deposit_amount
+                    .checked_add(vault_info.deposit_amounts[strategy_index])
+                    .ok_or(GauntletError::MathOverflow)?;
+            } else {
+                vault_info.total_deposit_amount = vault_info
+                    .total_deposit_amount
+                    .checked_sub(vault_info.deposit_amounts[strategy_index])
+                    .ok_or(GauntletError::MathOverflow)?;
+            }
+        }
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+
+        VaultStrategy::pack(
+            vault_strategy_info,
+            &mut vault_strategy_state_account.data.borrow_mut(),
+        )?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_accepting_deposits(
+        accounts: &[AccountInfo],
+        accepting_deposits: bool,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_account_owner(gauntlet_state_account, program_id)?;
+        check_account_owner(vault_strategy_state_account, program_id)?;
+        check_account_owner(vault_state_account, program_id)?;
+        check_account_owner(strategy_state_account, program_id)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+        let mut vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        let strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+        let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        if strategy_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
+        if strategy_index >= vault_strategy_info.capacity {
+            return Err(GauntletError::VaultStrategyCapacityExceeded.into());
+        }
+        // total_deposit_amount 는 건드리지 않음 (기존 예치금은 계속 reward 분배에 포함)
+        vault_strategy_info.accepting_deposits[strategy_index] = accepting_deposits;
+
+        VaultStrategy::pack(
+            vault_strategy_info,
+            &mut vault_strategy_state_account.data.borrow_mut(),
+        )?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+    fn sweep_tokens(
+        accounts: &[AccountInfo],
+        amount: u64,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let source_token_account = next_account_info(account_info_iter)?;
+        let destination_token_account = next_account_info(account_info_iter)?;
+        let gauntlet_signer_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_any_token_program_id(token_program_account)?;
+        check_account_owner(source_token_account, token_program_account.key)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        if *gauntlet_signer_account.key != pda {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
+
+        // 회계에 잡혀있는 mint (usdc, vault deposit/reward, strategy token) 는 sweep 할 수 없음
+        let source_token_account_info = unpack_token_account(&source_token_account.data.borrow())?;
+        if gauntlet_info.is_tracked_mint(&source_token_account_info.mint) {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        transfer_token_signed(
+            token_program_account.key,
+            source_token_account,
+            destination_token_account,
+            gauntlet_signer_account,
+            amount,
+            &[authority_seeds],
+        )?;
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// sweep_tokens 와 달리 회계에 잡혀있는 fee account 에서만 꺼낼 수 있음
+    fn collect_fees(
+        accounts: &[AccountInfo],
+        amount: u64,
+        admin_nonce: u64,
+        fee_source: FeeSource,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let fee_state_account = next_account_info(account_info_iter)?;
+        let fee_token_account = next_account_info(account_info_iter)?;
+        let destination_token_account = next_account_info(account_info_iter)?;
+        let gauntlet_signer_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_any_token_program_id(token_program_account)?;
+        check_account_owner(fee_token_account, token_program_account.key)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        if *gauntlet_signer_account.key != pda {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
+
+        // vault 면 withdraw / deposit fee, strategy 면 performance / strategist fee account
+        let fee_accounts = match fee_source {
+            FeeSource::VAULT => {
+                let vault_info = Vault::try_from_account(fee_state_account, program_id)?;
+                if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+                    return Err(GauntletError::WrongVaultStateAccount.into());
+                }
+                if *token_program_account.key != vault_info.token_program {
+                    return Err(GauntletError::InvalidProgramId.into());
+                }
+                vec![
+                    vault_info.withdraw_fee_account,
+                    vault_info.deposit_fee_account,
+                ]
+            }
+            FeeSource::STRATEGY => {
+                let strategy_info = Strategy::try_from_account(fee_state_account, program_id)?;
+                if strategy_info.gauntlet_state_account != *gauntlet_state_account.key {
+                    return Err(GauntletError::WrongStrategyStateAccount.into());
+                }
+                vec![
+                    strategy_info.performance_fee_account,
+                    strategy_info.strategist_fee_account,
+                ]
+            }
+        };
+        if *fee_token_account.key == Pubkey::default()
+            || !fee_accounts.contains(fee_token_account.key)
+        {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+
+        let fee_token_account_info = unpack_token_account(&fee_token_account.data.borrow())?;
+        let destination_token_account_info =
+            unpack_token_account(&destination_token_account.data.borrow())?;
+        if fee_token_account_info.mint != destination_token_account_info.mint {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        transfer_token_signed(
+            token_program_account.key,
+            fee_token_account,
+            destination_token_account,
+            gauntlet_signer_account,
+            amount,
+            &[authority_seeds],
+        )?;
+        msg!(
+            "Collected {} fees from {} to {}",
+            amount,
+            fee_token_account.key,
+            destination_token_account.key
+        );
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn grow_vault_strategy(
+        accounts: &[AccountInfo],
+        new_capacity: u8,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let new_vault_strategy_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_account_owner(gauntlet_state_account, program_id)?;
+        check_account_owner(vault_state_account, program_id)?;
+        check_account_owner(vault_strategy_state_account, program_id)?;
+        check_account_owner(new_vault_strategy_state_account, program_id)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        let mut vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        if *vault_state_account.key != vault_strategy_info.vault_account {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        // 새 account 는 admin 이 new_capacity 크기로 만들어 둔 빈 account 여야 함
+        let new_vault_strategy_info =
+            VaultStrategy::unpack_unchecked(&new_vault_strategy_state_account.data.borrow())?;
+        if new_vault_strategy_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if new_vault_strategy_info.capacity != new_capacity as usize
+            || new_vault_strategy_info.capacity <= vault_strategy_info.capacity
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !Rent::get()?.is_exempt(
+            new_vault_strategy_state_account.lamports(),
+            new_vault_strategy_state_account.data_len(),
+        ) {
+            return Err(GauntletError::NotRentExempt.into());
+        }
+
+        // 메모리에서는 이미 MAX_NUMBER_OF_STRATEGY 칸이라 늘어난 칸은 init 값 그대로 저장됨
+        vault_strategy_info.capacity = new_vault_strategy_info.capacity;
+        VaultStrategy::pack(
+            vault_strategy_info,
+            &mut new_vault_strategy_state_account.data.borrow_mut(),
+        )?;
+
+        // 예전 account 는 닫아서 다시 쓰이지 않게 함
+        close_program_account(vault_strategy_state_account, admin)?;
+        msg!(
+            "Vault strategy moved from {} to {} with capacity {}",
+            vault_strategy_state_account.key,
+            new_vault_strategy_state_account.key,
+            new_capacity
+        );
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn migrate_vault(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let v1_vault_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let v1_vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let legacy_authority = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+        let deposit_token_account = next_account_info(account_info_iter)?;
+        let farm_reward_token_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_token_program_id(token_program_account)?;
+
+        check_account_owner(gauntlet_state_account, program_id)?;
+        check_account_owner(v1_vault_state_account, program_id)?;
+        check_account_owner(vault_state_account, program_id)?;
+        check_account_owner(v1_vault_strategy_state_account, program_id)?;
+        check_account_owner(vault_strategy_state_account, program_id)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        // v1 크기가 아니거나 이미 닫힌 account 면 여기서 실패하므로 두 번 migrate 할 수 없음
+        let mut vault_info = Vault::migrate_from_v1(&v1_vault_state_account.data.borrow())?;
+        // v1 vault 는 v1 gauntlet 에 속하므로 MigrateGauntlet 이 먼저 실행되어야 함
+        if gauntlet_info.migrated_from == Pubkey::default()
+            || vault_info.gauntlet_state_account != gauntlet_info.migrated_from
+        {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        // 새 vault strategy 의 capacity 는 admin 이 만든 account 크기로 정함
+        let mut vault_strategy_info = VaultStrategy::migrate_from_v1(
+            &v1_vault_strategy_state_account.data.borrow(),
+            VaultStrategy::capacity_of(vault_strategy_state_account.data_len())?,
+        )?;
+        if vault_strategy_info.vault_account != *v1_vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        // 새 account 들은 admin 이 Vault::LEN / VaultStrategy::packed_len 크기로 만들어 둔 빈 account 여야 함
+        if Vault::unpack_unchecked(&vault_state_account.data.borrow())?.is_initialized()
+            || VaultStrategy::unpack_unchecked(&vault_strategy_state_account.data.borrow())?
+                .is_initialized()
+        {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let rent = Rent::get()?;
+        for new_account in [vault_state_account, vault_strategy_state_account] {
+            if !rent.is_exempt(new_account.lamports(), new_account.data_len()) {
+                return Err(GauntletError::NotRentExempt.into());
+            }
+        }
+
+        let (authority, _bump_seed) =
+            find_authority_address(gauntlet_state_account.key, program_id);
+        let (legacy_authority_address, legacy_bump_seed) =
+            find_legacy_authority_address(program_id);
+        if *legacy_authority.key != legacy_authority_address {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let legacy_authority_seeds: &[&[u8]] = &[AUTHORITY_SEED, &[legacy_bump_seed]];
+
+        // v1 에는 mint 가 없어서 vault token account 에서 읽음
+        let mut vault_token_accounts = vec![
+            (deposit_token_account, vault_info.deposit_token_account),
+            (farm_reward_token_account, vault_info.reward_token_account),
+        ];
+        if vault_info.has_second_reward {
+            vault_token_accounts.push((
+                next_account_info(account_info_iter)?,
+                vault_info.reward_token_b_account,
+            ));
+        }
+        let mut mints = vec![];
+        for (token_account, expected) in vault_token_accounts {
+            if *token_account.key != expected {
+                return Err(GauntletError::InvalidAccount.into());
+            }
+            mints.push(unpack_token_account(&token_account.data.borrow())?.mint);
+            Self::_hand_over_token_account(
+                token_account,
+                legacy_authority,
+                &authority,
+                legacy_authority_seeds,
+            )?;
+        }
+        vault_info.deposit_token_mint = mints[0];
+        vault_info.reward_token_mint = mints[1];
+        if let Some(reward_token_b_mint) = mints.get(2) {
+            vault_info.reward_token_b_mint = *reward_token_b_mint;
+        }
+        gauntlet_info.track_mint(vault_info.deposit_token_mint)?;
+        gauntlet_info.track_mint(vault_info.reward_token_mint)?;
+        gauntlet_info.track_mint(vault_info.reward_token_b_mint)?;
+
+        // raydium ledger 는 아직 v1 authority 소유, MigrateVaultStake 에서 옮김
+        vault_info.gauntlet_state_account = *gauntlet_state_account.key;
+        vault_info.migrated_from = *v1_vault_state_account.key;
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        vault_strategy_info.vault_account = *vault_state_account.key;
+        VaultStrategy::pack(
+            vault_strategy_info,
+            &mut vault_strategy_state_account.data.borrow_mut(),
+        )?;
+        close_program_account(v1_vault_state_account, admin)?;
+        close_program_account(v1_vault_strategy_state_account, admin)?;
+        msg!(
+            "Vault {} migrated to {}",
+            v1_vault_state_account.key,
+            vault_state_account.key
+        );
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// v1 gauntlet 을 새 account 로 옮김, v1 에는 nonce 가 없어서 v1 admin 의 서명만 확인
+    fn migrate_gauntlet(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let v1_gauntlet_state_account = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let legacy_authority = next_account_info(account_info_iter)?;
+        let usdc_token_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_token_program_id(token_program_account)?;
+
+        check_account_owner(v1_gauntlet_state_account, program_id)?;
+        check_account_owner(gauntlet_state_account, program_id)?;
+
+        let (authority, bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        let mut gauntlet_info =
+            Gauntlet::migrate_from_v1(&v1_gauntlet_state_account.data.borrow(), bump_seed)?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+
+        if Gauntlet::unpack_unchecked(&gauntlet_state_account.data.borrow())?.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if !Rent::get()?.is_exempt(
+            gauntlet_state_account.lamports(),
+            gauntlet_state_account.data_len(),
+        ) {
+            return Err(GauntletError::NotRentExempt.into());
+        }
+
+        if gauntlet_info.usdc_token_account != *usdc_token_account.key {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let (legacy_authority_address, legacy_bump_seed) =
+            find_legacy_authority_address(program_id);
+        if *legacy_authority.key != legacy_authority_address {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        let usdc_token_account_info = unpack_token_account(&usdc_token_account.data.borrow())?;
+        gauntlet_info.track_mint(usdc_token_account_info.mint)?;
+        gauntlet_info.migrated_from = *v1_gauntlet_state_account.key;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+
+        Self::_hand_over_token_account(
+            usdc_token_account,
+            legacy_authority,
+            &authority,
+            &[AUTHORITY_SEED, &[legacy_bump_seed]],
+        )?;
+        close_program_account(v1_gauntlet_state_account, admin)?;
+        msg!(
+            "Gauntlet {} migrated to {}",
+            v1_gauntlet_state_account.key,
+            gauntlet_state_account.key
+        );
+        Ok(())
+    }
+
+    fn migrate_strategy(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let v1_strategy_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+        let legacy_authority = next_account_info(account_info_iter)?;
+        let strategy_token_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_token_program_id(token_program_account)?;
+
+        check_account_owner(gauntlet_state_account, program_id)?;
+        check_account_owner(v1_strategy_state_account, program_id)?;
+        check_account_owner(strategy_state_account, program_id)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut strategy_info =
+            Strategy::migrate_from_v1(&v1_strategy_state_account.data.borrow())?;
+        if gauntlet_info.migrated_from == Pubkey::default()
+            || strategy_info.gauntlet_state_account != gauntlet_info.migrated_from
+        {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        if Strategy::unpack_unchecked(&strategy_state_account.data.borrow())?.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if !Rent::get()?.is_exempt(
+            strategy_state_account.lamports(),
+            strategy_state_account.data_len(),
+        ) {
+            return Err(GauntletError::NotRentExempt.into());
+        }
+
+        if strategy_info.strategy_token_account != *strategy_token_account.key {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let (authority, _bump_seed) =
+            find_authority_address(gauntlet_state_account.key, program_id);
+        let (legacy_authority_address, legacy_bump_seed) =
+            find_legacy_authority_address(program_id);
+        if *legacy_authority.key != legacy_authority_address {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        // v1 에는 mint 가 없어서 strategy token account 에서 읽음
+        strategy_info.strategy_token_mint =
+            unpack_token_account(&strategy_token_account.data.borrow())?.mint;
+        gauntlet_info.track_mint(strategy_info.strategy_token_mint)?;
+        strategy_info.gauntlet_state_account = *gauntlet_state_account.key;
+        strategy_info.migrated_from = *v1_strategy_state_account.key;
+        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
+
+        Self::_hand_over_token_account(
+            strategy_token_account,
+            legacy_authority,
+            &authority,
+            &[AUTHORITY_SEED, &[legacy_bump_seed]],
+        )?;
+        close_program_account(v1_strategy_state_account, admin)?;
+        msg!(
+            "Strategy {} migrated to {}",
+            v1_strategy_state_account.key,
+            strategy_state_account.key
+        );
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// migrate 된 vault 의 raydium stake 를 v1 authority 의 ledger 에서 이 gauntlet authority 의 새 ledger 로 옮김
+    fn migrate_vault_stake(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let legacy_authority = next_account_info(account_info_iter)?;
+        let vault_raydium_state_account = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_system_program_id(system_program_account)?;
+
+        check_account_owner(gauntlet_state_account, program_id)?;
+        check_account_owner(vault_state_account, program_id)?;
+        check_account_owner(vault_strategy_state_account, program_id)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key
+            || vault_info.migrated_from == Pubkey::default()
+        {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        let vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+        if vault_info.harvest_cursor != 0 {
+            return Err(GauntletError::HarvestInProgress.into());
+        }
+
+        let has_second_reward = vault_info.has_second_reward;
+        let staking_accounts = match has_second_reward {
+            false => next_account_infos(account_info_iter, 11)?,
+            true => next_account_infos(account_info_iter, 13)?,
+        };
+        check_staking_program_id(&staking_accounts[0])?;
+
+        let (raydium_state_address, raydium_state_bump) = Pubkey::find_program_address(
+            &[
+                &gauntlet_state_account.key.to_bytes(),
+                &vault_state_account.key.to_bytes(),
+                &vault_strategy_state_account.key.to_bytes(),
+            ],
+            program_id,
+        );
+        if *vault_raydium_state_account.key != raydium_state_address {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if vault_info.raydium_state_account == raydium_state_address {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        // staking accounts 는 v1 ledger 와 이 gauntlet 의 authority 로 넘김
+        if *staking_accounts[3].key != vault_info.raydium_state_account {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
+        let authority = Pubkey::create_program_address(authority_seeds, program_id)
+            .map_err(|_| GauntletError::InvalidAccount)?;
+        if *staking_accounts[4].key != authority {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let (legacy_authority_address, legacy_bump_seed) =
+            find_legacy_authority_address(program_id);
+        if *legacy_authority.key != legacy_authority_address {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        let legacy_bump = [legacy_bump_seed];
+        let legacy_authority_seeds: &[&[u8]] = &[AUTHORITY_SEED, &legacy_bump];
+        if *staking_accounts[5].key != vault_info.deposit_token_account
+            || *staking_accounts[7].key != vault_info.reward_token_account
+            || (has_second_reward && *staking_accounts[11].key != vault_info.reward_token_b_account)
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        let reward_balance = |index: usize| -> Result<u64, ProgramError> {
+            Ok(unpack_token_account(&staking_accounts[index].data.borrow())?.amount)
+        };
+        let before_reward_token_balance = reward_balance(7)?;
+        let before_reward_b_token_balance = match has_second_reward {
+            true => reward_balance(11)?,
+            false => 0,
+        };
+
+        // v1 authority 로 예전 ledger 에서 전부 빼고, 새 ledger 를 만들어 이 gauntlet authority 로 다시 예치
+        let amount = vault_info.total_deposit_amount;
+        let mut legacy_staking_accounts = staking_accounts.to_vec();
+        legacy_staking_accounts[4] = legacy_authority.clone();
+        match has_second_reward {
+            false => Raydium::raydium_withdraw(
+                &legacy_staking_accounts,
+                amount,
+                &[legacy_authority_seeds],
+            )?,
+            true => Raydium::raydium_withdraw_v4(
+                &legacy_staking_accounts,
+                amount,
+                &[legacy_authority_seeds],
+            )?,
+        }
+        // 이미 할당된 ledger 면 다시 만들지 않음
+        if vault_raydium_state_account.owner != staking_accounts[0].key {
+            create_pda_account(
+                admin,
+                Raydium::user_info_len(has_second_reward),
+                staking_accounts[0].key,
+                system_program_account,
+                vault_raydium_state_account,
+                &[
+                    &gauntlet_state_account.key.to_bytes(),
+                    &vault_state_account.key.to_bytes(),
+                    &vault_strategy_state_account.key.to_bytes(),
+                    &[raydium_state_bump],
+                ],
+            )?;
+        } else if vault_raydium_state_account.data_len()
+            != Raydium::user_info_len(has_second_reward)
+        {
+            return Err(GauntletError::RaydiumStateAccountSizeMismatch.into());
+        }
+        let mut new_staking_accounts = staking_accounts.to_vec();
+        new_staking_accounts[3] = vault_raydium_state_account.clone();
+        match has_second_reward {
+            false => Raydium::raydium_deposit(&new_staking_accounts, amount, &[authority_seeds])?,
+            true => Raydium::raydium_deposit_v4(&new_staking_accounts, amount, &[authority_seeds])?,
+        }
+
+        // unstake 하면서 받은 reward 는 harvest 와 같이 strategy 들에 배분
+        let reward_token_harvest_amount = reward_balance(7)?
+            .checked_sub(before_reward_token_balance)
+            .ok_or(GauntletError::AccountingDesync)?;
+        let reward_b_token_harvest_amount = match has_second_reward {
+            true => reward_balance(11)?
+                .checked_sub(before_reward_b_token_balance)
+                .ok_or(GauntletError::AccountingDesync)?,
+            false => 0,
+        };
+        vault_info.raydium_state_account = raydium_state_address;
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        {
+            let mut vault_data = vault_state_account.data.borrow_mut();
+            let mut vault_ref = VaultRef::new(&mut vault_data[..])?;
+            vault_ref.set_harvest_pending_amount(reward_token_harvest_amount);
+            vault_ref.set_harvest_pending_b_amount(reward_b_token_harvest_amount);
+            Self::_distribute_harvest(
+                &mut vault_ref,
+                &vault_strategy_info,
+                0,
+                (gauntlet_info.strategies_len as usize).min(MAX_NUMBER_OF_STRATEGY),
+            )?;
+            vault_ref.set_harvest_pending_amount(0);
+            vault_ref.set_harvest_pending_b_amount(0);
+        }
+        msg!(
+            "Vault {} stake moved to {}",
+            vault_state_account.key,
+            vault_raydium_state_account.key
+        );
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// v1 user 를 migrate 된 vault / strategy 의 user PDA 로 옮김, 누구나 실행할 수 있고 v1 rent 는 user 에게 돌려줌
+    fn migrate_user(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+        let v1_user_state_account = next_account_info(account_info_iter)?;
+        let user_state_account = next_account_info(account_info_iter)?;
+        let user = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+
+        if !payer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_system_program_id(system_program_account)?;
+
+        check_account_owner(vault_state_account, program_id)?;
+        check_account_owner(strategy_state_account, program_id)?;
+        check_account_owner(v1_user_state_account, program_id)?;
+
+        let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+        let mut user_info = User::migrate_from_v1(&v1_user_state_account.data.borrow())?;
+
+        if user_info.user != *user.key {
+            return Err(GauntletError::WrongUserAccount.into());
+        }
+        if vault_info.migrated_from == Pubkey::default()
+            || user_info.vault_account != vault_info.migrated_from
+        {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        if strategy_info.migrated_from == Pubkey::default()
+            || user_info.strategy_account != strategy_info.migrated_from
+        {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        let (user_address, bump_seed) = find_user_address(
+            vault_state_account.key,
+            user.key,
+            strategy_state_account.key,
+            program_id,
+        );
+        if *user_state_account.key != user_address {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        // 이미 할당된 account 면 다시 만들지 않지만, 초기화된 user 는 덮어쓰지 않음
+        if user_state_account.owner != program_id {
+            create_pda_account(
+                payer,
+                User::LEN,
+                program_id,
+                system_program_account,
+                user_state_account,
+                &[
+                    &vault_state_account.key.to_bytes(),
+                    &user.key.to_bytes(),
+                    &strategy_state_account.key.to_bytes(),
+                    &[bump_seed],
+                ],
+            )?;
+        } else if user_state_account.data_len() != User::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        } else if User::unpack_unchecked(&user_state_account.data.borrow())?.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        user_info.vault_account = *vault_state_account.key;
+        user_info.strategy_account = *strategy_state_account.key;
+        User::pack(user_info, &mut user_state_account.data.borrow_mut())?;
+        close_program_account(v1_user_state_account, user)?;
+        Ok(())
+    }
+
+    /// v1 authority 가 소유한 token account 를 이 gauntlet 의 authority 로 넘김, 이미 넘어갔으면 그대로 둠
+    fn _hand_over_token_account<'a>(
+        token_account: &AccountInfo<'a>,
+        legacy_authority: &AccountInfo<'a>,
+        authority: &Pubkey,
+        legacy_authority_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let owner = unpack_token_account(&token_account.data.borrow())?.owner;
+        if owner == *authority {
+            return Ok(());
+        }
+        if owner != *legacy_authority.key {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+        change_token_account_owner_signed(
+            token_account,
+            legacy_authority,
+            authority,
+            &[legacy_authority_seeds],
+        )
+    }
+
+    fn add_keeper(accounts: &[AccountInfo], admin_nonce: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let keeper = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let keepers_len = gauntlet_info.keepers_len as usize;
+        if gauntlet_info.keepers[..keepers_len].contains(keeper.key) {
+            return Err(GauntletError::KeeperAlreadyAdded.into());
+        }
+        if keepers_len >= MAX_KEEPERS {
+            return Err(GauntletError::KeeperListFull.into());
+        }
+        gauntlet_info.keepers[keepers_len] = *keeper.key;
+        gauntlet_info.keepers_len = gauntlet_info
+            .keepers_len
+            .checked_add(1)
+            .ok_or(GauntletError::MathOverflow)?;
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn remove_keeper(accounts: &[AccountInfo], admin_nonce: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let keeper = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let keepers_len = gauntlet_info.keepers_len as usize;
+        let position = gauntlet_info.keepers[..keepers_len]
+            .iter()
+            .position(|k| k == keeper.key)
+            .ok_or(GauntletError::KeeperNotFound)?;
+        // 마지막 keeper 를 빈 자리로 옮김
+        gauntlet_info.keepers[position] = gauntlet_info.keepers[keepers_len - 1];
+        gauntlet_info.keepers[keepers_len - 1] = Pubkey::default();
+        gauntlet_info.keepers_len = gauntlet_info
+            .keepers_len
+            .checked_sub(1)
+            .ok_or(GauntletError::MathOverflow)?;
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_strategy_deposit_cap(
+        accounts: &[AccountInfo],
+        deposit_cap: u64,
+        admin_nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+        if strategy_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        // cap 을 현재 예치량보다 낮춰도 기존 예치는 그대로, 새 deposit 만 막힘
+        strategy_info.deposit_cap = deposit_cap;
+        msg!(
+            "Strategy {} deposit cap set to {}",
+            strategy_state_account.key,
+            deposit_cap
+        );
+
+        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn retire_strategy(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+        if strategy_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+        if strategy_info.status == Status::RETIRED {
+            return Err(GauntletError::StrategyRetired.into());
+        }
+        if strategy_info.total_deposit_amount != 0 {
+            return Err(GauntletError::AccountNotEmpty.into());
+        }
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
+
+        let vaults_len = gauntlet_info.vaults_len as usize;
+        let vault_state_accounts = next_account_infos(account_info_iter, vaults_len)?;
+        let vault_strategy_state_accounts = next_account_infos(account_info_iter, vaults_len)?;
+        // 모든 vault 의 slot 을 정리해야 하므로 vault 0 부터 순서대로 받음
+        for (i, (vault_state_account, vault_strategy_state_account)) in vault_state_accounts
+            .iter()
+            .zip(vault_strategy_state_accounts.iter())
+            .enumerate()
+        {
+            check_account_owner(vault_state_account, program_id)?;
+            check_account_owner(vault_strategy_state_account, program_id)?;
+            let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+            if vault_info.gauntlet_state_account != *gauntlet_state_account.key
+                || vault_info.index as usize != i
+            {
+                msg!("vault state account {} is out of order", i);
+                return Err(GauntletError::WrongVaultStateAccount.into());
+            }
+            let mut vault_strategy_info =
+                VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+            if vault_strategy_info.vault_account != *vault_state_account.key {
+                return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+            }
+            if vault_info.deposit_amounts[strategy_index] != 0 {
+                msg!("vault {} still has deposits in the strategy", i);
+                return Err(GauntletError::AccountNotEmpty.into());
+            }
+            if vault_info.reward_token_remain_amounts[strategy_index] > 0
+                || vault_info.reward_token_b_remain_amounts[strategy_index] > 0
+                || vault_info.usdc_token_amounts[strategy_index] > 0
+            {
+                msg!("vault {} still has rewards waiting to be swapped", i);
+                return Err(GauntletError::SwapInFlight.into());
+            }
+            vault_strategy_info.availabilities[strategy_index] = false;
+            vault_strategy_info.accepting_deposits[strategy_index] = false;
+            VaultStrategy::pack(
+                vault_strategy_info,
+                &mut vault_strategy_state_account.data.borrow_mut(),
+            )?;
+        }
+
+        strategy_info.status = Status::RETIRED;
+        strategy_info.deposit_amounts = vec![0; MAX_NUMBER_OF_VAULTS];
+        msg!("Strategy {} retired", strategy_state_account.key);
+
+        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_vault_fee_accounts(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let withdraw_fee_token_account = next_account_info(account_info_iter)?;
+        let deposit_fee_token_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::try_from_account(vault_state_account, program_id)?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        if *token_program_account.key != vault_info.token_program {
+            return Err(GauntletError::InvalidProgramId.into());
+        }
+
+        // init_vault 와 같은 이유로 fee account 가 vault 의 token account 와 겹치면 안됨
+        let vault_token_accounts = [
+            vault_info.deposit_token_account,
+            vault_info.reward_token_account,
+            vault_info.reward_token_b_account,
+        ];
+        for fee_token_account in [withdraw_fee_token_account, deposit_fee_token_account] {
+            if fee_token_account.owner != token_program_account.key
+                || vault_token_accounts.contains(fee_token_account.key)
+            {
+                return Err(GauntletError::WrongFeeAccount.into());
+            }
+            let fee_token_account_info = unpack_token_account(&fee_token_account.data.borrow())?;
+            if fee_token_account_info.mint != vault_info.deposit_token_mint {
+                return Err(GauntletError::WrongFeeAccount.into());
+            }
+        }
+
+        // 지금 등록된 fee account 는 이미 pda 소유이므로 그대로 다시 쓸 수 있음
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        let registered_fee_accounts = [
+            vault_info.withdraw_fee_account,
+            vault_info.deposit_fee_account,
+            *withdraw_fee_token_account.key,
+        ];
+        Self::take_fee_account_ownership(
+            token_program_account,
+            withdraw_fee_token_account,
+            admin,
+            &pda,
+            &registered_fee_accounts[..2],
+        )?;
+        Self::take_fee_account_ownership(
+            token_program_account,
+            deposit_fee_token_account,
+            admin,
+            &pda,
+            &registered_fee_accounts,
+        )?;
+
+        vault_info.withdraw_fee_account = *withdraw_fee_token_account.key;
+        vault_info.deposit_fee_account = *deposit_fee_token_account.key;
+        msg!(
+            "Vault {} fee accounts set to withdraw {} deposit {}",
+            vault_state_account.key,
+            withdraw_fee_token_account.key,
+            deposit_fee_token_account.key
+        );
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_strategy_fee_account(
+        accounts: &[AccountInfo],
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+        let performance_fee_token_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_any_token_program_id(token_program_account)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut strategy_info = Strategy::try_from_account(strategy_state_account, program_id)?;
+        if strategy_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        if performance_fee_token_account.owner != token_program_account.key
+            || *performance_fee_token_account.key == strategy_info.strategy_token_account
+        {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+        let performance_fee_token_account_info =
+            unpack_token_account(&performance_fee_token_account.data.borrow())?;
+        if performance_fee_token_account_info.mint != strategy_info.strategy_token_mint {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        Self::take_fee_account_ownership(
+            token_program_account,
+            performance_fee_token_account,
+            admin,
+            &pda,
+            &[
+                strategy_info.performance_fee_account,
+                strategy_info.strategist_fee_account,
+            ],
+        )?;
+
+        strategy_info.performance_fee_account = *performance_fee_token_account.key;
+        msg!(
+            "Strategy {} performance fee account set to {}",
+            strategy_state_account.key,
+            performance_fee_token_account.key
+        );
+
+        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_strategist_fee(
+        accounts: &[AccountInfo],
+        strategist_fee_split_bps: u16,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+        let strategist_fee_token_account = next_account_info(account_info_iter)?;
+        let token_program_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_any_token_program_id(token_program_account)?;
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut strategy_info = Strategy::try_from_account(strategy_state_account, program_id)?;
+        if strategy_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        if strategist_fee_token_account.owner != token_program_account.key
+            || *strategist_fee_token_account.key == strategy_info.strategy_token_account
+        {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+        let strategist_fee_token_account_info =
+            unpack_token_account(&strategist_fee_token_account.data.borrow())?;
+        if strategist_fee_token_account_info.mint != strategy_info.strategy_token_mint {
+            return Err(GauntletError::WrongFeeAccount.into());
+        }
+
+        let (pda, _bump_seed) = find_authority_address(gauntlet_state_account.key, program_id);
+        Self::take_fee_account_ownership(
+            token_program_account,
+            strategist_fee_token_account,
+            admin,
+            &pda,
+            &[
+                strategy_info.performance_fee_account,
+                strategy_info.strategist_fee_account,
+            ],
+        )?;
+
+        strategy_info.strategist_fee_split_bps = strategist_fee_split_bps;
+        strategy_info.strategist_fee_account = *strategist_fee_token_account.key;
+        msg!(
+            "Strategy {} strategist fee set to {} bps to {}",
+            strategy_state_account.key,
+            strategist_fee_split_bps,
+            strategist_fee_token_account.key
+        );
+
+        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_global_pause(accounts: &[AccountInfo], paused: bool, admin_nonce: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        gauntlet_info.global_paused = paused;
+        msg!("Gauntlet global pause set to {}", paused);
+
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_vault_flags(
+        accounts: &[AccountInfo],
+        deposits_enabled: bool,
+        withdrawals_enabled: bool,
+        admin_nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        vault_info.deposits_enabled = deposits_enabled;
+        vault_info.withdrawals_enabled = withdrawals_enabled;
+        msg!(
+            "Vault {} deposits enabled {} withdrawals enabled {}",
+            vault_state_account.key,
+            deposits_enabled,
+            withdrawals_enabled
+        );
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_vault_keeper_fee(
+        accounts: &[AccountInfo],
+        keeper_fee_numerator: u64,
+        keeper_fee_denominator: u64,
+        admin_nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        vault_info.keeper_fee_numerator = keeper_fee_numerator;
+        vault_info.keeper_fee_denominator = keeper_fee_denominator;
+        msg!(
+            "Vault {} keeper fee set to {}/{}",
+            vault_state_account.key,
+            keeper_fee_numerator,
+            keeper_fee_denominator
+        );
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_vault_withdraw_cooldown(
+        accounts: &[AccountInfo],
+        withdraw_cooldown_secs: u64,
+        admin_nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        vault_info.withdraw_cooldown_secs = withdraw_cooldown_secs;
+        msg!(
+            "Vault {} withdraw cooldown set to {}s",
+            vault_state_account.key,
+            withdraw_cooldown_secs
+        );
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_vault_max_pending_reward(
+        accounts: &[AccountInfo],
+        max_pending_reward: u64,
+        admin_nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        vault_info.max_pending_reward = max_pending_reward;
+        msg!(
+            "Vault {} max pending reward set to {}",
+            vault_state_account.key,
+            max_pending_reward
+        );
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn set_vault_compound_ratio(
+        accounts: &[AccountInfo],
+        compound_ratio_bps: u16,
+        admin_nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        // 이미 harvest 된 reward 는 harvest 때의 ratio 로 swap / 재예치되어야 함
+        if vault_info.harvest_cursor != 0
+            || vault_info
+                .reward_token_remain_amounts
+                .iter()
+                .any(|&amount| amount > 0)
+            || vault_info
+                .reward_token_b_remain_amounts
+                .iter()
+                .any(|&amount| amount > 0)
+            || vault_info
+                .usdc_token_amounts
+                .iter()
+                .any(|&amount| amount > 0)
+        {
+            return Err(GauntletError::SwapInFlight.into());
+        }
+
+        vault_info.compound_ratio_bps = compound_ratio_bps;
+        msg!(
+            "Vault {} compound ratio set to {} bps",
+            vault_state_account.key,
+            compound_ratio_bps
+        );
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// 운영용 복구, total_deposit_amount 를 availability 가 켜진 strategy 의 deposit_amounts 합으로 다시 씀
+    fn recompute_vault_total(accounts: &[AccountInfo], admin_nonce: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        let mut total_deposit_amount: u64 = 0;
+        for (deposit_amount, available) in vault_info
+            .deposit_amounts
+            .iter()
+            .zip(vault_strategy_info.availabilities.iter())
+        {
+            if *available {
+                total_deposit_amount = total_deposit_amount
+                    .checked_add(*deposit_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+            }
+        }
+        msg!(
+            "Vault {} total deposit amount {} -> {}",
+            vault_state_account.key,
+            vault_info.total_deposit_amount,
+            total_deposit_amount
+        );
+        vault_info.total_deposit_amount = total_deposit_amount;
+
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn reset_user_status(
+        accounts: &[AccountInfo],
+        user: Pubkey,
+        admin_nonce: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let user_state_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+        gauntlet_info.use_admin_nonce(admin_nonce)?;
+
+        let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        if user_state_account.owner != program_id {
+            return Err(GauntletError::WrongUserAccount.into());
+        }
+        let mut user_info = User::unpack(&user_state_account.data.borrow())?;
+        if user_info.user != user || user_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongUserAccount.into());
+        }
+
+        // keeper 가 중간에 멈춘 경우, 예치가 있는 user 는 바로 deposit / withdraw 할 수 있게 READY 로
+        // 예치가 없으면 정산할 reward 가 없으므로 IDLE 로 돌림
+        // deadline 은 0 (없음) 으로 지워서 admin tx 직후 30 초 안에 user 가 tx 를 보내지 않아도 되게 함
+        user_info.user_status = if user_info.amount > 0 {
+            USER_STATUS_READY
+        } else {
+            USER_STATUS_IDLE
+        };
+        user_info.deadline = 0;
+
+        User::pack(user_info, &mut user_state_account.data.borrow_mut())?;
+        Gauntlet::pack(gauntlet_info, &mut gauntlet_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    fn validate_vault_ready(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin = next_account_info(account_info_iter)?;
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let withdraw_fee_token_account = next_account_info(account_info_iter)?;
+        let deposit_fee_token_account = next_account_info(account_info_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+
+        if gauntlet_info.admin != *admin.key {
+            return Err(GauntletError::NotAdmin.into());
+        }
+
+        let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+
+        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+            msg!("vault does not belong to this gauntlet");
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            msg!("vault strategy does not belong to this vault");
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+        if gauntlet_info.usdc_token_account == Pubkey::default() {
+            msg!("gauntlet usdc token account is not set");
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+        if vault_info.reward_token_account == Pubkey::default() {
+            msg!("vault reward token account is not set");
+            return Err(GauntletError::RewardTokenAccountError.into());
+        }
+        if vault_info.raydium_state_account == Pubkey::default() {
+            msg!("vault raydium state account is not created, run InitVaultRaydiumState");
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        // fee account 는 vault token account 와 겹치지 않고 deposit token(LP) mint 여야 함
+        let vault_token_accounts = [
+            vault_info.deposit_token_account,
+            vault_info.reward_token_account,
+            vault_info.reward_token_b_account,
+        ];
+        for (fee_account, fee_token_account) in [
+            (vault_info.withdraw_fee_account, withdraw_fee_token_account),
+            (vault_info.deposit_fee_account, deposit_fee_token_account),
+        ]
+        .iter()
+        {
+            if *fee_token_account.key != *fee_account || vault_token_accounts.contains(fee_account)
+            {
+                msg!(
+                    "fee account {} is not a dedicated vault fee account",
+                    fee_account
+                );
+                return Err(GauntletError::WrongFeeAccount.into());
+            }
+            let fee_token_account_info = unpack_token_account(&fee_token_account.data.borrow())?;
+            if fee_token_account_info.mint != vault_info.deposit_token_mint {
+                msg!("fee account {} has the wrong mint", fee_account);
+                return Err(GauntletError::WrongFeeAccount.into());
+            }
+        }
+
+        // available 한 strategy 마다 routing mode 가 strategy type 과 맞는지 확인
+        let strategy_state_accounts =
+            next_account_infos(account_info_iter, gauntlet_info.strategies_len as usize)?;
+        for (i, strategy_state_account) in strategy_state_accounts.iter().enumerate() {
+            if !vault_strategy_info.availabilities[i] {
+                continue;
+            }
+            let strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+            if strategy_info.gauntlet_state_account != *gauntlet_state_account.key
+                || strategy_info.index as usize != i
+            {
+                msg!("strategy state account {} is out of order", i);
+                return Err(GauntletError::WrongStrategyStateAccount.into());
+            }
+            match strategy_info.status {
+                Status::NORMAL => {}
+                Status::PAUSED => {
+                    msg!("strategy {} is paused but available in this vault", i);
+                    return Err(GauntletError::StrategyPaused.into());
+                }
+                Status::RETIRED => {
+                    msg!("strategy {} is retired but available in this vault", i);
+                    return Err(GauntletError::StrategyRetired.into());
+                }
+            }
+            // RAY strategy 는 reward token 자체가 strategy token 이라 usdc 를 거치면 안됨
+            if strategy_info.strategy_type == StrategyType::RAY
+                && vault_strategy_info.needs_usdc_pools[i]
+            {
+                msg!("strategy {} is RAY but routed through usdc", i);
+                return Err(GauntletError::WrongRoutingMode.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_config(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+
+        if gauntlet_state_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        let mut config_data = vec![0u8; GauntletConfig::LEN];
+        GauntletConfig::pack(
+            GauntletConfig::from_gauntlet(&gauntlet_info),
+            &mut config_data,
+        )?;
+
+        // solana-program 1.7 에는 set_return_data 가 없어서 log 로 전달, client 는 simulate 결과에서 읽음
+        let config_hex: String = config_data.iter().map(|b| format!("{:02x}", b)).collect();
+        msg!("gauntlet_config: {}", config_hex);
+        Ok(())
+    }
+
+    fn get_vault_tvl(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+
+        if vault_state_account.owner != program_id
+            || vault_strategy_state_account.owner != program_id
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        let mut tvl_data = vec![0u8; VaultTvl::LEN];
+        VaultTvl::pack(
+            VaultTvl::from_vault(&vault_info, &vault_strategy_info)?,
+            &mut tvl_data,
+        )?;
+
+        // get_config 과 같이 log 로 전달
+        let tvl_hex: String = tvl_data.iter().map(|b| format!("{:02x}", b)).collect();
+        msg!("vault_tvl: {}", tvl_hex);
+        Ok(())
+    }
+
+    fn get_user_position(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_state_account = next_account_info(account_info_iter)?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+
+        let user_info = User::try_from_account(user_state_account, program_id)?;
+        let vault_info = Vault::try_from_account(vault_state_account, program_id)?;
+        let vault_strategy_info =
+            VaultStrategy::try_from_account(vault_strategy_state_account, program_id)?;
+        let strategy_info = Strategy::try_from_account(strategy_state_account, program_id)?;
+        if user_info.vault_account != *vault_state_account.key
+            || user_info.strategy_account != *strategy_state_account.key
+        {
+            return Err(GauntletError::WrongUserAccount.into());
+        }
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
+
+        let mut position_data = vec![0u8; UserPosition::LEN];
+        UserPosition::pack(
+            UserPosition::from_user(&user_info, &vault_info, strategy_index)?,
+            &mut position_data,
+        )?;
+
+        // get_config 과 같이 log 로 전달
+        let position_hex: String = position_data.iter().map(|b| format!("{:02x}", b)).collect();
+        msg!("user_position: {}", position_hex);
+        Ok(())
+    }
 
-        if gauntlet_info.admin != *admin.key {
-            return Err(GauntletError::NotAdmin.into());
-        }
-        let mut vault_strategy_info =
-            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
-        let strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
-        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+    fn get_harvest_preview(
+        accounts: &[AccountInfo],
+        reward_amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
 
-        vault_strategy_info.needs_usdc_pools[strategy_info.index as usize] = needs_usdc_pool;
-        vault_strategy_info.availabilities[strategy_info.index as usize] = availability;
-        if vault_info.deposit_amounts[strategy_info.index as usize] > 0 {
-            // flag 에 따라서 valid 한 total deposit amount를 설정해줌
-            if availability {
-                vault_info.total_deposit_amount = vault_info
-                    .total_deposit_amount
-                    .checked_add(vault_info.deposit_amounts[strategy_info.index as usize])
-                    .unwrap();
-            } else {
-                vault_info.total_deposit_amount = vault_info
-                    .total_deposit_amount
-                    .checked_sub(vault_info.deposit_amounts[strategy_info.index as usize])
-                    .unwrap();
-            }
+        let vault_info = Vault::try_from_account(vault_state_account, program_id)?;
+        let vault_strategy_info =
+            VaultStrategy::try_from_account(vault_strategy_state_account, program_id)?;
+        if vault_strategy_info.vault_account != *vault_state_account.key {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
         }
 
-        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
-
-        VaultStrategy::pack(
-            vault_strategy_info,
-            &mut vault_strategy_state_account.data.borrow_mut(),
+        // _distribute_harvest 와 같은 계산, strategy index 순서의 u64 LE 배열
+        let reward_shares = distribute(
+            reward_amount as u128,
+            &vault_info.deposit_amounts,
+            &vault_strategy_info.availabilities,
+            vault_info.total_deposit_amount,
         )?;
+        let preview_hex: String = reward_shares
+            .iter()
+            .flat_map(|share| share.to_le_bytes())
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        msg!("harvest_preview: {}", preview_hex);
         Ok(())
     }
-    fn harvest(accounts: &[AccountInfo], deposit_type: DepositType) -> ProgramResult {
+
+    fn harvest(
+        accounts: &[AccountInfo],
+        deposit_type: DepositType,
+        max_strategies: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let harvestor = next_account_info(account_info_iter)?; // signer
         let gauntlet_state_account = next_account_info(account_info_iter)?;
+        Gauntlet::check_not_paused(&gauntlet_state_account.data.borrow())?;
         let harvestor_user_state_account = next_account_info(account_info_iter)?;
         let vault_state_account = next_account_info(account_info_iter)?;
         let vault_strategy_state_account = next_account_info(account_info_iter)?;
         let harvest_accounts = match deposit_type {
-            DepositType::RAYDIUM => next_account_infos(account_info_iter, 11).unwrap(),
-            DepositType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13).unwrap(),
+            DepositType::RAYDIUM => next_account_infos(account_info_iter, 11)?,
+            DepositType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13)?,
         };
+        // keeper fee 가 설정된 vault 에서만 필요
+        let keeper_reward_token_account = next_account_info(account_info_iter).ok();
+        let reward_token_mint = next_account_info(account_info_iter).ok();
         let vault_deposit_token_account = &harvest_accounts[5];
         let vault_reward_token_account = &harvest_accounts[7];
         let vault_reward_b_token_account = match deposit_type {
@@ -329,7 +2673,12 @@ impl Processor {
             DepositType::RAYDIUM_V4 => Some(&harvest_accounts[11]),
         };
         let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
-        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
         let vault_strategy_info =
             VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
         let mut harvestor_user_info =
@@ -339,7 +2688,28 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        if vault_info.gauntlet_state_account != *gauntlet_state_account.key {
+        if !gauntlet_info.is_keeper(harvestor.key) {
+            return Err(GauntletError::NotKeeper.into());
+        }
+
+        // bounty 는 서명한 keeper 본인의 token account 로만 보냄
+        if let Some(keeper_reward_token_account) = keeper_reward_token_account {
+            let keeper_reward_token_account_info =
+                unpack_token_account(&keeper_reward_token_account.data.borrow())?;
+            if keeper_reward_token_account_info.owner != *harvestor.key {
+                return Err(GauntletError::WrongFeeAccount.into());
+            }
+        }
+
+        // vault data 의 borrow 는 raydium harvest cpi 전에 풀어야 같은 account 를 넘기는 cpi 가 실패하지 않음
+        let mut vault_data = vault_state_account.data.borrow_mut();
+        let vault_ref = VaultRef::new(&mut vault_data[..])?;
+        if !vault_ref.is_initialized() {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+        Self::check_deposit_type(vault_ref.has_second_reward(), &deposit_type)?;
+
+        if vault_ref.gauntlet_state_account() != *gauntlet_state_account.key {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
 
@@ -347,76 +2717,163 @@ impl Processor {
             return Err(GauntletError::WrongVaultStrategyStateAccount.into());
         }
 
-        if vault_info.deposit_token_account != *vault_deposit_token_account.key {
+        if vault_ref.deposit_token_account() != *vault_deposit_token_account.key {
             return Err(GauntletError::WrongTokenAccount.into());
         }
 
-        if vault_info.reward_token_account != *vault_reward_token_account.key {
+        if vault_ref.reward_token_account() != *vault_reward_token_account.key {
             return Err(GauntletError::WrongTokenAccount.into());
         }
 
         if vault_reward_b_token_account.is_some() {
-            if vault_info.reward_token_b_account != *vault_reward_b_token_account.unwrap().key {
+            if vault_ref.reward_token_b_account() != *vault_reward_b_token_account.unwrap().key {
                 return Err(GauntletError::WrongTokenAccount.into());
             }
         }
 
-        if vault_info.total_deposit_amount > 0 {
-            Self::_harvest(
-                &gauntlet_info,
-                &mut vault_info,
-                &vault_strategy_info,
+        // raydium user_owner 는 gauntlet authority pda 여야 함, cpi 실패에 맡기지 않음
+        let authority = Pubkey::create_program_address(authority_seeds, program_id)
+            .map_err(|_| GauntletError::InvalidAccount)?;
+        if *harvest_accounts[4].key != authority {
+            return Err(GauntletError::InvalidAccount.into());
+        }
+
+        // reward 를 나눠줄 strategy 가 없으면 harvest 한 reward 가 vault 에 묶이게 됨
+        let strategies_len = gauntlet_info.strategies_len as usize;
+        if strategies_len == 0
+            || !vault_strategy_info.availabilities[..strategies_len.min(MAX_NUMBER_OF_STRATEGY)]
+                .contains(&true)
+        {
+            return Err(GauntletError::NotRegisteredStrategy.into());
+        }
+
+        // cursor 가 0 이 아니면 이전 harvest 의 배분을 이어서 함 (raydium harvest 는 다시 하지 않음)
+        let harvest_cursor = vault_ref.harvest_cursor() as usize;
+        // interval 은 raydium harvest cpi 에만 적용, 안 지났어도 harvestor 의 status 는 진행시킴
+        let next_harvest_time = vault_ref
+            .last_reward_update_time()
+            .saturating_add(vault_ref.min_harvest_interval() as UnixTimestamp);
+        let needs_harvest = harvest_cursor == 0
+            && clock.unix_timestamp >= next_harvest_time
+            && vault_ref.total_deposit_amount() > 0;
+        drop(vault_data);
+
+        if needs_harvest {
+            let harvested_amount = Self::_harvest(
+                vault_state_account,
                 harvest_accounts,
                 &vault_reward_token_account,
                 &vault_reward_b_token_account,
+                keeper_reward_token_account.zip(reward_token_mint),
                 &deposit_type,
-            )
-            .unwrap();
+                &[authority_seeds],
+            )?;
+            let mut vault_data = vault_state_account.data.borrow_mut();
+            let mut vault_ref = VaultRef::new(&mut vault_data[..])?;
+            if harvested_amount == 0 {
+                // 받은 reward 가 없으면 실패로 기록, keeper 가 off-chain 에서 감시
+                let failures = vault_ref.consecutive_harvest_failures().saturating_add(1);
+                vault_ref.set_consecutive_harvest_failures(failures);
+                if failures >= HARVEST_FAILURE_WARN_THRESHOLD {
+                    msg!(
+                        "Warning: vault {} had {} consecutive empty harvests",
+                        vault_state_account.key,
+                        failures
+                    );
+                }
+            } else {
+                vault_ref.set_consecutive_harvest_failures(0);
+            }
         }
 
-        harvestor_user_info.user_status = 1;
-        harvestor_user_info.deadline = clock
-            .unix_timestamp
-            .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
-            .unwrap();
+        let mut vault_data = vault_state_account.data.borrow_mut();
+        let mut vault_ref = VaultRef::new(&mut vault_data[..])?;
+        let harvest_end = match max_strategies {
+            0 => strategies_len,
+            max_strategies => strategies_len.min(harvest_cursor + max_strategies as usize),
+        };
+        Self::_distribute_harvest(
+            &mut vault_ref,
+            &vault_strategy_info,
+            harvest_cursor,
+            harvest_end,
+        )?;
+        if harvest_end < strategies_len {
+            // 아직 배분할 strategy 가 남음, 끝날 때까지 deposit/withdraw 는 막힘
+            vault_ref.set_harvest_cursor(harvest_end as u8);
+        } else {
+            vault_ref.set_harvest_cursor(0);
+            vault_ref.set_harvest_pending_amount(0);
+            vault_ref.set_harvest_pending_b_amount(0);
+
+            harvestor_user_info.user_status = USER_STATUS_HARVESTED;
+            harvestor_user_info.deadline = clock
+                .unix_timestamp
+                .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
+                .ok_or(GauntletError::MathOverflow)?;
+        }
 
         User::pack(
             harvestor_user_info,
             &mut harvestor_user_state_account.data.borrow_mut(),
         )?;
-        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
 
         Ok(())
     }
 
-    fn swap_farm_reward_to_usdc(accounts: &[AccountInfo], swap_type: SwapType) -> ProgramResult {
+    fn swap_farm_reward_to_usdc(
+        accounts: &[AccountInfo],
+        swap_type: SwapType,
+        max_slippage_bps: u64,
+        route_data: &[u8],
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let swaper = next_account_info(account_info_iter)?; // signer
         let gauntlet_state_account = next_account_info(account_info_iter)?;
+        Gauntlet::check_not_paused(&gauntlet_state_account.data.borrow())?;
         let swaper_user_state_account = next_account_info(account_info_iter)?;
         let vault_state_account = next_account_info(account_info_iter)?;
         let vault_strategy_state_account = next_account_info(account_info_iter)?;
         let strategy_state_account = next_account_info(account_info_iter)?;
-        let swap_reward_to_usdc_accounts = match swap_type {
-            SwapType::RAYDIUM => next_account_infos(account_info_iter, 19).unwrap(),
+        let oracle_accounts = if max_slippage_bps > 0 {
+            Some(next_account_infos(account_info_iter, 3)?)
+        } else {
+            None
         };
-        let vault_reward_token_account = &swap_reward_to_usdc_accounts[16];
-        let gauntlet_usdc_token_account = &swap_reward_to_usdc_accounts[17];
+        let swap_reward_to_usdc_accounts = Self::next_swap_accounts(account_info_iter, &swap_type)?;
+        let (source_index, dest_index) = Self::swap_token_indexes(&swap_type);
+        let vault_reward_token_account = &swap_reward_to_usdc_accounts[source_index];
+        let gauntlet_usdc_token_account = &swap_reward_to_usdc_accounts[dest_index];
         let mut swaper_user_info =
             User::unpack_unchecked(&swaper_user_state_account.data.borrow())?;
         let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
-        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
+        let mut vault_info = Vault::unpack_unchecked(&vault_state_account.data.borrow())?;
         let vault_strategy_info =
             VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
         let strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
-        let strategy_index = strategy_info.index as usize;
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
         let mut second_reward_token = false;
         let clock = &Clock::get()?;
+        let has_second_reward = vault_info.reward_token_b_account != Pubkey::default();
 
         if !swaper.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        if !gauntlet_info.is_keeper(swaper.key) {
+            return Err(GauntletError::NotKeeper.into());
+        }
+
+        if !vault_info.is_initialized() {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
         if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
@@ -426,7 +2883,8 @@ impl Processor {
         }
 
         if vault_strategy_info.needs_usdc_pools[strategy_index] == false {
-            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+            // usdc 를 거치지 않는 strategy 는 swap_reward_to_strategy_token 을 사용
+            return Err(GauntletError::WrongRoutingMode.into());
         }
 
         if *gauntlet_state_account.key != strategy_info.gauntlet_state_account {
@@ -443,10 +2901,10 @@ impl Processor {
             return Err(GauntletError::RewardTokenAccountError.into());
         }
 
-        if !second_reward_token && swaper_user_info.user_status != 1 {
+        if !second_reward_token && swaper_user_info.user_status != USER_STATUS_HARVESTED {
             return Err(GauntletError::UserStatusError.into());
         }
-        if second_reward_token && swaper_user_info.user_status != 2 {
+        if second_reward_token && swaper_user_info.user_status != USER_STATUS_REWARD_A_SWAPPED {
             return Err(GauntletError::UserStatusError.into());
         }
 
@@ -458,27 +2916,82 @@ impl Processor {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
 
+        // swap 할 reward 도 usdc 도 남아 있지 않으면 나머지 swap 단계를 건너뛰고 바로 deposit 가능 상태로
+        let nothing_pending = vault_info.reward_token_remain_amounts[strategy_index] == 0
+            && (!has_second_reward
+                || vault_info.reward_token_b_remain_amounts[strategy_index] == 0)
+            && vault_info.usdc_token_amounts[strategy_index] == 0;
+        if nothing_pending {
+            swaper_user_info.user_status = USER_STATUS_READY;
+            swaper_user_info.deadline = clock
+                .unix_timestamp
+                .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
+                .ok_or(GauntletError::MathOverflow)?;
+            User::pack(
+                swaper_user_info,
+                &mut swaper_user_state_account.data.borrow_mut(),
+            )?;
+            return Ok(());
+        }
+
+        // oracle 이 주어지면 oracle 가격 대비 slippage 를 넘는 swap 을 거부
+        // pyth account 대신 instructions sysvar 를 넘기면 바로 앞 ed25519 instruction 에서 keeper 가 서명한 가격을 씀
+        let price_check = match oracle_accounts {
+            Some(oracle_accounts) => {
+                let reward_mint =
+                    unpack_token_account(&vault_reward_token_account.data.borrow())?.mint;
+                let usdc_mint =
+                    unpack_token_account(&gauntlet_usdc_token_account.data.borrow())?.mint;
+                if *oracle_accounts[1].key != reward_mint || *oracle_accounts[2].key != usdc_mint {
+                    return Err(GauntletError::InvalidAccount.into());
+                }
+                if sysvar::instructions::check_id(oracle_accounts[0].key) {
+                    let (signer, attestation) = PriceAttestation::load(&oracle_accounts[0])?;
+                    if !gauntlet_info.is_keeper(&signer) {
+                        return Err(GauntletError::NotKeeper.into());
+                    }
+                    Some(OraclePriceCheck::from_attestation(
+                        &attestation,
+                        &oracle_accounts[1],
+                        &oracle_accounts[2],
+                        max_slippage_bps,
+                        clock,
+                    )?)
+                } else {
+                    Some(OraclePriceCheck::load(
+                        &oracle_accounts[0],
+                        &oracle_accounts[1],
+                        &oracle_accounts[2],
+                        max_slippage_bps,
+                        clock,
+                    )?)
+                }
+            }
+            None => None,
+        };
+
         if vault_strategy_info.availabilities[strategy_index] {
             // 해당 vault와 strategy가 available할때만 swap, available하지않으면 harvest만 하고 swap은 하지않음
             Self::_swap_farm_token_to_usdc(
                 &mut vault_info,
                 strategy_index,
                 gauntlet_usdc_token_account,
-                swap_reward_to_usdc_accounts,
-                &swap_type,
+                &SwapRoute {
+                    accounts: swap_reward_to_usdc_accounts,
+                    swap_type: &swap_type,
+                    route_data,
+                    price_check: &price_check,
+                },
                 second_reward_token,
-            )
-            .unwrap();
-        }
-        if vault_info.reward_token_b_account == Pubkey::default() {
-            swaper_user_info.user_status += 2;
-        } else {
-            swaper_user_info.user_status += 1;
+                &[authority_seeds],
+            )?;
         }
+        swaper_user_info.user_status =
+            next_swap_status(swaper_user_info.user_status, has_second_reward);
         swaper_user_info.deadline = clock
             .unix_timestamp
             .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
-            .unwrap();
+            .ok_or(GauntletError::MathOverflow)?;
         User::pack(
             swaper_user_info,
             &mut swaper_user_state_account.data.borrow_mut(),
@@ -492,29 +3005,50 @@ impl Processor {
         let account_info_iter = &mut accounts.iter();
         let swaper = next_account_info(account_info_iter)?; // signer
         let gauntlet_state_account = next_account_info(account_info_iter)?;
+        Gauntlet::check_not_paused(&gauntlet_state_account.data.borrow())?;
         let swaper_user_state_account = next_account_info(account_info_iter)?;
         let vault_state_account = next_account_info(account_info_iter)?;
         let vault_strategy_state_account = next_account_info(account_info_iter)?;
         let strategy_state_account = next_account_info(account_info_iter)?;
-        let swap_usdc_to_strategy_accounts = match swap_type {
-            SwapType::RAYDIUM => next_account_infos(account_info_iter, 19).unwrap(),
-        };
-        let gauntlet_usdc_token_account = &swap_usdc_to_strategy_accounts[16];
-        let strategy_token_account = &swap_usdc_to_strategy_accounts[17];
+        if let SwapType::JUPITER = swap_type {
+            return Err(GauntletError::WrongRoutingMode.into());
+        }
+        let swap_usdc_to_strategy_accounts =
+            Self::next_swap_accounts(account_info_iter, &swap_type)?;
+        // strategy token 이 deposit LP 인 경우 재예치에 쓰이는 raydium deposit accounts
+        let restake_accounts = account_info_iter.as_slice();
+        let (source_index, dest_index) = Self::swap_token_indexes(&swap_type);
+        let gauntlet_usdc_token_account = &swap_usdc_to_strategy_accounts[source_index];
+        let strategy_token_account = &swap_usdc_to_strategy_accounts[dest_index];
         let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
         let mut swaper_user_info =
             User::unpack_unchecked(&swaper_user_state_account.data.borrow())?;
-        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let mut vault_info = Vault::unpack_unchecked(&vault_state_account.data.borrow())?;
         let mut vault_strategy_info =
             VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
         let mut strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
-        let strategy_index = strategy_info.index as usize;
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
         let clock = &Clock::get()?;
+        let has_second_reward = vault_info.reward_token_b_account != Pubkey::default();
 
         if !swaper.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        if !gauntlet_info.is_keeper(swaper.key) {
+            return Err(GauntletError::NotKeeper.into());
+        }
+
+        if !vault_info.is_initialized() {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
         if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
@@ -523,7 +3057,8 @@ impl Processor {
             return Err(GauntletError::WrongVaultStrategyStateAccount.into());
         }
         if vault_strategy_info.needs_usdc_pools[strategy_index] == false {
-            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+            // usdc 를 거치지 않는 strategy 는 swap_reward_to_strategy_token 을 사용
+            return Err(GauntletError::WrongRoutingMode.into());
         }
 
         if *gauntlet_state_account.key != strategy_info.gauntlet_state_account {
@@ -542,7 +3077,7 @@ impl Processor {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
 
-        if swaper_user_info.user_status != 3 {
+        if swaper_user_info.user_status != USER_STATUS_REWARDS_SWAPPED {
             return Err(GauntletError::UserStatusError.into());
         }
 
@@ -556,18 +3091,22 @@ impl Processor {
                 &mut vault_info,
                 &mut vault_strategy_info,
                 &mut strategy_info,
-                strategy_token_account,
                 gauntlet_usdc_token_account,
-                swap_usdc_to_strategy_accounts,
-                &swap_type,
-            )
-            .unwrap();
+                &StrategyTokenSwap {
+                    strategy_token_account,
+                    accounts: swap_usdc_to_strategy_accounts,
+                    swap_type: &swap_type,
+                    restake_accounts,
+                },
+                &[authority_seeds],
+            )?;
         }
-        swaper_user_info.user_status += 1;
+        swaper_user_info.user_status =
+            next_swap_status(swaper_user_info.user_status, has_second_reward);
         swaper_user_info.deadline = clock
             .unix_timestamp
             .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
-            .unwrap();
+            .ok_or(GauntletError::MathOverflow)?;
         User::pack(
             swaper_user_info,
             &mut swaper_user_state_account.data.borrow_mut(),
@@ -585,33 +3124,80 @@ impl Processor {
     fn swap_reward_to_strategy_token(
         accounts: &[AccountInfo],
         swap_type: SwapType,
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let swaper = next_account_info(account_info_iter)?; // signer
         let gauntlet_state_account = next_account_info(account_info_iter)?;
+        Gauntlet::check_not_paused(&gauntlet_state_account.data.borrow())?;
         let swaper_user_state_account = next_account_info(account_info_iter)?;
         let vault_state_account = next_account_info(account_info_iter)?;
         let vault_strategy_state_account = next_account_info(account_info_iter)?;
         let strategy_state_account = next_account_info(account_info_iter)?;
-        let swap_reward_to_strategy_accounts = match swap_type {
-            SwapType::RAYDIUM => next_account_infos(account_info_iter, 19).unwrap(),
-        };
-        let vault_reward_token_account = &swap_reward_to_strategy_accounts[16];
-        let strategy_token_account = &swap_reward_to_strategy_accounts[17];
+        if let SwapType::JUPITER = swap_type {
+            return Err(GauntletError::WrongRoutingMode.into());
+        }
+        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
         let mut swaper_user_info =
             User::unpack_unchecked(&swaper_user_state_account.data.borrow())?;
-        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let mut vault_info = Vault::unpack_unchecked(&vault_state_account.data.borrow())?;
         let mut vault_strategy_info =
             VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
         let mut strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
-        let strategy_index = strategy_info.index as usize;
+        // reward 를 그대로 옮기기만 하는 RAY strategy 는 swap accounts 대신
+        // (vault reward token, strategy token, authority, token program, reward mint) 만 받음
+        let direct_transfer = Self::is_direct_reward_transfer(
+            &vault_info,
+            &strategy_info,
+            swaper_user_info.user_status == USER_STATUS_REWARD_A_SWAPPED,
+        );
+        let swap_reward_to_strategy_accounts = match direct_transfer {
+            true => next_account_infos(account_info_iter, REWARD_TRANSFER_ACCOUNT_COUNT)?,
+            false => Self::next_swap_accounts(account_info_iter, &swap_type)?,
+        };
+        // strategy token 이 deposit LP 인 경우 재예치에 쓰이는 raydium deposit accounts
+        let restake_accounts = account_info_iter.as_slice();
+        let (source_index, dest_index) = match direct_transfer {
+            true => (0, 1),
+            false => Self::swap_token_indexes(&swap_type),
+        };
+        let vault_reward_token_account = &swap_reward_to_strategy_accounts[source_index];
+        let strategy_token_account = &swap_reward_to_strategy_accounts[dest_index];
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
         let mut second_reward_token = false;
         let clock = &Clock::get()?;
+        let has_second_reward = vault_info.reward_token_b_account != Pubkey::default();
 
         if !swaper.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        if !gauntlet_info.is_keeper(swaper.key) {
+            return Err(GauntletError::NotKeeper.into());
+        }
+
+        if direct_transfer {
+            // token 을 옮기는 authority 는 gauntlet authority pda 여야 함, cpi 실패에 맡기지 않음
+            let authority = Pubkey::create_program_address(authority_seeds, program_id)
+                .map_err(|_| GauntletError::InvalidAccount)?;
+            if *swap_reward_to_strategy_accounts[2].key != authority {
+                return Err(GauntletError::InvalidAccount.into());
+            }
+            if *swap_reward_to_strategy_accounts[3].key != vault_info.token_program {
+                return Err(GauntletError::InvalidProgramId.into());
+            }
+        }
+
+        if !vault_info.is_initialized() {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
         if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
@@ -620,7 +3206,8 @@ impl Processor {
             return Err(GauntletError::WrongVaultStrategyStateAccount.into());
         }
         if vault_strategy_info.needs_usdc_pools[strategy_index] == true {
-            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+            // usdc 를 거치는 strategy 는 swap_farm_reward_to_usdc, swap_usdc_to_strategy_token 을 사용
+            return Err(GauntletError::WrongRoutingMode.into());
         }
 
         if *gauntlet_state_account.key != strategy_info.gauntlet_state_account {
@@ -640,15 +3227,32 @@ impl Processor {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
 
-        if !second_reward_token && swaper_user_info.user_status != 1 {
+        if !second_reward_token && swaper_user_info.user_status != USER_STATUS_HARVESTED {
             return Err(GauntletError::UserStatusError.into());
         }
-        if second_reward_token && swaper_user_info.user_status != 2 {
+        if second_reward_token && swaper_user_info.user_status != USER_STATUS_REWARD_A_SWAPPED {
             return Err(GauntletError::UserStatusError.into());
         }
         if clock.unix_timestamp > swaper_user_info.deadline {
             return Err(GauntletError::TimeoutError.into());
         }
+        // swap 할 reward 가 남아 있지 않으면 나머지 swap 단계를 건너뛰고 바로 deposit 가능 상태로
+        let nothing_pending = vault_info.reward_token_remain_amounts[strategy_index] == 0
+            && (!has_second_reward
+                || vault_info.reward_token_b_remain_amounts[strategy_index] == 0);
+        if nothing_pending {
+            swaper_user_info.user_status = USER_STATUS_READY;
+            swaper_user_info.deadline = clock
+                .unix_timestamp
+                .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
+                .ok_or(GauntletError::MathOverflow)?;
+            User::pack(
+                swaper_user_info,
+                &mut swaper_user_state_account.data.borrow_mut(),
+            )?;
+            return Ok(());
+        }
+
         if vault_strategy_info.availabilities[strategy_index]
             && vault_info.deposit_amounts[strategy_index] != 0
         {
@@ -656,24 +3260,27 @@ impl Processor {
                 &mut vault_info,
                 &mut vault_strategy_info,
                 &mut strategy_info,
-                strategy_token_account,
-                swap_reward_to_strategy_accounts,
-                &swap_type,
+                vault_reward_token_account,
+                &StrategyTokenSwap {
+                    strategy_token_account,
+                    accounts: swap_reward_to_strategy_accounts,
+                    swap_type: &swap_type,
+                    restake_accounts,
+                },
                 second_reward_token,
-            )
-            .unwrap();
+                &[authority_seeds],
+            )?;
         }
-        if vault_info.reward_token_b_account == Pubkey::default() {
-            swaper_user_info.user_status = 4;
-        } else if vault_info.reward_token_b_account == *vault_reward_token_account.key {
-            swaper_user_info.user_status = 4;
-        } else {
-            swaper_user_info.user_status += 1;
+        let mut next_status = next_swap_status(swaper_user_info.user_status, has_second_reward);
+        if next_status == USER_STATUS_REWARDS_SWAPPED {
+            // usdc 를 거치지 않으므로 usdc -> strategy token 단계는 건너뜀
+            next_status = next_swap_status(next_status, has_second_reward);
         }
+        swaper_user_info.user_status = next_status;
         swaper_user_info.deadline = clock
             .unix_timestamp
             .checked_add(Duration::from_secs(30).as_secs() as UnixTimestamp)
-            .unwrap();
+            .ok_or(GauntletError::MathOverflow)?;
         User::pack(
             swaper_user_info,
             &mut swaper_user_state_account.data.borrow_mut(),
@@ -688,76 +3295,376 @@ impl Processor {
         Ok(())
     }
 
-    fn raydium_swap(accounts: &[AccountInfo], amount_in: u64, amount_out: u64) -> ProgramResult {
-        // let pda = *accounts[18].key;
-        // let pda_address = Pubkey::from_str("KP2AwjL3wwpZcy37wiiDVS4qaVhYP4tU2xTunvWp2ut").unwrap();
-        // assert_eq!(pda, pda_address);
-        // let token_a_info = Account::unpack(&accounts[16].data.borrow())?;
-        // let token_b_info = Account::unpack(&accounts[17].data.borrow())?;
-        // assert_eq!(token_a_info.owner, pda_address);
-        // assert_eq!(token_b_info.owner, pda_address);
-        let pool_coin_token_account_info = Account::unpack(&accounts[6].data.borrow())?;
-        let pool_pc_token_account_info = Account::unpack(&accounts[7].data.borrow())?;
-        let source_token_account_info = Account::unpack(&accounts[16].data.borrow())?;
-        let dest_token_amount;
-        if pool_coin_token_account_info.mint == source_token_account_info.mint {
-            dest_token_amount = (pool_pc_token_account_info.amount as u128)
-                .checked_mul(source_token_account_info.amount as u128)
-                .unwrap()
-                .checked_div(pool_coin_token_account_info.amount as u128)
-                .unwrap() as u64;
-        } else {
-            dest_token_amount = (pool_coin_token_account_info.amount as u128)
-                .checked_mul(source_token_account_info.amount as u128)
-                .unwrap()
-                .checked_div(pool_pc_token_account_info.amount as u128)
-                .unwrap() as u64;
+    fn compound(accounts: &[AccountInfo], deposit_type: DepositType) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let keeper = next_account_info(account_info_iter)?; // signer
+        let gauntlet_state_account = next_account_info(account_info_iter)?;
+        Gauntlet::check_not_paused(&gauntlet_state_account.data.borrow())?;
+        let vault_state_account = next_account_info(account_info_iter)?;
+        let vault_strategy_state_account = next_account_info(account_info_iter)?;
+        let strategy_state_account = next_account_info(account_info_iter)?;
+        let swap_accounts = next_account_infos(account_info_iter, 19)?;
+        let add_liquidity_accounts = next_account_infos(account_info_iter, 14)?;
+        let restake_accounts = match deposit_type {
+            DepositType::RAYDIUM => next_account_infos(account_info_iter, 11)?,
+            DepositType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13)?,
+        };
+        let vault_reward_token_account = &swap_accounts[16];
+        let gauntlet_usdc_token_account = &swap_accounts[17];
+        let vault_deposit_token_account = &add_liquidity_accounts[12];
+
+        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
+        let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        Self::check_deposit_type(vault_info.has_second_reward, &deposit_type)?;
+        let vault_strategy_info =
+            VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
+        let mut strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
+
+        if !keeper.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !gauntlet_info.is_keeper(keeper.key) {
+            return Err(GauntletError::NotKeeper.into());
+        }
+
+        if !vault_info.compounding {
+            return Err(GauntletError::CompoundingDisabled.into());
+        }
+
+        if vault_info.harvest_cursor != 0 {
+            return Err(GauntletError::HarvestInProgress.into());
+        }
+
+        if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
+            return Err(GauntletError::WrongVaultStateAccount.into());
+        }
+
+        if *vault_state_account.key != vault_strategy_info.vault_account {
+            return Err(GauntletError::WrongVaultStrategyStateAccount.into());
+        }
+
+        if *gauntlet_state_account.key != strategy_info.gauntlet_state_account {
+            return Err(GauntletError::WrongStrategyStateAccount.into());
+        }
+
+        if !vault_strategy_info.availabilities[strategy_index] {
+            return Err(GauntletError::InvalidStatusStrategy.into());
+        }
+
+        if vault_info.reward_token_account != *vault_reward_token_account.key
+            || gauntlet_info.usdc_token_account != *gauntlet_usdc_token_account.key
+            || vault_info.deposit_token_account != *vault_deposit_token_account.key
+            || vault_info.deposit_token_account != *restake_accounts[5].key
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        // LP pair 는 reward / usdc 여야 함, coin 과 pc 순서는 pool 에 따라 다름
+        let reward_is_coin = *add_liquidity_accounts[10].key == *vault_reward_token_account.key
+            && *add_liquidity_accounts[11].key == *gauntlet_usdc_token_account.key;
+        let reward_is_pc = *add_liquidity_accounts[11].key == *vault_reward_token_account.key
+            && *add_liquidity_accounts[10].key == *gauntlet_usdc_token_account.key;
+        if !reward_is_coin && !reward_is_pc {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        if vault_info.deposit_amounts[strategy_index] == 0 {
+            msg!(
+                "compound skipped: strategy {} has no deposit",
+                strategy_index
+            );
+            return Ok(());
+        }
+
+        // compound_ratio_bps 만큼의 reward 만 LP 로 만들고 나머지는 strategy token swap 으로 남김
+        let remain_amount = vault_info.reward_token_remain_amounts[strategy_index];
+        let reward_amount = vault_info.compound_amount(remain_amount)?;
+        let skipped_reward_amount = remain_amount
+            .checked_sub(reward_amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        // reward 절반을 usdc 로 swap
+        let mut swap_in_amount = reward_amount / 2;
+        let before_usdc_amount =
+            unpack_token_account(&gauntlet_usdc_token_account.data.borrow())?.amount;
+        if swap_in_amount > 0 {
+            let min_out =
+                Self::raydium_min_out(swap_accounts, swap_in_amount, vault_info.swap_slippage_bps)?;
+            if !Self::raydium_swap(
+                swap_accounts,
+                swap_in_amount,
+                min_out,
+                vault_info.min_swap_output,
+                &[authority_seeds],
+            )? {
+                // 예상 수령량이 min_swap_output 보다 작아 swap 하지 않았으므로 reward 는 그대로 남아 있음
+                swap_in_amount = 0;
+            }
+        }
+        let swapped_usdc_amount = unpack_token_account(&gauntlet_usdc_token_account.data.borrow())?
+            .amount
+            .checked_sub(before_usdc_amount)
+            .ok_or(GauntletError::AccountingDesync)?;
+        let reward_amount = reward_amount
+            .checked_sub(swap_in_amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        let usdc_amount = vault_info.usdc_token_amounts[strategy_index]
+            .checked_add(swapped_usdc_amount)
+            .ok_or(GauntletError::MathOverflow)?;
+
+        // pool 비율상 부족한 쪽을 base 로 잡아야 add liquidity 가 max amount 를 넘지 않음
+        let pool_coin_amount =
+            unpack_token_account(&add_liquidity_accounts[7].data.borrow())?.amount;
+        let pool_pc_amount = unpack_token_account(&add_liquidity_accounts[8].data.borrow())?.amount;
+        let (max_coin_amount, max_pc_amount) = match reward_is_coin {
+            true => (reward_amount, usdc_amount),
+            false => (usdc_amount, reward_amount),
+        };
+        let pc_needed = (max_coin_amount as u128)
+            .checked_mul(pool_pc_amount as u128)
+            .ok_or(GauntletError::MathOverflow)?
+            .checked_div(pool_coin_amount as u128)
+            .ok_or(GauntletError::MathOverflow)?;
+        let base_side = match pc_needed <= max_pc_amount as u128 {
+            true => 0,
+            false => 1,
+        };
+
+        let before_reward_amount =
+            unpack_token_account(&vault_reward_token_account.data.borrow())?.amount;
+        let before_usdc_amount =
+            unpack_token_account(&gauntlet_usdc_token_account.data.borrow())?.amount;
+        let before_lp_amount =
+            unpack_token_account(&vault_deposit_token_account.data.borrow())?.amount;
+        Raydium::raydium_add_liquidity(
+            add_liquidity_accounts,
+            max_coin_amount,
+            max_pc_amount,
+            base_side,
+            &[authority_seeds],
+        )?;
+        let used_reward_amount = before_reward_amount
+            .checked_sub(unpack_token_account(&vault_reward_token_account.data.borrow())?.amount)
+            .ok_or(GauntletError::AccountingDesync)?;
+        let used_usdc_amount = before_usdc_amount
+            .checked_sub(unpack_token_account(&gauntlet_usdc_token_account.data.borrow())?.amount)
+            .ok_or(GauntletError::AccountingDesync)?;
+        let lp_amount = unpack_token_account(&vault_deposit_token_account.data.borrow())?
+            .amount
+            .checked_sub(before_lp_amount)
+            .ok_or(GauntletError::AccountingDesync)?;
+
+        // add liquidity 에 쓰이지 않은 reward / usdc 는 다음 compound 로 넘김
+        vault_info.reward_token_remain_amounts[strategy_index] = reward_amount
+            .checked_sub(used_reward_amount)
+            .ok_or(GauntletError::AccountingDesync)?
+            .checked_add(skipped_reward_amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        vault_info.usdc_token_amounts[strategy_index] = usdc_amount
+            .checked_sub(used_usdc_amount)
+            .ok_or(GauntletError::AccountingDesync)?;
+
+        if lp_amount > 0 {
+            match deposit_type {
+                DepositType::RAYDIUM => {
+                    Raydium::raydium_deposit(restake_accounts, lp_amount, &[authority_seeds])?
+                }
+                DepositType::RAYDIUM_V4 => {
+                    Raydium::raydium_deposit_v4(restake_accounts, lp_amount, &[authority_seeds])?
+                }
+            }
+            // 새 LP 를 기존 예치자들에게 지분 비율대로 원금으로 배분
+            vault_info.add_strategy_gain(strategy_index, 0, lp_amount)?;
+            vault_info.add_principal(&mut strategy_info, strategy_index, lp_amount)?;
         }
-        if dest_token_amount >= 20 {
-            Raydium::raydium_swap(accounts, amount_in, amount_out).unwrap();
+
+        msg!(
+            "Compound: vault {} strategy {} lp {}",
+            vault_state_account.key,
+            strategy_state_account.key,
+            lp_amount
+        );
+        Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// raydium swap account 19 개의 순서 확인, 잘못 넣으면 cpi 안에서 알기 어려운 에러가 나므로 미리 거부
+    fn validate_swap_accounts(accounts: &[AccountInfo]) -> ProgramResult {
+        if accounts.len() < 19 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        check_pool_program_id(&accounts[0])?;
+        check_token_program_id(&accounts[1])?;
+        check_serum_program_id(&accounts[8])?;
+        // user source / dest token account
+        if *accounts[16].owner != spl_token::id() || *accounts[17].owner != spl_token::id() {
+            return Err(GauntletError::WrongTokenAccount.into());
         }
         Ok(())
     }
 
-    fn deposit(accounts: &[AccountInfo], amount: u64, deposit_type: DepositType) -> ProgramResult {
+    /// 예상 수령량이 min_swap_output 보다 작으면 swap 하지 않고 false 를 반환, caller 는 남은 양을 다음 crank 로 넘김
+    fn raydium_swap(
+        accounts: &[AccountInfo],
+        amount_in: u64,
+        amount_out: u64,
+        min_swap_output: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<bool, ProgramError> {
+        // estimate 에 쓰이는 pool account 를 읽기 전에 swap account 순서부터 검증
+        Self::validate_swap_accounts(accounts)?;
+        let dest_token_amount = Self::raydium_estimate_out(accounts, amount_in)?;
+        if dest_token_amount < min_swap_output {
+            return Ok(false);
+        }
+        Raydium::raydium_swap(accounts, amount_in, amount_out, signer_seeds)?;
+        Ok(true)
+    }
+
+    /// pool reserve 기준 swap 예상 수령량, accounts 는 raydium_swap 과 동일
+    fn raydium_estimate_out(accounts: &[AccountInfo], amount_in: u64) -> Result<u64, ProgramError> {
+        let pool_coin_token_account_info = unpack_token_account(&accounts[6].data.borrow())?;
+        let pool_pc_token_account_info = unpack_token_account(&accounts[7].data.borrow())?;
+        let source_token_account_info = unpack_token_account(&accounts[16].data.borrow())?;
+        // source account 잔액이 아니라 실제 swap 할 amount_in 기준으로 예상 수령량 계산
+        let (reserve_in, reserve_out) =
+            if pool_coin_token_account_info.mint == source_token_account_info.mint {
+                (
+                    pool_coin_token_account_info.amount,
+                    pool_pc_token_account_info.amount,
+                )
+            } else {
+                (
+                    pool_pc_token_account_info.amount,
+                    pool_coin_token_account_info.amount,
+                )
+            };
+        // constant product, swap fee 는 amount_in 에서 먼저 빠짐
+        let amount_in_after_fee = (amount_in as u128)
+            .checked_mul(MAX_SLIPPAGE_BPS.saturating_sub(RAYDIUM_SWAP_FEE_BPS) as u128)
+            .ok_or(GauntletError::MathOverflow)?
+            / MAX_SLIPPAGE_BPS as u128;
+        let denominator = (reserve_in as u128)
+            .checked_add(amount_in_after_fee)
+            .ok_or(GauntletError::MathOverflow)?;
+        if denominator == 0 {
+            return Ok(0);
+        }
+        let dest_token_amount = (reserve_out as u128)
+            .checked_mul(amount_in_after_fee)
+            .ok_or(GauntletError::MathOverflow)?
+            / denominator;
+        Ok(dest_token_amount as u64)
+    }
+
+    /// 예상 수령량에서 slippage 만큼 뺀 raydium swap 의 최소 수령량, slippage 가 0 이면 검사하지 않음
+    fn raydium_min_out(
+        accounts: &[AccountInfo],
+        amount_in: u64,
+        slippage_bps: u64,
+    ) -> Result<u64, ProgramError> {
+        if slippage_bps == 0 {
+            return Ok(0);
+        }
+        check_pool_program_id(&accounts[0])?;
+        let estimate = Self::raydium_estimate_out(accounts, amount_in)?;
+        let min_out = (estimate as u128)
+            .checked_mul(MAX_SLIPPAGE_BPS.saturating_sub(slippage_bps) as u128)
+            .ok_or(GauntletError::MathOverflow)?
+            / MAX_SLIPPAGE_BPS as u128;
+        Ok(min_out as u64)
+    }
+
+    fn deposit(
+        accounts: &[AccountInfo],
+        amount: u64,
+        deposit_type: DepositType,
+        with_deposit_fee_account: bool,
+        with_referral_account: bool,
+        create_user: bool,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let depositor = next_account_info(account_info_iter)?;
         let depositor_user_state_account = next_account_info(account_info_iter)?;
         let depositor_deposit_token_account = next_account_info(account_info_iter)?;
         let gauntlet_state_account = next_account_info(account_info_iter)?;
+        Gauntlet::check_not_paused(&gauntlet_state_account.data.borrow())?;
         let vault_state_account = next_account_info(account_info_iter)?;
         let vault_strategy_state_account = next_account_info(account_info_iter)?;
         let strategy_account = next_account_info(account_info_iter)?;
         let deposit_accounts = match deposit_type {
-            DepositType::RAYDIUM => next_account_infos(account_info_iter, 11).unwrap(),
-            DepositType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13).unwrap(),
+            DepositType::RAYDIUM => next_account_infos(account_info_iter, 11)?,
+            DepositType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13)?,
+        };
+        let deposit_token_mint = next_account_info(account_info_iter)?;
+        let deposit_fee_token_account = match with_deposit_fee_account {
+            true => Some(next_account_info(account_info_iter)?),
+            false => None,
         };
+        // fee 분배용 referral 은 deposit 마다 넘겨야 함 (User.referrer 는 attribution 용)
+        let referral_token_account = match with_referral_account {
+            true => Some(next_account_info(account_info_iter)?),
+            false => None,
+        };
+        if create_user {
+            // user account 가 아직 없으면 system program 으로 depositor 가 비용을 내고 생성
+            let system_program_account = next_account_info(account_info_iter)?;
+            Self::_create_user_account(
+                depositor,
+                vault_state_account,
+                strategy_account,
+                depositor_user_state_account,
+                system_program_account,
+                program_id,
+            )?;
+        } else {
+            check_account_owner(depositor_user_state_account, program_id)?;
+            // 다른 vault / strategy 조합으로 만든 user PDA 를 넘기지 못하게 seed 로 다시 확인
+            let (user_address, _bump_seed) = find_user_address(
+                vault_state_account.key,
+                depositor.key,
+                strategy_account.key,
+                program_id,
+            );
+            if *depositor_user_state_account.key != user_address {
+                return Err(ProgramError::InvalidSeeds);
+            }
+        }
         let vault_deposit_token_account = &deposit_accounts[5];
         let vault_reward_token_account = &deposit_accounts[7];
+        let token_program_account = &deposit_accounts[10];
         let vault_reward_b_token_account = match deposit_type {
             DepositType::RAYDIUM => None,
             DepositType::RAYDIUM_V4 => Some(&deposit_accounts[11]),
         };
 
-        let mut depositor_user_info =
-            User::unpack_unchecked(&depositor_user_state_account.data.borrow())?;
+        let mut depositor_user_info = User::unpack(&depositor_user_state_account.data.borrow())?;
         let depositor_token_account_info =
-            Account::unpack(&depositor_deposit_token_account.data.borrow())?;
+            unpack_token_account(&depositor_deposit_token_account.data.borrow())?;
         let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        Self::check_deposit_type(vault_info.has_second_reward, &deposit_type)?;
+        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
         let vault_deposit_token_account_info =
-            Account::unpack(&vault_deposit_token_account.data.borrow())?;
+            unpack_token_account(&vault_deposit_token_account.data.borrow())?;
         let vault_strategy_info =
             VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
-        let strategy_info = Strategy::unpack(&strategy_account.data.borrow())?;
-        let strategy_index = strategy_info.index as usize;
-
-        if !depositor_user_info.is_initialized {
-            depositor_user_info.is_initialized = true;
-            depositor_user_info.user = *depositor.key;
-            depositor_user_info.vault_account = *vault_state_account.key;
-            depositor_user_info.strategy_account = *strategy_account.key;
-            depositor_user_info.amount = 0;
-        }
+        let mut strategy_info = Strategy::unpack(&strategy_account.data.borrow())?;
+        let strategy_index = checked_index(strategy_info.index, MAX_NUMBER_OF_STRATEGY)?;
 
         if !depositor.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
@@ -767,7 +3674,7 @@ impl Processor {
             return Err(GauntletError::WrongUserAccount.into());
         }
 
-        if *vault_state_account.key != depositor_user_info.vault_account {
+        if depositor_user_info.vault_account != *vault_state_account.key {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
 
@@ -795,6 +3702,14 @@ impl Processor {
             return Err(GauntletError::WrongTokenAccount.into());
         }
 
+        if vault_info.deposit_token_mint != *deposit_token_mint.key {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        if vault_info.token_program != *token_program_account.key {
+            return Err(GauntletError::InvalidProgramId.into());
+        }
+
         if vault_info.reward_token_account != *vault_reward_token_account.key {
             return Err(GauntletError::WrongTokenAccount.into());
         }
@@ -810,58 +3725,170 @@ impl Processor {
             return Err(GauntletError::InvalidStatusStrategy.into());
         }
 
-        if depositor_user_info.user_status != 4 {
-            return Err(GauntletError::UserStatusError.into());
+        if amount > 0
+            && (!vault_info.deposits_enabled
+                || !vault_strategy_info.accepting_deposits[strategy_index])
+        {
+            return Err(GauntletError::DepositsClosed.into());
+        }
+
+        // amount 0 은 pending reward 정산용으로만 허용
+        if amount > 0 && amount < vault_info.min_deposit {
+            return Err(GauntletError::DepositTooSmall.into());
         }
 
+        // harvest 배분 도중에 deposit amount 가 바뀌면 남은 strategy 들의 몫이 달라짐
+        if vault_info.harvest_cursor != 0 {
+            return Err(GauntletError::HarvestInProgress.into());
+        }
+
+        // 예치된 LP 가 없는 user (이 instruction 에서 새로 만든 user 포함) 는 정산할 pending reward 가 없으므로
+        // harvest / swap 단계를 거쳐 READY 가 될 필요가 없음
         let clock = &Clock::get()?;
-        if clock.unix_timestamp > depositor_user_info.deadline {
-            return Err(GauntletError::TimeoutError.into());
+        if depositor_user_info.amount > 0 {
+            if depositor_user_info.user_status != USER_STATUS_READY {
+                return Err(GauntletError::UserStatusError.into());
+            }
+            if depositor_user_info.deadline != 0
+                && clock.unix_timestamp > depositor_user_info.deadline
+            {
+                return Err(GauntletError::TimeoutError.into());
+            }
         }
 
         if depositor_user_info.amount > 0 {
-            let user_amount = depositor_user_info.amount as u128;
-            let p = (user_amount
-                .checked_mul(vault_info.accumulated_reward_per_shares[strategy_index])
-                .unwrap()
-                .checked_shr(64)
-                .unwrap() as u64)
-                .checked_sub(depositor_user_info.reward_debt)
-                .unwrap();
-            depositor_user_info.reward = depositor_user_info.reward.checked_add(p).unwrap();
+            // 재예치된 LP 는 원금으로 (vault deposit amount 에는 이미 반영됨), strategy token 은 reward 로
+            depositor_user_info.settle(&vault_info, strategy_index)?;
+        }
+
+        if amount > 0 && depositor_user_info.referrer == Pubkey::default() {
+            if let Some(referral_token_account) = referral_token_account {
+                // 첫 deposit 에서 한번만 기록, 이후에는 바꿀 수 없음
+                let referral_token_account_info =
+                    unpack_token_account(&referral_token_account.data.borrow())?;
+                if referral_token_account_info.owner == *depositor.key {
+                    return Err(GauntletError::InvalidAccount.into());
+                }
+                depositor_user_info.referrer = referral_token_account_info.owner;
+            }
         }
 
         if amount > 0 {
-            transfer_token(
-                &spl_token::id(),
+            depositor_user_info.last_deposit_time = clock.unix_timestamp;
+            let deposit_fee = vault_info
+                .fees
+                .deposit_fee(amount as u128)
+                .ok_or(GauntletError::MathOverflow)? as u64;
+            let amount = amount
+                .checked_sub(deposit_fee)
+                .ok_or(GauntletError::MathOverflow)?;
+            if deposit_fee > 0 {
+                let deposit_fee_token_account =
+                    deposit_fee_token_account.ok_or(GauntletError::WrongFeeAccount)?;
+                if *deposit_fee_token_account.key != vault_info.deposit_fee_account {
+                    return Err(GauntletError::WrongFeeAccount.into());
+                }
+                // referral 이 있으면 deposit fee 중 일부를 referral 에게
+                let referral_fee = match referral_token_account {
+                    Some(referral_token_account) => {
+                        let referral_token_account_info =
+                            unpack_token_account(&referral_token_account.data.borrow())?;
+                        if referral_token_account_info.mint != vault_deposit_token_account_info.mint
+                        {
+                            return Err(GauntletError::WrongTokenAccount.into());
+                        }
+                        if referral_token_account_info.owner == *depositor.key {
+                            // 자기 자신을 referral 로 지정할 수 없음
+                            return Err(GauntletError::InvalidAccount.into());
+                        }
+                        let referral_fee = vault_info
+                            .fees
+                            .referral_fee(deposit_fee as u128)
+                            .ok_or(GauntletError::MathOverflow)?
+                            as u64;
+                        transfer_token_checked(
+                            token_program_account.key,
+                            depositor_deposit_token_account,
+                            deposit_token_mint,
+                            referral_token_account,
+                            depositor,
+                            referral_fee,
+                            &[],
+                        )?;
+                        referral_fee
+                    }
+                    None => 0,
+                };
+                transfer_token_checked(
+                    token_program_account.key,
+                    depositor_deposit_token_account,
+                    deposit_token_mint,
+                    deposit_fee_token_account,
+                    depositor,
+                    deposit_fee
+                        .checked_sub(referral_fee)
+                        .ok_or(GauntletError::MathOverflow)?,
+                    &[],
+                )?;
+            }
+            // transfer fee 가 있는 mint 나 raydium 이 일부만 stake 하는 경우를 위해
+            // 요청한 amount 가 아니라 실제로 stake 된 양을 기록함
+            let before_vault_deposit_amount =
+                unpack_token_account(&vault_deposit_token_account.data.borrow())?.amount;
+            transfer_token_checked(
+                token_program_account.key,
                 depositor_deposit_token_account,
+                deposit_token_mint,
                 vault_deposit_token_account,
                 depositor,
                 amount,
+                &[],
             )?;
+            let received_vault_deposit_amount =
+                unpack_token_account(&vault_deposit_token_account.data.borrow())?.amount;
+            let received_amount = received_vault_deposit_amount
+                .checked_sub(before_vault_deposit_amount)
+                .ok_or(GauntletError::AccountingDesync)?;
             match deposit_type {
-                DepositType::RAYDIUM => Raydium::raydium_deposit(deposit_accounts, amount).unwrap(),
-                DepositType::RAYDIUM_V4 => {
-                    Raydium::raydium_deposit_v4(deposit_accounts, amount).unwrap()
+                DepositType::RAYDIUM => {
+                    Raydium::raydium_deposit(deposit_accounts, received_amount, &[authority_seeds])?
                 }
+                DepositType::RAYDIUM_V4 => Raydium::raydium_deposit_v4(
+                    deposit_accounts,
+                    received_amount,
+                    &[authority_seeds],
+                )?,
+            }
+            let amount = received_vault_deposit_amount
+                .checked_sub(
+                    unpack_token_account(&vault_deposit_token_account.data.borrow())?.amount,
+                )
+                .ok_or(GauntletError::AccountingDesync)?;
+            // stake 되지 않은 LP 가 vault account 에 남으면 어느 user 의 몫인지 기록할 곳이 없음
+            if amount != received_amount {
+                return Err(GauntletError::AccountingDesync.into());
             }
-            depositor_user_info.amount = depositor_user_info.amount.checked_add(amount).unwrap();
-            vault_info.total_deposit_amount =
-                vault_info.total_deposit_amount.checked_add(amount).unwrap();
-            vault_info.deposit_amounts[strategy_index] = vault_info.deposit_amounts[strategy_index]
+            strategy_info.check_deposit_cap(amount)?;
+            depositor_user_info.amount = depositor_user_info
+                .amount
                 .checked_add(amount)
-                .unwrap();
+                .ok_or(GauntletError::MathOverflow)?;
+            vault_info.add_principal(&mut strategy_info, strategy_index, amount)?;
         }
 
-        let user_amount = depositor_user_info.amount as u128;
-        depositor_user_info.reward_debt = user_amount
-            .checked_mul(vault_info.accumulated_reward_per_shares[strategy_index])
-            .unwrap()
-            .checked_shr(64)
-            .unwrap() as u64;
+        depositor_user_info.reset_debts(&vault_info, strategy_index)?;
 
-        depositor_user_info.user_status = 0;
+        msg!(
+            "Deposit: user {} vault {} strategy {} amount {} referrer {}",
+            depositor.key,
+            vault_state_account.key,
+            strategy_account.key,
+            amount,
+            depositor_user_info.referrer
+        );
+        depositor_user_info.user_status = USER_STATUS_IDLE;
         Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
+        Strategy::pack(strategy_info, &mut strategy_account.data.borrow_mut())?;
         User::pack(
             depositor_user_info,
             &mut depositor_user_state_account.data.borrow_mut(),
@@ -874,6 +3901,8 @@ impl Processor {
         amount: u64,
         mut reward_amount: u64,
         withdraw_type: WithdrawType,
+        gas_reimbursement_bps: u64,
+        to_recipient: bool,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let withdrawer = next_account_info(account_info_iter)?;
@@ -888,27 +3917,70 @@ impl Processor {
         let withdraw_fee_token_account = next_account_info(account_info_iter)?;
         let performance_fee_token_account = next_account_info(account_info_iter)?;
         let withdraw_accounts = match withdraw_type {
-            WithdrawType::RAYDIUM => next_account_infos(account_info_iter, 11).unwrap(),
-            WithdrawType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13).unwrap(),
+            WithdrawType::RAYDIUM => next_account_infos(account_info_iter, 11)?,
+            WithdrawType::RAYDIUM_V4 => next_account_infos(account_info_iter, 13)?,
+        };
+        let deposit_token_mint = next_account_info(account_info_iter)?;
+        let strategy_token_mint = next_account_info(account_info_iter)?;
+        // strategy token 은 vault 와 다른 token program 일 수 있음
+        let strategy_token_program_account = next_account_info(account_info_iter)?;
+        let gas_reimbursement_accounts = match gas_reimbursement_bps > 0 {
+            true => Some(next_account_infos(account_info_iter, 19)?),
+            false => None,
+        };
+        // recipient 가 있으면 원금/보상을 그 계정으로 보냄, 서명은 여전히 User owner
+        let (withdrawer_deposit_token_account, withdrawer_reward_token_account) = match to_recipient
+        {
+            true => (
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+            ),
+            false => (
+                withdrawer_deposit_token_account,
+                withdrawer_reward_token_account,
+            ),
         };
+        // strategy 가 performance fee 를 strategist 와 나눌 때만 필요
+        let strategist_fee_token_account = next_account_info(account_info_iter).ok();
         let gauntlet_signer_account = &withdraw_accounts[4];
         let vault_deposit_token_account = &withdraw_accounts[5];
+        let token_program_account = &withdraw_accounts[10];
 
         let mut withdrawer_user_info = User::unpack(&withdrawer_user_state_account.data.borrow())?;
         let withdrawer_deposit_token_account_info =
-            Account::unpack(&withdrawer_deposit_token_account.data.borrow())?;
+            unpack_token_account(&withdrawer_deposit_token_account.data.borrow())?;
         let withdrawer_reward_token_account_info =
-            Account::unpack(&withdrawer_reward_token_account.data.borrow())?;
+            unpack_token_account(&withdrawer_reward_token_account.data.borrow())?;
         let mut vault_info = Vault::unpack(&vault_state_account.data.borrow())?;
+        let withdraw_type_matches = match withdraw_type {
+            WithdrawType::RAYDIUM => !vault_info.has_second_reward,
+            WithdrawType::RAYDIUM_V4 => vault_info.has_second_reward,
+        };
+        if !withdraw_type_matches {
+            return Err(GauntletError::WithdrawTypeError.into());
+        }
+        let gauntlet_info = Gauntlet::unpack(&gauntlet_state_account.data.borrow())?;
+        let authority_bump = [gauntlet_info.authority_bump];
+        let authority_seeds: &[&[u8]] = &[
+            AUTHORITY_SEED,
+            gauntlet_state_account.key.as_ref(),
+            &authority_bump,
+        ];
         let vault_deposit_token_account_info =
-            Account::unpack(&vault_deposit_token_account.data.borrow())?;
+            unpack_token_account(&vault_deposit_token_account.data.borrow())?;
         let mut vault_strategy_info =
             VaultStrategy::unpack(&vault_strategy_state_account.data.borrow())?;
-        let mut strategy_info = Strategy::unpack(&strategy_state_account.data.borrow())?;
-        let strategy_token_account_info = Account::unpack(&strategy_token_account.data.borrow())?;
+        // withdraw 는 이 vault 의 deposit_amounts 한 칸만 쓰므로 전체 vec 을 만들지 않음
+        let mut strategy_data = strategy_state_account.data.borrow_mut();
+        let mut strategy_ref = StrategyRef::new(&mut strategy_data[..])?;
+        if !strategy_ref.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let strategy_token_account_info =
+            unpack_token_account(&strategy_token_account.data.borrow())?;
 
-        let vault_index = vault_info.index as usize;
-        let strategy_index = strategy_info.index as usize;
+        let vault_index = checked_index(vault_info.index, MAX_NUMBER_OF_VAULTS)?;
+        let strategy_index = checked_index(strategy_ref.index(), MAX_NUMBER_OF_STRATEGY)?;
 
         if !withdrawer.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
@@ -930,10 +4002,54 @@ impl Processor {
             return Err(GauntletError::WrongTokenAccount.into());
         }
 
+        if strategy_token_account_info.mint != strategy_ref.strategy_token_mint() {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        if vault_info.deposit_token_mint != *deposit_token_mint.key
+            || strategy_ref.strategy_token_mint() != *strategy_token_mint.key
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        if vault_info.token_program != *token_program_account.key {
+            return Err(GauntletError::InvalidProgramId.into());
+        }
+
+        check_any_token_program_id(strategy_token_program_account)?;
+        if strategy_token_account.owner != strategy_token_program_account.key {
+            return Err(GauntletError::InvalidProgramId.into());
+        }
+
         if withdrawer_reward_token_account_info.mint != strategy_token_account_info.mint {
             return Err(GauntletError::WrongTokenAccount.into());
         }
 
+        // recipient 를 명시하지 않았으면 제3자 계정으로 보내지 못하도록 destination owner 확인
+        if !to_recipient
+            && (withdrawer_deposit_token_account_info.owner != *withdrawer.key
+                || withdrawer_reward_token_account_info.owner != *withdrawer.key)
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        // recipient 는 mint 검사 외에 원금은 vault, 보상은 strategy token 의 token program account 이고
+        // 원금/보상을 같은 owner 가 받아야 함
+        if to_recipient
+            && (*withdrawer_deposit_token_account.owner != vault_info.token_program
+                || withdrawer_reward_token_account.owner != strategy_token_program_account.key
+                || withdrawer_deposit_token_account_info.owner
+                    != withdrawer_reward_token_account_info.owner)
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+
+        if withdrawer_deposit_token_account_info.is_frozen()
+            || withdrawer_reward_token_account_info.is_frozen()
+        {
+            return Err(GauntletError::FrozenTokenAccount.into());
+        }
+
         if *gauntlet_state_account.key != vault_info.gauntlet_state_account {
             return Err(GauntletError::WrongVaultStateAccount.into());
         }
@@ -942,143 +4058,278 @@ impl Processor {
             return Err(GauntletError::WrongVaultStrategyStateAccount.into());
         }
 
-        if *gauntlet_state_account.key != strategy_info.gauntlet_state_account {
+        if *gauntlet_state_account.key != strategy_ref.gauntlet_state_account() {
             return Err(GauntletError::WrongStrategyStateAccount.into());
         }
 
         if vault_info.deposit_token_account != *vault_deposit_token_account.key {
             return Err(GauntletError::WrongTokenAccount.into());
         }
-        if strategy_info.strategy_token_account != *strategy_token_account.key {
+        if strategy_ref.strategy_token_account() != *strategy_token_account.key {
             return Err(GauntletError::WrongTokenAccount.into());
         }
         if vault_info.withdraw_fee_account != *withdraw_fee_token_account.key {
             return Err(GauntletError::WrongFeeAccount.into());
         }
 
-        if strategy_info.performance_fee_account != *performance_fee_token_account.key {
+        if strategy_ref.performance_fee_account() != *performance_fee_token_account.key {
             return Err(GauntletError::WrongFeeAccount.into());
         }
 
-        if withdrawer_user_info.user_status != 4 {
-            return Err(GauntletError::UserStatusError.into());
+        if !vault_info.withdrawals_enabled {
+            return Err(GauntletError::WithdrawalsClosed.into());
+        }
+
+        if vault_info.harvest_cursor != 0 {
+            return Err(GauntletError::HarvestInProgress.into());
         }
+
         let clock = &Clock::get()?;
-        if clock.unix_timestamp > withdrawer_user_info.deadline {
-            return Err(GauntletError::TimeoutError.into());
+        // global pause 중에는 harvest / swap 으로 READY 가 될 수 없으므로 status 없이 인출 허용
+        // 아직 harvest 되지 않은 reward 는 받지 못함
+        if !gauntlet_info.global_paused {
+            if withdrawer_user_info.user_status != USER_STATUS_READY {
+                return Err(GauntletError::UserStatusError.into());
+            }
+            if withdrawer_user_info.deadline != 0
+                && clock.unix_timestamp > withdrawer_user_info.deadline
+            {
+                return Err(GauntletError::TimeoutError.into());
+            }
         }
+        // deposit 직후 harvest 만 받고 빠지는 sandwich 방지
+        let withdrawable_time = withdrawer_user_info
+            .last_deposit_time
+            .saturating_add(vault_info.withdraw_cooldown_secs as UnixTimestamp);
+        if clock.unix_timestamp < withdrawable_time {
+            return Err(GauntletError::WithdrawCooldown.into());
+        }
+        if withdrawer_user_info.amount.gt(&0) {
+            // 재예치된 LP 는 원금으로 (vault deposit amount 에는 이미 반영됨), strategy token 은 reward 로
+            withdrawer_user_info.settle(&vault_info, strategy_index)?;
+        }
+
         // 이거 반대 아닐까..!?
         if withdrawer_user_info.amount.lt(&amount) {
             return Err(GauntletError::InvalidWithdrawAmount.into());
         }
 
-        if withdrawer_user_info.amount.gt(&0) {
-            let user_amount = withdrawer_user_info.amount as u128;
-            let p = (user_amount
-                .checked_mul(vault_info.accumulated_reward_per_shares[strategy_index])
-                .unwrap()
-                .checked_shr(64)
-                .unwrap() as u64)
-                .checked_sub(withdrawer_user_info.reward_debt)
-                .unwrap();
-            withdrawer_user_info.reward = withdrawer_user_info.reward.checked_add(p).unwrap();
+        if withdrawer_user_info.reward.lt(&reward_amount) {
+            // 요청량이 보유 reward 보다 많으면 보유량 전부를 인출
+            reward_amount = withdrawer_user_info.reward;
         }
 
-        if withdrawer_user_info.reward.lt(&reward_amount) {
+        if reward_amount.gt(&0) && vault_strategy_info.strategy_token_amounts[strategy_index] == 0 {
+            // swap 이 한번도 끝나지 않은 vault 는 나눠줄 strategy token 이 없음
+            msg!("No strategy token has been produced for this vault strategy yet");
             return Err(GauntletError::InvalidWithdrawAmount.into());
         }
 
         if reward_amount.gt(&0) {
-            let strat_amount = strategy_info.deposit_amounts[vault_index] as u128;
-            reward_amount = withdrawer_user_info.reward;
+            let strat_amount = strategy_ref.deposit_amount(vault_index) as u128;
             let withdraw_amount = strat_amount
                 .checked_mul(reward_amount as u128)
-                .unwrap()
+                .ok_or(GauntletError::MathOverflow)?
                 .checked_div(vault_strategy_info.strategy_token_amounts[strategy_index] as u128)
-                .unwrap() as u64;
-            strategy_info.deposit_amounts[vault_index] = strategy_info.deposit_amounts[vault_index]
-                .checked_sub(reward_amount)
-                .unwrap();
+                .ok_or(GauntletError::MathOverflow)? as u64;
+            // strategy 에 기록된 양은 strategy token 단위이므로 실제로 빠져나가는 withdraw_amount 만큼 줄임
+            strategy_ref.set_deposit_amount(
+                vault_index,
+                strategy_ref
+                    .deposit_amount(vault_index)
+                    .checked_sub(withdraw_amount)
+                    .ok_or(GauntletError::MathOverflow)?,
+            );
+            // 합계도 같이 줄여야 모두 빠져나간 strategy 를 retire 할 수 있음
+            strategy_ref.set_total_deposit_amount(
+                strategy_ref
+                    .total_deposit_amount()
+                    .checked_sub(withdraw_amount)
+                    .ok_or(GauntletError::MathOverflow)?,
+            );
             withdrawer_user_info.reward = withdrawer_user_info
                 .reward
                 .checked_sub(reward_amount)
-                .unwrap();
+                .ok_or(GauntletError::MathOverflow)?;
             vault_strategy_info.strategy_token_amounts[strategy_index] = vault_strategy_info
                 .strategy_token_amounts[strategy_index]
                 .checked_sub(reward_amount)
-                .unwrap();
-            let fee = (withdraw_amount as u128)
-                .checked_mul(vault_info.fees.performance_fee_numerator as u128)
-                .unwrap()
-                .checked_div(vault_info.fees.performance_fee_denominator as u128)
-                .unwrap() as u64;
-            if fee.gt(&0) {
-                transfer_token_signed(
-                    &spl_token::id(),
+                .ok_or(GauntletError::MathOverflow)?;
+            // numerator > denominator 로 잘못 설정돼도 fee 는 withdraw_amount 를 넘지 않음
+            let fee = vault_info
+                .fees
+                .performance_fee(withdraw_amount as u128)
+                .ok_or(GauntletError::MathOverflow)?
+                .min(withdraw_amount as u128) as u64;
+            // strategist 몫은 내림, 나머지(반올림 오차 포함)는 protocol treasury 로
+            let strategist_fee = (fee as u128)
+                .checked_mul(strategy_ref.strategist_fee_split_bps() as u128)
+                .ok_or(GauntletError::MathOverflow)?
+                .checked_div(BPS_DENOMINATOR as u128)
+                .ok_or(GauntletError::MathOverflow)? as u64;
+            if strategist_fee.gt(&0) {
+                let strategist_fee_token_account =
+                    strategist_fee_token_account.ok_or(GauntletError::WrongFeeAccount)?;
+                if *strategist_fee_token_account.key != strategy_ref.strategist_fee_account() {
+                    return Err(GauntletError::WrongFeeAccount.into());
+                }
+                transfer_token_checked(
+                    strategy_token_program_account.key,
+                    strategy_token_account,
+                    strategy_token_mint,
+                    strategist_fee_token_account,
+                    gauntlet_signer_account,
+                    strategist_fee,
+                    &[authority_seeds],
+                )?;
+            }
+            let protocol_fee = fee
+                .checked_sub(strategist_fee)
+                .ok_or(GauntletError::MathOverflow)?;
+            if protocol_fee.gt(&0) {
+                transfer_token_checked(
+                    strategy_token_program_account.key,
                     strategy_token_account,
+                    strategy_token_mint,
                     performance_fee_token_account,
                     gauntlet_signer_account,
-                    fee,
+                    protocol_fee,
+                    &[authority_seeds],
+                )?;
+            }
+            // 통계용 값이라 overflow 시 saturate
+            vault_info.cumulative_performance_fees =
+                vault_info.cumulative_performance_fees.saturating_add(fee);
+            let mut reward_out_amount = withdraw_amount
+                .checked_sub(fee)
+                .ok_or(GauntletError::MathOverflow)?;
+            if let Some(gas_reimbursement_accounts) = gas_reimbursement_accounts {
+                if gas_reimbursement_bps > MAX_GAS_REIMBURSEMENT_BPS {
+                    return Err(GauntletError::GasReimbursementTooLarge.into());
+                }
+                // swap route: strategy token account -> withdrawer 의 wSOL account, authority 는 pda
+                let withdrawer_wsol_account = &gas_reimbursement_accounts[17];
+                if *gas_reimbursement_accounts[16].key != *strategy_token_account.key
+                    || *gas_reimbursement_accounts[18].key != *gauntlet_signer_account.key
+                {
+                    return Err(GauntletError::InvalidAccount.into());
+                }
+                let withdrawer_wsol_account_info =
+                    unpack_token_account(&withdrawer_wsol_account.data.borrow())?;
+                if withdrawer_wsol_account_info.mint != spl_token::native_mint::id()
+                    || withdrawer_wsol_account_info.owner != *withdrawer.key
+                {
+                    return Err(GauntletError::WrongTokenAccount.into());
+                }
+                let gas_amount = (reward_out_amount as u128)
+                    .checked_mul(gas_reimbursement_bps as u128)
+                    .ok_or(GauntletError::MathOverflow)?
+                    .checked_div(BPS_DENOMINATOR as u128)
+                    .ok_or(GauntletError::MathOverflow)? as u64;
+                if gas_amount.gt(&0) {
+                    let min_out = Self::raydium_min_out(
+                        gas_reimbursement_accounts,
+                        gas_amount,
+                        vault_info.swap_slippage_bps,
+                    )?;
+                    let swapped = Processor::raydium_swap(
+                        gas_reimbursement_accounts,
+                        gas_amount,
+                        min_out,
+                        vault_info.min_swap_output,
+                        &[authority_seeds],
+                    )?;
+                    if swapped {
+                        reward_out_amount = reward_out_amount
+                            .checked_sub(gas_amount)
+                            .ok_or(GauntletError::MathOverflow)?;
+                    }
+                }
+                // wSOL account 를 닫아서 SOL 로 받음
+                close_token_account(
+                    &spl_token::id(),
+                    withdrawer_wsol_account,
+                    withdrawer,
+                    withdrawer,
                 )?;
             }
-            transfer_token_signed(
-                &spl_token::id(),
+            transfer_token_checked(
+                strategy_token_program_account.key,
                 strategy_token_account,
+                strategy_token_mint,
                 withdrawer_reward_token_account,
                 gauntlet_signer_account,
-                withdraw_amount.checked_sub(fee).unwrap(),
+                reward_out_amount,
+                &[authority_seeds],
             )?;
         }
 
         if amount.gt(&0) {
             match withdraw_type {
                 WithdrawType::RAYDIUM => {
-                    Raydium::raydium_withdraw(withdraw_accounts, amount).unwrap()
+                    Raydium::raydium_withdraw(withdraw_accounts, amount, &[authority_seeds])?
                 }
                 WithdrawType::RAYDIUM_V4 => {
-                    Raydium::raydium_withdraw_v4(withdraw_accounts, amount).unwrap()
+                    Raydium::raydium_withdraw_v4(withdraw_accounts, amount, &[authority_seeds])?
                 }
             }
-            withdrawer_user_info.amount = withdrawer_user_info.amount.checked_sub(amount).unwrap();
+            withdrawer_user_info.amount = withdrawer_user_info
+                .amount
+                .checked_sub(amount)
+                .ok_or(GauntletError::MathOverflow)?;
             vault_info.deposit_amounts[strategy_index] = vault_info.deposit_amounts[strategy_index]
                 .checked_sub(amount)
-                .unwrap();
-            vault_info.total_deposit_amount =
-                vault_info.total_deposit_amount.checked_sub(amount).unwrap();
-            let fee = (amount as u128)
-                .checked_mul(vault_info.fees.withdrawal_fee_numerator as u128)
-                .unwrap()
-                .checked_div(vault_info.fees.withdrawal_fee_denominator as u128)
-                .unwrap() as u64;
+                .ok_or(GauntletError::MathOverflow)?;
+            vault_info.total_deposit_amount = vault_info
+                .total_deposit_amount
+                .checked_sub(amount)
+                .ok_or(GauntletError::MathOverflow)?;
+            // 재예치된 LP 도 add_principal 로 routed_deposit_amount 에 들어갔으므로 모자라면 장부가 어긋난 것
+            strategy_ref.set_routed_deposit_amount(
+                strategy_ref
+                    .routed_deposit_amount()
+                    .checked_sub(amount)
+                    .ok_or(GauntletError::AccountingDesync)?,
+            );
+            // 원금보다 큰 fee 는 amount 로 잘라서 인출액이 음수가 되지 않게 함
+            let fee = vault_info
+                .fees
+                .withdrawal_fee(amount as u128)
+                .ok_or(GauntletError::MathOverflow)?
+                .min(amount as u128) as u64;
             if fee.gt(&0) {
-                transfer_token_signed(
-                    &spl_token::id(),
+                transfer_token_checked(
+                    token_program_account.key,
                     vault_deposit_token_account,
+                    deposit_token_mint,
                     withdraw_fee_token_account,
                     gauntlet_signer_account,
                     fee,
+                    &[authority_seeds],
                 )?;
+                // 통계용 값이라 overflow 시 saturate
+                vault_info.cumulative_withdrawal_fees =
+                    vault_info.cumulative_withdrawal_fees.saturating_add(fee);
             }
-            transfer_token_signed(
-                &spl_token::id(),
+            transfer_token_checked(
+                token_program_account.key,
                 vault_deposit_token_account,
+                deposit_token_mint,
                 withdrawer_deposit_token_account,
                 gauntlet_signer_account,
-                amount.checked_sub(fee).unwrap(),
+                amount.checked_sub(fee).ok_or(GauntletError::MathOverflow)?,
+                &[authority_seeds],
             )?;
         }
-        withdrawer_user_info.reward_debt = (withdrawer_user_info.amount as u128)
-            .checked_mul(vault_info.accumulated_reward_per_shares[strategy_index])
-            .unwrap()
-            .checked_shr(64)
-            .unwrap() as u64;
-        withdrawer_user_info.user_status = 0;
+        // pending reward는 위에서 이미 reward로 옮겼으므로, 같은 acc 기준으로 debt를 다시 잡으면
+        // 부분 인출 직후 pending(amount, acc, reward_debt) 는 항상 0 이 됨
+        withdrawer_user_info.reset_debts(&vault_info, strategy_index)?;
+        withdrawer_user_info.user_status = USER_STATUS_IDLE;
         Vault::pack(vault_info, &mut vault_state_account.data.borrow_mut())?;
         VaultStrategy::pack(
             vault_strategy_info,
             &mut vault_strategy_state_account.data.borrow_mut(),
         )?;
-        Strategy::pack(strategy_info, &mut strategy_state_account.data.borrow_mut())?;
         User::pack(
             withdrawer_user_info,
             &mut withdrawer_user_state_account.data.borrow_mut(),
@@ -1087,251 +4338,600 @@ impl Processor {
         Ok(())
     }
 
-    fn _harvest(
-        gauntlet_account_info: &Gauntlet,
-        vault_account_info: &mut Vault,
-        vault_strategy_info: &VaultStrategy,
-        harvest_accounts: &[AccountInfo],
-        vault_reward_token_account: &AccountInfo,
-        vault_reward_b_token_account: &Option<&AccountInfo>,
+    /// second reward 가 있는 farm 만 raydium v4 staking 을 씀
+    fn check_deposit_type(has_second_reward: bool, deposit_type: &DepositType) -> ProgramResult {
+        let matches = match deposit_type {
+            DepositType::RAYDIUM => !has_second_reward,
+            DepositType::RAYDIUM_V4 => has_second_reward,
+        };
+        if !matches {
+            return Err(GauntletError::DepositTypeError.into());
+        }
+        Ok(())
+    }
+
+    fn _harvest<'a>(
+        vault_state_account: &AccountInfo<'a>,
+        harvest_accounts: &[AccountInfo<'a>],
+        vault_reward_token_account: &AccountInfo<'a>,
+        vault_reward_b_token_account: &Option<&AccountInfo<'a>>,
+        keeper_reward_accounts: Option<(&AccountInfo<'a>, &AccountInfo<'a>)>,
         deposit_type: &DepositType,
-    ) -> ProgramResult {
-        // _harvest함수는 farm_reward_token, farm_reward_token_b를 raydium에서 harvest한후 vault_state에 각 strategies에 배분될 farm_reward_token들 양을 계산해서 업데이트까지만 함
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<u64, ProgramError> {
+        // 반환값은 이번에 harvest 된 reward token (a + b) 양
+        // vault data 는 cpi 사이에 잡고 있지 않도록 필요할 때만 짧게 borrow 함
+        {
+            let mut vault_data = vault_state_account.data.borrow_mut();
+            let mut vault_ref = VaultRef::new(&mut vault_data[..])?;
+            // 예치가 없으면 배분할 곳이 없으므로 caller 의 guard 와 별개로 여기서도 harvest 하지 않음
+            if vault_ref.total_deposit_amount() == 0 {
+                return Ok(0);
+            }
+            vault_ref.set_last_reward_update_time(Clock::get()?.unix_timestamp);
+        }
+        // _harvest함수는 farm_reward_token, farm_reward_token_b를 raydium에서 harvest한후 strategies에 배분될 양을 vault_state에 기록까지만 함
+        // 실제 배분은 _distribute_harvest 에서 여러 번에 나눠서 할 수 있음
         let vault_reward_token_account_info =
-            Account::unpack(&vault_reward_token_account.data.borrow())?;
+            unpack_token_account(&vault_reward_token_account.data.borrow())?;
         let before_reward_token_balance = vault_reward_token_account_info.amount;
-        let strategies_len = gauntlet_account_info.strategies_len as usize;
-        if vault_reward_b_token_account.is_some() {
-            let vault_reward_b_token_account = vault_reward_b_token_account.unwrap();
-            let vault_reward_b_token_account_info =
-                Account::unpack(&vault_reward_b_token_account.data.borrow())?;
-            let before_reward_b_token_balance = vault_reward_b_token_account_info.amount;
+        let before_reward_b_token_balance = match vault_reward_b_token_account {
+            Some(vault_reward_b_token_account) => {
+                unpack_token_account(&vault_reward_b_token_account.data.borrow())?.amount
+            }
+            None => 0,
+        };
 
-            match deposit_type {
-                DepositType::RAYDIUM => Raydium::raydium_harvest(harvest_accounts).unwrap(),
-                DepositType::RAYDIUM_V4 => Raydium::raydium_harvest_v4(harvest_accounts).unwrap(),
+        match deposit_type {
+            DepositType::RAYDIUM => Raydium::raydium_harvest(harvest_accounts, signer_seeds)?,
+            DepositType::RAYDIUM_V4 => Raydium::raydium_harvest_v4(harvest_accounts, signer_seeds)?,
+        }
+
+        // reward token harvest 된 양 계산
+        let vault_reward_token_account_info =
+            unpack_token_account(&vault_reward_token_account.data.borrow())?;
+        let reward_token_harvest_amount = vault_reward_token_account_info
+            .amount
+            .checked_sub(before_reward_token_balance)
+            .ok_or(GauntletError::AccountingDesync)?;
+        let reward_b_token_harvest_amount = match vault_reward_b_token_account {
+            Some(vault_reward_b_token_account) => {
+                unpack_token_account(&vault_reward_b_token_account.data.borrow())?
+                    .amount
+                    .checked_sub(before_reward_b_token_balance)
+                    .ok_or(GauntletError::AccountingDesync)?
             }
-            let vault_reward_token_account_info =
-                Account::unpack(&vault_reward_token_account.data.borrow())?;
-            let vault_reward_b_token_account_info =
-                Account::unpack(&vault_reward_b_token_account.data.borrow())?;
+            None => 0,
+        };
 
-            // reward token harvest 된 양 계산
-            let reward_token_harvest_amount = vault_reward_token_account_info
-                .amount
-                .checked_sub(before_reward_token_balance)
-                .unwrap() as u128;
+        // strategy 에 배분하기 전에 keeper bounty 를 먼저 떼어줌 (reward a 에서만)
+        let (keeper_fee, reward_token_mint, token_program) = {
+            let mut vault_data = vault_state_account.data.borrow_mut();
+            let vault_ref = VaultRef::new(&mut vault_data[..])?;
+            (
+                vault_ref.keeper_fee(reward_token_harvest_amount)?,
+                vault_ref.reward_token_mint(),
+                vault_ref.token_program(),
+            )
+        };
+        if keeper_fee > 0 {
+            let (keeper_reward_token_account, reward_token_mint_account) =
+                keeper_reward_accounts.ok_or(GauntletError::WrongFeeAccount)?;
+            let keeper_reward_token_account_info =
+                unpack_token_account(&keeper_reward_token_account.data.borrow())?;
+            if keeper_reward_token_account_info.mint != vault_reward_token_account_info.mint
+                || *reward_token_mint_account.key != reward_token_mint
+                || *keeper_reward_token_account.owner != token_program
+            {
+                return Err(GauntletError::WrongFeeAccount.into());
+            }
+            // harvest_accounts[4] 는 harvest 에서 확인한 gauntlet authority
+            transfer_token_checked(
+                &token_program,
+                vault_reward_token_account,
+                reward_token_mint_account,
+                keeper_reward_token_account,
+                &harvest_accounts[4],
+                keeper_fee,
+                signer_seeds,
+            )?;
+        }
 
-            let reward_b_token_harvest_amount = vault_reward_b_token_account_info
-                .amount
-                .checked_sub(before_reward_b_token_balance)
-                .unwrap() as u128;
-
-            // 각 strategies에 deposit token양 비율 만큼 reward token양 배분
-            for i in 0..strategies_len {
-                if vault_strategy_info.availabilities[i] {
-                    // availabilty가 true일때만 reward 계산 및 분배
-                    vault_account_info.reward_token_remain_amounts[i] = vault_account_info
-                        .reward_token_remain_amounts[i]
-                        .checked_add(
-                            reward_token_harvest_amount
-                                .checked_mul(vault_account_info.deposit_amounts[i] as u128)
-                                .unwrap()
-                                .checked_div(vault_account_info.total_deposit_amount as u128)
-                                .unwrap() as u64,
-                        )
-                        .unwrap();
-
-                    vault_account_info.reward_token_b_remain_amounts[i] = vault_account_info
-                        .reward_token_b_remain_amounts[i]
-                        .checked_add(
-                            reward_b_token_harvest_amount
-                                .checked_mul(vault_account_info.deposit_amounts[i] as u128)
-                                .unwrap()
-                                .checked_div(vault_account_info.total_deposit_amount as u128)
-                                .unwrap() as u64,
-                        )
-                        .unwrap();
+        let mut vault_data = vault_state_account.data.borrow_mut();
+        let mut vault_ref = VaultRef::new(&mut vault_data[..])?;
+        vault_ref.set_harvest_pending_amount(
+            reward_token_harvest_amount
+                .checked_sub(keeper_fee)
+                .ok_or(GauntletError::MathOverflow)?,
+        );
+        vault_ref.set_harvest_pending_b_amount(reward_b_token_harvest_amount);
+        let harvested_amount = reward_token_harvest_amount
+            .checked_add(reward_b_token_harvest_amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        Ok(harvested_amount)
+    }
+
+    /// harvest 된 reward 를 [start, end) strategy 들에 deposit token 양 비율 만큼 배분
+    fn _distribute_harvest(
+        vault_ref: &mut VaultRef,
+        vault_strategy_info: &VaultStrategy,
+        start: usize,
+        end: usize,
+    ) -> ProgramResult {
+        let reward_token_harvest_amount = vault_ref.harvest_pending_amount() as u128;
+        let reward_b_token_harvest_amount = vault_ref.harvest_pending_b_amount() as u128;
+        let total_deposit_amount = vault_ref.total_deposit_amount();
+        if total_deposit_amount == 0 {
+            return Ok(());
+        }
+        // swap 이 안 돌아서 reward 가 계속 쌓이면 조용히 넘어가지 않고 harvest 를 실패시킴, 0 이면 cap 없음
+        let max_pending_reward = match vault_ref.max_pending_reward() {
+            0 => u64::MAX,
+            cap => cap,
+        };
+        let deposit_amounts: Vec<u64> = (start..end).map(|i| vault_ref.deposit_amount(i)).collect();
+        let availabilities = &vault_strategy_info.availabilities[start..end];
+        // availabilty가 true일때만 reward 계산 및 분배
+        let reward_shares = distribute(
+            reward_token_harvest_amount,
+            &deposit_amounts,
+            availabilities,
+            total_deposit_amount,
+        )?;
+        let reward_b_shares = distribute(
+            reward_b_token_harvest_amount,
+            &deposit_amounts,
+            availabilities,
+            total_deposit_amount,
+        )?;
+        for i in start..end {
+            if vault_strategy_info.availabilities[i] {
+                let reward_token_remain_amount = vault_ref
+                    .reward_token_remain_amount(i)
+                    .checked_add(reward_shares[i - start])
+                    .ok_or(GauntletError::MathOverflow)?;
+                if reward_token_remain_amount > max_pending_reward {
+                    msg!("strategy {} pending reward exceeds the vault cap", i);
+                    return Err(GauntletError::PendingRewardOverflow.into());
+                }
+                vault_ref.set_reward_token_remain_amount(i, reward_token_remain_amount);
+
+                if reward_b_token_harvest_amount > 0 {
+                    let reward_token_b_remain_amount = vault_ref
+                        .reward_token_b_remain_amount(i)
+                        .checked_add(reward_b_shares[i - start])
+                        .ok_or(GauntletError::MathOverflow)?;
+                    if reward_token_b_remain_amount > max_pending_reward {
+                        msg!("strategy {} pending reward b exceeds the vault cap", i);
+                        return Err(GauntletError::PendingRewardOverflow.into());
+                    }
+                    vault_ref.set_reward_token_b_remain_amount(i, reward_token_b_remain_amount);
                 }
             }
-        } else {
-            match deposit_type {
-                DepositType::RAYDIUM => Raydium::raydium_harvest(harvest_accounts).unwrap(),
-                DepositType::RAYDIUM_V4 => Raydium::raydium_harvest_v4(harvest_accounts).unwrap(),
+        }
+        Ok(())
+    }
+
+    /// backend 별 swap account 개수, jupiter 는 route 에 따라 늘어나므로 최소 개수
+    /// backend 를 추가할 때는 이 함수와 swap_token_indexes 만 고치면 됨
+    fn swap_account_count(swap_type: &SwapType) -> usize {
+        match swap_type {
+            SwapType::RAYDIUM => 19,
+            SwapType::ORCA => 12,
+            SwapType::JUPITER => JUPITER_ROUTE_ACCOUNTS_OFFSET,
+        }
+    }
+
+    /// backend 별 swap account 안의 (source, dest) token account index
+    fn swap_token_indexes(swap_type: &SwapType) -> (usize, usize) {
+        match swap_type {
+            SwapType::RAYDIUM => (16, 17),
+            SwapType::ORCA => (4, 6),
+            SwapType::JUPITER => (2, 3),
+        }
+    }
+
+    /// swap account slice 를 꺼냄, jupiter 는 route 에 따라 account 개수가 달라서 남은 account 를 모두 사용
+    fn next_swap_accounts<'a, 'b>(
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        swap_type: &SwapType,
+    ) -> Result<&'a [AccountInfo<'b>], ProgramError> {
+        let count = Self::swap_account_count(swap_type);
+        match swap_type {
+            SwapType::JUPITER => {
+                let route_accounts = account_info_iter.as_slice();
+                if route_accounts.len() < count {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                }
+                account_info_iter.nth(route_accounts.len() - 1);
+                Ok(route_accounts)
             }
-            let vault_reward_token_account_info =
-                Account::unpack(&vault_reward_token_account.data.borrow())?;
-            let reward_token_harvest_amount = vault_reward_token_account_info
-                .amount
-                .checked_sub(before_reward_token_balance)
-                .unwrap() as u128;
-            // 각 Strategy별 swap하기를 나기다리는 남은 reward의 양을 업데이트함
-            for i in 0..strategies_len {
-                if vault_strategy_info.availabilities[i] {
-                    // availabilty가 true일때만 reward 계산 및 분배
-                    vault_account_info.reward_token_remain_amounts[i] = vault_account_info
-                        .reward_token_remain_amounts[i]
-                        .checked_add(
-                            reward_token_harvest_amount
-                                .checked_mul(vault_account_info.deposit_amounts[i] as u128)
-                                .unwrap()
-                                .checked_div(vault_account_info.total_deposit_amount as u128)
-                                .unwrap() as u64,
-                        )
-                        .unwrap();
+            _ => next_account_infos(account_info_iter, count),
+        }
+    }
+
+    /// swap 의 source token account 와 pool 이 reward mint 를 거래하는지 확인 (account 순서만 믿지 않음)
+    fn check_swap_source_mint(
+        swap_accounts: &[AccountInfo],
+        swap_type: &SwapType,
+        reward_mint: &Pubkey,
+    ) -> ProgramResult {
+        let (source_index, _) = Self::swap_token_indexes(swap_type);
+        let pool_indexes: &[usize] = match swap_type {
+            // pool coin / pc 중 하나가 reward mint 여야 함
+            SwapType::RAYDIUM => &[6, 7],
+            // source_token_vault
+            SwapType::ORCA => &[5],
+            // route 는 jupiter 가 검증, source 만 확인
+            SwapType::JUPITER => &[],
+        };
+        let source_mint = unpack_token_account(&swap_accounts[source_index].data.borrow())?.mint;
+        if source_mint != *reward_mint {
+            return Err(GauntletError::RewardTokenAccountError.into());
+        }
+        if !pool_indexes.is_empty() {
+            let mut pool_trades_reward = false;
+            for &i in pool_indexes {
+                if unpack_token_account(&swap_accounts[i].data.borrow())?.mint == *reward_mint {
+                    pool_trades_reward = true;
                 }
             }
+            if !pool_trades_reward {
+                return Err(GauntletError::RewardTokenAccountError.into());
+            }
         }
         Ok(())
     }
 
+    /// 예상 수령량이 min_swap_output 보다 작으면 swap 하지 않고 false 를 반환, caller 는 남은 양을 다음 crank 로 넘김
+    fn orca_swap(
+        accounts: &[AccountInfo],
+        vault_account_info: &Vault,
+        price_check: &Option<OraclePriceCheck>,
+        amount_in: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<bool, ProgramError> {
+        let slippage_bps = vault_account_info.swap_slippage_bps;
+        let mut estimate = 0;
+        if vault_account_info.min_swap_output > 0 || slippage_bps > 0 {
+            estimate = Orca::whirlpool_estimate_out(accounts, amount_in)?;
+            if estimate < vault_account_info.min_swap_output {
+                return Ok(false);
+            }
+        }
+        let min_out = Self::orca_min_out(estimate, slippage_bps, price_check, amount_in)?;
+        Orca::whirlpool_swap(accounts, amount_in, min_out, signer_seeds)?;
+        Ok(true)
+    }
+
+    /// whirlpool 예상 수령량에서 slippage 만큼 뺀 값과 oracle 가격 중 큰 값을 min-out 으로 사용
+    /// 0 을 받는 swap 은 항상 실패하도록 최소 1
+    fn orca_min_out(
+        estimate: u64,
+        slippage_bps: u64,
+        price_check: &Option<OraclePriceCheck>,
+        amount_in: u64,
+    ) -> Result<u64, ProgramError> {
+        let oracle_min_out = match price_check {
+            Some(price_check) => price_check.min_amount_out(amount_in)?,
+            None => 0,
+        };
+        let pool_min_out = match slippage_bps {
+            0 => 0,
+            _ => {
+                ((estimate as u128)
+                    .checked_mul(MAX_SLIPPAGE_BPS.saturating_sub(slippage_bps) as u128)
+                    .ok_or(GauntletError::MathOverflow)?
+                    / MAX_SLIPPAGE_BPS as u128) as u64
+            }
+        };
+        Ok(oracle_min_out.max(pool_min_out).max(1))
+    }
+
+    /// RAY strategy 가 이번에 swap 할 reward 를 그대로 strategy token 으로 쓰는 경우
+    fn is_direct_reward_transfer(
+        vault_account_info: &Vault,
+        strategy_account_info: &Strategy,
+        second_reward_token: bool,
+    ) -> bool {
+        strategy_account_info.strategy_type == StrategyType::RAY
+            && strategy_account_info.strategy_token_mint
+                == vault_account_info.reward_mint(second_reward_token)
+    }
+
     fn _swap_farm_token_to_usdc(
         vault_account_info: &mut Vault,
         strategy_index: usize,
         usdc_token_account: &AccountInfo,
-        swap_reward_to_usdc_accounts: &[AccountInfo],
-        swap_type: &SwapType,
+        route: &SwapRoute,
         second_reward_token: bool,
+        signer_seeds: &[&[&[u8]]],
     ) -> ProgramResult {
-        let usdc_token_account_info = Account::unpack(&usdc_token_account.data.borrow())?;
+        let swap_reward_to_usdc_accounts = route.accounts;
+        let usdc_token_account_info = unpack_token_account(&usdc_token_account.data.borrow())?;
         let before_usdc_token_amount = usdc_token_account_info.amount;
         let reward_token_remain_amounts = match second_reward_token {
             false => vault_account_info.reward_token_remain_amounts[strategy_index],
             true => vault_account_info.reward_token_b_remain_amounts[strategy_index],
         };
         if reward_token_remain_amounts.gt(&0) {
-            match swap_type {
+            Self::check_swap_source_mint(
+                swap_reward_to_usdc_accounts,
+                route.swap_type,
+                &vault_account_info.reward_mint(second_reward_token),
+            )?;
+            match route.swap_type {
                 SwapType::RAYDIUM => {
-                    Self::raydium_swap(
+                    let min_out = Self::raydium_min_out(
+                        swap_reward_to_usdc_accounts,
+                        reward_token_remain_amounts,
+                        vault_account_info.swap_slippage_bps,
+                    )?;
+                    if !Self::raydium_swap(
+                        swap_reward_to_usdc_accounts,
+                        reward_token_remain_amounts,
+                        min_out,
+                        vault_account_info.min_swap_output,
+                        signer_seeds,
+                    )? {
+                        // dust 는 remain amount 에 남겨두고 다음 harvest 분과 함께 swap
+                        return Ok(());
+                    }
+                }
+                SwapType::ORCA => {
+                    if !Self::orca_swap(
+                        swap_reward_to_usdc_accounts,
+                        vault_account_info,
+                        route.price_check,
+                        reward_token_remain_amounts,
+                        signer_seeds,
+                    )? {
+                        return Ok(());
+                    }
+                }
+                SwapType::JUPITER => {
+                    Jupiter::route_swap(
                         swap_reward_to_usdc_accounts,
+                        route.route_data,
                         reward_token_remain_amounts,
-                        0,
-                    )
-                    .unwrap();
+                        signer_seeds,
+                    )?;
                 }
             }
             match second_reward_token {
                 false => vault_account_info.reward_token_remain_amounts[strategy_index] = 0,
                 true => vault_account_info.reward_token_b_remain_amounts[strategy_index] = 0,
             }
-            let usdc_token_account_info = Account::unpack(&usdc_token_account.data.borrow())?;
+            let usdc_token_account_info = unpack_token_account(&usdc_token_account.data.borrow())?;
             let swap_amount = usdc_token_account_info
                 .amount
                 .checked_sub(before_usdc_token_amount)
-                .unwrap() as u128;
+                .ok_or(GauntletError::MathOverflow)? as u128;
+            // solana-program 1.7 에는 set_return_data 가 없어서 실제 swap 양을 log 로 남김, keeper 는 tx log 에서 읽음
+            msg!(
+                "swap_result: in {} out {}",
+                reward_token_remain_amounts,
+                swap_amount
+            );
+            if let Some(price_check) = route.price_check {
+                if (swap_amount as u64) < price_check.min_amount_out(reward_token_remain_amounts)? {
+                    return Err(GauntletError::PriceDeviation.into());
+                }
+            }
 
             vault_account_info.usdc_token_amounts[strategy_index] = vault_account_info
                 .usdc_token_amounts[strategy_index]
                 .checked_add(swap_amount as u64)
-                .unwrap(); // 스왑한 usdc amount를 vault state에 update
+                .ok_or(GauntletError::MathOverflow)?; // 스왑한 usdc amount를 vault state에 update
         }
 
         Ok(())
     }
 
-    fn _swap_usdc_to_strategy_token(
+    fn _swap_usdc_to_strategy_token<'a>(
         vault_account_info: &mut Vault,
         vault_strategy_account_info: &mut VaultStrategy,
         strategy_account_info: &mut Strategy,
-        strategy_token_account: &AccountInfo,
-        usdc_token_account: &AccountInfo,
-        swap_usdc_to_strategy_accounts: &[AccountInfo],
-        swap_type: &SwapType,
+        usdc_token_account: &AccountInfo<'a>,
+        swap: &StrategyTokenSwap<'_, 'a>,
+        signer_seeds: &[&[&[u8]]],
     ) -> ProgramResult {
-        let vault_index = vault_account_info.index as usize;
-        let strategy_index = strategy_account_info.index as usize;
-
-        let available_usdc_amount = vault_account_info.usdc_token_amounts[strategy_index];
+        let StrategyTokenSwap {
+            strategy_token_account,
+            accounts: swap_usdc_to_strategy_accounts,
+            swap_type,
+            restake_accounts,
+        } = swap;
+        let vault_index = checked_index(vault_account_info.index, MAX_NUMBER_OF_VAULTS)?;
+        let strategy_index = checked_index(strategy_account_info.index, MAX_NUMBER_OF_STRATEGY)?;
+
+        // 한번에 swap 하는 양을 제한, 나머지는 다음 crank 에서 swap
+        let available_usdc_amount = match vault_account_info.max_swap_usdc_per_tx {
+            0 => vault_account_info.usdc_token_amounts[strategy_index],
+            cap => vault_account_info.usdc_token_amounts[strategy_index].min(cap),
+        };
 
-        let usdc_token_account_info = Account::unpack(&usdc_token_account.data.borrow())?;
+        let usdc_token_account_info = unpack_token_account(&usdc_token_account.data.borrow())?;
 
         let before_usdc_balance = usdc_token_account_info.amount;
 
-        let strategy_token_account_info = Account::unpack(&strategy_token_account.data.borrow())?;
+        let strategy_token_account_info =
+            unpack_token_account(&strategy_token_account.data.borrow())?;
         let before_strategy_token_amount = strategy_token_account_info.amount;
         if available_usdc_amount.gt(&0) {
             match swap_type {
                 SwapType::RAYDIUM => {
-                    Processor::raydium_swap(
+                    let min_out = Self::raydium_min_out(
+                        swap_usdc_to_strategy_accounts,
+                        available_usdc_amount,
+                        vault_account_info.swap_slippage_bps,
+                    )?;
+                    if !Processor::raydium_swap(
+                        swap_usdc_to_strategy_accounts,
+                        available_usdc_amount,
+                        min_out,
+                        vault_account_info.min_swap_output,
+                        signer_seeds,
+                    )? {
+                        return Ok(());
+                    }
+                }
+                SwapType::ORCA => {
+                    if !Self::orca_swap(
                         swap_usdc_to_strategy_accounts,
+                        vault_account_info,
+                        &None,
                         available_usdc_amount,
-                        0,
-                    )
-                    .unwrap();
+                        signer_seeds,
+                    )? {
+                        return Ok(());
+                    }
                 }
+                SwapType::JUPITER => return Err(GauntletError::WrongRoutingMode.into()),
             }
 
-            let usdc_token_account_info = Account::unpack(&usdc_token_account.data.borrow())?;
+            let usdc_token_account_info = unpack_token_account(&usdc_token_account.data.borrow())?;
             let swaped_usdc_amount = before_usdc_balance
                 .checked_sub(usdc_token_account_info.amount)
-                .unwrap();
-            vault_account_info.usdc_token_amounts[strategy_index] = available_usdc_amount
+                .ok_or(GauntletError::MathOverflow)?;
+            // usdc account 는 strategy 끼리 공유하므로 잔액 감소량이 이 strategy 몫보다 크면 회계가 어긋난 것
+            vault_account_info.usdc_token_amounts[strategy_index] = vault_account_info
+                .usdc_token_amounts[strategy_index]
                 .checked_sub(swaped_usdc_amount)
-                .unwrap(); // swap하고 남은 짜투리 usdc양 업데이트
+                .ok_or(GauntletError::AccountingDesync)?; // swap하고 남은 짜투리 usdc양 업데이트
 
             let strategy_token_account_info =
-                Account::unpack(&strategy_token_account.data.borrow())?;
+                unpack_token_account(&strategy_token_account.data.borrow())?;
             let swap_amount = strategy_token_account_info
                 .amount
                 .checked_sub(before_strategy_token_amount)
-                .unwrap() as u128;
-            // 해당 strategy의 acc 업데이트
-            vault_account_info.accumulated_reward_per_shares[strategy_index] = vault_account_info
-                .accumulated_reward_per_shares[strategy_index]
-                .checked_add(
-                    swap_amount
-                        .checked_shl(64)
-                        .unwrap()
-                        .checked_div(vault_account_info.deposit_amounts[strategy_index] as u128)
-                        .unwrap(),
-                )
-                .unwrap();
-
-            // 해당 strategy state들 업데이트
-            strategy_account_info.total_deposit_amount = strategy_account_info
-                .total_deposit_amount
-                .checked_add(swap_amount as u64)
-                .unwrap();
-            strategy_account_info.deposit_amounts[vault_index] = strategy_account_info
-                .deposit_amounts[vault_index]
-                .checked_add(swap_amount as u64)
-                .unwrap();
-
-            vault_strategy_account_info.strategy_token_amounts[strategy_index] =
-                vault_strategy_account_info.strategy_token_amounts[strategy_index]
-                    .checked_add(swap_amount as u64)
-                    .unwrap();
+                .ok_or(GauntletError::MathOverflow)? as u128;
+            msg!("swap_result: in {} out {}", swaped_usdc_amount, swap_amount);
+            let mut claimable_amount = swap_amount as u64;
+            let mut restake_amount = 0;
+            if vault_strategy_account_info.compounding[strategy_index] {
+                // strategy token 이 deposit LP 이면 compound_ratio_bps 만큼 다시 staking 해서 원금으로 복리
+                // (update_vault_strategy 가 strategy token mint 로 표시함)
+                restake_amount = vault_account_info.compound_amount(claimable_amount)?;
+                claimable_amount = claimable_amount
+                    .checked_sub(restake_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+            }
+            // 해당 strategy의 acc 업데이트, 재예치 몫은 _restake_strategy_token 이 실제 재예치된 양으로 기록
+            vault_account_info.add_strategy_gain(strategy_index, claimable_amount, 0)?;
+            if restake_amount > 0 {
+                Self::_restake_strategy_token(
+                    vault_account_info,
+                    strategy_account_info,
+                    strategy_index,
+                    strategy_token_account,
+                    restake_accounts,
+                    restake_amount,
+                    signer_seeds,
+                )?;
+            }
+            if claimable_amount > 0 {
+                // 해당 strategy state들 업데이트
+                strategy_account_info.total_deposit_amount = strategy_account_info
+                    .total_deposit_amount
+                    .checked_add(claimable_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+                strategy_account_info.deposit_amounts[vault_index] = strategy_account_info
+                    .deposit_amounts[vault_index]
+                    .checked_add(claimable_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+
+                vault_strategy_account_info.strategy_token_amounts[strategy_index] =
+                    vault_strategy_account_info.strategy_token_amounts[strategy_index]
+                        .checked_add(claimable_amount)
+                        .ok_or(GauntletError::MathOverflow)?;
+            }
         }
         Ok(())
     }
 
-    fn _swap_reward_to_strategy_token(
+    fn _swap_reward_to_strategy_token<'a>(
         vault_account_info: &mut Vault,
         vault_strategy_account_info: &mut VaultStrategy,
         strategy_account_info: &mut Strategy,
-        strategy_token_account: &AccountInfo,
-        swap_reward_to_strategy_accounts: &[AccountInfo],
-        swap_type: &SwapType,
+        vault_reward_token_account: &AccountInfo<'a>,
+        swap: &StrategyTokenSwap<'_, 'a>,
         second_reward_token: bool,
+        signer_seeds: &[&[&[u8]]],
     ) -> ProgramResult {
-        let vault_index = vault_account_info.index as usize;
-        let strategy_index = strategy_account_info.index as usize;
+        let StrategyTokenSwap {
+            strategy_token_account,
+            accounts: swap_reward_to_strategy_accounts,
+            swap_type,
+            restake_accounts,
+        } = swap;
+        let vault_index = checked_index(vault_account_info.index, MAX_NUMBER_OF_VAULTS)?;
+        let strategy_index = checked_index(strategy_account_info.index, MAX_NUMBER_OF_STRATEGY)?;
 
         let reward_token_remain_amounts = match second_reward_token {
             false => vault_account_info.reward_token_remain_amounts[strategy_index],
             true => vault_account_info.reward_token_b_remain_amounts[strategy_index],
         };
-        let strategy_token_account_info = Account::unpack(&strategy_token_account.data.borrow())?;
+        let strategy_token_account_info =
+            unpack_token_account(&strategy_token_account.data.borrow())?;
         let before_strategy_token_amount = strategy_token_account_info.amount;
         if reward_token_remain_amounts.gt(&0) {
-            match swap_type {
-                SwapType::RAYDIUM => {
-                    Processor::raydium_swap(
-                        swap_reward_to_strategy_accounts,
-                        reward_token_remain_amounts,
-                        0,
-                    )
-                    .unwrap();
+            if Self::is_direct_reward_transfer(
+                vault_account_info,
+                strategy_account_info,
+                second_reward_token,
+            ) {
+                // RAY strategy는 reward token이 곧 strategy token 이므로 swap 없이 옮기기만 함
+                // accounts 는 (vault reward token, strategy token, authority, token program, reward mint),
+                // authority 와 token program 은 호출 전에 확인됨
+                let reward_mint = &swap_reward_to_strategy_accounts[4];
+                if *reward_mint.key != vault_account_info.reward_mint(second_reward_token) {
+                    return Err(GauntletError::WrongTokenAccount.into());
+                }
+                transfer_token_checked(
+                    swap_reward_to_strategy_accounts[3].key,
+                    vault_reward_token_account,
+                    reward_mint,
+                    strategy_token_account,
+                    &swap_reward_to_strategy_accounts[2],
+                    reward_token_remain_amounts,
+                    signer_seeds,
+                )?;
+            } else {
+                Self::check_swap_source_mint(
+                    swap_reward_to_strategy_accounts,
+                    swap_type,
+                    &vault_account_info.reward_mint(second_reward_token),
+                )?;
+                match swap_type {
+                    SwapType::RAYDIUM => {
+                        let min_out = Self::raydium_min_out(
+                            swap_reward_to_strategy_accounts,
+                            reward_token_remain_amounts,
+                            vault_account_info.swap_slippage_bps,
+                        )?;
+                        if !Processor::raydium_swap(
+                            swap_reward_to_strategy_accounts,
+                            reward_token_remain_amounts,
+                            min_out,
+                            vault_account_info.min_swap_output,
+                            signer_seeds,
+                        )? {
+                            return Ok(());
+                        }
+                    }
+                    SwapType::ORCA => {
+                        if !Self::orca_swap(
+                            swap_reward_to_strategy_accounts,
+                            vault_account_info,
+                            &None,
+                            reward_token_remain_amounts,
+                            signer_seeds,
+                        )? {
+                            return Ok(());
+                        }
+                    }
+                    SwapType::JUPITER => return Err(GauntletError::WrongRoutingMode.into()),
                 }
             }
             match second_reward_token {
@@ -1340,38 +4940,121 @@ impl Processor {
             }
 
             let strategy_token_account_info =
-                Account::unpack(&strategy_token_account.data.borrow())?;
+                unpack_token_account(&strategy_token_account.data.borrow())?;
             let swap_amount = strategy_token_account_info
                 .amount
                 .checked_sub(before_strategy_token_amount)
-                .unwrap() as u128;
-            // 해당 strategy의 acc 업데이트
-            vault_account_info.accumulated_reward_per_shares[strategy_index] = vault_account_info
-                .accumulated_reward_per_shares[strategy_index]
-                .checked_add(
-                    swap_amount
-                        .checked_shl(64)
-                        .unwrap()
-                        .checked_div(vault_account_info.deposit_amounts[strategy_index] as u128)
-                        .unwrap(),
-                )
-                .unwrap();
+                .ok_or(GauntletError::MathOverflow)? as u128;
+            msg!(
+                "swap_result: in {} out {}",
+                reward_token_remain_amounts,
+                swap_amount
+            );
+            let mut claimable_amount = swap_amount as u64;
+            let mut restake_amount = 0;
+            if vault_strategy_account_info.compounding[strategy_index] {
+                // strategy token 이 deposit LP 이면 compound_ratio_bps 만큼 다시 staking 해서 원금으로 복리
+                // (update_vault_strategy 가 strategy token mint 로 표시함)
+                restake_amount = vault_account_info.compound_amount(claimable_amount)?;
+                claimable_amount = claimable_amount
+                    .checked_sub(restake_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+            }
+            // 해당 strategy의 acc 업데이트, 재예치 몫은 _restake_strategy_token 이 실제 재예치된 양으로 기록
+            vault_account_info.add_strategy_gain(strategy_index, claimable_amount, 0)?;
+            if restake_amount > 0 {
+                Self::_restake_strategy_token(
+                    vault_account_info,
+                    strategy_account_info,
+                    strategy_index,
+                    strategy_token_account,
+                    restake_accounts,
+                    restake_amount,
+                    signer_seeds,
+                )?;
+            }
+            if claimable_amount > 0 {
+                // 해당 strategy state들 업데이트
+                strategy_account_info.total_deposit_amount = strategy_account_info
+                    .total_deposit_amount
+                    .checked_add(claimable_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+                strategy_account_info.deposit_amounts[vault_index] = strategy_account_info
+                    .deposit_amounts[vault_index]
+                    .checked_add(claimable_amount)
+                    .ok_or(GauntletError::MathOverflow)?;
+
+                vault_strategy_account_info.strategy_token_amounts[strategy_index] =
+                    vault_strategy_account_info.strategy_token_amounts[strategy_index]
+                        .checked_add(claimable_amount)
+                        .ok_or(GauntletError::MathOverflow)?;
+            }
+        }
+        Ok(())
+    }
 
-            // 해당 strategy state들 업데이트
-            strategy_account_info.total_deposit_amount = strategy_account_info
-                .total_deposit_amount
-                .checked_add(swap_amount as u64)
-                .unwrap();
-            strategy_account_info.deposit_amounts[vault_index] = strategy_account_info
-                .deposit_amounts[vault_index]
-                .checked_add(swap_amount as u64)
-                .unwrap();
+    fn _restake_strategy_token<'a>(
+        vault_account_info: &mut Vault,
+        strategy_account_info: &mut Strategy,
+        strategy_index: usize,
+        strategy_token_account: &AccountInfo<'a>,
+        restake_accounts: &[AccountInfo<'a>],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        // 남은 account 개수가 아니라 vault 설정으로 raydium deposit 방식을 고름
+        let (deposit_type, account_count) = if vault_account_info.has_second_reward {
+            (DepositType::RAYDIUM_V4, 13)
+        } else {
+            (DepositType::RAYDIUM, 11)
+        };
+        // raydium deposit accounts 뒤에 deposit LP mint
+        if restake_accounts.len() <= account_count {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let deposit_token_mint = &restake_accounts[account_count];
+        let restake_accounts = &restake_accounts[..account_count];
+        let gauntlet_signer_account = &restake_accounts[4];
+        let vault_deposit_token_account = &restake_accounts[5];
+        let token_program_account = &restake_accounts[10];
+
+        if *vault_deposit_token_account.key != vault_account_info.deposit_token_account
+            || *deposit_token_mint.key != vault_account_info.deposit_token_mint
+        {
+            return Err(GauntletError::WrongTokenAccount.into());
+        }
+        if *token_program_account.key != vault_account_info.token_program {
+            return Err(GauntletError::InvalidProgramId.into());
+        }
 
-            vault_strategy_account_info.strategy_token_amounts[strategy_index] =
-                vault_strategy_account_info.strategy_token_amounts[strategy_index]
-                    .checked_add(swap_amount as u64)
-                    .unwrap();
+        // transfer fee 가 있는 LP 면 vault 에 도착한 양만 원금이 됨
+        let before_vault_deposit_amount =
+            unpack_token_account(&vault_deposit_token_account.data.borrow())?.amount;
+        transfer_token_checked(
+            token_program_account.key,
+            strategy_token_account,
+            deposit_token_mint,
+            vault_deposit_token_account,
+            gauntlet_signer_account,
+            amount,
+            signer_seeds,
+        )?;
+        let amount = unpack_token_account(&vault_deposit_token_account.data.borrow())?
+            .amount
+            .checked_sub(before_vault_deposit_amount)
+            .ok_or(GauntletError::AccountingDesync)?;
+        // 재예치 몫은 재예치 전 deposit amount 기준으로 지금의 ratio 로 기록해서 정산 때 원금으로 돌려줌
+        vault_account_info.add_strategy_gain(strategy_index, 0, amount)?;
+        match deposit_type {
+            DepositType::RAYDIUM => {
+                Raydium::raydium_deposit(restake_accounts, amount, signer_seeds)?
+            }
+            DepositType::RAYDIUM_V4 => {
+                Raydium::raydium_deposit_v4(restake_accounts, amount, signer_seeds)?
+            }
         }
+
+        vault_account_info.add_principal(strategy_account_info, strategy_index, amount)?;
         Ok(())
     }
 
@@ -1382,30 +5065,67 @@ impl Processor {
         let strategy_state_account = next_account_info(account_info_iter)?;
         let depositor_user_state_account = next_account_info(account_info_iter)?;
         let system_program_account = next_account_info(account_info_iter)?;
-        let (_pda, _seed) = Pubkey::find_program_address(
-            &[
-                &vault_state_account.key.to_bytes(),
-                &depositor.key.to_bytes(),
-                &strategy_state_account.key.to_bytes(),
-            ],
+        Self::_create_user_account(
+            depositor,
+            vault_state_account,
+            strategy_state_account,
+            depositor_user_state_account,
+            system_program_account,
+            program_id,
+        )
+    }
+
+    /// CreateUserAccount 와 deposit 에서 같이 쓰는 user PDA 생성 / 초기화
+    fn _create_user_account<'a>(
+        depositor: &AccountInfo<'a>,
+        vault_state_account: &AccountInfo<'a>,
+        strategy_state_account: &AccountInfo<'a>,
+        depositor_user_state_account: &AccountInfo<'a>,
+        system_program_account: &AccountInfo<'a>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        check_system_program_id(system_program_account)?;
+        let (_pda, _seed) = find_user_address(
+            vault_state_account.key,
+            depositor.key,
+            strategy_state_account.key,
             program_id,
         );
         if *depositor_user_state_account.key != _pda {
             return Err(ProgramError::InvalidSeeds);
         }
-        create_pda_account(
-            depositor,
-            130,
-            program_id,
-            system_program_account,
-            depositor_user_state_account,
-            &[
-                &vault_state_account.key.to_bytes(),
-                &depositor.key.to_bytes(),
-                &strategy_state_account.key.to_bytes(),
-                &[_seed],
-            ],
-        )?;
+
+        // 이미 생성된 account 면 다시 만들지 않음
+        if depositor_user_state_account.owner != program_id {
+            create_pda_account(
+                depositor,
+                User::LEN,
+                program_id,
+                system_program_account,
+                depositor_user_state_account,
+                &[
+                    &vault_state_account.key.to_bytes(),
+                    &depositor.key.to_bytes(),
+                    &strategy_state_account.key.to_bytes(),
+                    &[_seed],
+                ],
+            )?;
+        } else if depositor_user_state_account.data_len() != User::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let depositor_user_info =
+            User::unpack_unchecked(&depositor_user_state_account.data.borrow())?;
+        if !depositor_user_info.is_initialized {
+            User::pack(
+                User::init(
+                    *depositor.key,
+                    *vault_state_account.key,
+                    *strategy_state_account.key,
+                ),
+                &mut depositor_user_state_account.data.borrow_mut(),
+            )?;
+        }
         Ok(())
     }
 }
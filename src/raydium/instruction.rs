@@ -4,14 +4,49 @@ use solana_program::{
     pubkey::Pubkey,
 };
 use std::mem::size_of;
+
+/// accounts of a raydium amm add liquidity, coin / pc in pool order
+pub struct AddLiquidityAccounts<'a> {
+    pub amm_program_id: &'a Pubkey,
+    pub token_program_id: &'a Pubkey,
+    pub amm_id: &'a Pubkey,
+    pub amm_authority: &'a Pubkey,
+    pub amm_open_orders: &'a Pubkey,
+    pub amm_target_orders: &'a Pubkey,
+    pub lp_mint: &'a Pubkey,
+    pub pool_coin_token_account: &'a Pubkey,
+    pub pool_pc_token_account: &'a Pubkey,
+    pub serum_market: &'a Pubkey,
+    pub user_coin_token_account: &'a Pubkey,
+    pub user_pc_token_account: &'a Pubkey,
+    pub user_lp_token_account: &'a Pubkey,
+    pub user_owner: &'a Pubkey,
+}
+
 pub enum RaydiumInstruction {
-    Deposit { amount: u64 },
-    DepositV4 { amount: u64 },
+    Deposit {
+        amount: u64,
+    },
+    DepositV4 {
+        amount: u64,
+    },
     Harvest {},
     HarvestV4 {},
-    Withdraw { amount: u64 },
-    WithdrawV4 { amount: u64 },
-    Swap { amount_in: u64, amount_out: u64 },
+    Withdraw {
+        amount: u64,
+    },
+    WithdrawV4 {
+        amount: u64,
+    },
+    Swap {
+        amount_in: u64,
+        amount_out: u64,
+    },
+    AddLiquidity {
+        max_coin_amount: u64,
+        max_pc_amount: u64,
+        base_side: u64,
+    },
 }
 
 impl RaydiumInstruction {
@@ -52,6 +87,16 @@ impl RaydiumInstruction {
                 buf.extend_from_slice(&amount_in.to_le_bytes());
                 buf.extend_from_slice(&amount_out.to_le_bytes());
             }
+            RaydiumInstruction::AddLiquidity {
+                max_coin_amount,
+                max_pc_amount,
+                base_side,
+            } => {
+                buf.push(3);
+                buf.extend_from_slice(&max_coin_amount.to_le_bytes());
+                buf.extend_from_slice(&max_pc_amount.to_le_bytes());
+                buf.extend_from_slice(&base_side.to_le_bytes());
+            }
         };
         buf
     }
@@ -317,4 +362,38 @@ impl RaydiumInstruction {
             data,
         })
     }
+    pub fn add_liquidity(
+        add_liquidity_accounts: &AddLiquidityAccounts,
+        max_coin_amount: u64,
+        max_pc_amount: u64,
+        base_side: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = RaydiumInstruction::AddLiquidity {
+            max_coin_amount,
+            max_pc_amount,
+            base_side,
+        }
+        .pack();
+        let accounts = vec![
+            AccountMeta::new_readonly(*add_liquidity_accounts.token_program_id, false),
+            AccountMeta::new(*add_liquidity_accounts.amm_id, false),
+            AccountMeta::new_readonly(*add_liquidity_accounts.amm_authority, false),
+            AccountMeta::new_readonly(*add_liquidity_accounts.amm_open_orders, false),
+            AccountMeta::new(*add_liquidity_accounts.amm_target_orders, false),
+            AccountMeta::new(*add_liquidity_accounts.lp_mint, false),
+            AccountMeta::new(*add_liquidity_accounts.pool_coin_token_account, false),
+            AccountMeta::new(*add_liquidity_accounts.pool_pc_token_account, false),
+            AccountMeta::new_readonly(*add_liquidity_accounts.serum_market, false),
+            AccountMeta::new(*add_liquidity_accounts.user_coin_token_account, false),
+            AccountMeta::new(*add_liquidity_accounts.user_pc_token_account, false),
+            AccountMeta::new(*add_liquidity_accounts.user_lp_token_account, false),
+            AccountMeta::new_readonly(*add_liquidity_accounts.user_owner, true),
+        ];
+
+        Ok(Instruction {
+            program_id: *add_liquidity_accounts.amm_program_id,
+            accounts,
+            data,
+        })
+    }
 }
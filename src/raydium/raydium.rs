@@ -1,13 +1,40 @@
-use crate::raydium::instruction::RaydiumInstruction;
-use crate::utils::{check_pool_program_id, check_staking_program_id};
+use crate::raydium::instruction::{AddLiquidityAccounts, RaydiumInstruction};
+use crate::utils::{check_pool_program_id, check_serum_program_id, check_staking_program_id};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program::invoke_signed,
 };
+/// swap fee of the raydium amm, taken from amount_in
+pub const RAYDIUM_SWAP_FEE_BPS: u64 = 25;
+
+/// size of the raydium staking UserInfo account (single reward farm)
+/// state u64 + pool id 32 + staker owner 32 + deposit balance u64 + reward debt u64
+pub const RAYDIUM_USER_INFO_LEN: usize = 8 + 32 + 32 + 8 + 8;
+/// size of the raydium staking v4 UserInfo account (dual reward farm)
+/// same as above with an extra reward debt u64 for reward b
+pub const RAYDIUM_USER_INFO_V4_LEN: usize = RAYDIUM_USER_INFO_LEN + 8;
+
+// raydium 쪽 layout 이 바뀌면 여기서 컴파일이 깨지도록
+const _: () = assert!(RAYDIUM_USER_INFO_LEN == 88);
+const _: () = assert!(RAYDIUM_USER_INFO_V4_LEN == 96);
+
 pub struct Raydium;
 impl Raydium {
-    pub fn raydium_deposit(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    /// size of the staking UserInfo account the farm expects, dual reward farms use the v4 layout
+    pub fn user_info_len(has_reward_b: bool) -> usize {
+        if has_reward_b {
+            RAYDIUM_USER_INFO_V4_LEN
+        } else {
+            RAYDIUM_USER_INFO_LEN
+        }
+    }
+
+    pub fn raydium_deposit(
+        accounts: &[AccountInfo],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let program_id = next_account_info(account_info_iter)?;
         let pool_id = next_account_info(account_info_iter)?;
@@ -20,7 +47,7 @@ impl Raydium {
         let pool_reward_token_account = next_account_info(account_info_iter)?;
         let clock_account = next_account_info(account_info_iter)?;
         let token_program = next_account_info(account_info_iter)?;
-        check_staking_program_id(program_id).unwrap();
+        check_staking_program_id(program_id)?;
         let deposit_ix = RaydiumInstruction::deposit(
             program_id.key,
             pool_id.key,
@@ -35,10 +62,14 @@ impl Raydium {
             token_program.key,
             amount,
         )?;
-        invoke_signed(&deposit_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&deposit_ix, accounts, signer_seeds)?;
         Ok(())
     }
-    pub fn raydium_deposit_v4(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    pub fn raydium_deposit_v4(
+        accounts: &[AccountInfo],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let program_id = next_account_info(account_info_iter)?;
         let pool_id = next_account_info(account_info_iter)?;
@@ -53,7 +84,7 @@ impl Raydium {
         let token_program = next_account_info(account_info_iter)?;
         let user_reward_token_account_b = next_account_info(account_info_iter)?;
         let pool_reward_token_account_b = next_account_info(account_info_iter)?;
-        check_staking_program_id(program_id).unwrap();
+        check_staking_program_id(program_id)?;
         let deposit_v4_ix = RaydiumInstruction::deposit_v4(
             program_id.key,
             pool_id.key,
@@ -70,10 +101,10 @@ impl Raydium {
             pool_reward_token_account_b.key,
             amount,
         )?;
-        invoke_signed(&deposit_v4_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&deposit_v4_ix, accounts, signer_seeds)?;
         Ok(())
     }
-    pub fn raydium_harvest(accounts: &[AccountInfo]) -> ProgramResult {
+    pub fn raydium_harvest(accounts: &[AccountInfo], signer_seeds: &[&[&[u8]]]) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let program_id = next_account_info(account_info_iter)?;
         let pool_id = next_account_info(account_info_iter)?;
@@ -86,7 +117,7 @@ impl Raydium {
         let pool_reward_token_account = next_account_info(account_info_iter)?;
         let clock_account = next_account_info(account_info_iter)?;
         let token_program = next_account_info(account_info_iter)?;
-        check_staking_program_id(program_id).unwrap();
+        check_staking_program_id(program_id)?;
         let harvest_ix = RaydiumInstruction::harvest(
             program_id.key,
             pool_id.key,
@@ -100,10 +131,13 @@ impl Raydium {
             clock_account.key,
             token_program.key,
         )?;
-        invoke_signed(&harvest_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&harvest_ix, accounts, signer_seeds)?;
         Ok(())
     }
-    pub fn raydium_harvest_v4(accounts: &[AccountInfo]) -> ProgramResult {
+    pub fn raydium_harvest_v4(
+        accounts: &[AccountInfo],
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let program_id = next_account_info(account_info_iter)?;
         let pool_id = next_account_info(account_info_iter)?;
@@ -118,7 +152,7 @@ impl Raydium {
         let token_program = next_account_info(account_info_iter)?;
         let user_reward_token_account_b = next_account_info(account_info_iter)?;
         let pool_reward_token_account_b = next_account_info(account_info_iter)?;
-        check_staking_program_id(program_id).unwrap();
+        check_staking_program_id(program_id)?;
         let deposit_v4_ix = RaydiumInstruction::harvest_v4(
             program_id.key,
             pool_id.key,
@@ -134,10 +168,14 @@ impl Raydium {
             user_reward_token_account_b.key,
             pool_reward_token_account_b.key,
         )?;
-        invoke_signed(&deposit_v4_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&deposit_v4_ix, accounts, signer_seeds)?;
         Ok(())
     }
-    pub fn raydium_withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    pub fn raydium_withdraw(
+        accounts: &[AccountInfo],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let program_id = next_account_info(account_info_iter)?;
         let pool_id = next_account_info(account_info_iter)?;
@@ -150,7 +188,7 @@ impl Raydium {
         let pool_reward_token_account = next_account_info(account_info_iter)?;
         let clock_account = next_account_info(account_info_iter)?;
         let token_program = next_account_info(account_info_iter)?;
-        check_staking_program_id(program_id).unwrap();
+        check_staking_program_id(program_id)?;
         let withdraw_ix = RaydiumInstruction::withdraw(
             program_id.key,
             pool_id.key,
@@ -165,10 +203,14 @@ impl Raydium {
             token_program.key,
             amount,
         )?;
-        invoke_signed(&withdraw_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&withdraw_ix, accounts, signer_seeds)?;
         Ok(())
     }
-    pub fn raydium_withdraw_v4(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    pub fn raydium_withdraw_v4(
+        accounts: &[AccountInfo],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let program_id = next_account_info(account_info_iter)?;
         let pool_id = next_account_info(account_info_iter)?;
@@ -183,7 +225,7 @@ impl Raydium {
         let token_program = next_account_info(account_info_iter)?;
         let user_reward_token_account_b = next_account_info(account_info_iter)?;
         let pool_reward_token_account_b = next_account_info(account_info_iter)?;
-        check_staking_program_id(program_id).unwrap();
+        check_staking_program_id(program_id)?;
         let withdraw_v4_ix = RaydiumInstruction::withdraw_v4(
             program_id.key,
             pool_id.key,
@@ -200,13 +242,14 @@ impl Raydium {
             pool_reward_token_account_b.key,
             amount,
         )?;
-        invoke_signed(&withdraw_v4_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&withdraw_v4_ix, accounts, signer_seeds)?;
         Ok(())
     }
     pub fn raydium_swap(
         accounts: &[AccountInfo],
         amount_in: u64,
         amount_out: u64,
+        signer_seeds: &[&[&[u8]]],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let amm_program_id = next_account_info(account_info_iter)?;
@@ -228,7 +271,8 @@ impl Raydium {
         let user_source_token_account = next_account_info(account_info_iter)?;
         let user_dest_token_account = next_account_info(account_info_iter)?;
         let user_owner = next_account_info(account_info_iter)?;
-        check_pool_program_id(amm_program_id).unwrap();
+        check_pool_program_id(amm_program_id)?;
+        check_serum_program_id(serum_program_id)?;
         let swap_ix = RaydiumInstruction::swap(
             amm_program_id.key,
             token_program_id.key,
@@ -252,7 +296,55 @@ impl Raydium {
             amount_in,
             amount_out,
         )?;
-        invoke_signed(&swap_ix, accounts, &[&[&b"glt"[..], &[255]]])?;
+        invoke_signed(&swap_ix, accounts, signer_seeds)?;
+        Ok(())
+    }
+    /// base_side 0 은 coin amount 기준, 1 은 pc amount 기준으로 상대 토큰 양이 정해짐
+    pub fn raydium_add_liquidity(
+        accounts: &[AccountInfo],
+        max_coin_amount: u64,
+        max_pc_amount: u64,
+        base_side: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let amm_program_id = next_account_info(account_info_iter)?;
+        let token_program_id = next_account_info(account_info_iter)?;
+        let amm_id = next_account_info(account_info_iter)?;
+        let amm_authority = next_account_info(account_info_iter)?;
+        let amm_open_orders = next_account_info(account_info_iter)?;
+        let amm_target_orders = next_account_info(account_info_iter)?;
+        let lp_mint = next_account_info(account_info_iter)?;
+        let pool_coin_token_account = next_account_info(account_info_iter)?;
+        let pool_pc_token_account = next_account_info(account_info_iter)?;
+        let serum_market = next_account_info(account_info_iter)?;
+        let user_coin_token_account = next_account_info(account_info_iter)?;
+        let user_pc_token_account = next_account_info(account_info_iter)?;
+        let user_lp_token_account = next_account_info(account_info_iter)?;
+        let user_owner = next_account_info(account_info_iter)?;
+        check_pool_program_id(amm_program_id)?;
+        let add_liquidity_ix = RaydiumInstruction::add_liquidity(
+            &AddLiquidityAccounts {
+                amm_program_id: amm_program_id.key,
+                token_program_id: token_program_id.key,
+                amm_id: amm_id.key,
+                amm_authority: amm_authority.key,
+                amm_open_orders: amm_open_orders.key,
+                amm_target_orders: amm_target_orders.key,
+                lp_mint: lp_mint.key,
+                pool_coin_token_account: pool_coin_token_account.key,
+                pool_pc_token_account: pool_pc_token_account.key,
+                serum_market: serum_market.key,
+                user_coin_token_account: user_coin_token_account.key,
+                user_pc_token_account: user_pc_token_account.key,
+                user_lp_token_account: user_lp_token_account.key,
+                user_owner: user_owner.key,
+            },
+            max_coin_amount,
+            max_pc_amount,
+            base_side,
+        )?;
+        invoke_signed(&add_liquidity_ix, accounts, signer_seeds)?;
         Ok(())
     }
 }
@@ -1,6 +1,7 @@
-use crate::error::GauntletError;
+use crate::{error::GauntletError, instruction::StrategyType};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
+    account_info::AccountInfo,
     clock::{Clock, UnixTimestamp},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
@@ -13,6 +14,8 @@ use std::convert::TryFrom;
 pub enum Status {
     PAUSED,
     NORMAL,
+    /// strategy only, set by RetireStrategy and never cleared
+    RETIRED,
 }
 
 impl Default for Status {
@@ -21,6 +24,82 @@ impl Default for Status {
     }
 }
 
+/// One-call read of a gauntlet state account for integrators
+pub trait TryFromAccount: Pack + IsInitialized {
+    /// Fails with IncorrectProgramId if the account isn't owned by program_id,
+    /// InvalidAccountData on a size mismatch and UninitializedAccount if not initialized
+    fn try_from_account(account: &AccountInfo, program_id: &Pubkey) -> Result<Self, ProgramError> {
+        if account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::unpack(&account.data.borrow())
+    }
+}
+
+impl TryFromAccount for Gauntlet {}
+impl TryFromAccount for Vault {}
+impl TryFromAccount for VaultStrategy {}
+impl TryFromAccount for Strategy {}
+impl TryFromAccount for User {}
+
+/// Fixed-point shift applied to `accumulated_reward_per_shares`
+pub const REWARD_PRECISION_SHIFT: u32 = 64;
+
+/// Add `amount` reward spread over `base` deposit to the accumulator
+pub fn acc_add(acc: u128, amount: u64, base: u64) -> Result<u128, GauntletError> {
+    let scaled = (amount as u128)
+        .checked_mul(1u128 << REWARD_PRECISION_SHIFT)
+        .ok_or(GauntletError::MathOverflow)?;
+    let per_share = scaled
+        .checked_div(base as u128)
+        .ok_or(GauntletError::MathOverflow)?;
+    acc.checked_add(per_share)
+        .ok_or(GauntletError::MathOverflow)
+}
+
+/// Reward already accounted for `amount` deposit at accumulator `acc`
+pub fn reward_debt(amount: u64, acc: u128) -> Result<u64, GauntletError> {
+    let debt = (amount as u128)
+        .checked_mul(acc)
+        .ok_or(GauntletError::MathOverflow)?
+        >> REWARD_PRECISION_SHIFT;
+    u64::try_from(debt).map_err(|_| GauntletError::MathOverflow)
+}
+
+/// Pending reward of `amount` deposit at accumulator `acc` given its `debt`
+pub fn pending(amount: u64, acc: u128, debt: u64) -> Result<u64, GauntletError> {
+    reward_debt(amount, acc)?
+        .checked_sub(debt)
+        .ok_or(GauntletError::MathOverflow)
+}
+
+/// Split `reward` across strategies by their share of `total`, unavailable strategies get 0
+pub fn distribute(
+    reward: u128,
+    deposit_amounts: &[u64],
+    availabilities: &[bool],
+    total: u64,
+) -> Result<Vec<u64>, GauntletError> {
+    if total == 0 {
+        return Ok(vec![0; deposit_amounts.len()]);
+    }
+    deposit_amounts
+        .iter()
+        .zip(availabilities.iter())
+        .map(|(&deposit_amount, &available)| {
+            if !available {
+                return Ok(0);
+            }
+            let share = reward
+                .checked_mul(deposit_amount as u128)
+                .ok_or(GauntletError::MathOverflow)?
+                .checked_div(total as u128)
+                .ok_or(GauntletError::MathOverflow)?;
+            u64::try_from(share).map_err(|_| GauntletError::MathOverflow)
+        })
+        .collect()
+}
+
 /// Encapsulates all fee information and calculations for swap operations
 #[derive(Debug)]
 pub struct Fees {
@@ -32,6 +111,16 @@ pub struct Fees {
     pub withdrawal_fee_numerator: u64,
     /// Withdrawal fee denominator
     pub withdrawal_fee_denominator: u64,
+    /// Deposit fee numerator
+    pub deposit_fee_numerator: u64,
+    /// Deposit fee denominator
+    pub deposit_fee_denominator: u64,
+    /// Referral fee numerator, share of the deposit fee paid to the referrer
+    pub referral_fee_numerator: u64,
+    /// Referral fee denominator
+    pub referral_fee_denominator: u64,
+    /// Charge at least one token when a non-zero fee rounds down to zero
+    pub min_fee_enabled: bool,
 }
 
 /// Helper function for calculating fee
@@ -39,6 +128,7 @@ pub fn calculate_fee(
     token_amount: u128,
     fee_numerator: u128,
     fee_denominator: u128,
+    min_fee_enabled: bool,
 ) -> Option<u128> {
     if fee_numerator == 0 || token_amount == 0 {
         Some(0)
@@ -46,7 +136,7 @@ pub fn calculate_fee(
         let fee = token_amount
             .checked_mul(fee_numerator)?
             .checked_div(fee_denominator)?;
-        if fee == 0 {
+        if fee == 0 && min_fee_enabled {
             Some(1) // minimum fee of one token
         } else {
             Some(fee)
@@ -54,9 +144,59 @@ pub fn calculate_fee(
     }
 }
 
+/// Vault settings passed to InitVault next to the fees
+#[derive(Debug)]
+pub struct VaultConfig {
+    /// smallest non-zero deposit the vault accepts
+    pub min_deposit: u64,
+    /// minimum seconds between two farm harvests, 0 disables the guard
+    pub min_harvest_interval: u64,
+    /// allowed shortfall of raydium swaps against the pool estimate, 0 disables min-out
+    pub swap_slippage_bps: u64,
+    /// largest usdc amount swapped to strategy token per crank, 0 means no cap
+    pub max_swap_usdc_per_tx: u64,
+    /// reinvest reward into the deposit LP with Compound instead of only accruing strategy token
+    pub compounding: bool,
+    /// raydium / orca swaps estimated to return less than this are skipped, in destination token base units
+    pub min_swap_output: u64,
+    /// harvest fails once a strategy's unswapped reward would exceed this, 0 disables the cap
+    pub max_pending_reward: u64,
+}
+
+impl VaultConfig {
+    /// Validate that the settings are reasonable
+    pub fn validate(&self) -> Result<(), GauntletError> {
+        if self.swap_slippage_bps > MAX_SLIPPAGE_BPS {
+            return Err(GauntletError::InvalidSlippage);
+        }
+        Ok(())
+    }
+}
+
+/// Largest share of a reward claim that can be swapped to SOL for gas (5%)
+pub const MAX_GAS_REIMBURSEMENT_BPS: u64 = 500;
+/// Largest keeper bounty on harvested reward (1%)
+pub const MAX_KEEPER_FEE_BPS: u64 = 100;
+/// 100% in bps, upper bound of every slippage setting
+pub const MAX_SLIPPAGE_BPS: u64 = 10_000;
+/// 100% in bps, denominator of every bps ratio
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// keeper bounty 는 MAX_KEEPER_FEE_BPS 이하만 허용, 0/0 은 꺼짐
+pub fn validate_keeper_fee(numerator: u64, denominator: u64) -> Result<(), GauntletError> {
+    validate_fraction(numerator, denominator)?;
+    if (numerator as u128) * 10_000 > (denominator as u128) * (MAX_KEEPER_FEE_BPS as u128) {
+        return Err(GauntletError::InvalidFee);
+    }
+    Ok(())
+}
+
 fn validate_fraction(numerator: u64, denominator: u64) -> Result<(), GauntletError> {
     if denominator == 0 && numerator == 0 {
         Ok(())
+    } else if denominator == 0 {
+        // 0 으로 나누는 fee 는 계산 시점에 panic 이 나므로 미리 거부
+        Err(GauntletError::InvalidFee)
     } else if numerator >= denominator {
         Err(GauntletError::InvalidFee)
     } else {
@@ -71,6 +211,7 @@ impl Fees {
             reward_tokens,
             u128::try_from(self.performance_fee_numerator).ok()?,
             u128::try_from(self.performance_fee_denominator).ok()?,
+            self.min_fee_enabled,
         )
     }
 
@@ -80,6 +221,27 @@ impl Fees {
             pool_tokens,
             u128::try_from(self.withdrawal_fee_numerator).ok()?,
             u128::try_from(self.withdrawal_fee_denominator).ok()?,
+            self.min_fee_enabled,
+        )
+    }
+
+    /// Calculate the deposit fee in pool tokens
+    pub fn deposit_fee(&self, pool_tokens: u128) -> Option<u128> {
+        calculate_fee(
+            pool_tokens,
+            u128::try_from(self.deposit_fee_numerator).ok()?,
+            u128::try_from(self.deposit_fee_denominator).ok()?,
+            self.min_fee_enabled,
+        )
+    }
+
+    /// Calculate the referrer's cut of the deposit fee
+    pub fn referral_fee(&self, deposit_fee: u128) -> Option<u128> {
+        calculate_fee(
+            deposit_fee,
+            u128::try_from(self.referral_fee_numerator).ok()?,
+            u128::try_from(self.referral_fee_denominator).ok()?,
+            self.min_fee_enabled,
         )
     }
 
@@ -93,6 +255,8 @@ impl Fees {
             self.withdrawal_fee_numerator,
             self.withdrawal_fee_denominator,
         )?;
+        validate_fraction(self.deposit_fee_numerator, self.deposit_fee_denominator)?;
+        validate_fraction(self.referral_fee_numerator, self.referral_fee_denominator)?;
 
         Ok(())
     }
@@ -106,39 +270,71 @@ impl IsInitialized for Fees {
 }
 
 impl Pack for Fees {
-    const LEN: usize = 32;
+    const LEN: usize = 65;
 
     fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, 32];
+        let output = array_mut_ref![output, 0, 65];
         let (
             performance_fee_numerator,
             performance_fee_denominator,
             withdrawal_fee_numerator,
             withdrawal_fee_denominator,
-        ) = mut_array_refs![output, 8, 8, 8, 8];
+            deposit_fee_numerator,
+            deposit_fee_denominator,
+            referral_fee_numerator,
+            referral_fee_denominator,
+            min_fee_enabled,
+        ) = mut_array_refs![output, 8, 8, 8, 8, 8, 8, 8, 8, 1];
         *performance_fee_numerator = self.performance_fee_numerator.to_le_bytes();
         *performance_fee_denominator = self.performance_fee_denominator.to_le_bytes();
         *withdrawal_fee_numerator = self.withdrawal_fee_numerator.to_le_bytes();
         *withdrawal_fee_denominator = self.withdrawal_fee_denominator.to_le_bytes();
+        *deposit_fee_numerator = self.deposit_fee_numerator.to_le_bytes();
+        *deposit_fee_denominator = self.deposit_fee_denominator.to_le_bytes();
+        *referral_fee_numerator = self.referral_fee_numerator.to_le_bytes();
+        *referral_fee_denominator = self.referral_fee_denominator.to_le_bytes();
+        min_fee_enabled[0] = self.min_fee_enabled as u8;
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Fees, ProgramError> {
-        let input = array_ref![input, 0, 32];
+        let input = array_ref![input, 0, 65];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             performance_fee_numerator,
             performance_fee_denominator,
             withdrawal_fee_numerator,
             withdrawal_fee_denominator,
-        ) = array_refs![input, 8, 8, 8, 8];
+            deposit_fee_numerator,
+            deposit_fee_denominator,
+            referral_fee_numerator,
+            referral_fee_denominator,
+            min_fee_enabled,
+        ) = array_refs![input, 8, 8, 8, 8, 8, 8, 8, 8, 1];
         Ok(Self {
             performance_fee_numerator: u64::from_le_bytes(*performance_fee_numerator),
             performance_fee_denominator: u64::from_le_bytes(*performance_fee_denominator),
             withdrawal_fee_numerator: u64::from_le_bytes(*withdrawal_fee_numerator),
             withdrawal_fee_denominator: u64::from_le_bytes(*withdrawal_fee_denominator),
+            deposit_fee_numerator: u64::from_le_bytes(*deposit_fee_numerator),
+            deposit_fee_denominator: u64::from_le_bytes(*deposit_fee_denominator),
+            referral_fee_numerator: u64::from_le_bytes(*referral_fee_numerator),
+            referral_fee_denominator: u64::from_le_bytes(*referral_fee_denominator),
+            min_fee_enabled: match min_fee_enabled {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
         })
     }
 }
+/// Maximum number of keepers allowed to crank harvest/swap
+pub const MAX_KEEPERS: usize = 8;
+/// Maximum number of distinct mints held by gauntlet accounts
+pub const MAX_TRACKED_MINTS: usize = 64;
+/// 처음 배포된 Gauntlet layout 크기 (usdc_token_account 까지)
+pub const GAUNTLET_V1_LEN: usize = 1 + 32 + 8 + 8 + 32;
+/// offset of `global_paused` in the gauntlet account data
+pub const GAUNTLET_GLOBAL_PAUSED_OFFSET: usize = 1 + 32 + 8 + 8 + 32 + 1 + 32 * MAX_KEEPERS + 1 + 8;
 pub struct Gauntlet {
     /// init
     pub is_initialized: bool,
@@ -150,18 +346,111 @@ pub struct Gauntlet {
     pub vaults_len: u8,
     /// usdc token account for swap
     pub usdc_token_account: Pubkey,
+    /// Number of registered keepers
+    pub keepers_len: u8,
+    /// Keepers allowed to call harvest and the swap instructions
+    pub keepers: Vec<Pubkey>,
+    /// bump of the per-gauntlet authority pda
+    pub authority_bump: u8,
+    /// Nonce every admin instruction must present, bumped on each use
+    pub admin_nonce: u64,
+    /// Emergency switch, blocks deposit/harvest/swap of every vault but not withdraw
+    pub global_paused: bool,
+    /// Number of tracked mints
+    pub tracked_mints_len: u8,
+    /// usdc, vault deposit/reward and strategy token mints, SweepTokens can't move these
+    pub tracked_mints: Vec<Pubkey>,
+    /// v1 gauntlet this gauntlet was migrated from, its vaults and strategies migrate into this one
+    pub migrated_from: Pubkey,
+    /// token program owning usdc_token_account, spl-token or Token-2022
+    pub token_program: Pubkey,
 }
 
 impl Gauntlet {
-    pub fn init(admin: Pubkey, usdc_token_account: Pubkey) -> Self {
+    pub fn init(admin: Pubkey, usdc_token_account: Pubkey, authority_bump: u8) -> Self {
         Gauntlet {
             is_initialized: true,
             admin,
             strategies_len: 0,
             vaults_len: 0,
             usdc_token_account,
+            keepers_len: 0,
+            keepers: vec![Pubkey::default(); MAX_KEEPERS],
+            authority_bump,
+            admin_nonce: 0,
+            global_paused: false,
+            tracked_mints_len: 0,
+            tracked_mints: vec![Pubkey::default(); MAX_TRACKED_MINTS],
+            migrated_from: Pubkey::default(),
+            token_program: spl_token::id(),
+        }
+    }
+
+    /// Decodes a v1 gauntlet (GAUNTLET_V1_LEN bytes) into the current layout.
+    /// v1 had no keepers or nonce, the usdc mint is left for the caller to track
+    pub fn migrate_from_v1(old_bytes: &[u8], authority_bump: u8) -> Result<Gauntlet, ProgramError> {
+        if old_bytes.len() != GAUNTLET_V1_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![old_bytes, 0, GAUNTLET_V1_LEN];
+        let (is_initialized, admin, strategies_len, vaults_len, usdc_token_account) =
+            array_refs![input, 1, 32, 8, 8, 32];
+        if *is_initialized != [1] {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let mut gauntlet = Gauntlet::init(
+            Pubkey::new_from_array(*admin),
+            Pubkey::new_from_array(*usdc_token_account),
+            authority_bump,
+        );
+        gauntlet.strategies_len = strategies_len[0];
+        gauntlet.vaults_len = vaults_len[0];
+        Ok(gauntlet)
+    }
+
+    /// vault / strategy 가 쓰는 mint 를 등록, 이미 등록된 mint 는 무시
+    pub fn track_mint(&mut self, mint: Pubkey) -> Result<(), GauntletError> {
+        if mint == Pubkey::default() || self.is_tracked_mint(&mint) {
+            return Ok(());
+        }
+        let len = self.tracked_mints_len as usize;
+        if len >= MAX_TRACKED_MINTS {
+            return Err(GauntletError::TrackedMintListFull);
+        }
+        self.tracked_mints[len] = mint;
+        self.tracked_mints_len += 1;
+        Ok(())
+    }
+
+    pub fn is_tracked_mint(&self, mint: &Pubkey) -> bool {
+        self.tracked_mints[..self.tracked_mints_len as usize].contains(mint)
+    }
+
+    /// account 전체를 unpack 하지 않고 global pause flag 만 확인
+    pub fn check_not_paused(data: &[u8]) -> Result<(), ProgramError> {
+        match data.get(GAUNTLET_GLOBAL_PAUSED_OFFSET) {
+            Some(0) => Ok(()),
+            Some(1) => Err(GauntletError::VaultPaused.into()),
+            _ => Err(ProgramError::InvalidAccountData),
         }
     }
+
+    /// admin instruction 의 nonce 를 확인하고 증가시킴
+    pub fn use_admin_nonce(&mut self, nonce: u64) -> Result<(), ProgramError> {
+        if nonce != self.admin_nonce {
+            return Err(GauntletError::StaleNonce.into());
+        }
+        self.admin_nonce = self
+            .admin_nonce
+            .checked_add(1)
+            .ok_or(GauntletError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// admin 은 항상 keeper 로 취급
+    pub fn is_keeper(&self, key: &Pubkey) -> bool {
+        self.admin == *key || self.keepers[..self.keepers_len as usize].contains(key)
+    }
 }
 
 impl Sealed for Gauntlet {}
@@ -172,23 +461,113 @@ impl IsInitialized for Gauntlet {
 }
 
 impl Pack for Gauntlet {
-    const LEN: usize = 1 + 32 + 8 + 8 + 32; // 81
+    const LEN: usize = 1
+        + 32
+        + 8
+        + 8
+        + 32
+        + 1
+        + 32 * MAX_KEEPERS
+        + 1
+        + 8
+        + 1
+        + 1
+        + 32 * MAX_TRACKED_MINTS
+        + 32
+        + 32; // 2461
     fn pack_into_slice(&self, output: &mut [u8]) {
         let output = array_mut_ref![output, 0, Gauntlet::LEN];
-        let (is_initialized, admin, strategies_len, vaults_len, usdc_token_account) =
-            mut_array_refs![output, 1, 32, 8, 8, 32];
+        let (
+            is_initialized,
+            admin,
+            strategies_len,
+            vaults_len,
+            usdc_token_account,
+            keepers_len,
+            keepers,
+            authority_bump,
+            admin_nonce,
+            global_paused,
+            tracked_mints_len,
+            tracked_mints,
+            migrated_from,
+            token_program,
+        ) = mut_array_refs![
+            output,
+            1,
+            32,
+            8,
+            8,
+            32,
+            1,
+            32 * MAX_KEEPERS,
+            1,
+            8,
+            1,
+            1,
+            32 * MAX_TRACKED_MINTS,
+            32,
+            32
+        ];
 
         is_initialized[0] = self.is_initialized as u8;
         admin.copy_from_slice(self.admin.as_ref());
         strategies_len[0] = self.strategies_len as u8;
         vaults_len[0] = self.vaults_len as u8;
         usdc_token_account.copy_from_slice(self.usdc_token_account.as_ref());
+        keepers_len[0] = self.keepers_len;
+        for i in 0..MAX_KEEPERS {
+            let arr_ref = array_mut_ref![keepers, i * 32, 32];
+            arr_ref.copy_from_slice(self.keepers[i].as_ref());
+        }
+        authority_bump[0] = self.authority_bump;
+        *admin_nonce = self.admin_nonce.to_le_bytes();
+        global_paused[0] = self.global_paused as u8;
+        tracked_mints_len[0] = self.tracked_mints_len;
+        for i in 0..MAX_TRACKED_MINTS {
+            let arr_ref = array_mut_ref![tracked_mints, i * 32, 32];
+            arr_ref.copy_from_slice(self.tracked_mints[i].as_ref());
+        }
+        migrated_from.copy_from_slice(self.migrated_from.as_ref());
+        token_program.copy_from_slice(self.token_program.as_ref());
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
         let input = array_ref![input, 0, Gauntlet::LEN];
-        let (is_initialized, admin, strategies_len, vaults_len, usdc_token_account) =
-            array_refs![input, 1, 32, 8, 8, 32];
+        let (
+            is_initialized,
+            admin,
+            strategies_len,
+            vaults_len,
+            usdc_token_account,
+            keepers_len,
+            keepers,
+            authority_bump,
+            admin_nonce,
+            global_paused,
+            tracked_mints_len,
+            tracked_mints,
+            migrated_from,
+            token_program,
+        ) = array_refs![
+            input,
+            1,
+            32,
+            8,
+            8,
+            32,
+            1,
+            32 * MAX_KEEPERS,
+            1,
+            8,
+            1,
+            1,
+            32 * MAX_TRACKED_MINTS,
+            32,
+            32
+        ];
+        let keepers_array = keepers.chunks_exact(32).map(Pubkey::new).collect();
+        let tracked_mints_array = tracked_mints.chunks_exact(32).map(Pubkey::new).collect();
 
         Ok(Self {
             is_initialized: match is_initialized {
@@ -200,9 +579,306 @@ impl Pack for Gauntlet {
             strategies_len: strategies_len[0],
             vaults_len: vaults_len[0],
             usdc_token_account: Pubkey::new_from_array(*usdc_token_account),
+            keepers_len: keepers_len[0],
+            keepers: keepers_array,
+            authority_bump: authority_bump[0],
+            admin_nonce: u64::from_le_bytes(*admin_nonce),
+            global_paused: match global_paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            tracked_mints_len: tracked_mints_len[0],
+            tracked_mints: tracked_mints_array,
+            migrated_from: Pubkey::new_from_array(*migrated_from),
+            token_program: Pubkey::new_from_array(*token_program),
+        })
+    }
+}
+/// Version of the GauntletConfig layout, bumped whenever a field is added
+pub const GAUNTLET_CONFIG_VERSION: u8 = 2;
+/// Read-only view of the gauntlet settings returned by GetConfig,
+/// decoupled from the Gauntlet account layout
+pub struct GauntletConfig {
+    /// layout version
+    pub version: u8,
+    /// admin account
+    pub admin: Pubkey,
+    /// usdc token account for swap
+    pub usdc_token_account: Pubkey,
+    /// Number of strategies
+    pub strategies_len: u8,
+    /// Number of vaults
+    pub vaults_len: u8,
+    /// Number of registered keepers
+    pub keepers_len: u8,
+    /// bump of the per-gauntlet authority pda
+    pub authority_bump: u8,
+    /// Nonce the next admin instruction must present
+    pub admin_nonce: u64,
+    /// Emergency switch of every vault (since version 2)
+    pub global_paused: bool,
+}
+
+impl GauntletConfig {
+    pub fn from_gauntlet(gauntlet: &Gauntlet) -> Self {
+        GauntletConfig {
+            version: GAUNTLET_CONFIG_VERSION,
+            admin: gauntlet.admin,
+            usdc_token_account: gauntlet.usdc_token_account,
+            strategies_len: gauntlet.strategies_len,
+            vaults_len: gauntlet.vaults_len,
+            keepers_len: gauntlet.keepers_len,
+            authority_bump: gauntlet.authority_bump,
+            admin_nonce: gauntlet.admin_nonce,
+            global_paused: gauntlet.global_paused,
+        }
+    }
+}
+
+impl Sealed for GauntletConfig {}
+impl Pack for GauntletConfig {
+    const LEN: usize = 1 + 32 + 32 + 1 + 1 + 1 + 1 + 8 + 1; // 78
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, GauntletConfig::LEN];
+        let (
+            version,
+            admin,
+            usdc_token_account,
+            strategies_len,
+            vaults_len,
+            keepers_len,
+            authority_bump,
+            admin_nonce,
+            global_paused,
+        ) = mut_array_refs![output, 1, 32, 32, 1, 1, 1, 1, 8, 1];
+
+        version[0] = self.version;
+        admin.copy_from_slice(self.admin.as_ref());
+        usdc_token_account.copy_from_slice(self.usdc_token_account.as_ref());
+        strategies_len[0] = self.strategies_len;
+        vaults_len[0] = self.vaults_len;
+        keepers_len[0] = self.keepers_len;
+        authority_bump[0] = self.authority_bump;
+        *admin_nonce = self.admin_nonce.to_le_bytes();
+        global_paused[0] = self.global_paused as u8;
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, GauntletConfig::LEN];
+        let (
+            version,
+            admin,
+            usdc_token_account,
+            strategies_len,
+            vaults_len,
+            keepers_len,
+            authority_bump,
+            admin_nonce,
+            global_paused,
+        ) = array_refs![input, 1, 32, 32, 1, 1, 1, 1, 8, 1];
+
+        Ok(Self {
+            version: version[0],
+            admin: Pubkey::new_from_array(*admin),
+            usdc_token_account: Pubkey::new_from_array(*usdc_token_account),
+            strategies_len: strategies_len[0],
+            vaults_len: vaults_len[0],
+            keepers_len: keepers_len[0],
+            authority_bump: authority_bump[0],
+            admin_nonce: u64::from_le_bytes(*admin_nonce),
+            global_paused: global_paused[0] != 0,
+        })
+    }
+}
+
+/// Version of the VaultTvl layout, bumped whenever a field is added
+pub const VAULT_TVL_VERSION: u8 = 1;
+/// Per-vault totals returned by GetVaultTvl, each amount is a raw token amount
+/// summed over every strategy of the vault
+pub struct VaultTvl {
+    /// layout version
+    pub version: u8,
+    /// staked deposit token(LP)
+    pub total_deposit_amount: u64,
+    /// harvested reward not yet swapped
+    pub reward_token_remain_amount: u64,
+    /// harvested second reward not yet swapped
+    pub reward_token_b_remain_amount: u64,
+    /// usdc waiting to be swapped to strategy tokens
+    pub usdc_token_amount: u64,
+    /// strategy tokens claimable by depositors
+    pub strategy_token_amount: u64,
+}
+
+impl VaultTvl {
+    pub fn from_vault(
+        vault: &Vault,
+        vault_strategy: &VaultStrategy,
+    ) -> Result<Self, GauntletError> {
+        let sum = |amounts: &[u64]| {
+            amounts
+                .iter()
+                .try_fold(0u64, |acc, amount| acc.checked_add(*amount))
+                .ok_or(GauntletError::MathOverflow)
+        };
+        Ok(VaultTvl {
+            version: VAULT_TVL_VERSION,
+            total_deposit_amount: vault.total_deposit_amount,
+            reward_token_remain_amount: sum(&vault.reward_token_remain_amounts)?,
+            reward_token_b_remain_amount: sum(&vault.reward_token_b_remain_amounts)?,
+            usdc_token_amount: sum(&vault.usdc_token_amounts)?,
+            strategy_token_amount: sum(&vault_strategy.strategy_token_amounts)?,
+        })
+    }
+}
+
+impl Sealed for VaultTvl {}
+impl Pack for VaultTvl {
+    const LEN: usize = 1 + 8 + 8 + 8 + 8 + 8; // 41
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, VaultTvl::LEN];
+        let (
+            version,
+            total_deposit_amount,
+            reward_token_remain_amount,
+            reward_token_b_remain_amount,
+            usdc_token_amount,
+            strategy_token_amount,
+        ) = mut_array_refs![output, 1, 8, 8, 8, 8, 8];
+
+        version[0] = self.version;
+        *total_deposit_amount = self.total_deposit_amount.to_le_bytes();
+        *reward_token_remain_amount = self.reward_token_remain_amount.to_le_bytes();
+        *reward_token_b_remain_amount = self.reward_token_b_remain_amount.to_le_bytes();
+        *usdc_token_amount = self.usdc_token_amount.to_le_bytes();
+        *strategy_token_amount = self.strategy_token_amount.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, VaultTvl::LEN];
+        let (
+            version,
+            total_deposit_amount,
+            reward_token_remain_amount,
+            reward_token_b_remain_amount,
+            usdc_token_amount,
+            strategy_token_amount,
+        ) = array_refs![input, 1, 8, 8, 8, 8, 8];
+
+        Ok(Self {
+            version: version[0],
+            total_deposit_amount: u64::from_le_bytes(*total_deposit_amount),
+            reward_token_remain_amount: u64::from_le_bytes(*reward_token_remain_amount),
+            reward_token_b_remain_amount: u64::from_le_bytes(*reward_token_b_remain_amount),
+            usdc_token_amount: u64::from_le_bytes(*usdc_token_amount),
+            strategy_token_amount: u64::from_le_bytes(*strategy_token_amount),
+        })
+    }
+}
+
+pub const USER_POSITION_VERSION: u8 = 1;
+/// A user's position returned by GetUserPosition, as a withdraw right now would see it
+pub struct UserPosition {
+    /// layout version
+    pub version: u8,
+    /// deposit token(LP), includes LP restaked for the user since the last deposit/withdraw
+    pub amount: u64,
+    /// reward accrued since the last deposit/withdraw
+    pub pending_reward: u64,
+    /// strategy tokens the user can withdraw as reward
+    pub claimable_reward: u64,
+}
+
+impl UserPosition {
+    pub fn from_user(
+        user: &User,
+        vault: &Vault,
+        strategy_index: usize,
+    ) -> Result<Self, GauntletError> {
+        // withdraw 와 같은 계산
+        let pending_reward = pending(
+            user.amount,
+            vault.accumulated_reward_per_shares[strategy_index],
+            user.reward_debt,
+        )?;
+        let mut settled = user.clone();
+        settled.settle(vault, strategy_index)?;
+        Ok(UserPosition {
+            version: USER_POSITION_VERSION,
+            amount: settled.amount,
+            pending_reward,
+            claimable_reward: settled.reward,
+        })
+    }
+}
+
+impl Sealed for UserPosition {}
+impl Pack for UserPosition {
+    const LEN: usize = 1 + 8 + 8 + 8; // 25
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, UserPosition::LEN];
+        let (version, amount, pending_reward, claimable_reward) =
+            mut_array_refs![output, 1, 8, 8, 8];
+
+        version[0] = self.version;
+        *amount = self.amount.to_le_bytes();
+        *pending_reward = self.pending_reward.to_le_bytes();
+        *claimable_reward = self.claimable_reward.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, UserPosition::LEN];
+        let (version, amount, pending_reward, claimable_reward) = array_refs![input, 1, 8, 8, 8];
+
+        Ok(Self {
+            version: version[0],
+            amount: u64::from_le_bytes(*amount),
+            pending_reward: u64::from_le_bytes(*pending_reward),
+            claimable_reward: u64::from_le_bytes(*claimable_reward),
         })
     }
 }
+/// User status flow driven by the keeper before a deposit/withdraw:
+///
+/// ```text
+///            harvest
+/// IDLE(0) ----------> HARVESTED(1)
+///                        | swap reward A (reward B exists)
+///                        v
+///                     REWARD_A_SWAPPED(2)
+///                        | swap reward B
+///                        v
+///                     REWARDS_SWAPPED(3)   <- single reward vaults jump here from HARVESTED
+///                        | swap usdc -> strategy token (skipped when no usdc pool is needed)
+///                        v
+///                     READY(4) --deposit/withdraw--> IDLE(0)
+/// ```
+///
+/// needs_usdc_pool 가 false 인 strategy 는 swap_reward_to_strategy_token 이
+/// HARVESTED / REWARD_A_SWAPPED 에서 바로 다음 reward 단계 또는 READY 로 보냄.
+/// 중간에 needs_usdc_pool 이 바뀌면 이미 usdc 로 바뀐 양이나 REWARDS_SWAPPED 의 user 가
+/// 새 경로에서 받아주지 않으므로, update_vault_strategy 는 해당 strategy 의
+/// reward/usdc 잔량이 모두 swap 된 뒤에만 flag 변경을 허용함
+pub const USER_STATUS_IDLE: u8 = 0;
+pub const USER_STATUS_HARVESTED: u8 = 1;
+pub const USER_STATUS_REWARD_A_SWAPPED: u8 = 2;
+pub const USER_STATUS_REWARDS_SWAPPED: u8 = 3;
+pub const USER_STATUS_READY: u8 = 4;
+/// 처음 배포된 User layout 크기 (deadline 까지)
+pub const USER_V1_LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 8;
+
+/// Next user status after a swap step completes at `current`
+pub fn next_swap_status(current: u8, has_second_reward: bool) -> u8 {
+    match current {
+        USER_STATUS_HARVESTED if has_second_reward => USER_STATUS_REWARD_A_SWAPPED,
+        USER_STATUS_HARVESTED | USER_STATUS_REWARD_A_SWAPPED => USER_STATUS_REWARDS_SWAPPED,
+        USER_STATUS_REWARDS_SWAPPED => USER_STATUS_READY,
+        _ => current,
+    }
+}
+
+#[derive(Clone)]
 pub struct User {
     /// Initialized state
     pub is_initialized: bool,
@@ -220,8 +896,16 @@ pub struct User {
     pub reward_debt: u64,
     // user status
     pub user_status: u8,
-    // last timestamp
+    // last timestamp, 0 이면 deadline 없음 (admin reset 후)
     pub deadline: UnixTimestamp,
+    // referrer set on the first deposit, Pubkey::default() 이면 referrer 없음
+    pub referrer: Pubkey,
+    /// Time of the last deposit, withdraws wait vault.withdraw_cooldown_secs after it
+    pub last_deposit_time: UnixTimestamp,
+    /// Value for calculate user's pending compounded (restaked LP) amount
+    pub compound_debt: u64,
+    // status 를 진행시키는 instruction 이 실행 중인지, 재진입 방지용
+    pub locked: bool,
 }
 
 impl User {
@@ -236,10 +920,109 @@ impl User {
             reward_debt: 0,
             user_status: 0,
             deadline: 0,
+            referrer: Pubkey::default(),
+            last_deposit_time: 0,
+            compound_debt: 0,
+            locked: false,
+        }
+    }
+
+    /// Decodes a v1 user (USER_V1_LEN bytes) into the current layout.
+    /// vault_account / strategy_account still hold the v1 addresses, the caller rebinds them
+    pub fn migrate_from_v1(old_bytes: &[u8]) -> Result<User, ProgramError> {
+        if old_bytes.len() != USER_V1_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![old_bytes, 0, USER_V1_LEN];
+        let (
+            is_initialized,
+            user,
+            vault_account,
+            strategy_account,
+            amount,
+            reward,
+            reward_debt,
+            user_status,
+            deadline,
+        ) = array_refs![input, 1, 32, 32, 32, 8, 8, 8, 1, 8];
+        if *is_initialized != [1] {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let mut user_info = User::init(
+            Pubkey::new_from_array(*user),
+            Pubkey::new_from_array(*vault_account),
+            Pubkey::new_from_array(*strategy_account),
+        );
+        user_info.amount = u64::from_le_bytes(*amount);
+        user_info.reward = u64::from_le_bytes(*reward);
+        user_info.reward_debt = u64::from_le_bytes(*reward_debt);
+        user_info.user_status = user_status[0];
+        user_info.deadline = UnixTimestamp::from_le_bytes(*deadline);
+        Ok(user_info)
+    }
+
+    /// 마지막 deposit/withdraw 이후 쌓인 strategy token 은 reward 로, 재예치된 LP 는 원금으로 옮김
+    /// 끝나면 amount 가 바뀌므로 caller 는 reset_debts 를 다시 호출해야 함
+    pub fn settle(&mut self, vault: &Vault, strategy_index: usize) -> Result<(), GauntletError> {
+        let reward = pending(
+            self.amount,
+            vault.accumulated_reward_per_shares[strategy_index],
+            self.reward_debt,
+        )?;
+        let compounded = pending(
+            self.amount,
+            vault.compounded_per_shares[strategy_index],
+            self.compound_debt,
+        )?;
+        self.reward = self
+            .reward
+            .checked_add(reward)
+            .ok_or(GauntletError::MathOverflow)?;
+        self.amount = self
+            .amount
+            .checked_add(compounded)
+            .ok_or(GauntletError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// amount 가 바뀐 뒤 두 accumulator 의 debt 를 현재 값으로 다시 잡음
+    pub fn reset_debts(
+        &mut self,
+        vault: &Vault,
+        strategy_index: usize,
+    ) -> Result<(), GauntletError> {
+        self.reward_debt = reward_debt(
+            self.amount,
+            vault.accumulated_reward_per_shares[strategy_index],
+        )?;
+        self.compound_debt = reward_debt(self.amount, vault.compounded_per_shares[strategy_index])?;
+        Ok(())
+    }
+
+    /// status 를 진행시키는 instruction 시작 시 호출, unpack 전에 account data 에 바로 기록해서
+    /// 같은 user account 로 재진입하는 호출은 UserBusy 로 막음. 실패하면 tx 가 revert 되므로 lock 이 남지 않음
+    /// User::LEN 이 아닌 account (아직 만들지 않은 user 등) 는 건너뛰고 unpack 에서 걸러짐
+    pub fn acquire_lock(data: &mut [u8]) -> Result<(), GauntletError> {
+        if data.len() != User::LEN {
+            return Ok(());
+        }
+        if data[USER_LOCKED_OFFSET] == 1 {
+            return Err(GauntletError::UserBusy);
+        }
+        data[USER_LOCKED_OFFSET] = 1;
+        Ok(())
+    }
+
+    /// instruction 이 성공하고 user 를 pack 한 뒤 lock 을 풂
+    pub fn release_lock(data: &mut [u8]) {
+        if data.len() == User::LEN {
+            data[USER_LOCKED_OFFSET] = 0;
         }
     }
 }
 
+const USER_LOCKED_OFFSET: usize = User::LEN - 1;
+
 impl Sealed for User {}
 impl IsInitialized for User {
     fn is_initialized(&self) -> bool {
@@ -248,7 +1031,7 @@ impl IsInitialized for User {
 }
 
 impl Pack for User {
-    const LEN: usize = 130;
+    const LEN: usize = 179;
     fn pack_into_slice(&self, output: &mut [u8]) {
         let output = array_mut_ref![output, 0, User::LEN];
         let (
@@ -261,7 +1044,11 @@ impl Pack for User {
             reward_debt,
             user_status,
             deadline,
-        ) = mut_array_refs![output, 1, 32, 32, 32, 8, 8, 8, 1, 8];
+            referrer,
+            last_deposit_time,
+            compound_debt,
+            locked,
+        ) = mut_array_refs![output, 1, 32, 32, 32, 8, 8, 8, 1, 8, 32, 8, 8, 1];
 
         is_initialized[0] = self.is_initialized as u8;
         user.copy_from_slice(self.user.as_ref());
@@ -272,6 +1059,10 @@ impl Pack for User {
         *reward_debt = self.reward_debt.to_le_bytes();
         user_status[0] = self.user_status as u8;
         *deadline = self.deadline.to_le_bytes();
+        referrer.copy_from_slice(self.referrer.as_ref());
+        *last_deposit_time = self.last_deposit_time.to_le_bytes();
+        *compound_debt = self.compound_debt.to_le_bytes();
+        locked[0] = self.locked as u8;
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
@@ -286,7 +1077,11 @@ impl Pack for User {
             reward_debt,
             user_status,
             deadline,
-        ) = array_refs![input, 1, 32, 32, 32, 8, 8, 8, 1, 8];
+            referrer,
+            last_deposit_time,
+            compound_debt,
+            locked,
+        ) = array_refs![input, 1, 32, 32, 32, 8, 8, 8, 1, 8, 32, 8, 8, 1];
 
         Ok(Self {
             is_initialized: match is_initialized {
@@ -302,12 +1097,39 @@ impl Pack for User {
             reward_debt: u64::from_le_bytes(*reward_debt),
             user_status: user_status[0],
             deadline: UnixTimestamp::from_le_bytes(*deadline),
+            referrer: Pubkey::new_from_array(*referrer),
+            last_deposit_time: UnixTimestamp::from_le_bytes(*last_deposit_time),
+            compound_debt: u64::from_le_bytes(*compound_debt),
+            locked: match locked {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
         })
     }
 }
 
 /// 전략 개수 상한 : 일단 50개로 잡아놓음 * TODO
 pub const MAX_NUMBER_OF_STRATEGY: usize = 50;
+/// 처음 배포된 Vault layout 크기 (Fees 32 byte, raydium_state_account 까지)
+/// 예전 주석의 2251 은 잘못 계산된 값, 실제 layout 은 2643 byte
+pub const VAULT_V1_LEN: usize = 1
+    + 1
+    + 1
+    + 32
+    + 32
+    + 32
+    + 32
+    + 32
+    + 32
+    + 8
+    + 8 * 4 * MAX_NUMBER_OF_STRATEGY
+    + 16 * MAX_NUMBER_OF_STRATEGY
+    + 8
+    + 32;
+/// layout_version 이 없던 v1 은 VAULT_V1_LEN 크기로 구분함
+pub const VAULT_LAYOUT_VERSION: u8 = 2;
+/// v1 vault 는 MigrateVault 로 이 크기의 새 account 에 옮김 (solana-program 1.7 에는 realloc 이 없음)
 pub const MAX_VAULT_SIZE: usize = 1
     + 1
     + 1
@@ -321,15 +1143,45 @@ pub const MAX_VAULT_SIZE: usize = 1
     + 8 * 4 * MAX_NUMBER_OF_STRATEGY
     + 16 * MAX_NUMBER_OF_STRATEGY
     + 8
-    + 32;
-pub struct Vault {
-    /// Initialized state
-    pub is_initialized: bool,
-    /// Vault index
-    pub index: u8,
-    /// Vault's running status
-    pub status: Status,
-    /// Vault fees
+    + 32
+    + 32
+    + 8
+    + 1
+    + 32
+    + 8
+    + 8
+    + 8
+    + 8
+    + 8
+    + 1
+    + 1
+    + 8
+    + 8
+    + 8
+    + 8
+    + 1
+    + 1
+    + 1
+    + 32
+    + 32
+    + 8
+    + 8
+    + 8
+    + 2
+    + 16 * MAX_NUMBER_OF_STRATEGY
+    + 1
+    + 32
+    + 32;
+/// consecutive empty harvests after which harvest logs a warning
+pub const HARVEST_FAILURE_WARN_THRESHOLD: u8 = 3;
+pub struct Vault {
+    /// Initialized state
+    pub is_initialized: bool,
+    /// Vault index
+    pub index: u8,
+    /// Vault's running status
+    pub status: Status,
+    /// Vault fees
     pub fees: Fees,
     /// Gauntlet Account,
     pub gauntlet_state_account: Pubkey,
@@ -357,6 +1209,212 @@ pub struct Vault {
     pub last_reward_update_time: UnixTimestamp,
     /// raydium state account
     pub raydium_state_account: Pubkey,
+    /// Deposit token(LP) mint
+    pub deposit_token_mint: Pubkey,
+    /// Minimum non-zero deposit amount
+    pub min_deposit: u64,
+    /// Harvests in a row that distributed nothing, reset on a successful harvest
+    pub consecutive_harvest_failures: u8,
+    /// deposit fee account
+    pub deposit_fee_account: Pubkey,
+    /// Minimum seconds between two farm harvests, 0 disables the guard
+    pub min_harvest_interval: u64,
+    /// Total withdrawal fee collected, informational only
+    pub cumulative_withdrawal_fees: u64,
+    /// Total performance fee collected, informational only
+    pub cumulative_performance_fees: u64,
+    /// Allowed shortfall of a raydium swap against the pool reserve estimate, 0 disables min-out
+    pub swap_slippage_bps: u64,
+    /// Largest usdc amount swapped to strategy token per crank, 0 means no cap
+    pub max_swap_usdc_per_tx: u64,
+    /// reward is paired with usdc into the deposit LP and re-staked by Compound
+    pub compounding: bool,
+    /// Next strategy index to distribute the in-flight harvest to, 0 when no harvest is in progress
+    pub harvest_cursor: u8,
+    /// Reward token harvested by the in-flight harvest, distributed across calls
+    pub harvest_pending_amount: u64,
+    /// Second reward token harvested by the in-flight harvest
+    pub harvest_pending_b_amount: u64,
+    /// Raydium / Orca swaps whose estimated output is below this are skipped and retried next crank
+    pub min_swap_output: u64,
+    /// Most reward (a or b) a strategy may have waiting to be swapped, 0 (or u64::MAX) disables the cap
+    pub max_pending_reward: u64,
+    /// Whether the farm pays a second reward, set at init and decides the raydium staking version
+    pub has_second_reward: bool,
+    /// Deposits are rejected while false, harvest and swaps keep running
+    pub deposits_enabled: bool,
+    /// Withdrawals are rejected while false, harvest and swaps keep running
+    pub withdrawals_enabled: bool,
+    /// Mint of the farm reward token account
+    pub reward_token_mint: Pubkey,
+    /// Mint of the farm second reward token account, default if there's none
+    pub reward_token_b_mint: Pubkey,
+    /// Keeper bounty numerator, share of harvested reward a paid to the harvesting keeper
+    pub keeper_fee_numerator: u64,
+    /// Keeper bounty denominator, 0 disables the bounty
+    pub keeper_fee_denominator: u64,
+    /// Seconds a user must wait after a deposit before withdrawing, 0 disables the cooldown
+    pub withdraw_cooldown_secs: u64,
+    /// Share (bps) of LP strategy token gains restaked as principal, the rest stays claimable
+    pub compound_ratio_bps: u16,
+    /// 전략 별 원금으로 재예치된 LP 의 share 당 누적값 (accumulated_reward_per_shares 와 같은 scale)
+    /// 재예치할 때 기록하므로 정산 시점의 compound_ratio_bps 와 무관함
+    pub compounded_per_shares: Vec<u128>,
+    /// Layout version, VAULT_LAYOUT_VERSION for vaults created or migrated by this program
+    pub layout_version: u8,
+    /// v1 vault this vault was migrated from, its users move here with MigrateUser
+    pub migrated_from: Pubkey,
+    /// token program owning the deposit / reward token accounts, spl-token or Token-2022
+    pub token_program: Pubkey,
+}
+
+impl Vault {
+    /// Decodes a v1 vault (VAULT_V1_LEN bytes) into the current layout.
+    /// Fields v1 didn't have take the init_vault defaults, the token mints are left for the caller
+    pub fn migrate_from_v1(old_bytes: &[u8]) -> Result<Vault, ProgramError> {
+        if old_bytes.len() != VAULT_V1_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![old_bytes, 0, VAULT_V1_LEN];
+        let (
+            is_initialized,
+            index,
+            status,
+            fees,
+            accounts,
+            total_deposit_amount,
+            amounts,
+            accumulated_reward_per_shares,
+            last_reward_update_time,
+            raydium_state_account,
+        ) = array_refs![
+            input,
+            1,
+            1,
+            1,
+            32,
+            32 * 5,
+            8,
+            8 * 4 * MAX_NUMBER_OF_STRATEGY,
+            16 * MAX_NUMBER_OF_STRATEGY,
+            8,
+            32
+        ];
+        if *is_initialized != [1] {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let mut vault = Vault::unpack_unchecked(&vec![0; Vault::LEN])?;
+        vault.is_initialized = true;
+        vault.index = index[0];
+        vault.status = match status {
+            [0] => Status::PAUSED,
+            [1] => Status::NORMAL,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        // v1 Fees 는 performance / withdrawal 만 있었음
+        let fee = |i: usize| u64::from_le_bytes(*array_ref![fees, i * 8, 8]);
+        vault.fees.performance_fee_numerator = fee(0);
+        vault.fees.performance_fee_denominator = fee(1);
+        vault.fees.withdrawal_fee_numerator = fee(2);
+        vault.fees.withdrawal_fee_denominator = fee(3);
+        // v1 은 0 으로 내림된 fee 도 최소 1 을 받았음
+        vault.fees.min_fee_enabled = true;
+        let account = |i: usize| Pubkey::new_from_array(*array_ref![accounts, i * 32, 32]);
+        vault.gauntlet_state_account = account(0);
+        vault.deposit_token_account = account(1);
+        vault.reward_token_account = account(2);
+        vault.reward_token_b_account = account(3);
+        vault.withdraw_fee_account = account(4);
+        // v1 은 deposit fee account 가 따로 없었음
+        vault.deposit_fee_account = vault.withdraw_fee_account;
+        vault.total_deposit_amount = u64::from_le_bytes(*total_deposit_amount);
+        let amount = |i: usize| u64::from_le_bytes(*array_ref![amounts, i * 8, 8]);
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            vault.deposit_amounts[i] = amount(i);
+            vault.reward_token_remain_amounts[i] = amount(MAX_NUMBER_OF_STRATEGY + i);
+            vault.reward_token_b_remain_amounts[i] = amount(2 * MAX_NUMBER_OF_STRATEGY + i);
+            vault.usdc_token_amounts[i] = amount(3 * MAX_NUMBER_OF_STRATEGY + i);
+            vault.accumulated_reward_per_shares[i] =
+                u128::from_le_bytes(*array_ref![accumulated_reward_per_shares, i * 16, 16]);
+        }
+        vault.last_reward_update_time = UnixTimestamp::from_le_bytes(*last_reward_update_time);
+        vault.raydium_state_account = Pubkey::new_from_array(*raydium_state_account);
+        vault.has_second_reward = vault.reward_token_b_account != Pubkey::default();
+        vault.deposits_enabled = true;
+        vault.withdrawals_enabled = true;
+        // v1 은 pending reward cap 이 없었음
+        vault.max_pending_reward = u64::MAX;
+        // v1 은 legacy spl-token 만 썼음
+        vault.token_program = spl_token::id();
+        vault.compound_ratio_bps = BPS_DENOMINATOR as u16;
+        vault.layout_version = VAULT_LAYOUT_VERSION;
+        Ok(vault)
+    }
+
+    /// Mint of the reward swapped by the second_reward_token flag of the swap handlers
+    pub fn reward_mint(&self, second_reward_token: bool) -> Pubkey {
+        match second_reward_token {
+            false => self.reward_token_mint,
+            true => self.reward_token_b_mint,
+        }
+    }
+
+    /// Credits strategy `strategy_index` depositors with `claimable_amount` strategy tokens and
+    /// `restaked_amount` LP restaked as principal, before the restake grows deposit_amounts
+    pub fn add_strategy_gain(
+        &mut self,
+        strategy_index: usize,
+        claimable_amount: u64,
+        restaked_amount: u64,
+    ) -> Result<(), GauntletError> {
+        let deposit_amount = self.deposit_amounts[strategy_index];
+        if claimable_amount > 0 {
+            self.accumulated_reward_per_shares[strategy_index] = acc_add(
+                self.accumulated_reward_per_shares[strategy_index],
+                claimable_amount,
+                deposit_amount,
+            )?;
+        }
+        if restaked_amount > 0 {
+            self.compounded_per_shares[strategy_index] = acc_add(
+                self.compounded_per_shares[strategy_index],
+                restaked_amount,
+                deposit_amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds `amount` LP staked for strategy `strategy_index` to the vault deposits and to the
+    /// strategy's routed_deposit_amount, shared by deposit and every restake of principal
+    pub fn add_principal(
+        &mut self,
+        strategy: &mut Strategy,
+        strategy_index: usize,
+        amount: u64,
+    ) -> Result<(), GauntletError> {
+        self.deposit_amounts[strategy_index] = self.deposit_amounts[strategy_index]
+            .checked_add(amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        self.total_deposit_amount = self
+            .total_deposit_amount
+            .checked_add(amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        strategy.routed_deposit_amount = strategy
+            .routed_deposit_amount
+            .checked_add(amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Part of `amount` restaked as principal according to compound_ratio_bps
+    pub fn compound_amount(&self, amount: u64) -> Result<u64, GauntletError> {
+        let compound_amount = (amount as u128)
+            .checked_mul(self.compound_ratio_bps as u128)
+            .ok_or(GauntletError::MathOverflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(compound_amount as u64)
+    }
 }
 
 impl Sealed for Vault {}
@@ -368,7 +1426,7 @@ impl IsInitialized for Vault {
 }
 
 impl Pack for Vault {
-    const LEN: usize = MAX_VAULT_SIZE; // 2251
+    const LEN: usize = MAX_VAULT_SIZE; // 3242
 
     fn pack_into_slice(&self, output: &mut [u8]) {
         let output = array_mut_ref![output, 0, Vault::LEN];
@@ -390,6 +1448,34 @@ impl Pack for Vault {
             accumulated_reward_per_shares,
             last_reward_update_time,
             raydium_state_account,
+            deposit_token_mint,
+            min_deposit,
+            consecutive_harvest_failures,
+            deposit_fee_account,
+            min_harvest_interval,
+            cumulative_withdrawal_fees,
+            cumulative_performance_fees,
+            swap_slippage_bps,
+            max_swap_usdc_per_tx,
+            compounding,
+            harvest_cursor,
+            harvest_pending_amount,
+            harvest_pending_b_amount,
+            min_swap_output,
+            max_pending_reward,
+            has_second_reward,
+            deposits_enabled,
+            withdrawals_enabled,
+            reward_token_mint,
+            reward_token_b_mint,
+            keeper_fee_numerator,
+            keeper_fee_denominator,
+            withdraw_cooldown_secs,
+            compound_ratio_bps,
+            compounded_per_shares,
+            layout_version,
+            migrated_from,
+            token_program,
         ) = mut_array_refs![
             output,
             1,
@@ -408,6 +1494,34 @@ impl Pack for Vault {
             8 * MAX_NUMBER_OF_STRATEGY,
             16 * MAX_NUMBER_OF_STRATEGY,
             8,
+            32,
+            32,
+            8,
+            1,
+            32,
+            8,
+            8,
+            8,
+            8,
+            8,
+            1,
+            1,
+            8,
+            8,
+            8,
+            8,
+            1,
+            1,
+            1,
+            32,
+            32,
+            8,
+            8,
+            8,
+            2,
+            16 * MAX_NUMBER_OF_STRATEGY,
+            1,
+            32,
             32
         ];
         is_initialized[0] = self.is_initialized as u8;
@@ -442,6 +1556,37 @@ impl Pack for Vault {
         }
         *last_reward_update_time = self.last_reward_update_time.to_le_bytes();
         raydium_state_account.copy_from_slice(self.raydium_state_account.as_ref());
+        deposit_token_mint.copy_from_slice(self.deposit_token_mint.as_ref());
+        *min_deposit = self.min_deposit.to_le_bytes();
+        consecutive_harvest_failures[0] = self.consecutive_harvest_failures;
+        deposit_fee_account.copy_from_slice(self.deposit_fee_account.as_ref());
+        *min_harvest_interval = self.min_harvest_interval.to_le_bytes();
+        *cumulative_withdrawal_fees = self.cumulative_withdrawal_fees.to_le_bytes();
+        *cumulative_performance_fees = self.cumulative_performance_fees.to_le_bytes();
+        *swap_slippage_bps = self.swap_slippage_bps.to_le_bytes();
+        *max_swap_usdc_per_tx = self.max_swap_usdc_per_tx.to_le_bytes();
+        compounding[0] = self.compounding as u8;
+        harvest_cursor[0] = self.harvest_cursor;
+        *harvest_pending_amount = self.harvest_pending_amount.to_le_bytes();
+        *harvest_pending_b_amount = self.harvest_pending_b_amount.to_le_bytes();
+        *min_swap_output = self.min_swap_output.to_le_bytes();
+        *max_pending_reward = self.max_pending_reward.to_le_bytes();
+        has_second_reward[0] = self.has_second_reward as u8;
+        deposits_enabled[0] = self.deposits_enabled as u8;
+        withdrawals_enabled[0] = self.withdrawals_enabled as u8;
+        reward_token_mint.copy_from_slice(self.reward_token_mint.as_ref());
+        reward_token_b_mint.copy_from_slice(self.reward_token_b_mint.as_ref());
+        *keeper_fee_numerator = self.keeper_fee_numerator.to_le_bytes();
+        *keeper_fee_denominator = self.keeper_fee_denominator.to_le_bytes();
+        *withdraw_cooldown_secs = self.withdraw_cooldown_secs.to_le_bytes();
+        *compound_ratio_bps = self.compound_ratio_bps.to_le_bytes();
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![compounded_per_shares, i * 16, 16];
+            *arr_ref = self.compounded_per_shares[i].to_le_bytes();
+        }
+        layout_version[0] = self.layout_version;
+        migrated_from.copy_from_slice(self.migrated_from.as_ref());
+        token_program.copy_from_slice(self.token_program.as_ref());
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
@@ -464,6 +1609,34 @@ impl Pack for Vault {
             accumulated_reward_per_shares,
             last_reward_update_time,
             raydium_state_account,
+            deposit_token_mint,
+            min_deposit,
+            consecutive_harvest_failures,
+            deposit_fee_account,
+            min_harvest_interval,
+            cumulative_withdrawal_fees,
+            cumulative_performance_fees,
+            swap_slippage_bps,
+            max_swap_usdc_per_tx,
+            compounding,
+            harvest_cursor,
+            harvest_pending_amount,
+            harvest_pending_b_amount,
+            min_swap_output,
+            max_pending_reward,
+            has_second_reward,
+            deposits_enabled,
+            withdrawals_enabled,
+            reward_token_mint,
+            reward_token_b_mint,
+            keeper_fee_numerator,
+            keeper_fee_denominator,
+            withdraw_cooldown_secs,
+            compound_ratio_bps,
+            compounded_per_shares,
+            layout_version,
+            migrated_from,
+            token_program,
         ) = array_refs![
             input,
             1,
@@ -482,6 +1655,34 @@ impl Pack for Vault {
             8 * MAX_NUMBER_OF_STRATEGY,
             16 * MAX_NUMBER_OF_STRATEGY,
             8,
+            32,
+            32,
+            8,
+            1,
+            32,
+            8,
+            8,
+            8,
+            8,
+            8,
+            1,
+            1,
+            8,
+            8,
+            8,
+            8,
+            1,
+            1,
+            1,
+            32,
+            32,
+            8,
+            8,
+            8,
+            2,
+            16 * MAX_NUMBER_OF_STRATEGY,
+            1,
+            32,
             32
         ];
         let mut deposit_amounts_array = vec![0; MAX_NUMBER_OF_STRATEGY];
@@ -535,11 +1736,285 @@ impl Pack for Vault {
             accumulated_reward_per_shares: accumulated_reward_per_shares_array,
             last_reward_update_time: UnixTimestamp::from_le_bytes(*last_reward_update_time),
             raydium_state_account: Pubkey::new_from_array(*raydium_state_account),
+            deposit_token_mint: Pubkey::new_from_array(*deposit_token_mint),
+            min_deposit: u64::from_le_bytes(*min_deposit),
+            consecutive_harvest_failures: consecutive_harvest_failures[0],
+            deposit_fee_account: Pubkey::new_from_array(*deposit_fee_account),
+            min_harvest_interval: u64::from_le_bytes(*min_harvest_interval),
+            cumulative_withdrawal_fees: u64::from_le_bytes(*cumulative_withdrawal_fees),
+            cumulative_performance_fees: u64::from_le_bytes(*cumulative_performance_fees),
+            swap_slippage_bps: u64::from_le_bytes(*swap_slippage_bps),
+            max_swap_usdc_per_tx: u64::from_le_bytes(*max_swap_usdc_per_tx),
+            compounding: match compounding {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            harvest_cursor: harvest_cursor[0],
+            harvest_pending_amount: u64::from_le_bytes(*harvest_pending_amount),
+            harvest_pending_b_amount: u64::from_le_bytes(*harvest_pending_b_amount),
+            min_swap_output: u64::from_le_bytes(*min_swap_output),
+            max_pending_reward: u64::from_le_bytes(*max_pending_reward),
+            has_second_reward: match has_second_reward {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            deposits_enabled: match deposits_enabled {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            withdrawals_enabled: match withdrawals_enabled {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            reward_token_mint: Pubkey::new_from_array(*reward_token_mint),
+            reward_token_b_mint: Pubkey::new_from_array(*reward_token_b_mint),
+            keeper_fee_numerator: u64::from_le_bytes(*keeper_fee_numerator),
+            keeper_fee_denominator: u64::from_le_bytes(*keeper_fee_denominator),
+            withdraw_cooldown_secs: u64::from_le_bytes(*withdraw_cooldown_secs),
+            compound_ratio_bps: u16::from_le_bytes(*compound_ratio_bps),
+            compounded_per_shares: compounded_per_shares
+                .chunks_exact(16)
+                .map(|acc| u128::from_le_bytes(*array_ref![acc, 0, 16]))
+                .collect(),
+            layout_version: layout_version[0],
+            migrated_from: Pubkey::new_from_array(*migrated_from),
+            token_program: Pubkey::new_from_array(*token_program),
         })
     }
 }
 
+const VAULT_IS_INITIALIZED_OFFSET: usize = 0;
+const VAULT_GAUNTLET_STATE_ACCOUNT_OFFSET: usize = 1 + 1 + 1 + Fees::LEN;
+const VAULT_DEPOSIT_TOKEN_ACCOUNT_OFFSET: usize = VAULT_GAUNTLET_STATE_ACCOUNT_OFFSET + 32;
+const VAULT_REWARD_TOKEN_ACCOUNT_OFFSET: usize = VAULT_DEPOSIT_TOKEN_ACCOUNT_OFFSET + 32;
+const VAULT_REWARD_TOKEN_B_ACCOUNT_OFFSET: usize = VAULT_REWARD_TOKEN_ACCOUNT_OFFSET + 32;
+const VAULT_WITHDRAW_FEE_ACCOUNT_OFFSET: usize = VAULT_REWARD_TOKEN_B_ACCOUNT_OFFSET + 32;
+const VAULT_TOTAL_DEPOSIT_AMOUNT_OFFSET: usize = VAULT_WITHDRAW_FEE_ACCOUNT_OFFSET + 32;
+const VAULT_DEPOSIT_AMOUNTS_OFFSET: usize = VAULT_TOTAL_DEPOSIT_AMOUNT_OFFSET + 8;
+const VAULT_REWARD_TOKEN_REMAIN_AMOUNTS_OFFSET: usize =
+    VAULT_DEPOSIT_AMOUNTS_OFFSET + 8 * MAX_NUMBER_OF_STRATEGY;
+const VAULT_REWARD_TOKEN_B_REMAIN_AMOUNTS_OFFSET: usize =
+    VAULT_REWARD_TOKEN_REMAIN_AMOUNTS_OFFSET + 8 * MAX_NUMBER_OF_STRATEGY;
+const VAULT_USDC_TOKEN_AMOUNTS_OFFSET: usize =
+    VAULT_REWARD_TOKEN_B_REMAIN_AMOUNTS_OFFSET + 8 * MAX_NUMBER_OF_STRATEGY;
+const VAULT_ACCUMULATED_REWARD_PER_SHARES_OFFSET: usize =
+    VAULT_USDC_TOKEN_AMOUNTS_OFFSET + 8 * MAX_NUMBER_OF_STRATEGY;
+const VAULT_LAST_REWARD_UPDATE_TIME_OFFSET: usize =
+    VAULT_ACCUMULATED_REWARD_PER_SHARES_OFFSET + 16 * MAX_NUMBER_OF_STRATEGY;
+// raydium_state_account, deposit_token_mint, min_deposit 다음
+const VAULT_CONSECUTIVE_HARVEST_FAILURES_OFFSET: usize =
+    VAULT_LAST_REWARD_UPDATE_TIME_OFFSET + 8 + 32 + 32 + 8;
+// deposit_fee_account 다음
+const VAULT_MIN_HARVEST_INTERVAL_OFFSET: usize = VAULT_CONSECUTIVE_HARVEST_FAILURES_OFFSET + 1 + 32;
+// cumulative fees 2개, swap_slippage_bps, max_swap_usdc_per_tx, compounding 다음
+const VAULT_HARVEST_CURSOR_OFFSET: usize = VAULT_MIN_HARVEST_INTERVAL_OFFSET + 8 + 8 * 4 + 1;
+const VAULT_HARVEST_PENDING_AMOUNT_OFFSET: usize = VAULT_HARVEST_CURSOR_OFFSET + 1;
+const VAULT_HARVEST_PENDING_B_AMOUNT_OFFSET: usize = VAULT_HARVEST_PENDING_AMOUNT_OFFSET + 8;
+// min_swap_output 다음
+const VAULT_MAX_PENDING_REWARD_OFFSET: usize = VAULT_HARVEST_PENDING_B_AMOUNT_OFFSET + 8 + 8;
+const VAULT_HAS_SECOND_REWARD_OFFSET: usize = VAULT_MAX_PENDING_REWARD_OFFSET + 8;
+// deposits_enabled, withdrawals_enabled 다음
+const VAULT_REWARD_TOKEN_MINT_OFFSET: usize = VAULT_HAS_SECOND_REWARD_OFFSET + 1 + 1 + 1;
+// reward_token_b_mint 다음
+const VAULT_KEEPER_FEE_NUMERATOR_OFFSET: usize = VAULT_REWARD_TOKEN_MINT_OFFSET + 32 + 32;
+const VAULT_KEEPER_FEE_DENOMINATOR_OFFSET: usize = VAULT_KEEPER_FEE_NUMERATOR_OFFSET + 8;
+// withdraw_cooldown_secs, compound_ratio_bps, compounded_per_shares, layout_version, migrated_from 다음
+const VAULT_TOKEN_PROGRAM_OFFSET: usize =
+    VAULT_KEEPER_FEE_DENOMINATOR_OFFSET + 8 + 8 + 2 + 16 * MAX_NUMBER_OF_STRATEGY + 1 + 32;
+
+/// Zero-copy view over a packed `Vault`, reads and writes fields in place
+/// without allocating the per-strategy vectors of `Vault::unpack`
+pub struct VaultRef<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> VaultRef<'a> {
+    pub fn new(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if data.len() < Vault::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(VaultRef { data })
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(*array_ref![self.data, offset, 8])
+    }
+
+    fn write_u64(&mut self, offset: usize, value: u64) {
+        *array_mut_ref![self.data, offset, 8] = value.to_le_bytes();
+    }
+
+    fn read_pubkey(&self, offset: usize) -> Pubkey {
+        Pubkey::new_from_array(*array_ref![self.data, offset, 32])
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.data[VAULT_IS_INITIALIZED_OFFSET] == 1
+    }
+
+    pub fn gauntlet_state_account(&self) -> Pubkey {
+        self.read_pubkey(VAULT_GAUNTLET_STATE_ACCOUNT_OFFSET)
+    }
+
+    pub fn deposit_token_account(&self) -> Pubkey {
+        self.read_pubkey(VAULT_DEPOSIT_TOKEN_ACCOUNT_OFFSET)
+    }
+
+    pub fn reward_token_account(&self) -> Pubkey {
+        self.read_pubkey(VAULT_REWARD_TOKEN_ACCOUNT_OFFSET)
+    }
+
+    pub fn reward_token_b_account(&self) -> Pubkey {
+        self.read_pubkey(VAULT_REWARD_TOKEN_B_ACCOUNT_OFFSET)
+    }
+
+    pub fn withdraw_fee_account(&self) -> Pubkey {
+        self.read_pubkey(VAULT_WITHDRAW_FEE_ACCOUNT_OFFSET)
+    }
+
+    pub fn total_deposit_amount(&self) -> u64 {
+        self.read_u64(VAULT_TOTAL_DEPOSIT_AMOUNT_OFFSET)
+    }
+
+    pub fn set_total_deposit_amount(&mut self, value: u64) {
+        self.write_u64(VAULT_TOTAL_DEPOSIT_AMOUNT_OFFSET, value)
+    }
+
+    pub fn deposit_amount(&self, i: usize) -> u64 {
+        self.read_u64(VAULT_DEPOSIT_AMOUNTS_OFFSET + i * 8)
+    }
+
+    pub fn set_deposit_amount(&mut self, i: usize, value: u64) {
+        self.write_u64(VAULT_DEPOSIT_AMOUNTS_OFFSET + i * 8, value)
+    }
+
+    pub fn reward_token_remain_amount(&self, i: usize) -> u64 {
+        self.read_u64(VAULT_REWARD_TOKEN_REMAIN_AMOUNTS_OFFSET + i * 8)
+    }
+
+    pub fn set_reward_token_remain_amount(&mut self, i: usize, value: u64) {
+        self.write_u64(VAULT_REWARD_TOKEN_REMAIN_AMOUNTS_OFFSET + i * 8, value)
+    }
+
+    pub fn reward_token_b_remain_amount(&self, i: usize) -> u64 {
+        self.read_u64(VAULT_REWARD_TOKEN_B_REMAIN_AMOUNTS_OFFSET + i * 8)
+    }
+
+    pub fn set_reward_token_b_remain_amount(&mut self, i: usize, value: u64) {
+        self.write_u64(VAULT_REWARD_TOKEN_B_REMAIN_AMOUNTS_OFFSET + i * 8, value)
+    }
+
+    pub fn usdc_token_amount(&self, i: usize) -> u64 {
+        self.read_u64(VAULT_USDC_TOKEN_AMOUNTS_OFFSET + i * 8)
+    }
+
+    pub fn set_usdc_token_amount(&mut self, i: usize, value: u64) {
+        self.write_u64(VAULT_USDC_TOKEN_AMOUNTS_OFFSET + i * 8, value)
+    }
+
+    pub fn accumulated_reward_per_share(&self, i: usize) -> u128 {
+        u128::from_le_bytes(*array_ref![
+            self.data,
+            VAULT_ACCUMULATED_REWARD_PER_SHARES_OFFSET + i * 16,
+            16
+        ])
+    }
+
+    pub fn set_accumulated_reward_per_share(&mut self, i: usize, value: u128) {
+        *array_mut_ref![
+            self.data,
+            VAULT_ACCUMULATED_REWARD_PER_SHARES_OFFSET + i * 16,
+            16
+        ] = value.to_le_bytes();
+    }
+
+    pub fn last_reward_update_time(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(*array_ref![
+            self.data,
+            VAULT_LAST_REWARD_UPDATE_TIME_OFFSET,
+            8
+        ])
+    }
+
+    pub fn set_last_reward_update_time(&mut self, value: UnixTimestamp) {
+        *array_mut_ref![self.data, VAULT_LAST_REWARD_UPDATE_TIME_OFFSET, 8] = value.to_le_bytes();
+    }
+
+    pub fn consecutive_harvest_failures(&self) -> u8 {
+        self.data[VAULT_CONSECUTIVE_HARVEST_FAILURES_OFFSET]
+    }
+
+    pub fn set_consecutive_harvest_failures(&mut self, value: u8) {
+        self.data[VAULT_CONSECUTIVE_HARVEST_FAILURES_OFFSET] = value;
+    }
+
+    pub fn min_harvest_interval(&self) -> u64 {
+        self.read_u64(VAULT_MIN_HARVEST_INTERVAL_OFFSET)
+    }
+
+    pub fn harvest_cursor(&self) -> u8 {
+        self.data[VAULT_HARVEST_CURSOR_OFFSET]
+    }
+
+    pub fn set_harvest_cursor(&mut self, value: u8) {
+        self.data[VAULT_HARVEST_CURSOR_OFFSET] = value;
+    }
+
+    pub fn harvest_pending_amount(&self) -> u64 {
+        self.read_u64(VAULT_HARVEST_PENDING_AMOUNT_OFFSET)
+    }
+
+    pub fn set_harvest_pending_amount(&mut self, value: u64) {
+        self.write_u64(VAULT_HARVEST_PENDING_AMOUNT_OFFSET, value)
+    }
+
+    pub fn harvest_pending_b_amount(&self) -> u64 {
+        self.read_u64(VAULT_HARVEST_PENDING_B_AMOUNT_OFFSET)
+    }
+
+    pub fn set_harvest_pending_b_amount(&mut self, value: u64) {
+        self.write_u64(VAULT_HARVEST_PENDING_B_AMOUNT_OFFSET, value)
+    }
+
+    pub fn max_pending_reward(&self) -> u64 {
+        self.read_u64(VAULT_MAX_PENDING_REWARD_OFFSET)
+    }
+
+    pub fn has_second_reward(&self) -> bool {
+        self.data[VAULT_HAS_SECOND_REWARD_OFFSET] == 1
+    }
+
+    pub fn reward_token_mint(&self) -> Pubkey {
+        self.read_pubkey(VAULT_REWARD_TOKEN_MINT_OFFSET)
+    }
+
+    pub fn token_program(&self) -> Pubkey {
+        self.read_pubkey(VAULT_TOKEN_PROGRAM_OFFSET)
+    }
+
+    /// Keeper bounty on `amount` harvested reward, 0 when disabled
+    pub fn keeper_fee(&self, amount: u64) -> Result<u64, GauntletError> {
+        match self.read_u64(VAULT_KEEPER_FEE_DENOMINATOR_OFFSET) {
+            0 => Ok(0),
+            denominator => {
+                let fee = (amount as u128)
+                    .checked_mul(self.read_u64(VAULT_KEEPER_FEE_NUMERATOR_OFFSET) as u128)
+                    .ok_or(GauntletError::MathOverflow)?
+                    / denominator as u128;
+                u64::try_from(fee).map_err(|_| GauntletError::MathOverflow)
+            }
+        }
+    }
+}
+
 pub const MAX_NUMBER_OF_VAULTS: usize = 50;
+/// 처음 배포된 Strategy layout 크기 (strategy_token_account 까지)
+/// 예전 주석의 515 는 strategy_token_account 를 빼고 계산한 값, 실제 layout 은 547 byte
+pub const STRATEGY_V1_LEN: usize = 1 + 1 + 32 + 32 + 32 + 1 + 8 + 8 + 8 * MAX_NUMBER_OF_VAULTS + 32;
 /// 전략 정보
 pub struct Strategy {
     /// Initialized state
@@ -562,6 +2037,20 @@ pub struct Strategy {
     pub deposit_amounts: Vec<u64>,
     /// Strategy Token Account
     pub strategy_token_account: Pubkey,
+    /// Strategy type (RAY: strategy token is the farm reward token itself)
+    pub strategy_type: StrategyType,
+    /// Mint of the strategy token account
+    pub strategy_token_mint: Pubkey,
+    /// Most deposit token(LP) all vaults together can route into this strategy, 0 means unlimited
+    pub deposit_cap: u64,
+    /// Share of the performance fee (bps) sent to the strategist, the rest goes to performance_fee_account
+    pub strategist_fee_split_bps: u16,
+    /// Strategist's strategy token account, receives strategist_fee_split_bps of the performance fee
+    pub strategist_fee_account: Pubkey,
+    /// Deposit token(LP) users deposited into this strategy across all vaults, checked against deposit_cap
+    pub routed_deposit_amount: u64,
+    /// v1 strategy this strategy was migrated from, users created under it migrate into this one
+    pub migrated_from: Pubkey,
 }
 impl Strategy {
     pub fn init(
@@ -570,6 +2059,8 @@ impl Strategy {
         admin: Pubkey,
         performance_fee_account: Pubkey,
         strategy_token_account: Pubkey,
+        strategy_type: StrategyType,
+        strategy_token_mint: Pubkey,
     ) -> Self {
         Strategy {
             is_initialized: true,
@@ -582,7 +2073,84 @@ impl Strategy {
             total_deposit_amount: 0,
             deposit_amounts: vec![0; MAX_NUMBER_OF_VAULTS],
             strategy_token_account,
+            strategy_type,
+            strategy_token_mint,
+            deposit_cap: 0,
+            strategist_fee_split_bps: 0,
+            strategist_fee_account: Pubkey::default(),
+            routed_deposit_amount: 0,
+            migrated_from: Pubkey::default(),
+        }
+    }
+
+    /// Decodes a v1 strategy (STRATEGY_V1_LEN bytes) into the current layout.
+    /// v1 only had token strategies, the strategy token mint is left for the caller
+    pub fn migrate_from_v1(old_bytes: &[u8]) -> Result<Strategy, ProgramError> {
+        if old_bytes.len() != STRATEGY_V1_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![old_bytes, 0, STRATEGY_V1_LEN];
+        let (
+            is_initialized,
+            index,
+            gauntlet_state_account,
+            admin,
+            performance_fee_account,
+            status,
+            last_reward_update_time,
+            total_deposit_amount,
+            deposit_amounts,
+            strategy_token_account,
+        ) = array_refs![
+            input,
+            1,
+            1,
+            32,
+            32,
+            32,
+            1,
+            8,
+            8,
+            8 * MAX_NUMBER_OF_VAULTS,
+            32
+        ];
+        if *is_initialized != [1] {
+            return Err(ProgramError::UninitializedAccount);
         }
+        let mut strategy = Strategy::unpack_unchecked(&vec![0; Strategy::LEN])?;
+        strategy.is_initialized = true;
+        strategy.index = index[0];
+        strategy.gauntlet_state_account = Pubkey::new_from_array(*gauntlet_state_account);
+        strategy.admin = Pubkey::new_from_array(*admin);
+        strategy.performance_fee_account = Pubkey::new_from_array(*performance_fee_account);
+        strategy.status = match status {
+            [0] => Status::PAUSED,
+            [1] => Status::NORMAL,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        strategy.last_reward_update_time = UnixTimestamp::from_le_bytes(*last_reward_update_time);
+        strategy.total_deposit_amount = u64::from_le_bytes(*total_deposit_amount);
+        for i in 0..MAX_NUMBER_OF_VAULTS {
+            strategy.deposit_amounts[i] =
+                u64::from_le_bytes(*array_ref![deposit_amounts, i * 8, 8]);
+        }
+        strategy.strategy_token_account = Pubkey::new_from_array(*strategy_token_account);
+        strategy.strategy_type = StrategyType::RAY;
+        // v1 에는 cap 이 없었으므로 지금까지 들어온 deposit 부터 세기 시작함
+        strategy.routed_deposit_amount = strategy.total_deposit_amount;
+        Ok(strategy)
+    }
+
+    /// cap 은 모든 vault 에서 이 strategy 로 들어온 deposit 합계 기준, 0 이면 제한 없음
+    pub fn check_deposit_cap(&self, amount: u64) -> Result<(), GauntletError> {
+        let routed_deposit_amount = self
+            .routed_deposit_amount
+            .checked_add(amount)
+            .ok_or(GauntletError::MathOverflow)?;
+        if self.deposit_cap > 0 && routed_deposit_amount > self.deposit_cap {
+            return Err(GauntletError::DepositCapExceeded);
+        }
+        Ok(())
     }
 }
 
@@ -594,7 +2162,23 @@ impl IsInitialized for Strategy {
 }
 
 impl Pack for Strategy {
-    const LEN: usize = 1 + 1 + 32 + 32 + 32 + 1 + 8 + 8 + 8 * MAX_NUMBER_OF_VAULTS + 32; // 515
+    const LEN: usize = 1
+        + 1
+        + 32
+        + 32
+        + 32
+        + 1
+        + 8
+        + 8
+        + 8 * MAX_NUMBER_OF_VAULTS
+        + 32
+        + 1
+        + 32
+        + 8
+        + 2
+        + 32
+        + 8
+        + 32; // 662
 
     fn pack_into_slice(&self, output: &mut [u8]) {
         let output = array_mut_ref![output, 0, Strategy::LEN];
@@ -609,6 +2193,13 @@ impl Pack for Strategy {
             total_deposit_amount,
             deposit_amounts,
             strategy_token_account,
+            strategy_type,
+            strategy_token_mint,
+            deposit_cap,
+            strategist_fee_split_bps,
+            strategist_fee_account,
+            routed_deposit_amount,
+            migrated_from,
         ) = mut_array_refs![
             output,
             1,
@@ -620,6 +2211,13 @@ impl Pack for Strategy {
             8,
             8,
             8 * MAX_NUMBER_OF_VAULTS,
+            32,
+            1,
+            32,
+            8,
+            2,
+            32,
+            8,
             32
         ];
 
@@ -636,6 +2234,13 @@ impl Pack for Strategy {
             *strategy_deposit_amount = self.deposit_amounts[i].to_le_bytes();
         }
         strategy_token_account.copy_from_slice(self.strategy_token_account.as_ref());
+        strategy_type[0] = self.strategy_type as u8;
+        strategy_token_mint.copy_from_slice(self.strategy_token_mint.as_ref());
+        *deposit_cap = self.deposit_cap.to_le_bytes();
+        *strategist_fee_split_bps = self.strategist_fee_split_bps.to_le_bytes();
+        strategist_fee_account.copy_from_slice(self.strategist_fee_account.as_ref());
+        *routed_deposit_amount = self.routed_deposit_amount.to_le_bytes();
+        migrated_from.copy_from_slice(self.migrated_from.as_ref());
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -651,7 +2256,33 @@ impl Pack for Strategy {
             total_deposit_amount,
             deposit_amounts,
             strategy_token_account,
-        ) = array_refs![src, 1, 1, 32, 32, 32, 1, 8, 8, 8 * MAX_NUMBER_OF_VAULTS, 32];
+            strategy_type,
+            strategy_token_mint,
+            deposit_cap,
+            strategist_fee_split_bps,
+            strategist_fee_account,
+            routed_deposit_amount,
+            migrated_from,
+        ) = array_refs![
+            src,
+            1,
+            1,
+            32,
+            32,
+            32,
+            1,
+            8,
+            8,
+            8 * MAX_NUMBER_OF_VAULTS,
+            32,
+            1,
+            32,
+            8,
+            2,
+            32,
+            8,
+            32
+        ];
         let mut deposit_amounts_array = vec![0; MAX_NUMBER_OF_VAULTS];
 
         for i in 0..MAX_NUMBER_OF_VAULTS {
@@ -672,121 +2303,329 @@ impl Pack for Strategy {
             status: match status {
                 [0] => Status::PAUSED,
                 [1] => Status::NORMAL,
+                [2] => Status::RETIRED,
                 _ => return Err(ProgramError::InvalidAccountData),
             },
             last_reward_update_time: UnixTimestamp::from_le_bytes(*last_reward_update_time),
             total_deposit_amount: u64::from_le_bytes(*total_deposit_amount),
             deposit_amounts: deposit_amounts_array,
             strategy_token_account: Pubkey::new_from_array(*strategy_token_account),
+            strategy_type: match strategy_type {
+                [0] => StrategyType::RAY,
+                [1] => StrategyType::RAYDIUM_LP,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            strategy_token_mint: Pubkey::new_from_array(*strategy_token_mint),
+            deposit_cap: u64::from_le_bytes(*deposit_cap),
+            strategist_fee_split_bps: u16::from_le_bytes(*strategist_fee_split_bps),
+            strategist_fee_account: Pubkey::new_from_array(*strategist_fee_account),
+            routed_deposit_amount: u64::from_le_bytes(*routed_deposit_amount),
+            migrated_from: Pubkey::new_from_array(*migrated_from),
         })
     }
 }
 
+// Strategy pack layout 기준 offset
+const STRATEGY_IS_INITIALIZED_OFFSET: usize = 0;
+const STRATEGY_INDEX_OFFSET: usize = 1;
+const STRATEGY_GAUNTLET_STATE_ACCOUNT_OFFSET: usize = 2;
+const STRATEGY_PERFORMANCE_FEE_ACCOUNT_OFFSET: usize =
+    STRATEGY_GAUNTLET_STATE_ACCOUNT_OFFSET + 32 + 32;
+const STRATEGY_TOTAL_DEPOSIT_AMOUNT_OFFSET: usize =
+    STRATEGY_PERFORMANCE_FEE_ACCOUNT_OFFSET + 32 + 1 + 8;
+const STRATEGY_DEPOSIT_AMOUNTS_OFFSET: usize = STRATEGY_TOTAL_DEPOSIT_AMOUNT_OFFSET + 8;
+const STRATEGY_TOKEN_ACCOUNT_OFFSET: usize =
+    STRATEGY_DEPOSIT_AMOUNTS_OFFSET + 8 * MAX_NUMBER_OF_VAULTS;
+const STRATEGY_TOKEN_MINT_OFFSET: usize = STRATEGY_TOKEN_ACCOUNT_OFFSET + 32 + 1;
+const STRATEGY_STRATEGIST_FEE_SPLIT_BPS_OFFSET: usize = STRATEGY_TOKEN_MINT_OFFSET + 32 + 8;
+const STRATEGY_STRATEGIST_FEE_ACCOUNT_OFFSET: usize = STRATEGY_STRATEGIST_FEE_SPLIT_BPS_OFFSET + 2;
+const STRATEGY_ROUTED_DEPOSIT_AMOUNT_OFFSET: usize = STRATEGY_STRATEGIST_FEE_ACCOUNT_OFFSET + 32;
+
+/// Zero-copy view over a packed `Strategy`, reads and writes fields in place
+/// without allocating `deposit_amounts` like `Strategy::unpack`
+pub struct StrategyRef<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> StrategyRef<'a> {
+    pub fn new(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if data.len() < Strategy::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(StrategyRef { data })
+    }
+
+    fn read_pubkey(&self, offset: usize) -> Pubkey {
+        Pubkey::new_from_array(*array_ref![self.data, offset, 32])
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.data[STRATEGY_IS_INITIALIZED_OFFSET] == 1
+    }
+
+    pub fn index(&self) -> u8 {
+        self.data[STRATEGY_INDEX_OFFSET]
+    }
+
+    pub fn gauntlet_state_account(&self) -> Pubkey {
+        self.read_pubkey(STRATEGY_GAUNTLET_STATE_ACCOUNT_OFFSET)
+    }
+
+    pub fn performance_fee_account(&self) -> Pubkey {
+        self.read_pubkey(STRATEGY_PERFORMANCE_FEE_ACCOUNT_OFFSET)
+    }
+
+    pub fn total_deposit_amount(&self) -> u64 {
+        u64::from_le_bytes(*array_ref![
+            self.data,
+            STRATEGY_TOTAL_DEPOSIT_AMOUNT_OFFSET,
+            8
+        ])
+    }
+
+    pub fn set_total_deposit_amount(&mut self, value: u64) {
+        *array_mut_ref![self.data, STRATEGY_TOTAL_DEPOSIT_AMOUNT_OFFSET, 8] = value.to_le_bytes();
+    }
+
+    pub fn deposit_amount(&self, vault_index: usize) -> u64 {
+        u64::from_le_bytes(*array_ref![
+            self.data,
+            STRATEGY_DEPOSIT_AMOUNTS_OFFSET + vault_index * 8,
+            8
+        ])
+    }
+
+    pub fn set_deposit_amount(&mut self, vault_index: usize, value: u64) {
+        *array_mut_ref![
+            self.data,
+            STRATEGY_DEPOSIT_AMOUNTS_OFFSET + vault_index * 8,
+            8
+        ] = value.to_le_bytes();
+    }
+
+    pub fn strategy_token_account(&self) -> Pubkey {
+        self.read_pubkey(STRATEGY_TOKEN_ACCOUNT_OFFSET)
+    }
+
+    pub fn strategy_token_mint(&self) -> Pubkey {
+        self.read_pubkey(STRATEGY_TOKEN_MINT_OFFSET)
+    }
+
+    pub fn strategist_fee_split_bps(&self) -> u16 {
+        u16::from_le_bytes(*array_ref![
+            self.data,
+            STRATEGY_STRATEGIST_FEE_SPLIT_BPS_OFFSET,
+            2
+        ])
+    }
+
+    pub fn strategist_fee_account(&self) -> Pubkey {
+        self.read_pubkey(STRATEGY_STRATEGIST_FEE_ACCOUNT_OFFSET)
+    }
+
+    pub fn routed_deposit_amount(&self) -> u64 {
+        u64::from_le_bytes(*array_ref![
+            self.data,
+            STRATEGY_ROUTED_DEPOSIT_AMOUNT_OFFSET,
+            8
+        ])
+    }
+
+    pub fn set_routed_deposit_amount(&mut self, value: u64) {
+        *array_mut_ref![self.data, STRATEGY_ROUTED_DEPOSIT_AMOUNT_OFFSET, 8] = value.to_le_bytes();
+    }
+}
+
+// offset 이 Pack layout 과 어긋나면 컴파일이 깨지도록
+const _: () = assert!(STRATEGY_ROUTED_DEPOSIT_AMOUNT_OFFSET + 8 + 32 == Strategy::LEN);
+
+/// vault 별 strategy 설정, 칸 수(capacity)는 account 크기에서 정해짐.
+/// capacity 를 적게 잡은 vault 는 GrowVaultStrategy 로 더 큰 account 에 옮겨서 늘림
 pub struct VaultStrategy {
     /// Initialized state
     pub is_initialized: bool,
     /// vault
     pub vault_account: Pubkey,
+    /// number of strategy slots stored in the account, derived from its size
+    pub capacity: usize,
     /// need usdc pool
     pub needs_usdc_pools: Vec<bool>,
     /// vault and strategy mapping status
     pub availabilities: Vec<bool>,
     // User deposit reward token amount (BTC, ETH 등)
     pub strategy_token_amounts: Vec<u64>,
+    /// whether the strategy takes new deposits (independent of availability)
+    pub accepting_deposits: Vec<bool>,
+    /// strategy token is the deposit LP (set by UpdateVaultStrategy), so swaps re-stake
+    /// compound_ratio_bps of the swapped strategy token as principal
+    pub compounding: Vec<bool>,
 }
+/// strategy 한 칸의 크기 : needs_usdc_pool, availability, strategy_token_amount, accepting_deposits, compounding
+const VAULT_STRATEGY_SLOT_LEN: usize = 1 + 1 + 8 + 1 + 1;
+const VAULT_STRATEGY_HEADER_LEN: usize = 1 + 32;
+/// 처음 배포된 VaultStrategy layout 크기 (strategy 50 칸 고정, accepting_deposits / compounding 없음)
+pub const VAULT_STRATEGY_V1_LEN: usize = 1 + 32 + MAX_NUMBER_OF_STRATEGY * (1 + 1 + 8);
+
 impl VaultStrategy {
-    pub fn init(vault_account: Pubkey) -> Self {
+    pub fn init(vault_account: Pubkey, capacity: usize) -> Self {
         VaultStrategy {
             is_initialized: true,
             vault_account,
+            capacity,
             needs_usdc_pools: vec![false; MAX_NUMBER_OF_STRATEGY],
             availabilities: vec![false; MAX_NUMBER_OF_STRATEGY],
             strategy_token_amounts: vec![0; MAX_NUMBER_OF_STRATEGY],
+            accepting_deposits: vec![true; MAX_NUMBER_OF_STRATEGY],
+            compounding: vec![false; MAX_NUMBER_OF_STRATEGY],
         }
     }
-}
 
-impl Sealed for VaultStrategy {}
-impl IsInitialized for VaultStrategy {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+    /// Account size holding `capacity` strategy slots
+    pub fn packed_len(capacity: usize) -> usize {
+        VAULT_STRATEGY_HEADER_LEN + VAULT_STRATEGY_SLOT_LEN * capacity
     }
-}
 
-impl Pack for VaultStrategy {
-    const LEN: usize =
-        1 + 32 + 8 * MAX_NUMBER_OF_STRATEGY + MAX_NUMBER_OF_STRATEGY + MAX_NUMBER_OF_STRATEGY;
+    /// Strategy slots held by an account of `len` bytes, InvalidAccountData if no capacity fits
+    pub fn capacity_of(len: usize) -> Result<usize, ProgramError> {
+        let slots_len = len
+            .checked_sub(VAULT_STRATEGY_HEADER_LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let capacity = slots_len / VAULT_STRATEGY_SLOT_LEN;
+        if slots_len % VAULT_STRATEGY_SLOT_LEN != 0
+            || capacity == 0
+            || capacity > MAX_NUMBER_OF_STRATEGY
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(capacity)
+    }
 
-    fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, VaultStrategy::LEN];
+    /// Decodes a v1 vault strategy (VAULT_STRATEGY_V1_LEN bytes) into a `capacity` slot layout.
+    /// v1 slots past `capacity` must be empty, see VaultStrategy::pack
+    pub fn migrate_from_v1(
+        old_bytes: &[u8],
+        capacity: usize,
+    ) -> Result<VaultStrategy, ProgramError> {
+        if old_bytes.len() != VAULT_STRATEGY_V1_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![old_bytes, 0, VAULT_STRATEGY_V1_LEN];
         let (
             is_initialized,
             vault_account,
             needs_usdc_pools,
             availabilities,
             strategy_token_amounts,
-        ) = mut_array_refs![
-            output,
+        ) = array_refs![
+            input,
             1,
             32,
             MAX_NUMBER_OF_STRATEGY,
             MAX_NUMBER_OF_STRATEGY,
             8 * MAX_NUMBER_OF_STRATEGY
         ];
-
-        is_initialized[0] = self.is_initialized as u8;
-        vault_account.copy_from_slice(self.vault_account.as_ref());
-        for i in 0..MAX_NUMBER_OF_STRATEGY {
-            let arr_ref = array_mut_ref![needs_usdc_pools, i, 1];
-            arr_ref[0] = self.needs_usdc_pools[i] as u8;
+        if *is_initialized != [1] {
+            return Err(ProgramError::UninitializedAccount);
         }
+        let flag = |flags: &[u8], i: usize| match flags[i] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ProgramError::InvalidAccountData),
+        };
+        let mut vault_strategy =
+            VaultStrategy::init(Pubkey::new_from_array(*vault_account), capacity);
         for i in 0..MAX_NUMBER_OF_STRATEGY {
-            let arr_ref = array_mut_ref![availabilities, i, 1];
-            arr_ref[0] = self.availabilities[i] as u8;
+            vault_strategy.needs_usdc_pools[i] = flag(needs_usdc_pools, i)?;
+            vault_strategy.availabilities[i] = flag(availabilities, i)?;
+            vault_strategy.strategy_token_amounts[i] =
+                u64::from_le_bytes(*array_ref![strategy_token_amounts, i * 8, 8]);
         }
-        for i in 0..MAX_NUMBER_OF_STRATEGY {
+        Ok(vault_strategy)
+    }
+
+    /// capacity 밖의 칸은 account 에 저장되지 않으므로 비어 있어야 함
+    /// (accepting_deposits 는 availability 가 꺼진 칸에서는 의미가 없어서 보지 않음)
+    fn fits_capacity(&self) -> bool {
+        (self.capacity..MAX_NUMBER_OF_STRATEGY).all(|i| {
+            !self.needs_usdc_pools[i]
+                && !self.availabilities[i]
+                && self.strategy_token_amounts[i] == 0
+                && !self.compounding[i]
+        })
+    }
+}
+
+impl Sealed for VaultStrategy {}
+impl IsInitialized for VaultStrategy {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// LEN 은 MAX_NUMBER_OF_STRATEGY 칸 (capacity 가 생기기 전 layout 과 같음),
+/// unpack/pack 은 account 크기에 맞는 capacity 로 읽고 씀. 메모리에서는 항상 MAX_NUMBER_OF_STRATEGY 칸
+impl Pack for VaultStrategy {
+    const LEN: usize = VAULT_STRATEGY_HEADER_LEN + VAULT_STRATEGY_SLOT_LEN * MAX_NUMBER_OF_STRATEGY;
+
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::unpack_from_slice(input)
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if Self::capacity_of(dst.len())? != src.capacity {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !src.fits_capacity() {
+            return Err(GauntletError::VaultStrategyCapacityExceeded.into());
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let capacity = self.capacity;
+        let (is_initialized, output) = output.split_at_mut(1);
+        let (vault_account, output) = output.split_at_mut(32);
+        let (needs_usdc_pools, output) = output.split_at_mut(capacity);
+        let (availabilities, output) = output.split_at_mut(capacity);
+        let (strategy_token_amounts, output) = output.split_at_mut(8 * capacity);
+        let (accepting_deposits, output) = output.split_at_mut(capacity);
+        let compounding = &mut output[..capacity];
+
+        is_initialized[0] = self.is_initialized as u8;
+        vault_account.copy_from_slice(self.vault_account.as_ref());
+        for i in 0..capacity {
+            needs_usdc_pools[i] = self.needs_usdc_pools[i] as u8;
+            availabilities[i] = self.availabilities[i] as u8;
             let arr_ref = array_mut_ref![strategy_token_amounts, i * 8, 8];
             *arr_ref = self.strategy_token_amounts[i].to_le_bytes();
+            accepting_deposits[i] = self.accepting_deposits[i] as u8;
+            compounding[i] = self.compounding[i] as u8;
         }
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, VaultStrategy::LEN];
-        let (
-            is_initialized,
-            vault_account,
-            needs_usdc_pools,
-            availabilities,
-            strategy_token_amounts,
-        ) = array_refs![
-            src,
-            1,
-            32,
-            MAX_NUMBER_OF_STRATEGY,
-            MAX_NUMBER_OF_STRATEGY,
-            8 * MAX_NUMBER_OF_STRATEGY
-        ];
+        let capacity = Self::capacity_of(src.len())?;
+        let (is_initialized, src) = src.split_at(1);
+        let (vault_account, src) = src.split_at(32);
+        let (needs_usdc_pools, src) = src.split_at(capacity);
+        let (availabilities, src) = src.split_at(capacity);
+        let (strategy_token_amounts, src) = src.split_at(8 * capacity);
+        let (accepting_deposits, compounding) = src.split_at(capacity);
 
-        let mut needs_usdc_pools_array = vec![false; MAX_NUMBER_OF_STRATEGY];
-        for i in 0..MAX_NUMBER_OF_STRATEGY {
-            let arr_ref = array_ref![needs_usdc_pools, i, 1];
-            needs_usdc_pools_array[i] = match arr_ref {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
+        let unpack_flags = |flags: &[u8], default: bool| {
+            let mut array = vec![default; MAX_NUMBER_OF_STRATEGY];
+            for (i, flag) in flags.iter().enumerate() {
+                array[i] = match flag {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(ProgramError::InvalidAccountData),
+                }
             }
-        }
-        let mut availabilities_array = vec![false; MAX_NUMBER_OF_STRATEGY];
-        for i in 0..MAX_NUMBER_OF_STRATEGY {
-            let arr_ref = array_ref![availabilities, i, 1];
-            availabilities_array[i] = match arr_ref {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            }
-        }
+            Ok(array)
+        };
         let mut strategy_token_amounts_array = vec![0; MAX_NUMBER_OF_STRATEGY];
-        for i in 0..MAX_NUMBER_OF_STRATEGY {
+        for i in 0..capacity {
             let arr_ref = array_ref![strategy_token_amounts, i * 8, 8];
             strategy_token_amounts_array[i] = u64::from_le_bytes(*arr_ref);
         }
@@ -797,10 +2636,735 @@ impl Pack for VaultStrategy {
                 [1] => true,
                 _ => return Err(ProgramError::InvalidAccountData),
             },
-            vault_account: Pubkey::new_from_array(*vault_account),
-            needs_usdc_pools: needs_usdc_pools_array,
-            availabilities: availabilities_array,
+            vault_account: Pubkey::new_from_array(*array_ref![vault_account, 0, 32]),
+            capacity,
+            needs_usdc_pools: unpack_flags(needs_usdc_pools, false)?,
+            availabilities: unpack_flags(availabilities, false)?,
             strategy_token_amounts: strategy_token_amounts_array,
+            accepting_deposits: unpack_flags(accepting_deposits, true)?,
+            compounding: unpack_flags(compounding, false)?,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    fn sample_vault() -> Vault {
+        let mut vault = Vault::unpack_unchecked(&vec![0; Vault::LEN]).unwrap();
+        vault.is_initialized = true;
+        vault.index = 3;
+        vault.gauntlet_state_account = Pubkey::new_unique();
+        vault.deposit_token_account = Pubkey::new_unique();
+        vault.reward_token_account = Pubkey::new_unique();
+        vault.reward_token_b_account = Pubkey::new_unique();
+        vault.withdraw_fee_account = Pubkey::new_unique();
+        vault.total_deposit_amount = 1_000_000;
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            vault.deposit_amounts[i] = 100 + i as u64;
+            vault.reward_token_remain_amounts[i] = 200 + i as u64;
+            vault.reward_token_b_remain_amounts[i] = 300 + i as u64;
+            vault.usdc_token_amounts[i] = 400 + i as u64;
+            vault.accumulated_reward_per_shares[i] = (u64::MAX as u128) + i as u128;
+        }
+        vault.last_reward_update_time = 1_650_000_000;
+        vault.consecutive_harvest_failures = 2;
+        vault.token_program = Pubkey::new_unique();
+        vault.min_harvest_interval = 3600;
+        vault.harvest_cursor = 1;
+        vault.harvest_pending_amount = 11;
+        vault.harvest_pending_b_amount = 12;
+        vault.max_pending_reward = u64::MAX;
+        vault.has_second_reward = true;
+        vault.reward_token_mint = Pubkey::new_unique();
+        vault.keeper_fee_numerator = 1;
+        vault.keeper_fee_denominator = 200;
+        vault
+    }
+
+    fn packed(vault: &Vault) -> Vec<u8> {
+        let mut data = vec![0; Vault::LEN];
+        vault.pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_vault_ref_reads_packed_vault() {
+        let vault = sample_vault();
+        let mut data = packed(&vault);
+        let vault_ref = VaultRef::new(&mut data).unwrap();
+
+        assert!(vault_ref.is_initialized());
+        assert_eq!(
+            vault_ref.gauntlet_state_account(),
+            vault.gauntlet_state_account
+        );
+        assert_eq!(
+            vault_ref.deposit_token_account(),
+            vault.deposit_token_account
+        );
+        assert_eq!(vault_ref.reward_token_account(), vault.reward_token_account);
+        assert_eq!(
+            vault_ref.reward_token_b_account(),
+            vault.reward_token_b_account
+        );
+        assert_eq!(vault_ref.withdraw_fee_account(), vault.withdraw_fee_account);
+        assert_eq!(vault_ref.total_deposit_amount(), vault.total_deposit_amount);
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            assert_eq!(vault_ref.deposit_amount(i), vault.deposit_amounts[i]);
+            assert_eq!(
+                vault_ref.reward_token_remain_amount(i),
+                vault.reward_token_remain_amounts[i]
+            );
+            assert_eq!(
+                vault_ref.reward_token_b_remain_amount(i),
+                vault.reward_token_b_remain_amounts[i]
+            );
+            assert_eq!(vault_ref.usdc_token_amount(i), vault.usdc_token_amounts[i]);
+            assert_eq!(
+                vault_ref.accumulated_reward_per_share(i),
+                vault.accumulated_reward_per_shares[i]
+            );
+        }
+        assert_eq!(
+            vault_ref.last_reward_update_time(),
+            vault.last_reward_update_time
+        );
+        assert_eq!(
+            vault_ref.consecutive_harvest_failures(),
+            vault.consecutive_harvest_failures
+        );
+        assert_eq!(vault_ref.min_harvest_interval(), vault.min_harvest_interval);
+        assert_eq!(vault_ref.harvest_cursor(), vault.harvest_cursor);
+        assert_eq!(
+            vault_ref.harvest_pending_amount(),
+            vault.harvest_pending_amount
+        );
+        assert_eq!(
+            vault_ref.harvest_pending_b_amount(),
+            vault.harvest_pending_b_amount
+        );
+        assert_eq!(vault_ref.max_pending_reward(), vault.max_pending_reward);
+        assert_eq!(vault_ref.has_second_reward(), vault.has_second_reward);
+        assert_eq!(vault_ref.reward_token_mint(), vault.reward_token_mint);
+        assert_eq!(vault_ref.token_program(), vault.token_program);
+    }
+
+    #[test]
+    fn test_vault_ref_writes_match_pack() {
+        let mut vault = sample_vault();
+        let mut data = packed(&vault);
+        let last = MAX_NUMBER_OF_STRATEGY - 1;
+        {
+            let mut vault_ref = VaultRef::new(&mut data).unwrap();
+            vault_ref.set_total_deposit_amount(7);
+            vault_ref.set_deposit_amount(last, 8);
+            vault_ref.set_reward_token_remain_amount(last, 9);
+            vault_ref.set_reward_token_b_remain_amount(0, 10);
+            vault_ref.set_usdc_token_amount(last, 11);
+            vault_ref.set_accumulated_reward_per_share(last, u128::MAX);
+            vault_ref.set_last_reward_update_time(-1);
+            vault_ref.set_consecutive_harvest_failures(u8::MAX);
+            vault_ref.set_harvest_cursor(4);
+            vault_ref.set_harvest_pending_amount(12);
+            vault_ref.set_harvest_pending_b_amount(13);
+        }
+        vault.total_deposit_amount = 7;
+        vault.deposit_amounts[last] = 8;
+        vault.reward_token_remain_amounts[last] = 9;
+        vault.reward_token_b_remain_amounts[0] = 10;
+        vault.usdc_token_amounts[last] = 11;
+        vault.accumulated_reward_per_shares[last] = u128::MAX;
+        vault.last_reward_update_time = -1;
+        vault.consecutive_harvest_failures = u8::MAX;
+        vault.harvest_cursor = 4;
+        vault.harvest_pending_amount = 12;
+        vault.harvest_pending_b_amount = 13;
+
+        assert_eq!(data, packed(&vault));
+    }
+
+    fn sample_strategy() -> Strategy {
+        let mut strategy = Strategy::unpack_unchecked(&vec![0; Strategy::LEN]).unwrap();
+        strategy.is_initialized = true;
+        strategy.index = 2;
+        strategy
+    }
+
+    #[test]
+    fn test_strategy_ref_matches_pack() {
+        let mut strategy = Strategy::unpack_unchecked(&vec![0; Strategy::LEN]).unwrap();
+        strategy.is_initialized = true;
+        strategy.index = 7;
+        strategy.gauntlet_state_account = Pubkey::new_unique();
+        strategy.admin = Pubkey::new_unique();
+        strategy.performance_fee_account = Pubkey::new_unique();
+        strategy.total_deposit_amount = 1_000;
+        for i in 0..MAX_NUMBER_OF_VAULTS {
+            strategy.deposit_amounts[i] = 100 + i as u64;
+        }
+        strategy.strategy_token_account = Pubkey::new_unique();
+        strategy.strategy_token_mint = Pubkey::new_unique();
+        strategy.deposit_cap = u64::MAX;
+        strategy.strategist_fee_split_bps = 2_500;
+        strategy.strategist_fee_account = Pubkey::new_unique();
+        strategy.routed_deposit_amount = 42;
+        let mut data = vec![0; Strategy::LEN];
+        strategy.pack_into_slice(&mut data);
+
+        let last = MAX_NUMBER_OF_VAULTS - 1;
+        {
+            let mut strategy_ref = StrategyRef::new(&mut data).unwrap();
+            assert!(strategy_ref.is_initialized());
+            assert_eq!(strategy_ref.index(), 7);
+            assert_eq!(
+                strategy_ref.gauntlet_state_account(),
+                strategy.gauntlet_state_account
+            );
+            assert_eq!(
+                strategy_ref.performance_fee_account(),
+                strategy.performance_fee_account
+            );
+            assert_eq!(strategy_ref.total_deposit_amount(), 1_000);
+            assert_eq!(strategy_ref.deposit_amount(last), 100 + last as u64);
+            assert_eq!(
+                strategy_ref.strategy_token_account(),
+                strategy.strategy_token_account
+            );
+            assert_eq!(
+                strategy_ref.strategy_token_mint(),
+                strategy.strategy_token_mint
+            );
+            assert_eq!(strategy_ref.strategist_fee_split_bps(), 2_500);
+            assert_eq!(
+                strategy_ref.strategist_fee_account(),
+                strategy.strategist_fee_account
+            );
+            assert_eq!(strategy_ref.routed_deposit_amount(), 42);
+            strategy_ref.set_total_deposit_amount(7);
+            strategy_ref.set_deposit_amount(last, 8);
+            strategy_ref.set_routed_deposit_amount(9);
+        }
+        strategy.total_deposit_amount = 7;
+        strategy.deposit_amounts[last] = 8;
+        strategy.routed_deposit_amount = 9;
+        let mut expected = vec![0; Strategy::LEN];
+        strategy.pack_into_slice(&mut expected);
+        assert_eq!(data, expected);
+
+        let mut data = vec![0; Strategy::LEN - 1];
+        assert_eq!(
+            StrategyRef::new(&mut data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_deposit_cap() {
+        let mut strategy = sample_strategy();
+        // 0 은 제한 없음
+        strategy.routed_deposit_amount = u64::MAX - 1;
+        assert!(strategy.check_deposit_cap(1).is_ok());
+
+        strategy.deposit_cap = 1_000;
+        strategy.routed_deposit_amount = 600;
+        assert!(strategy.check_deposit_cap(400).is_ok());
+        assert_matches!(
+            strategy.check_deposit_cap(401),
+            Err(GauntletError::DepositCapExceeded)
+        );
+        // 다른 vault 에서 들어온 양도 같은 cap 에 포함
+        strategy.routed_deposit_amount = 1_000;
+        assert_matches!(
+            strategy.check_deposit_cap(1),
+            Err(GauntletError::DepositCapExceeded)
+        );
+        strategy.routed_deposit_amount = u64::MAX;
+        assert_matches!(
+            strategy.check_deposit_cap(1),
+            Err(GauntletError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_fees_validate() {
+        let fees = |withdrawal_fee_numerator, withdrawal_fee_denominator| Fees {
+            performance_fee_numerator: 0,
+            performance_fee_denominator: 0,
+            withdrawal_fee_numerator,
+            withdrawal_fee_denominator,
+            deposit_fee_numerator: 0,
+            deposit_fee_denominator: 0,
+            referral_fee_numerator: 0,
+            referral_fee_denominator: 0,
+            min_fee_enabled: false,
+        };
+        // 0/0 은 fee 없음
+        assert!(fees(0, 0).validate().is_ok());
+        assert!(fees(1, 10).validate().is_ok());
+        // 0 으로 나누는 fee 와 100% 이상의 fee 는 거부
+        assert_matches!(fees(1, 0).validate(), Err(GauntletError::InvalidFee));
+        assert_matches!(fees(10, 10).validate(), Err(GauntletError::InvalidFee));
+    }
+
+    #[test]
+    fn test_vault_config_validate() {
+        let config = |swap_slippage_bps| VaultConfig {
+            min_deposit: 0,
+            min_harvest_interval: 0,
+            swap_slippage_bps,
+            max_swap_usdc_per_tx: 0,
+            compounding: false,
+            min_swap_output: 0,
+            max_pending_reward: u64::MAX,
+        };
+        assert!(config(0).validate().is_ok());
+        assert!(config(MAX_SLIPPAGE_BPS).validate().is_ok());
+        assert_matches!(
+            config(MAX_SLIPPAGE_BPS + 1).validate(),
+            Err(GauntletError::InvalidSlippage)
+        );
+    }
+
+    #[test]
+    fn test_vault_ref_rejects_short_data() {
+        let mut data = vec![0; Vault::LEN - 1];
+        assert_eq!(
+            VaultRef::new(&mut data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_track_mint() {
+        let mut gauntlet = Gauntlet::init(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        let mint = Pubkey::new_unique();
+        gauntlet.track_mint(mint).unwrap();
+        // 같은 mint 나 비어있는 reward b mint 는 자리를 차지하지 않음
+        gauntlet.track_mint(mint).unwrap();
+        gauntlet.track_mint(Pubkey::default()).unwrap();
+        assert_eq!(gauntlet.tracked_mints_len, 1);
+        assert!(gauntlet.is_tracked_mint(&mint));
+        assert!(!gauntlet.is_tracked_mint(&Pubkey::default()));
+
+        for _ in 1..MAX_TRACKED_MINTS {
+            gauntlet.track_mint(Pubkey::new_unique()).unwrap();
+        }
+        assert_matches!(
+            gauntlet.track_mint(Pubkey::new_unique()),
+            Err(GauntletError::TrackedMintListFull)
+        );
+        let mut data = vec![0; Gauntlet::LEN];
+        gauntlet.pack_into_slice(&mut data);
+        let unpacked = Gauntlet::unpack(&data).unwrap();
+        assert_eq!(unpacked.tracked_mints, gauntlet.tracked_mints);
+        assert_eq!(unpacked.tracked_mints_len, MAX_TRACKED_MINTS as u8);
+    }
+
+    fn try_vault_from_account(
+        data: &mut [u8],
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vault, ProgramError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        Vault::try_from_account(&account, program_id)
+    }
+
+    #[test]
+    fn test_try_from_account() {
+        let program_id = Pubkey::new_unique();
+        let vault = sample_vault();
+        let mut data = packed(&vault);
+        let unpacked = try_vault_from_account(&mut data, &program_id, &program_id).unwrap();
+        assert_eq!(
+            unpacked.gauntlet_state_account,
+            vault.gauntlet_state_account
+        );
+
+        // 같은 layout 이라도 다른 program 소유면 거부
+        assert_eq!(
+            try_vault_from_account(&mut data, &Pubkey::new_unique(), &program_id).err(),
+            Some(ProgramError::IncorrectProgramId)
+        );
+        let mut uninitialized = vec![0; Vault::LEN];
+        assert_eq!(
+            try_vault_from_account(&mut uninitialized, &program_id, &program_id).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn test_compounded_gain_uses_ratio_at_restake() {
+        // (restake 때의 ratio, 정산 전에 바뀐 ratio, 기대 재예치량), 나누어 떨어지는 양으로 rounding 을 피함
+        for &(ratio_bps, later_ratio_bps, restaked) in &[
+            (0u16, 10_000u16, 0u64),
+            (5_000, 0, 128),
+            (10_000, 5_000, 256),
+        ] {
+            let mut vault = Vault::unpack_unchecked(&vec![0; Vault::LEN]).unwrap();
+            vault.deposit_amounts[0] = 1_024;
+            let mut user = User::init(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            user.amount = 1_024;
+            user.reset_debts(&vault, 0).unwrap();
+
+            vault.compound_ratio_bps = ratio_bps;
+            let restake_amount = vault.compound_amount(256).unwrap();
+            assert_eq!(restake_amount, restaked);
+            vault
+                .add_strategy_gain(0, 256 - restake_amount, restake_amount)
+                .unwrap();
+            vault.deposit_amounts[0] += restake_amount;
+
+            vault.compound_ratio_bps = later_ratio_bps;
+            user.settle(&vault, 0).unwrap();
+            assert_eq!(user.amount, 1_024 + restaked);
+            assert_eq!(user.reward, 256 - restaked);
+
+            // 정산 후에는 다시 정산해도 늘지 않음
+            user.reset_debts(&vault, 0).unwrap();
+            user.settle(&vault, 0).unwrap();
+            assert_eq!(user.amount, 1_024 + restaked);
+            assert_eq!(user.reward, 256 - restaked);
+        }
+    }
+
+    #[test]
+    fn test_pending_is_zero_after_partial_withdraw() {
+        let pending_of = |user: &User, vault: &Vault| {
+            pending(
+                user.amount,
+                vault.accumulated_reward_per_shares[0],
+                user.reward_debt,
+            )
+            .unwrap()
+        };
+        let mut vault = Vault::unpack_unchecked(&vec![0; Vault::LEN]).unwrap();
+        let mut users = [1_024u64, 3_072].map(|amount| {
+            let mut user = User::init(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            // deposit
+            user.settle(&vault, 0).unwrap();
+            user.amount = amount;
+            user.reset_debts(&vault, 0).unwrap();
+            vault.deposit_amounts[0] += amount;
+            user
+        });
+        assert!(users.iter().all(|user| pending_of(user, &vault) == 0));
+
+        // harvest 후 swap 한 strategy token 이 acc 로 들어감
+        vault.add_strategy_gain(0, 512, 0).unwrap();
+        assert_eq!(pending_of(&users[0], &vault), 128);
+        assert_eq!(pending_of(&users[1], &vault), 384);
+
+        // 부분 인출, pending 은 모두 reward 로 옮겨지고 남은 amount 로 debt 를 다시 잡음
+        users[0].settle(&vault, 0).unwrap();
+        users[0].amount -= 256;
+        users[0].reset_debts(&vault, 0).unwrap();
+        vault.deposit_amounts[0] -= 256;
+        assert_eq!(pending_of(&users[0], &vault), 0);
+        assert_eq!(users[0].reward, 128);
+        assert_eq!(pending_of(&users[1], &vault), 384);
+
+        // 다음 harvest 는 줄어든 amount 기준으로만 쌓임
+        vault.add_strategy_gain(0, 3_840, 0).unwrap();
+        assert_eq!(pending_of(&users[0], &vault), 768);
+        assert_eq!(pending_of(&users[1], &vault), 384 + 3_072);
+    }
+
+    #[test]
+    fn test_reward_math_overflow() {
+        let one = 1u128 << REWARD_PRECISION_SHIFT;
+        assert_eq!(acc_add(0, 512, 1_024).unwrap(), one / 2);
+        assert_eq!(reward_debt(1_024, one / 2).unwrap(), 512);
+        assert_eq!(pending(1_024, one / 2, 500).unwrap(), 12);
+        // deposit 이 없는 strategy 에 gain 을 나누거나 acc 가 넘치면 panic 대신 error
+        assert_matches!(acc_add(0, 512, 0), Err(GauntletError::MathOverflow));
+        assert_matches!(acc_add(u128::MAX, 1, 1), Err(GauntletError::MathOverflow));
+        // u64 를 넘는 debt, 이미 받은 것보다 큰 debt
+        assert_matches!(
+            reward_debt(u64::MAX, one * 2),
+            Err(GauntletError::MathOverflow)
+        );
+        assert_matches!(
+            pending(1_024, one / 2, 513),
+            Err(GauntletError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_vault_strategy_capacity() {
+        // capacity 가 생기기 전 account 크기는 MAX_NUMBER_OF_STRATEGY 칸
+        assert_eq!(
+            VaultStrategy::capacity_of(VaultStrategy::LEN),
+            Ok(MAX_NUMBER_OF_STRATEGY)
+        );
+        assert_eq!(
+            VaultStrategy::capacity_of(VaultStrategy::packed_len(4) + 1),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut vault_strategy = VaultStrategy::init(Pubkey::new_unique(), 4);
+        vault_strategy.availabilities[3] = true;
+        vault_strategy.strategy_token_amounts[3] = 42;
+        let mut small = vec![0; VaultStrategy::packed_len(4)];
+        VaultStrategy::pack(vault_strategy, &mut small).unwrap();
+
+        let mut vault_strategy = VaultStrategy::unpack(&small).unwrap();
+        assert_eq!(vault_strategy.capacity, 4);
+        assert!(vault_strategy.availabilities[3]);
+        assert_eq!(vault_strategy.strategy_token_amounts[3], 42);
+
+        // capacity 밖의 칸은 저장할 수 없음
+        vault_strategy.availabilities[4] = true;
+        assert_eq!(
+            VaultStrategy::pack(vault_strategy, &mut small),
+            Err(GauntletError::VaultStrategyCapacityExceeded.into())
+        );
+
+        let mut vault_strategy = VaultStrategy::unpack(&small).unwrap();
+        vault_strategy.capacity = 16;
+        let mut grown = vec![0; VaultStrategy::packed_len(16)];
+        VaultStrategy::pack(vault_strategy, &mut grown).unwrap();
+        let vault_strategy = VaultStrategy::unpack(&grown).unwrap();
+        assert_eq!(vault_strategy.capacity, 16);
+        assert!(vault_strategy.availabilities[3]);
+        assert_eq!(vault_strategy.strategy_token_amounts[3], 42);
+        assert!(!vault_strategy.availabilities[4]);
+        assert!(vault_strategy.accepting_deposits[15]);
+    }
+
+    #[test]
+    fn test_migrate_vault_from_v1() {
+        assert_eq!(VAULT_V1_LEN, 2643);
+        let (gauntlet, deposit_token, reward_token, reward_token_b, withdraw_fee, raydium) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        // v1 layout 을 offset 으로 직접 씀
+        let mut v1 = vec![0u8; VAULT_V1_LEN];
+        v1[0] = 1;
+        v1[1] = 3;
+        v1[2] = 1;
+        for (i, fee) in [1u64, 100, 2, 1_000].iter().enumerate() {
+            v1[3 + i * 8..11 + i * 8].copy_from_slice(&fee.to_le_bytes());
+        }
+        for (i, key) in [
+            gauntlet,
+            deposit_token,
+            reward_token,
+            reward_token_b,
+            withdraw_fee,
+        ]
+        .iter()
+        .enumerate()
+        {
+            v1[35 + i * 32..67 + i * 32].copy_from_slice(key.as_ref());
+        }
+        v1[195..203].copy_from_slice(&500u64.to_le_bytes());
+        let amounts = 203;
+        let strategies = MAX_NUMBER_OF_STRATEGY;
+        v1[amounts + 8..amounts + 16].copy_from_slice(&500u64.to_le_bytes());
+        v1[amounts + strategies * 8 + 8..amounts + strategies * 8 + 16]
+            .copy_from_slice(&7u64.to_le_bytes());
+        v1[amounts + strategies * 24 + 8..amounts + strategies * 24 + 16]
+            .copy_from_slice(&9u64.to_le_bytes());
+        let acc = amounts + strategies * 32;
+        v1[acc + 16..acc + 32].copy_from_slice(&(5u128 << 64).to_le_bytes());
+        v1[2603..2611].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        v1[2611..2643].copy_from_slice(raydium.as_ref());
+
+        let vault = Vault::migrate_from_v1(&v1).unwrap();
+        assert!(vault.is_initialized);
+        assert_eq!(vault.index, 3);
+        assert!(vault.status == Status::NORMAL);
+        assert_eq!(vault.fees.performance_fee_numerator, 1);
+        assert_eq!(vault.fees.performance_fee_denominator, 100);
+        assert_eq!(vault.fees.withdrawal_fee_numerator, 2);
+        assert_eq!(vault.fees.withdrawal_fee_denominator, 1_000);
+        assert_eq!(vault.fees.deposit_fee_denominator, 0);
+        assert!(vault.fees.min_fee_enabled);
+        assert_eq!(vault.max_pending_reward, u64::MAX);
+        assert_eq!(vault.gauntlet_state_account, gauntlet);
+        assert_eq!(vault.deposit_token_account, deposit_token);
+        assert_eq!(vault.reward_token_account, reward_token);
+        assert_eq!(vault.reward_token_b_account, reward_token_b);
+        assert_eq!(vault.withdraw_fee_account, withdraw_fee);
+        assert_eq!(vault.deposit_fee_account, withdraw_fee);
+        assert_eq!(vault.total_deposit_amount, 500);
+        assert_eq!(vault.deposit_amounts[1], 500);
+        assert_eq!(vault.reward_token_remain_amounts[1], 7);
+        assert_eq!(vault.usdc_token_amounts[1], 9);
+        assert_eq!(vault.accumulated_reward_per_shares[1], 5 << 64);
+        assert_eq!(vault.last_reward_update_time, 1_700_000_000);
+        assert_eq!(vault.raydium_state_account, raydium);
+        assert!(vault.has_second_reward);
+        assert!(vault.deposits_enabled && vault.withdrawals_enabled);
+        assert_eq!(vault.compound_ratio_bps as u64, BPS_DENOMINATOR);
+        assert_eq!(vault.layout_version, VAULT_LAYOUT_VERSION);
+
+        // 새 layout 으로 round-trip
+        let mut data = vec![0; Vault::LEN];
+        Vault::pack(vault, &mut data).unwrap();
+        let vault = Vault::unpack(&data).unwrap();
+        assert!(vault.fees.min_fee_enabled);
+        assert_eq!(vault.deposit_amounts[1], 500);
+        assert_eq!(vault.accumulated_reward_per_shares[1], 5 << 64);
+        assert_eq!(vault.raydium_state_account, raydium);
+        assert_eq!(vault.layout_version, VAULT_LAYOUT_VERSION);
+
+        // 현재 layout 이나 닫힌 account 는 v1 으로 읽지 않음
+        assert_eq!(
+            Vault::migrate_from_v1(&data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            Vault::migrate_from_v1(&vec![0; VAULT_V1_LEN]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn test_migrate_accounts_from_v1() {
+        assert_eq!(GAUNTLET_V1_LEN, 81);
+        assert_eq!(USER_V1_LEN, 130);
+        assert_eq!(STRATEGY_V1_LEN, 547);
+        assert_eq!(VAULT_STRATEGY_V1_LEN, 533);
+        let (admin, usdc_token, user, vault, strategy) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        let mut v1 = vec![0u8; GAUNTLET_V1_LEN];
+        v1[0] = 1;
+        v1[1..33].copy_from_slice(admin.as_ref());
+        v1[33] = 2;
+        v1[41] = 3;
+        v1[49..81].copy_from_slice(usdc_token.as_ref());
+        let gauntlet = Gauntlet::migrate_from_v1(&v1, 254).unwrap();
+        assert!(gauntlet.is_initialized);
+        assert_eq!(gauntlet.admin, admin);
+        assert_eq!(gauntlet.strategies_len, 2);
+        assert_eq!(gauntlet.vaults_len, 3);
+        assert_eq!(gauntlet.usdc_token_account, usdc_token);
+        assert_eq!(gauntlet.authority_bump, 254);
+        assert_eq!(gauntlet.admin_nonce, 0);
+
+        let mut v1 = vec![0u8; USER_V1_LEN];
+        v1[0] = 1;
+        v1[1..33].copy_from_slice(user.as_ref());
+        v1[33..65].copy_from_slice(vault.as_ref());
+        v1[65..97].copy_from_slice(strategy.as_ref());
+        v1[97..105].copy_from_slice(&500u64.to_le_bytes());
+        v1[105..113].copy_from_slice(&7u64.to_le_bytes());
+        v1[113..121].copy_from_slice(&9u64.to_le_bytes());
+        v1[121] = USER_STATUS_READY;
+        v1[122..130].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        let user_info = User::migrate_from_v1(&v1).unwrap();
+        assert_eq!(user_info.user, user);
+        assert_eq!(user_info.vault_account, vault);
+        assert_eq!(user_info.strategy_account, strategy);
+        assert_eq!(user_info.amount, 500);
+        assert_eq!(user_info.reward, 7);
+        assert_eq!(user_info.reward_debt, 9);
+        assert_eq!(user_info.user_status, USER_STATUS_READY);
+        assert_eq!(user_info.deadline, 1_700_000_000);
+        assert_eq!(user_info.compound_debt, 0);
+
+        let mut v1 = vec![0u8; STRATEGY_V1_LEN];
+        v1[0] = 1;
+        v1[1] = 4;
+        v1[2..34].copy_from_slice(admin.as_ref());
+        v1[98] = 1;
+        v1[107..115].copy_from_slice(&500u64.to_le_bytes());
+        v1[115 + 8..115 + 16].copy_from_slice(&500u64.to_le_bytes());
+        v1[515..547].copy_from_slice(usdc_token.as_ref());
+        let strategy_info = Strategy::migrate_from_v1(&v1).unwrap();
+        assert_eq!(strategy_info.index, 4);
+        assert_eq!(strategy_info.gauntlet_state_account, admin);
+        assert!(strategy_info.status == Status::NORMAL);
+        assert_eq!(strategy_info.total_deposit_amount, 500);
+        assert_eq!(strategy_info.deposit_amounts[1], 500);
+        assert_eq!(strategy_info.routed_deposit_amount, 500);
+        assert_eq!(strategy_info.strategy_token_account, usdc_token);
+        assert_eq!(strategy_info.migrated_from, Pubkey::default());
+
+        let mut v1 = vec![0u8; VAULT_STRATEGY_V1_LEN];
+        v1[0] = 1;
+        v1[1..33].copy_from_slice(vault.as_ref());
+        v1[33 + 2] = 1;
+        v1[83 + 2] = 1;
+        v1[133 + 16..133 + 24].copy_from_slice(&42u64.to_le_bytes());
+        let vault_strategy = VaultStrategy::migrate_from_v1(&v1, 4).unwrap();
+        assert_eq!(vault_strategy.vault_account, vault);
+        assert_eq!(vault_strategy.capacity, 4);
+        assert!(vault_strategy.needs_usdc_pools[2]);
+        assert!(vault_strategy.availabilities[2]);
+        assert!(vault_strategy.accepting_deposits[2]);
+        assert_eq!(vault_strategy.strategy_token_amounts[2], 42);
+        // 새 account 가 작아서 v1 의 칸을 다 담지 못하면 pack 에서 실패
+        let vault_strategy = VaultStrategy::migrate_from_v1(&v1, 2).unwrap();
+        assert_eq!(
+            VaultStrategy::pack(vault_strategy, &mut vec![0; VaultStrategy::packed_len(2)]),
+            Err(GauntletError::VaultStrategyCapacityExceeded.into())
+        );
+
+        // 현재 layout 이나 닫힌 account 는 v1 으로 읽지 않음
+        assert_eq!(
+            User::migrate_from_v1(&[0; User::LEN]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            Strategy::migrate_from_v1(&vec![0; STRATEGY_V1_LEN]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        assert_eq!(
+            VaultStrategy::migrate_from_v1(&vec![0; VaultStrategy::LEN], 4).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            Gauntlet::migrate_from_v1(&vec![0; Gauntlet::LEN], 254).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_user_lock_rejects_reentry_until_released() {
+        let mut data = [0; User::LEN];
+        User::init(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        )
+        .pack_into_slice(&mut data);
+        User::acquire_lock(&mut data).unwrap();
+        // lock 은 unpack 한 user 에도 보임
+        assert!(User::unpack(&data).unwrap().locked);
+        assert!(matches!(
+            User::acquire_lock(&mut data),
+            Err(GauntletError::UserBusy)
+        ));
+        User::release_lock(&mut data);
+        assert!(!User::unpack(&data).unwrap().locked);
+        assert!(User::acquire_lock(&mut data).is_ok());
+        // 아직 만들지 않은 user account 는 건너뜀
+        assert!(User::acquire_lock(&mut []).is_ok());
+    }
+}
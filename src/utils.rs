@@ -1,23 +1,64 @@
 use solana_program::{
     account_info::AccountInfo,
-    bpf_loader_upgradeable::{UpgradeableLoaderState},
+    bpf_loader_upgradeable::UpgradeableLoaderState,
     entrypoint::ProgramResult,
     instruction::{AccountMeta, Instruction},
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_option::COption,
+    program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
-    system_instruction,
+    system_instruction, system_program,
+    sysvar::Sysvar,
 };
 
 use crate::error::GauntletError;
-use spl_token::instruction::AuthorityType::AccountOwner;
+use spl_token::{
+    instruction::AuthorityType::AccountOwner,
+    state::{Account, Mint},
+};
 use std::result::Result;
-use std::str::FromStr;
+
+/// gauntlet 마다 authority pda 를 분리하기 위한 seed prefix, seeds = [AUTHORITY_SEED, gauntlet, bump]
+pub const AUTHORITY_SEED: &[u8] = b"glt";
+
+pub fn find_authority_address(
+    gauntlet_state_account: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[AUTHORITY_SEED, gauntlet_state_account.as_ref()],
+        program_id,
+    )
+}
+
+/// v1 에서 모든 gauntlet 이 같이 쓰던 authority pda, seeds = [AUTHORITY_SEED, bump]
+/// v1 token account 를 migrate 할 때 owner 를 넘겨주는 데만 씀
+pub fn find_legacy_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED], program_id)
+}
+
+/// user state pda, seeds = [vault, user, strategy, bump]
+pub fn find_user_address(
+    vault_state_account: &Pubkey,
+    user: &Pubkey,
+    strategy_state_account: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &vault_state_account.to_bytes(),
+            &user.to_bytes(),
+            &strategy_state_account.to_bytes(),
+        ],
+        program_id,
+    )
+}
 
 // token account의 owner를 변경하는 instruction을 생성 및 invoke
 pub fn change_token_account_owner<'a>(
+    token_program_id: &Pubkey,
     token_account: &AccountInfo<'a>,
     current_owner: &AccountInfo<'a>,
     new_owner: &Pubkey,
@@ -35,7 +76,7 @@ pub fn change_token_account_owner<'a>(
     ];
 
     let ix = &Instruction {
-        program_id: spl_token::id(),
+        program_id: *token_program_id,
         accounts,
         data,
     };
@@ -44,6 +85,32 @@ pub fn change_token_account_owner<'a>(
     Ok(())
 }
 
+// pda 가 소유한 token account 의 owner 를 new_owner 로 변경
+pub fn change_token_account_owner_signed<'a>(
+    token_account: &AccountInfo<'a>,
+    current_owner: &AccountInfo<'a>,
+    new_owner: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let ix = spl_token::instruction::set_authority(
+        &spl_token::id(),
+        token_account.key,
+        Some(new_owner),
+        AccountOwner,
+        current_owner.key,
+        &[],
+    )?;
+    invoke_signed(
+        &ix,
+        &[token_account.clone(), current_owner.clone()],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+// spl-token-2022 crate 는 solana-program 1.10 이상이 필요해서 1.7.14 에 고정된 이 program 에서는 쓸 수 없음.
+// Token-2022 의 base account / mint layout 과 Transfer, TransferChecked, SetAuthority 는 legacy 와 같으므로
+// legacy instruction 을 token program id 만 바꿔서 보냄. 우리가 cpi 하는 raydium staking / amm 은 legacy 만 받음
 pub fn transfer_token<'a>(
     token_program_id: &Pubkey,
     from: &AccountInfo<'a>,
@@ -75,6 +142,7 @@ pub fn transfer_token_signed<'a>(
     to: &AccountInfo<'a>,
     owner: &AccountInfo<'a>,
     amount: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> ProgramResult {
     let data = spl_token::instruction::TokenInstruction::Transfer { amount }.pack();
 
@@ -90,14 +158,117 @@ pub fn transfer_token_signed<'a>(
         data,
     };
 
+    invoke_signed(ix, &[from.clone(), to.clone(), owner.clone()], signer_seeds)?;
+    Ok(())
+}
+
+// mint 의 decimals 를 같이 넘기는 TransferChecked, transfer fee / hook extension 이 있는 Token-2022 mint 는
+// Transfer 를 거부하므로 vault / strategy token 은 이걸로 옮김. fee 가 빠질 수 있으니 받는 쪽 수량은 잔액 차이로 셈
+pub fn transfer_token_checked<'a>(
+    token_program_id: &Pubkey,
+    from: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let decimals = unpack_mint(&mint.data.borrow())?.decimals;
+    let data =
+        spl_token::instruction::TokenInstruction::TransferChecked { amount, decimals }.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*from.key, false),
+        AccountMeta::new_readonly(*mint.key, false),
+        AccountMeta::new(*to.key, false),
+        AccountMeta::new_readonly(*owner.key, true),
+    ];
+
+    let ix = &Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    };
+
     invoke_signed(
         ix,
-        &[from.clone(), to.clone(), owner.clone()],
-        &[&[&b"glt"[..], &[255]]],
+        &[from.clone(), mint.clone(), to.clone(), owner.clone()],
+        signer_seeds,
     )?;
     Ok(())
 }
 
+/// Token-2022 account type byte, extension 이 있으면 base account (Account::LEN) 바로 뒤에 옴
+const TOKEN_2022_ACCOUNT_TYPE_MINT: u8 = 1;
+const TOKEN_2022_ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// legacy / Token-2022 token account, extension 은 무시하고 base account 만 읽음
+pub fn unpack_token_account(data: &[u8]) -> Result<Account, ProgramError> {
+    if data.len() > Account::LEN && data[Account::LEN] == TOKEN_2022_ACCOUNT_TYPE_ACCOUNT {
+        return Account::unpack(&data[..Account::LEN]);
+    }
+    Account::unpack(data)
+}
+
+/// legacy / Token-2022 mint, Token-2022 mint 의 extension 은 Account::LEN 까지 padding 한 뒤에 붙음
+pub fn unpack_mint(data: &[u8]) -> Result<Mint, ProgramError> {
+    if data.len() > Account::LEN && data[Account::LEN] == TOKEN_2022_ACCOUNT_TYPE_MINT {
+        return Mint::unpack(&data[..Mint::LEN]);
+    }
+    Mint::unpack(data)
+}
+
+// owner 가 signer 인 token account 를 닫고 lamports 를 destination 으로 보냄 (wSOL 이면 잔액 포함)
+pub fn close_token_account<'a>(
+    token_program_id: &Pubkey,
+    account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+) -> ProgramResult {
+    let data = spl_token::instruction::TokenInstruction::CloseAccount.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*account.key, false),
+        AccountMeta::new(*destination.key, false),
+        AccountMeta::new_readonly(*owner.key, true),
+    ];
+
+    let ix = &Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    };
+
+    invoke(ix, &[account.clone(), destination.clone(), owner.clone()])?;
+    Ok(())
+}
+
+// 이 program 소유 state account 를 비우고 lamports 를 destination 으로 보냄, 같은 tx 안에서도 다시 읽을 수 없음
+pub fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    account.data.borrow_mut().fill(0);
+    let lamports = account.lamports();
+    **account.lamports.borrow_mut() = 0;
+    **destination.lamports.borrow_mut() = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(GauntletError::MathOverflow)?;
+    Ok(())
+}
+
+/// u8 index 는 255 까지 가능하지만 per-strategy/vault 배열은 len 까지만 있음, panic 대신 에러
+pub fn checked_index(index: u8, len: usize) -> Result<usize, ProgramError> {
+    let index = index as usize;
+    if index >= len {
+        return Err(GauntletError::StrategyIdSizeError.into());
+    }
+    Ok(index)
+}
+
+// pda account 생성시 필요한 rent-exempt lamports (fresh / pre-funded 두 경로 공통)
+pub fn pda_rent_lamports(rent: &Rent, space: usize) -> u64 {
+    rent.minimum_balance(space).max(1)
+}
+
 pub fn create_pda_account<'a>(
     payer: &AccountInfo<'a>,
     space: usize,
@@ -106,12 +277,11 @@ pub fn create_pda_account<'a>(
     new_pda_account: &AccountInfo<'a>,
     new_pda_signer_seeds: &[&[u8]],
 ) -> ProgramResult {
-    let rent = Rent::default();
+    // Rent::default() 는 cluster 의 실제 rent 와 다를 수 있음
+    let rent = Rent::get()?;
     if new_pda_account.lamports() > 0 {
-        let required_lamports = rent
-            .minimum_balance(space)
-            .max(1)
-            .saturating_sub(new_pda_account.lamports());
+        let required_lamports =
+            pda_rent_lamports(&rent, space).saturating_sub(new_pda_account.lamports());
 
         if required_lamports > 0 {
             invoke(
@@ -134,13 +304,13 @@ pub fn create_pda_account<'a>(
             &system_instruction::assign(new_pda_account.key, owner),
             &[new_pda_account.clone(), system_program.clone()],
             &[new_pda_signer_seeds],
-        )
+        )?;
     } else {
         invoke_signed(
             &system_instruction::create_account(
                 payer.key,
                 new_pda_account.key,
-                rent.minimum_balance(space).max(1),
+                pda_rent_lamports(&rent, space),
                 space as u64,
                 owner,
             ),
@@ -150,8 +320,18 @@ pub fn create_pda_account<'a>(
                 system_program.clone(),
             ],
             &[new_pda_signer_seeds],
-        )
+        )?;
     }
+
+    check_rent_exempt(&rent, new_pda_account.lamports(), space)
+}
+
+// 생성 후 실제 잔액으로 다시 확인, pre-funded 경로에서 계산이 어긋나도 잡힘
+pub fn check_rent_exempt(rent: &Rent, lamports: u64, space: usize) -> ProgramResult {
+    if !rent.is_exempt(lamports, space) {
+        return Err(GauntletError::NotRentExempt.into());
+    }
+    Ok(())
 }
 
 pub fn get_program_upgrade_authority(
@@ -168,29 +348,238 @@ pub fn get_program_upgrade_authority(
     Ok(upgrade_authority)
 }
 
-pub const STAKING_PROGRAM_ID: [&str; 3] = [
-    "EhhTKczWMGQt46ynNeRX1WfeagwwJd7ufHvCDjRxjo5Q",
-    "CBuCnLe26faBpcBP2fktp4rp8abpcAnTWft6ZrP5Q4T",
-    "9KEPoZmtHUrBbhWN1v1KWLMkkvwY6WLtAVUCPRtRjP4z",
+// base58 문자열을 매번 parse 하지 않도록 byte array 로 미리 계산
+pub const STAKING_PROGRAM_ID: [Pubkey; 3] = [
+    // EhhTKczWMGQt46ynNeRX1WfeagwwJd7ufHvCDjRxjo5Q
+    Pubkey::new_from_array([
+        203, 148, 231, 87, 73, 16, 164, 107, 38, 209, 158, 32, 199, 23, 128, 188, 97, 204, 33, 114,
+        251, 42, 172, 229, 110, 76, 254, 1, 216, 214, 190, 247,
+    ]),
+    // CBuCnLe26faBpcBP2fktp4rp8abpcAnTWft6ZrP5Q4T
+    Pubkey::new_from_array([
+        2, 221, 182, 176, 119, 214, 236, 246, 174, 217, 69, 35, 153, 26, 133, 219, 39, 167, 247,
+        117, 74, 176, 18, 12, 137, 216, 10, 212, 216, 181, 218, 36,
+    ]),
+    // 9KEPoZmtHUrBbhWN1v1KWLMkkvwY6WLtAVUCPRtRjP4z
+    Pubkey::new_from_array([
+        123, 137, 23, 250, 26, 10, 89, 193, 22, 51, 132, 229, 123, 202, 83, 82, 69, 21, 191, 128,
+        113, 75, 167, 227, 123, 241, 51, 40, 17, 56, 66, 239,
+    ]),
 ];
 pub fn check_staking_program_id(program_id: &AccountInfo) -> ProgramResult {
-    for i in 0..3 {
-        if Pubkey::from_str(STAKING_PROGRAM_ID[i]).unwrap() == *program_id.key {
-            return Ok(());
-        }
+    if STAKING_PROGRAM_ID.contains(program_id.key) {
+        return Ok(());
     }
     Err(GauntletError::InvalidProgramId.into())
 }
-pub const POOL_PROGRAM_ID: [&str; 3] = [
-    "RVKd61ztZW9GUwhRbbLoYVRE5Xf1B2tVscKqwZqXgEr",
-    "27haf8L6oxUeXrHrgEgsexjSY5hbVUWEmvv9Nyxg8vQv",
-    "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+pub const POOL_PROGRAM_ID: [Pubkey; 3] = [
+    // RVKd61ztZW9GUwhRbbLoYVRE5Xf1B2tVscKqwZqXgEr
+    Pubkey::new_from_array([
+        6, 69, 246, 79, 220, 187, 0, 119, 77, 26, 248, 137, 2, 164, 13, 182, 80, 212, 12, 41, 218,
+        185, 4, 86, 201, 187, 11, 142, 46, 167, 60, 207,
+    ]),
+    // 27haf8L6oxUeXrHrgEgsexjSY5hbVUWEmvv9Nyxg8vQv
+    Pubkey::new_from_array([
+        16, 147, 14, 90, 177, 4, 111, 9, 208, 119, 49, 181, 250, 32, 64, 112, 202, 211, 171, 164,
+        205, 246, 151, 136, 208, 202, 2, 134, 245, 50, 160, 71,
+    ]),
+    // 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8
+    Pubkey::new_from_array([
+        75, 217, 73, 196, 54, 2, 195, 63, 32, 119, 144, 237, 22, 163, 82, 76, 161, 185, 151, 92,
+        241, 33, 162, 169, 12, 255, 236, 125, 248, 182, 138, 205,
+    ]),
 ];
+/// CPI 는 고정 id 로 만들지만 invoke 에 넘기는 program account 는 호출자가 준 것이므로 확인
+pub fn check_system_program_id(program_id: &AccountInfo) -> ProgramResult {
+    if *program_id.key == system_program::id() {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
+/// gauntlet state account 는 unpack 전에 이 program 소유인지 확인
+pub fn check_account_owner(account: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    if account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+pub fn check_token_program_id(program_id: &AccountInfo) -> ProgramResult {
+    if *program_id.key == spl_token::id() {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
+/// TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252, 77,
+    131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252,
+]);
+/// raydium / orca 로 넘기는 token program 은 check_token_program_id 로 legacy 만 받고,
+/// pda 가 들고 있는 token 을 옮기기만 하는 곳에서는 Token-2022 도 받음
+pub fn check_any_token_program_id(program_id: &AccountInfo) -> ProgramResult {
+    if *program_id.key == spl_token::id() || *program_id.key == TOKEN_2022_PROGRAM_ID {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
 pub fn check_pool_program_id(program_id: &AccountInfo) -> ProgramResult {
-    for i in 0..3 {
-        if Pubkey::from_str(POOL_PROGRAM_ID[i]).unwrap() == *program_id.key {
-            return Ok(());
-        }
+    if POOL_PROGRAM_ID.contains(program_id.key) {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
+pub const SERUM_PROGRAM_ID: [Pubkey; 1] = [
+    // 9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin
+    Pubkey::new_from_array([
+        133, 15, 45, 110, 2, 164, 122, 248, 36, 208, 154, 182, 157, 196, 45, 112, 203, 40, 203,
+        250, 36, 159, 183, 238, 87, 185, 210, 86, 193, 39, 98, 239,
+    ]),
+];
+pub const OPENBOOK_PROGRAM_ID: [Pubkey; 1] = [
+    // srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX
+    Pubkey::new_from_array([
+        13, 7, 81, 168, 40, 45, 166, 19, 5, 254, 41, 156, 55, 185, 152, 229, 132, 113, 219, 17, 53,
+        3, 115, 16, 248, 190, 16, 69, 166, 10, 246, 238,
+    ]),
+];
+/// serum 이 닫힌 뒤 market 은 openbook 으로 옮겨졌으므로 둘 다 허용
+pub fn check_serum_program_id(program_id: &AccountInfo) -> ProgramResult {
+    if SERUM_PROGRAM_ID.contains(program_id.key) || OPENBOOK_PROGRAM_ID.contains(program_id.key) {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
+pub const JUPITER_PROGRAM_ID: [Pubkey; 1] = [
+    // JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4
+    Pubkey::new_from_array([
+        4, 121, 213, 91, 242, 49, 192, 110, 238, 116, 197, 110, 206, 104, 21, 7, 253, 177, 178,
+        222, 163, 244, 142, 81, 2, 177, 205, 162, 86, 188, 19, 143,
+    ]),
+];
+pub fn check_jupiter_program_id(program_id: &AccountInfo) -> ProgramResult {
+    if JUPITER_PROGRAM_ID.contains(program_id.key) {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
+pub const PYTH_PROGRAM_ID: [Pubkey; 1] = [
+    // FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH
+    Pubkey::new_from_array([
+        220, 229, 235, 225, 228, 156, 59, 159, 17, 76, 181, 84, 76, 80, 169, 158, 192, 214, 146,
+        214, 63, 86, 121, 90, 224, 41, 172, 131, 217, 234, 139, 226,
+    ]),
+];
+/// oracle account 는 owner 로 확인
+pub fn check_pyth_program_id(owner: &Pubkey) -> ProgramResult {
+    if PYTH_PROGRAM_ID.contains(owner) {
+        return Ok(());
+    }
+    Err(GauntletError::InvalidProgramId.into())
+}
+/// Ed25519SigVerify111111111111111111111111111, native precompile that verifies ed25519 signatures
+/// before the transaction runs. solana-program 1.7 has no ed25519_program module
+pub const ED25519_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    3, 125, 70, 214, 124, 147, 251, 190, 18, 249, 66, 143, 131, 141, 64, 255, 5, 112, 116, 73, 39,
+    244, 138, 100, 252, 202, 112, 68, 128, 0, 0, 0,
+]);
+pub const WHIRLPOOL_PROGRAM_ID: [Pubkey; 1] = [
+    // whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc
+    Pubkey::new_from_array([
+        14, 3, 104, 95, 142, 144, 144, 83, 228, 88, 18, 28, 102, 245, 167, 106, 237, 199, 112, 106,
+        161, 28, 130, 248, 170, 149, 42, 143, 43, 120, 121, 169,
+    ]),
+];
+pub fn check_whirlpool_program_id(program_id: &AccountInfo) -> ProgramResult {
+    if WHIRLPOOL_PROGRAM_ID.contains(program_id.key) {
+        return Ok(());
     }
     Err(GauntletError::InvalidProgramId.into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_checked_index() {
+        assert_eq!(checked_index(0, 50), Ok(0));
+        assert_eq!(checked_index(49, 50), Ok(49));
+        // u8 로는 표현되지만 배열 밖인 index 는 panic 대신 에러
+        assert_eq!(
+            checked_index(50, 50),
+            Err(GauntletError::StrategyIdSizeError.into())
+        );
+        assert_eq!(
+            checked_index(u8::MAX, 50),
+            Err(GauntletError::StrategyIdSizeError.into())
+        );
+    }
+
+    #[test]
+    fn test_pda_rent_lamports() {
+        let rent = Rent::default();
+        assert_eq!(pda_rent_lamports(&rent, 178), rent.minimum_balance(178));
+        assert!(rent.is_exempt(pda_rent_lamports(&rent, 178), 178));
+        // rent 가 없는 cluster 에서도 0 lamports account 는 만들지 않음
+        let free = Rent {
+            lamports_per_byte_year: 0,
+            ..Rent::default()
+        };
+        assert_eq!(pda_rent_lamports(&free, 178), 1);
+    }
+
+    #[test]
+    fn test_check_rent_exempt() {
+        let rent = Rent::default();
+        let minimum = pda_rent_lamports(&rent, 178);
+        assert!(check_rent_exempt(&rent, minimum, 178).is_ok());
+        assert_eq!(
+            check_rent_exempt(&rent, minimum - 1, 178),
+            Err(GauntletError::NotRentExempt.into())
+        );
+    }
+
+    #[test]
+    fn test_program_ids_match_base58() {
+        let parse = |ids: &[&str]| -> Vec<Pubkey> {
+            ids.iter().map(|id| Pubkey::from_str(id).unwrap()).collect()
+        };
+        assert_eq!(
+            STAKING_PROGRAM_ID.to_vec(),
+            parse(&[
+                "EhhTKczWMGQt46ynNeRX1WfeagwwJd7ufHvCDjRxjo5Q",
+                "CBuCnLe26faBpcBP2fktp4rp8abpcAnTWft6ZrP5Q4T",
+                "9KEPoZmtHUrBbhWN1v1KWLMkkvwY6WLtAVUCPRtRjP4z",
+            ])
+        );
+        assert_eq!(
+            POOL_PROGRAM_ID.to_vec(),
+            parse(&[
+                "RVKd61ztZW9GUwhRbbLoYVRE5Xf1B2tVscKqwZqXgEr",
+                "27haf8L6oxUeXrHrgEgsexjSY5hbVUWEmvv9Nyxg8vQv",
+                "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+            ])
+        );
+        assert_eq!(
+            SERUM_PROGRAM_ID.to_vec(),
+            parse(&["9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"])
+        );
+        assert_eq!(
+            OPENBOOK_PROGRAM_ID.to_vec(),
+            parse(&["srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX"])
+        );
+        assert_eq!(
+            JUPITER_PROGRAM_ID.to_vec(),
+            parse(&["JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"])
+        );
+        assert_eq!(
+            PYTH_PROGRAM_ID.to_vec(),
+            parse(&["FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH"])
+        );
+        assert_eq!(
+            WHIRLPOOL_PROGRAM_ID.to_vec(),
+            parse(&["whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"])
+        );
+    }
+}
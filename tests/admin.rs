@@ -0,0 +1,2479 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use common::*;
+use gauntlet_program::{
+    error::GauntletError,
+    instruction::{
+        DepositType, FeeSource, GauntletInstruction, StrategyType, SwapType, WithdrawType,
+        ABI_VERSION_SHIFT,
+    },
+    instruction_builder::{
+        self, AdminAccounts, CollectFeesAccounts, DepositAccounts, InitStrategyAccounts,
+        InitVaultAccounts, MigrateUserAccounts, MigrateVaultAccounts, SwapAccounts,
+        WithdrawAccounts,
+    },
+    raydium::raydium::Raydium,
+    state::{
+        Fees, Gauntlet, Status, Strategy, User, Vault, VaultConfig, VaultStrategy,
+        MAX_NUMBER_OF_VAULTS, USER_STATUS_HARVESTED, USER_STATUS_IDLE, USER_STATUS_READY,
+        USER_STATUS_REWARD_A_SWAPPED, VAULT_LAYOUT_VERSION,
+    },
+    utils::{
+        find_authority_address, find_legacy_authority_address, find_user_address,
+        TOKEN_2022_PROGRAM_ID,
+    },
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transport::TransportError,
+};
+
+fn set_vault_compound_ratio(
+    fixture: &Fixture,
+    admin: &Keypair,
+    compound_ratio_bps: u16,
+    admin_nonce: u64,
+) -> Instruction {
+    instruction_builder::set_vault_compound_ratio(
+        &fixture.program_id,
+        &AdminAccounts {
+            admin: admin.pubkey(),
+            gauntlet_state: fixture.gauntlet,
+        },
+        &fixture.vault,
+        compound_ratio_bps,
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_set_vault_compound_ratio_waits_for_swaps() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.reward_token_remain_amounts[0] = 10;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // harvest 된 reward 가 아직 swap 되지 않았으면 ratio 를 바꿀 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        set_vault_compound_ratio(&fixture, &fixture.admin, 5_000, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::SwapInFlight as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_set_vault_compound_ratio() {
+    let fixture = Fixture::new();
+    let program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        set_vault_compound_ratio(&fixture, &fixture.admin, 5_000, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.compound_ratio_bps, 5_000);
+}
+
+fn set_vault_fee_accounts(
+    fixture: &Fixture,
+    withdraw_fee: Pubkey,
+    deposit_fee: Pubkey,
+    admin_nonce: u64,
+) -> Instruction {
+    instruction_builder::set_vault_fee_accounts(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.vault,
+        &withdraw_fee,
+        &deposit_fee,
+        &spl_token::id(),
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_set_vault_fee_accounts_requires_deposit_token_accounts() {
+    let fixture = Fixture::new();
+    let withdraw_fee = Pubkey::new_unique();
+    let deposit_fee = Pubkey::new_unique();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_token_account(
+        &mut program_test,
+        withdraw_fee,
+        fixture.lp_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    // deposit fee 는 LP 가 아닌 reward token account
+    add_token_account(
+        &mut program_test,
+        deposit_fee,
+        fixture.reward_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    let deposit_lp_fee = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        deposit_lp_fee,
+        fixture.lp_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        set_vault_fee_accounts(&fixture, withdraw_fee, deposit_fee, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        set_vault_fee_accounts(&fixture, withdraw_fee, deposit_lp_fee, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.withdraw_fee_account, withdraw_fee);
+    assert_eq!(vault.deposit_fee_account, deposit_lp_fee);
+    // 등록된 fee account 는 pda 소유로 넘어감
+    for address in [withdraw_fee, deposit_lp_fee] {
+        let fee: spl_token::state::Account = get_packed(&mut banks_client, address).await;
+        assert_eq!(fee.owner, fixture.authority);
+    }
+}
+
+fn update_vault_strategy(
+    fixture: &Fixture,
+    availability: bool,
+    needs_usdc_pool: bool,
+    admin_nonce: u64,
+) -> Instruction {
+    instruction_builder::update_vault_strategy(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.vault_strategy_accounts(),
+        availability,
+        needs_usdc_pool,
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_update_vault_strategy_moves_deposits_out_of_and_back_into_total() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 600;
+    vault.total_deposit_amount = 600;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // enable -> disable: strategy 의 deposit 은 total 에서 빠짐
+    process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, false, false, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.total_deposit_amount, 0);
+    assert_eq!(vault.deposit_amounts[0], 600);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(!vault_strategy.availabilities[0]);
+
+    // disable -> enable: 다시 total 에 더해짐
+    process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, false, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.total_deposit_amount, 600);
+
+    // 같은 flag 로 다시 호출해도 두번 더하지 않음
+    process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, false, 2),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.total_deposit_amount, 600);
+}
+
+#[tokio::test]
+async fn test_update_vault_strategy_marks_deposit_lp_strategy_compounding() {
+    let fixture = Fixture::new();
+    // fixture strategy 의 token 은 reward mint, deposit LP 로 바꾼 strategy 만 compounding
+    let mut lp_strategy = fixture.strategy_state();
+    lp_strategy.strategy_token_mint = fixture.lp_mint;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.compounding[0] = true;
+    let program_test = fixture.program_test(&fixture.vault_state(), &vault_strategy, &lp_strategy);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, false, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(vault_strategy.compounding[0]);
+
+    let program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy,
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, false, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(!vault_strategy.compounding[0]);
+}
+
+#[tokio::test]
+async fn test_update_vault_strategy_disables_only_once() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 600;
+    vault.deposit_amounts[1] = 400;
+    vault.total_deposit_amount = 1_000;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 이미 꺼진 strategy 를 다시 끄면 다른 strategy 의 deposit 까지 빠지면 안 됨
+    for admin_nonce in 0..2 {
+        process(
+            &mut banks_client,
+            &payer,
+            update_vault_strategy(&fixture, false, false, admin_nonce),
+            &[&fixture.admin],
+        )
+        .await
+        .unwrap();
+        let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+        assert_eq!(vault.total_deposit_amount, 400);
+    }
+}
+
+#[tokio::test]
+async fn test_recompute_vault_total_sums_available_strategies() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 600;
+    vault.deposit_amounts[1] = 400;
+    // 어긋난 total, 꺼진 strategy 1 의 deposit 은 빠져야 함
+    vault.total_deposit_amount = 1_234;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::recompute_vault_total(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault_strategy,
+            &fixture.vault,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.total_deposit_amount, 600);
+}
+
+#[tokio::test]
+async fn test_update_vault_strategy_rejects_foreign_accounts() {
+    let fixture = Fixture::new();
+    let mut strategy = fixture.strategy_state();
+    strategy.gauntlet_state_account = Pubkey::new_unique();
+    let program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &strategy,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, false, false, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongStrategyStateAccount as u32)
+    );
+
+    // 같은 layout 이라도 다른 program 이 소유한 vault 는 unpack 전에 거부
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_packed(
+        &mut program_test,
+        fixture.vault,
+        &fixture.vault_state(),
+        &Pubkey::new_unique(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, false, false, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::IncorrectProgramId)
+    );
+}
+
+#[tokio::test]
+async fn test_update_vault_strategy_keeps_swap_route_while_swaps_in_flight() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.reward_token_remain_amounts[0] = 10;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // swap 되지 않은 reward 가 남아 있으면 usdc pool 경로를 바꿀 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, true, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::SwapInFlight as u32)
+    );
+
+    // 경로가 그대로면 availability 는 바꿀 수 있음
+    process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, false, false, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(!vault_strategy.needs_usdc_pools[0]);
+    assert!(!vault_strategy.availabilities[0]);
+}
+
+fn retire_strategy(fixture: &Fixture, admin_nonce: u64) -> Instruction {
+    instruction_builder::retire_strategy(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.strategy,
+        &[fixture.vault],
+        &[fixture.vault_strategy],
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_retire_strategy_requires_empty_vaults_and_is_permanent() {
+    // vault 에 아직 deposit 이 남아 있으면 retire 할 수 없음
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 600;
+    vault.total_deposit_amount = 600;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        retire_strategy(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::AccountNotEmpty as u32)
+    );
+
+    let fixture = Fixture::new();
+    let program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    process(
+        &mut banks_client,
+        &payer,
+        retire_strategy(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert!(strategy.status == Status::RETIRED);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(!vault_strategy.availabilities[0]);
+    assert!(!vault_strategy.accepting_deposits[0]);
+
+    // retire 된 strategy 는 다시 켤 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, false, 1),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::StrategyRetired as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_retire_strategy_rejects_vault_accounts_owned_by_another_program() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    // 같은 내용의 빈 vault / vault strategy 를 다른 program 소유로 만들어 넘김
+    let fake_vault = Pubkey::new_unique();
+    let fake_vault_strategy = Pubkey::new_unique();
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.vault_account = fake_vault;
+    let other_program = Pubkey::new_unique();
+    add_packed(
+        &mut program_test,
+        fake_vault,
+        &fixture.vault_state(),
+        &other_program,
+    );
+    add_packed(
+        &mut program_test,
+        fake_vault_strategy,
+        &vault_strategy,
+        &other_program,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::retire_strategy(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.strategy,
+            &[fake_vault],
+            &[fake_vault_strategy],
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::IncorrectProgramId)
+    );
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert!(strategy.status != Status::RETIRED);
+}
+
+async fn validate_vault_ready(strategy_status: Status) -> Result<(), TransportError> {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.raydium_state_account = Pubkey::new_unique();
+    let mut strategy = fixture.strategy_state();
+    strategy.status = strategy_status;
+    let program_test = fixture.program_test(&vault, &fixture.vault_strategy_state(), &strategy);
+    let (mut banks_client, payer) = start(program_test).await;
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::validate_vault_ready(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault,
+            &fixture.vault_strategy,
+            &fixture.fee_account,
+            &fixture.fee_account,
+            &[fixture.strategy],
+        ),
+        &[&fixture.admin],
+    )
+    .await
+}
+
+#[test]
+fn test_instruction_tag_rejects_unknown_abi_version() {
+    assert!(matches!(
+        GauntletInstruction::unpack(&[19]),
+        Ok(GauntletInstruction::ValidateVaultReady {})
+    ));
+    // 최상위 bit 가 켜진 tag 는 아직 없는 version 1 의 instruction
+    assert_eq!(
+        GauntletInstruction::unpack(&[(1 << ABI_VERSION_SHIFT) | 19]).err(),
+        Some(GauntletError::UnsupportedAbiVersion.into())
+    );
+}
+
+#[tokio::test]
+async fn test_validate_vault_ready_reports_strategy_status() {
+    validate_vault_ready(Status::NORMAL).await.unwrap();
+    assert_eq!(
+        custom_error(validate_vault_ready(Status::PAUSED).await),
+        Some(GauntletError::StrategyPaused as u32)
+    );
+    assert_eq!(
+        custom_error(validate_vault_ready(Status::RETIRED).await),
+        Some(GauntletError::StrategyRetired as u32)
+    );
+}
+
+fn sweep_tokens(
+    fixture: &Fixture,
+    source: Pubkey,
+    destination: Pubkey,
+    token_program: Pubkey,
+) -> Instruction {
+    instruction_builder::sweep_tokens(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &source,
+        &destination,
+        &token_program,
+        10,
+        0,
+    )
+}
+
+#[tokio::test]
+async fn test_sweep_tokens_rejects_tracked_mints() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    // vault 회계에 없는 pda 소유 account 라도 reward mint 면 sweep 불가
+    let (stray_reward, airdrop_mint, airdrop, destination, airdrop_destination) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_mint(&mut program_test, airdrop_mint);
+    add_token_account(
+        &mut program_test,
+        stray_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        10,
+    );
+    add_token_account(
+        &mut program_test,
+        airdrop,
+        airdrop_mint,
+        fixture.authority,
+        10,
+    );
+    let admin = fixture.admin.pubkey();
+    add_token_account(
+        &mut program_test,
+        destination,
+        fixture.reward_mint,
+        admin,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        airdrop_destination,
+        airdrop_mint,
+        admin,
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        sweep_tokens(&fixture, stray_reward, destination, spl_token::id()),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+    let result = process(
+        &mut banks_client,
+        &payer,
+        sweep_tokens(&fixture, airdrop, airdrop_destination, Pubkey::new_unique()),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        sweep_tokens(&fixture, airdrop, airdrop_destination, spl_token::id()),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let swept: spl_token::state::Account = get_packed(&mut banks_client, airdrop_destination).await;
+    assert_eq!(swept.amount, 10);
+}
+
+#[tokio::test]
+async fn test_sweep_tokens_accepts_token_2022() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (airdrop_mint, airdrop, destination, legacy_airdrop) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_mint(&mut program_test, airdrop_mint);
+    for (address, owner, amount) in [
+        (airdrop, fixture.authority, 10),
+        (destination, fixture.admin.pubkey(), 0),
+    ] {
+        add_packed(
+            &mut program_test,
+            address,
+            &spl_token::state::Account {
+                mint: airdrop_mint,
+                owner,
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                ..spl_token::state::Account::default()
+            },
+            &TOKEN_2022_PROGRAM_ID,
+        );
+    }
+    add_token_account(
+        &mut program_test,
+        legacy_airdrop,
+        airdrop_mint,
+        fixture.authority,
+        10,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // source 를 소유하지 않은 token program 으로는 옮길 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        sweep_tokens(&fixture, legacy_airdrop, destination, TOKEN_2022_PROGRAM_ID),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::IncorrectProgramId)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        sweep_tokens(&fixture, airdrop, destination, TOKEN_2022_PROGRAM_ID),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let swept: spl_token::state::Account = get_packed(&mut banks_client, destination).await;
+    assert_eq!(swept.amount, 10);
+}
+
+fn collect_fees(
+    fixture: &Fixture,
+    fee_source: FeeSource,
+    fee_token: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+    admin_nonce: u64,
+) -> Instruction {
+    let fee_state = if matches!(fee_source, FeeSource::STRATEGY) {
+        fixture.strategy
+    } else {
+        fixture.vault
+    };
+    instruction_builder::collect_fees(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &CollectFeesAccounts {
+            fee_state,
+            fee_token,
+            destination_token: destination,
+            token_program: spl_token::id(),
+        },
+        fee_source,
+        amount,
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_collect_fees_only_from_registered_fee_accounts() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    for (address, amount) in [(fixture.fee_account, 500), (fixture.vault_lp, 1_000)] {
+        add_token_account(
+            &mut program_test,
+            address,
+            fixture.lp_mint,
+            fixture.authority,
+            amount,
+        );
+    }
+    let destination = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        destination,
+        fixture.lp_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 예치금이 있는 vault LP account 는 fee account 가 아니므로 꺼낼 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        collect_fees(
+            &fixture,
+            FeeSource::VAULT,
+            fixture.vault_lp,
+            destination,
+            300,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        collect_fees(
+            &fixture,
+            FeeSource::VAULT,
+            fixture.fee_account,
+            destination,
+            300,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, fixture.fee_account).await;
+    assert_eq!(fee.amount, 200);
+    let destination: spl_token::state::Account = get_packed(&mut banks_client, destination).await;
+    assert_eq!(destination.amount, 300);
+}
+
+#[tokio::test]
+async fn test_init_strategy_hands_performance_fee_account_to_pda() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    program_test.add_account(
+        fixture.strategy,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; Strategy::LEN],
+            owner: fixture.program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (strategy_token, performance_fee) = (Pubkey::new_unique(), Pubkey::new_unique());
+    for address in [strategy_token, performance_fee] {
+        add_token_account(
+            &mut program_test,
+            address,
+            fixture.reward_mint,
+            fixture.admin.pubkey(),
+            0,
+        );
+    }
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::init_strategy(
+            &fixture.program_id,
+            InitStrategyAccounts {
+                admin: fixture.admin.pubkey(),
+                gauntlet_state: fixture.gauntlet,
+                strategy_state: fixture.strategy,
+                strategy_token,
+                performance_fee_token: performance_fee,
+                token_program: spl_token::id(),
+            },
+            StrategyType::RAYDIUM_LP,
+            0,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.performance_fee_account, performance_fee);
+    for address in [strategy_token, performance_fee] {
+        let token: spl_token::state::Account = get_packed(&mut banks_client, address).await;
+        assert_eq!(token.owner, fixture.authority);
+    }
+}
+
+#[tokio::test]
+async fn test_collect_fees_from_strategist_fee_account() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (strategist_fee, other, destination) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_token_account(
+        &mut program_test,
+        strategist_fee,
+        fixture.reward_mint,
+        fixture.admin.pubkey(),
+        500,
+    );
+    // 다른 vault 의 예치금처럼 이미 pda 소유인 account 는 fee account 로 등록할 수 없음
+    add_token_account(
+        &mut program_test,
+        other,
+        fixture.reward_mint,
+        fixture.authority,
+        1_000,
+    );
+    add_token_account(
+        &mut program_test,
+        destination,
+        fixture.reward_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let set_strategist_fee = |strategist_fee: Pubkey, admin_nonce: u64| {
+        instruction_builder::set_strategist_fee(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.strategy,
+            &strategist_fee,
+            &spl_token::id(),
+            2_500,
+            admin_nonce,
+        )
+    };
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        set_strategist_fee(other, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        set_strategist_fee(strategist_fee, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, strategist_fee).await;
+    assert_eq!(fee.owner, fixture.authority);
+
+    // strategist fee account 는 vault 의 fee account 가 아님
+    let result = process(
+        &mut banks_client,
+        &payer,
+        collect_fees(
+            &fixture,
+            FeeSource::VAULT,
+            strategist_fee,
+            destination,
+            300,
+            1,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+    // strategy account 를 vault 로 넘기면 state 로 읽히지 않음
+    let mut instruction = collect_fees(
+        &fixture,
+        FeeSource::VAULT,
+        strategist_fee,
+        destination,
+        300,
+        1,
+    );
+    instruction.accounts[2].pubkey = fixture.strategy;
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert!(result.is_err());
+
+    process(
+        &mut banks_client,
+        &payer,
+        collect_fees(
+            &fixture,
+            FeeSource::STRATEGY,
+            strategist_fee,
+            destination,
+            300,
+            1,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, strategist_fee).await;
+    assert_eq!(fee.amount, 200);
+    let destination: spl_token::state::Account = get_packed(&mut banks_client, destination).await;
+    assert_eq!(destination.amount, 300);
+}
+
+fn grow_vault_strategy(
+    fixture: &Fixture,
+    new_vault_strategy: Pubkey,
+    new_capacity: u8,
+    admin_nonce: u64,
+) -> Instruction {
+    instruction_builder::grow_vault_strategy(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.vault,
+        &fixture.vault_strategy,
+        &new_vault_strategy,
+        new_capacity,
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_grow_vault_strategy_keeps_existing_slots() {
+    let fixture = Fixture::new();
+    let mut strategy = fixture.strategy_state();
+    strategy.index = 10;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.capacity = 4;
+    vault_strategy.needs_usdc_pools[1] = true;
+    vault_strategy.availabilities[1] = true;
+    vault_strategy.strategy_token_amounts[0] = 7;
+    vault_strategy.strategy_token_amounts[3] = 11;
+    vault_strategy.accepting_deposits[3] = true;
+    vault_strategy.compounding[3] = true;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &strategy,
+    );
+    // capacity 4 로 할당된 vault strategy 로 덮어씀
+    let mut data = vec![0; VaultStrategy::packed_len(4)];
+    VaultStrategy::pack(vault_strategy, &mut data).unwrap();
+    program_test.add_account(
+        fixture.vault_strategy,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: fixture.program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let new_vault_strategy = Pubkey::new_unique();
+    program_test.add_account(
+        new_vault_strategy,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; VaultStrategy::packed_len(16)],
+            owner: fixture.program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // index 10 은 capacity 4 밖이라 켤 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        update_vault_strategy(&fixture, true, false, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::VaultStrategyCapacityExceeded as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        grow_vault_strategy(&fixture, new_vault_strategy, 16, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let grown: VaultStrategy = get_packed(&mut banks_client, new_vault_strategy).await;
+    assert!(grown.is_initialized);
+    assert_eq!(grown.vault_account, fixture.vault);
+    assert_eq!(grown.capacity, 16);
+    assert_eq!(grown.needs_usdc_pools[..4], [false, true, false, false]);
+    assert_eq!(grown.availabilities[..4], [true, true, false, false]);
+    assert_eq!(grown.strategy_token_amounts[..4], [7, 0, 0, 11]);
+    assert_eq!(grown.accepting_deposits[..4], [true, false, false, true]);
+    assert_eq!(grown.compounding[..4], [false, false, false, true]);
+    assert!(grown.availabilities[4..].iter().all(|available| !available));
+    assert!(grown.strategy_token_amounts[4..]
+        .iter()
+        .all(|amount| *amount == 0));
+    // 예전 account 는 닫혀서 더 이상 vault strategy 로 쓸 수 없음
+    assert!(banks_client
+        .get_account(fixture.vault_strategy)
+        .await
+        .unwrap()
+        .is_none());
+
+    let mut instruction = update_vault_strategy(&fixture, true, false, 1);
+    instruction.accounts[2] = AccountMeta::new(new_vault_strategy, false);
+    process(&mut banks_client, &payer, instruction, &[&fixture.admin])
+        .await
+        .unwrap();
+    let grown: VaultStrategy = get_packed(&mut banks_client, new_vault_strategy).await;
+    assert!(grown.availabilities[10]);
+}
+
+/// v1 program 이 만든 account 들, 새 account 는 fixture 의 주소에 admin 이 빈 account 로 만들어 둠
+struct V1Deployment {
+    gauntlet: Pubkey,
+    vault: Pubkey,
+    vault_strategy: Pubkey,
+    strategy: Pubkey,
+    depositor: Keypair,
+    depositor_lp: Pubkey,
+    depositor_reward: Pubkey,
+    user: Pubkey,
+}
+
+fn add_empty_account(program_test: &mut ProgramTest, address: Pubkey, len: usize, owner: &Pubkey) {
+    program_test.add_account(
+        address,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; len],
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+/// v1 gauntlet / vault / vault strategy / strategy / user 를 baseline layout 으로 쓰고,
+/// vault 에 1_000 LP 가 stake 된 상태 (token account 와 fixture.user_info ledger 는 v1 authority 소유)
+fn v1_deployment(fixture: &Fixture) -> (ProgramTest, V1Deployment) {
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let program_id = fixture.program_id;
+    let (authority, _bump_seed) = find_legacy_authority_address(&program_id);
+    let (gauntlet, vault, vault_strategy, strategy) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_packed(
+        &mut program_test,
+        gauntlet,
+        &v1::Gauntlet {
+            is_initialized: true,
+            admin: fixture.admin.pubkey(),
+            strategies_len: 1,
+            vaults_len: 1,
+            usdc_token_account: fixture.vault_usdc,
+        },
+        &program_id,
+    );
+    let mut deposit_amounts = vec![0; v1::MAX_NUMBER_OF_STRATEGY];
+    deposit_amounts[0] = 1_000;
+    add_packed(
+        &mut program_test,
+        vault,
+        &v1::Vault {
+            is_initialized: true,
+            index: 0,
+            status: 1,
+            fees: v1::Fees::default(),
+            gauntlet_state_account: gauntlet,
+            deposit_token_account: fixture.vault_lp,
+            reward_token_account: fixture.vault_reward,
+            reward_token_b_account: Pubkey::default(),
+            withdraw_fee_account: fixture.fee_account,
+            total_deposit_amount: 1_000,
+            deposit_amounts: deposit_amounts.clone(),
+            reward_token_remain_amounts: vec![0; v1::MAX_NUMBER_OF_STRATEGY],
+            reward_token_b_remain_amounts: vec![0; v1::MAX_NUMBER_OF_STRATEGY],
+            usdc_token_amounts: vec![0; v1::MAX_NUMBER_OF_STRATEGY],
+            accumulated_reward_per_shares: vec![0; v1::MAX_NUMBER_OF_STRATEGY],
+            last_reward_update_time: 0,
+            raydium_state_account: fixture.user_info,
+        },
+        &program_id,
+    );
+    let mut availabilities = vec![false; v1::MAX_NUMBER_OF_STRATEGY];
+    availabilities[0] = true;
+    add_packed(
+        &mut program_test,
+        vault_strategy,
+        &v1::VaultStrategy {
+            is_initialized: true,
+            vault_account: vault,
+            needs_usdc_pools: vec![false; v1::MAX_NUMBER_OF_STRATEGY],
+            availabilities,
+            strategy_token_amounts: vec![0; v1::MAX_NUMBER_OF_STRATEGY],
+        },
+        &program_id,
+    );
+    add_packed(
+        &mut program_test,
+        strategy,
+        &v1::Strategy {
+            is_initialized: true,
+            index: 0,
+            gauntlet_state_account: gauntlet,
+            admin: fixture.admin.pubkey(),
+            performance_fee_account: fixture.fee_account,
+            status: 1,
+            last_reward_update_time: 0,
+            total_deposit_amount: 1_000,
+            deposit_amounts,
+            strategy_token_account: fixture.strategy_token,
+        },
+        &program_id,
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 0);
+    let depositor_reward = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        depositor_reward,
+        fixture.reward_mint,
+        depositor.pubkey(),
+        0,
+    );
+    let (user, _bump_seed) = find_user_address(&vault, &depositor.pubkey(), &strategy, &program_id);
+    add_packed(
+        &mut program_test,
+        user,
+        &v1::User {
+            is_initialized: true,
+            user: depositor.pubkey(),
+            vault_account: vault,
+            strategy_account: strategy,
+            amount: 1_000,
+            reward: 0,
+            reward_debt: 0,
+            user_status: USER_STATUS_READY,
+            deadline: i64::MAX,
+        },
+        &program_id,
+    );
+
+    // v1 token account 는 모두 v1 authority 소유, stake 된 LP 는 pool 에 있음
+    for (token_account, mint, amount) in [
+        (fixture.vault_lp, fixture.lp_mint, 0),
+        (fixture.vault_reward, fixture.reward_mint, 0),
+        (fixture.vault_usdc, fixture.usdc_mint, 0),
+        (fixture.strategy_token, fixture.reward_mint, 0),
+    ] {
+        add_token_account(&mut program_test, token_account, mint, authority, amount);
+    }
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        1_000,
+    );
+    // stub 은 unstake / stake 할 때마다 300 씩 reward 를 줌
+    program_test.add_account(
+        fixture.pool_id,
+        Account {
+            lamports: 1_000_000_000,
+            data: 300u64.to_le_bytes().to_vec(),
+            owner: fixture.staking_program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // 새 account 는 fixture 주소에 admin 이 만들어 둔 빈 account
+    add_empty_account(
+        &mut program_test,
+        fixture.gauntlet,
+        Gauntlet::LEN,
+        &program_id,
+    );
+    add_empty_account(&mut program_test, fixture.vault, Vault::LEN, &program_id);
+    add_empty_account(
+        &mut program_test,
+        fixture.vault_strategy,
+        VaultStrategy::LEN,
+        &program_id,
+    );
+    add_empty_account(
+        &mut program_test,
+        fixture.strategy,
+        Strategy::LEN,
+        &program_id,
+    );
+    // native program-test 는 CPI 로 account 를 만들지 못하므로 새 ledger 와 user PDA 도 할당만 해 둠
+    let (ledger, _bump_seed) = Pubkey::find_program_address(
+        &[
+            &fixture.gauntlet.to_bytes(),
+            &fixture.vault.to_bytes(),
+            &fixture.vault_strategy.to_bytes(),
+        ],
+        &program_id,
+    );
+    add_empty_account(
+        &mut program_test,
+        ledger,
+        Raydium::user_info_len(false),
+        &fixture.staking_program_id,
+    );
+    add_empty_account(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        User::LEN,
+        &program_id,
+    );
+    (
+        program_test,
+        V1Deployment {
+            gauntlet,
+            vault,
+            vault_strategy,
+            strategy,
+            depositor,
+            depositor_lp,
+            depositor_reward,
+            user,
+        },
+    )
+}
+
+async fn token_owner(banks_client: &mut BanksClient, address: Pubkey) -> Pubkey {
+    get_packed::<spl_token::state::Account>(banks_client, address)
+        .await
+        .owner
+}
+
+async fn token_amount(banks_client: &mut BanksClient, address: Pubkey) -> u64 {
+    get_packed::<spl_token::state::Account>(banks_client, address)
+        .await
+        .amount
+}
+
+fn migrate_vault(fixture: &Fixture, v1: &V1Deployment, admin_nonce: u64) -> Instruction {
+    instruction_builder::migrate_vault(
+        &fixture.program_id,
+        MigrateVaultAccounts {
+            admin: fixture.admin.pubkey(),
+            gauntlet_state: fixture.gauntlet,
+            v1_vault_state: v1.vault,
+            vault_state: fixture.vault,
+            v1_vault_strategy_state: v1.vault_strategy,
+            vault_strategy_state: fixture.vault_strategy,
+            deposit_token: fixture.vault_lp,
+            reward_token: fixture.vault_reward,
+            reward_b_token: None,
+        },
+        admin_nonce,
+    )
+}
+
+fn migrate_user(fixture: &Fixture, v1: &V1Deployment, payer: &Pubkey) -> Instruction {
+    instruction_builder::migrate_user(
+        &fixture.program_id,
+        MigrateUserAccounts {
+            payer: *payer,
+            vault_state: fixture.vault,
+            strategy_state: fixture.strategy,
+            v1_user_state: v1.user,
+            user: v1.depositor.pubkey(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_migrate_v1_deployment() {
+    let fixture = Fixture::new();
+    let (program_test, v1) = v1_deployment(&fixture);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // gauntlet 보다 먼저 vault 를 옮길 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        migrate_vault(&fixture, &v1, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::UninitializedAccount)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::migrate_gauntlet(
+            &fixture.program_id,
+            &fixture.admin.pubkey(),
+            &v1.gauntlet,
+            &fixture.gauntlet,
+            &fixture.vault_usdc,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let gauntlet: Gauntlet = get_packed(&mut banks_client, fixture.gauntlet).await;
+    assert_eq!(gauntlet.admin, fixture.admin.pubkey());
+    assert_eq!(gauntlet.strategies_len, 1);
+    assert_eq!(gauntlet.vaults_len, 1);
+    assert_eq!(gauntlet.authority_bump, fixture.authority_bump);
+    assert_eq!(gauntlet.migrated_from, v1.gauntlet);
+    assert!(gauntlet.is_tracked_mint(&fixture.usdc_mint));
+    assert_eq!(
+        token_owner(&mut banks_client, fixture.vault_usdc).await,
+        fixture.authority
+    );
+    assert!(banks_client
+        .get_account(v1.gauntlet)
+        .await
+        .unwrap()
+        .is_none());
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::migrate_strategy(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &v1.strategy,
+            &fixture.strategy,
+            &fixture.strategy_token,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.gauntlet_state_account, fixture.gauntlet);
+    assert_eq!(strategy.deposit_amounts[0], 1_000);
+    assert_eq!(strategy.strategy_token_mint, fixture.reward_mint);
+    assert_eq!(strategy.migrated_from, v1.strategy);
+    assert_eq!(
+        token_owner(&mut banks_client, fixture.strategy_token).await,
+        fixture.authority
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        migrate_vault(&fixture, &v1, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.layout_version, VAULT_LAYOUT_VERSION);
+    assert_eq!(vault.gauntlet_state_account, fixture.gauntlet);
+    assert_eq!(vault.migrated_from, v1.vault);
+    assert_eq!(vault.total_deposit_amount, 1_000);
+    assert_eq!(vault.deposit_token_mint, fixture.lp_mint);
+    assert_eq!(vault.raydium_state_account, fixture.user_info);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert_eq!(vault_strategy.vault_account, fixture.vault);
+    assert!(vault_strategy.availabilities[0]);
+    for token_account in [fixture.vault_lp, fixture.vault_reward] {
+        assert_eq!(
+            token_owner(&mut banks_client, token_account).await,
+            fixture.authority
+        );
+    }
+    for closed in [v1.vault, v1.vault_strategy] {
+        assert!(banks_client.get_account(closed).await.unwrap().is_none());
+    }
+
+    // stake 는 v1 authority 로 빼고 새 ledger 로 다시 넣음, 그 사이 받은 reward 는 strategy 몫
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::migrate_vault_stake(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault,
+            &fixture.vault_strategy,
+            fixture.staking_accounts(),
+            2,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let (ledger, _bump_seed) = Pubkey::find_program_address(
+        &[
+            &fixture.gauntlet.to_bytes(),
+            &fixture.vault.to_bytes(),
+            &fixture.vault_strategy.to_bytes(),
+        ],
+        &fixture.program_id,
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.raydium_state_account, ledger);
+    assert_eq!(vault.reward_token_remain_amounts[0], 600);
+    assert_eq!(vault.harvest_pending_amount, 0);
+    assert_eq!(
+        token_amount(&mut banks_client, fixture.pool_lp).await,
+        1_000
+    );
+    assert_eq!(
+        banks_client
+            .get_account(ledger)
+            .await
+            .unwrap()
+            .unwrap()
+            .owner,
+        fixture.staking_program_id
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        migrate_user(&fixture, &v1, &payer.pubkey()),
+        &[],
+    )
+    .await
+    .unwrap();
+    let user_address = fixture.user_address(&v1.depositor.pubkey());
+    let user = get_user(&mut banks_client, user_address).await;
+    assert_eq!(user.vault_account, fixture.vault);
+    assert_eq!(user.strategy_account, fixture.strategy);
+    assert_eq!(user.amount, 1_000);
+    assert_eq!(user.user_status, USER_STATUS_READY);
+    assert!(banks_client.get_account(v1.user).await.unwrap().is_none());
+
+    // v1 user 는 닫혀서 다시 옮길 수 없음 (같은 tx 로 중복 처리되지 않게 user 가 냄)
+    let result = process(
+        &mut banks_client,
+        &payer,
+        migrate_user(&fixture, &v1, &v1.depositor.pubkey()),
+        &[&v1.depositor],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::IncorrectProgramId)
+    );
+
+    // 옮겨진 user 가 새 ledger 에서 원금을 뺌
+    let mut withdraw_accounts = fixture.staking_accounts();
+    withdraw_accounts[3].pubkey = ledger;
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            WithdrawAccounts {
+                withdrawer: v1.depositor.pubkey(),
+                withdrawer_user_state: user_address,
+                withdrawer_deposit_token: v1.depositor_lp,
+                withdrawer_reward_token: v1.depositor_reward,
+                gauntlet_state: fixture.gauntlet,
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                strategy_token: fixture.strategy_token,
+                withdraw_fee_token: fixture.fee_account,
+                performance_fee_token: fixture.fee_account,
+                withdraw_accounts,
+                deposit_token_mint: fixture.lp_mint,
+                strategy_token_mint: fixture.reward_mint,
+                strategy_token_program: spl_token::id(),
+                gas_reimbursement_swap_accounts: vec![],
+                recipient_tokens: None,
+                strategist_fee_token: None,
+            },
+            400,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&v1.depositor],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_amount(&mut banks_client, v1.depositor_lp).await, 400);
+    let user = get_user(&mut banks_client, user_address).await;
+    assert_eq!(user.amount, 600);
+}
+
+#[tokio::test]
+async fn test_keeper_allow_list_gates_keeper_instructions() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let keeper = Keypair::new();
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper.pubkey()),
+        &User::init(keeper.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    // swap_usdc_to_strategy_token 은 signer 다음에 keeper 를 확인
+    let keeper_swap = || {
+        let mut restake_accounts = fixture.restake_accounts();
+        // 같은 transaction 이 다시 처리되지 않도록 쓰이지 않는 account 를 붙임
+        restake_accounts.push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+        instruction_builder::swap_usdc_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper.pubkey(),
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state: fixture.user_address(&keeper.pubkey()),
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts: fixture.staking_accounts(),
+                restake_accounts,
+            },
+            SwapType::RAYDIUM,
+        )
+    };
+
+    let result = process(&mut banks_client, &payer, keeper_swap(), &[&keeper]).await;
+    assert_eq!(custom_error(result), Some(GauntletError::NotKeeper as u32));
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::add_keeper(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &keeper.pubkey(),
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let gauntlet: Gauntlet = get_packed(&mut banks_client, fixture.gauntlet).await;
+    assert!(gauntlet.is_keeper(&keeper.pubkey()));
+    // 허용된 keeper 는 keeper 확인을 통과하고 그 뒤의 검사에서 멈춤
+    let result = process(&mut banks_client, &payer, keeper_swap(), &[&keeper]).await;
+    assert_ne!(custom_error(result), Some(GauntletError::NotKeeper as u32));
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::remove_keeper(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &keeper.pubkey(),
+            1,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let gauntlet: Gauntlet = get_packed(&mut banks_client, fixture.gauntlet).await;
+    assert!(!gauntlet.is_keeper(&keeper.pubkey()));
+    assert_eq!(gauntlet.keepers_len, 0);
+    let result = process(&mut banks_client, &payer, keeper_swap(), &[&keeper]).await;
+    assert_eq!(custom_error(result), Some(GauntletError::NotKeeper as u32));
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::remove_keeper(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &keeper.pubkey(),
+            2,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::KeeperNotFound as u32)
+    );
+}
+
+fn init_vault_raydium_state(fixture: &Fixture, raydium_state: Pubkey) -> Instruction {
+    instruction_builder::init_vault_raydium_state(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.vault,
+        &fixture.vault_strategy,
+        &raydium_state,
+        &fixture.staking_program_id,
+        0,
+    )
+}
+
+#[tokio::test]
+async fn test_init_vault_raydium_state_checks_vault_and_address() {
+    // native program-test 는 CPI 로 account 를 만들지 못하므로 생성 전의 검사만 확인
+    let fixture = Fixture::new();
+    let raydium_state = Pubkey::find_program_address(
+        &[
+            &fixture.gauntlet.to_bytes(),
+            &fixture.vault.to_bytes(),
+            &fixture.vault_strategy.to_bytes(),
+        ],
+        &fixture.program_id,
+    )
+    .0;
+    let program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        init_vault_raydium_state(&fixture, Pubkey::new_unique()),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::InvalidSeeds)
+    );
+
+    // 이미 raydium state 가 있는 vault 는 다시 만들지 않음
+    let mut vault = fixture.vault_state();
+    vault.raydium_state_account = raydium_state;
+    let program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        init_vault_raydium_state(&fixture, raydium_state),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn test_raydium_state_size_follows_reward_b() {
+    // 생성 자체는 native program-test 에서 돌지 않으므로 크기 선택만 확인
+    assert_eq!(Raydium::user_info_len(false), 88);
+    assert_eq!(Raydium::user_info_len(true), 96);
+}
+
+/// init_vault(1) with zero fees and config except `swap_slippage_bps`
+fn init_vault(
+    fixture: &Fixture,
+    deposit_token: Pubkey,
+    withdraw_fee_token: Pubkey,
+    reward_token: Pubkey,
+    deposit_fee_token: Pubkey,
+    swap_slippage_bps: u64,
+) -> Instruction {
+    instruction_builder::init_vault(
+        &fixture.program_id,
+        InitVaultAccounts {
+            admin: fixture.admin.pubkey(),
+            gauntlet_state: fixture.gauntlet,
+            vault_state: fixture.vault,
+            vault_strategy_state: fixture.vault_strategy,
+            deposit_token,
+            withdraw_fee_token,
+            reward_token,
+            deposit_fee_token,
+            reward_b_token: None,
+            token_program: spl_token::id(),
+        },
+        &Fees {
+            performance_fee_numerator: 0,
+            performance_fee_denominator: 0,
+            withdrawal_fee_numerator: 0,
+            withdrawal_fee_denominator: 0,
+            deposit_fee_numerator: 0,
+            deposit_fee_denominator: 0,
+            referral_fee_numerator: 0,
+            referral_fee_denominator: 0,
+            min_fee_enabled: false,
+        },
+        &VaultConfig {
+            min_deposit: 1,
+            min_harvest_interval: 0,
+            swap_slippage_bps,
+            max_swap_usdc_per_tx: 0,
+            compounding: false,
+            min_swap_output: 0,
+            max_pending_reward: 0,
+        },
+        0,
+    )
+}
+
+/// init_vault 전의 빈 vault / vault strategy account 와 admin 소유의 (deposit lp, reward, fee) token account
+fn init_vault_program_test(fixture: &Fixture) -> (ProgramTest, Pubkey, Pubkey, Pubkey) {
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    for (address, len) in [
+        (fixture.vault, Vault::LEN),
+        (fixture.vault_strategy, VaultStrategy::LEN),
+    ] {
+        program_test.add_account(
+            address,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![0; len],
+                owner: fixture.program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+    let (deposit_lp, reward, fee) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_token_account(
+        &mut program_test,
+        deposit_lp,
+        fixture.lp_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        reward,
+        fixture.reward_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        fee,
+        fixture.lp_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    (program_test, deposit_lp, reward, fee)
+}
+
+#[tokio::test]
+async fn test_init_vault_rejects_aliased_fee_accounts() {
+    let fixture = Fixture::new();
+    let (program_test, deposit_lp, reward, fee) = init_vault_program_test(&fixture);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // withdraw fee 가 deposit account, deposit fee 가 reward account 인 경우
+    for (withdraw_fee_token, deposit_fee_token) in [(deposit_lp, fee), (fee, reward)] {
+        let result = process(
+            &mut banks_client,
+            &payer,
+            init_vault(
+                &fixture,
+                deposit_lp,
+                withdraw_fee_token,
+                reward,
+                deposit_fee_token,
+                0,
+            ),
+            &[&fixture.admin],
+        )
+        .await;
+        assert_eq!(
+            custom_error(result),
+            Some(GauntletError::InvalidAccount as u32)
+        );
+    }
+
+    process(
+        &mut banks_client,
+        &payer,
+        init_vault(&fixture, deposit_lp, fee, reward, fee, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert!(vault.is_initialized);
+    assert_eq!(vault.withdraw_fee_account, fee);
+    assert_eq!(vault.deposit_fee_account, fee);
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, fee).await;
+    assert_eq!(fee.owner, fixture.authority);
+}
+
+#[tokio::test]
+async fn test_authority_is_separate_per_gauntlet() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    // 같은 admin 의 두번째 gauntlet
+    let other_gauntlet = Pubkey::new_unique();
+    let (other_authority, other_bump) =
+        find_authority_address(&other_gauntlet, &fixture.program_id);
+    assert_ne!(other_authority, fixture.authority);
+    add_packed(
+        &mut program_test,
+        other_gauntlet,
+        &Gauntlet::init(fixture.admin.pubkey(), Pubkey::new_unique(), other_bump),
+        &fixture.program_id,
+    );
+    let (airdrop_mint, airdrop, destination) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_mint(&mut program_test, airdrop_mint);
+    add_token_account(
+        &mut program_test,
+        airdrop,
+        airdrop_mint,
+        fixture.authority,
+        10,
+    );
+    add_token_account(
+        &mut program_test,
+        destination,
+        airdrop_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 두번째 gauntlet 으로는 첫번째 gauntlet authority 의 account 를 움직일 수 없음
+    let mut instruction = sweep_tokens(&fixture, airdrop, destination, spl_token::id());
+    instruction.accounts[1].pubkey = other_gauntlet;
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        sweep_tokens(&fixture, airdrop, destination, spl_token::id()),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let swept: spl_token::state::Account = get_packed(&mut banks_client, destination).await;
+    assert_eq!(swept.amount, 10);
+}
+
+#[tokio::test]
+async fn test_admin_instructions_require_current_nonce() {
+    let fixture = Fixture::new();
+    let program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    // 아직 쓰이지 않은 nonce 는 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::add_keeper(&fixture.program_id, &fixture.admin_accounts(), &first, 1),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(custom_error(result), Some(GauntletError::StaleNonce as u32));
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::add_keeper(&fixture.program_id, &fixture.admin_accounts(), &first, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let gauntlet: Gauntlet = get_packed(&mut banks_client, fixture.gauntlet).await;
+    assert_eq!(gauntlet.admin_nonce, 1);
+
+    // 이미 쓰인 nonce 로 만든 instruction 은 다시 실행할 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::add_keeper(&fixture.program_id, &fixture.admin_accounts(), &second, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(custom_error(result), Some(GauntletError::StaleNonce as u32));
+    let gauntlet: Gauntlet = get_packed(&mut banks_client, fixture.gauntlet).await;
+    assert_eq!(gauntlet.admin_nonce, 1);
+}
+
+fn reset_user_status(fixture: &Fixture, user: Pubkey, admin_nonce: u64) -> Instruction {
+    instruction_builder::reset_user_status(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.vault,
+        &fixture.user_address(&user),
+        &user,
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_reset_user_status_readies_stranded_user() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 600;
+    vault.total_deposit_amount = 600;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    // keeper 가 reward a swap 후 멈춰서 deadline 이 지난 user
+    let (owner, owner_lp) = fixture.add_depositor(&mut program_test, 400);
+    let mut user = User::init(owner.pubkey(), fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_REWARD_A_SWAPPED;
+    user.deadline = 1;
+    user.amount = 600;
+    user.reward = 50;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&owner.pubkey()),
+        &user,
+        &fixture.program_id,
+    );
+    // 예치가 없는 user 는 IDLE 로 돌아감
+    let empty_owner = Pubkey::new_unique();
+    let mut empty_user = User::init(empty_owner, fixture.vault, fixture.strategy);
+    empty_user.user_status = USER_STATUS_HARVESTED;
+    empty_user.deadline = 1;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&empty_owner),
+        &empty_user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // user state 의 owner 가 아닌 key 로는 reset 할 수 없음
+    let mut wrong_owner = reset_user_status(&fixture, Pubkey::new_unique(), 0);
+    wrong_owner.accounts[3] = AccountMeta::new(fixture.user_address(&owner.pubkey()), false);
+    let result = process(&mut banks_client, &payer, wrong_owner, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongUserAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        reset_user_status(&fixture, owner.pubkey(), 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let user = get_user(&mut banks_client, fixture.user_address(&owner.pubkey())).await;
+    assert_eq!(user.user_status, USER_STATUS_READY);
+    // reset 은 deadline 을 지우므로 admin tx 이후 시간이 지나도 timeout 되지 않음
+    assert_eq!(user.deadline, 0);
+    assert_eq!(user.amount, 600);
+    assert_eq!(user.reward, 50);
+
+    process(
+        &mut banks_client,
+        &payer,
+        reset_user_status(&fixture, empty_owner, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let empty_user = get_user(&mut banks_client, fixture.user_address(&empty_owner)).await;
+    assert_eq!(empty_user.user_status, USER_STATUS_IDLE);
+    assert_eq!(empty_user.deadline, 0);
+
+    // reset 된 user 는 keeper 를 기다리지 않고 바로 추가 deposit 가능
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            DepositAccounts {
+                depositor: owner.pubkey(),
+                depositor_user_state: fixture.user_address(&owner.pubkey()),
+                depositor_deposit_token: owner_lp,
+                gauntlet_state: fixture.gauntlet,
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                deposit_accounts: fixture.staking_accounts(),
+                deposit_token_mint: fixture.lp_mint,
+                deposit_fee_token: None,
+                referral_token: None,
+                create_user: false,
+            },
+            400,
+            DepositType::RAYDIUM,
+        ),
+        &[&owner],
+    )
+    .await
+    .unwrap();
+    let user = get_user(&mut banks_client, fixture.user_address(&owner.pubkey())).await;
+    assert_eq!(user.amount, 1_000);
+}
+
+#[tokio::test]
+async fn test_init_vault_stores_swap_slippage() {
+    let fixture = Fixture::new();
+    let (program_test, deposit_lp, reward, fee) = init_vault_program_test(&fixture);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 100% 를 넘는 slippage 는 unpack 에서 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        init_vault(&fixture, deposit_lp, fee, reward, fee, 10_001),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidSlippage as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        init_vault(&fixture, deposit_lp, fee, reward, fee, 150),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.swap_slippage_bps, 150);
+    assert_eq!(vault.token_program, spl_token::id());
+}
+
+#[tokio::test]
+async fn test_init_vault_rejects_initialized_vault_strategy() {
+    let fixture = Fixture::new();
+    let (mut program_test, deposit_lp, reward, fee) = init_vault_program_test(&fixture);
+    add_packed(
+        &mut program_test,
+        fixture.vault_strategy,
+        &fixture.vault_strategy_state(),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        init_vault(&fixture, deposit_lp, fee, reward, fee, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn test_init_vault_rejects_when_max_vaults_reached() {
+    let fixture = Fixture::new();
+    let (mut program_test, deposit_lp, reward, fee) = init_vault_program_test(&fixture);
+    let mut gauntlet = fixture.gauntlet_state();
+    gauntlet.vaults_len = MAX_NUMBER_OF_VAULTS as u8;
+    add_packed(
+        &mut program_test,
+        fixture.gauntlet,
+        &gauntlet,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        init_vault(&fixture, deposit_lp, fee, reward, fee, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::MaxVaultsReached as u32)
+    );
+    // vault 와 token account 는 그대로 남아야 함
+    let vault_account = banks_client
+        .get_account(fixture.vault)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(vault_account.data.iter().all(|byte| *byte == 0));
+    let deposit_lp_account: spl_token::state::Account =
+        get_packed(&mut banks_client, deposit_lp).await;
+    assert_eq!(deposit_lp_account.owner, fixture.admin.pubkey());
+}
+
+#[tokio::test]
+async fn test_init_vault_rejects_deposit_account_used_by_another_vault() {
+    let fixture = Fixture::new();
+    let (mut program_test, _, reward, fee) = init_vault_program_test(&fixture);
+    // 다른 vault 의 deposit LP account 는 이미 authority 소유
+    let used_deposit_lp = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        used_deposit_lp,
+        fixture.lp_mint,
+        fixture.authority,
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        init_vault(&fixture, used_deposit_lp, fee, reward, fee, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::TokenAccountAlreadyUsed as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_init_vault_rejects_wrong_token_program() {
+    let fixture = Fixture::new();
+    let (program_test, deposit_lp, reward, fee) = init_vault_program_test(&fixture);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let mut instruction = init_vault(&fixture, deposit_lp, fee, reward, fee, 0);
+    instruction.accounts[6] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+
+    // Token-2022 도 받지만 vault token account 들이 그 program 소유여야 함
+    let mut instruction = init_vault(&fixture, deposit_lp, fee, reward, fee, 0);
+    instruction.accounts[6] = AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false);
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+}
+
+#[cfg(feature = "anchor-discriminator")]
+#[test]
+fn test_anchor_discriminator_selects_same_instruction_as_tag() {
+    use gauntlet_program::instruction::anchor_discriminator;
+
+    let mut args = vec![1];
+    args.extend_from_slice(&7u64.to_le_bytes());
+    let mut tagged = vec![17];
+    tagged.extend_from_slice(&args);
+    let mut anchor = anchor_discriminator("set_global_pause").to_vec();
+    anchor.extend_from_slice(&args);
+
+    // anchor client 와 기존 client 가 같은 instruction 으로 읽혀야 함
+    for data in [tagged, anchor] {
+        assert!(matches!(
+            GauntletInstruction::unpack(&data),
+            Ok(GauntletInstruction::SetGlobalPause {
+                paused: true,
+                admin_nonce: 7,
+            })
+        ));
+    }
+}
+
+#[test]
+fn test_admin_builders_match_program_layout() {
+    let fixture = Fixture::new();
+    let user = Pubkey::new_unique();
+
+    let instruction = init_vault(
+        &fixture,
+        fixture.vault_lp,
+        fixture.fee_account,
+        fixture.vault_reward,
+        fixture.fee_account,
+        150,
+    );
+    match GauntletInstruction::unpack(&instruction.data) {
+        Ok(GauntletInstruction::InitVault {
+            fees,
+            config,
+            admin_nonce: 0,
+        }) => {
+            assert_eq!(fees.performance_fee_denominator, 0);
+            assert!(!fees.min_fee_enabled);
+            assert_eq!(config.min_deposit, 1);
+            assert_eq!(config.swap_slippage_bps, 150);
+            // 0 은 pending reward cap 없음
+            assert_eq!(config.max_pending_reward, 0);
+        }
+        _ => panic!("init_vault builder does not unpack to InitVault"),
+    }
+    // min_fee_enabled byte 가 없는 기존 client 는 최소 fee 를 켠 것으로 읽음
+    let mut data = instruction.data.clone();
+    data.pop();
+    match GauntletInstruction::unpack(&data) {
+        Ok(GauntletInstruction::InitVault { fees, .. }) => assert!(fees.min_fee_enabled),
+        _ => panic!("init_vault without min_fee_enabled does not unpack to InitVault"),
+    }
+    // reward b 가 없으면 deposit fee account 가 마지막
+    assert_eq!(instruction.accounts.len(), 9);
+    assert_eq!(instruction.accounts[6].pubkey, spl_token::id());
+
+    let instruction = instruction_builder::init_strategy(
+        &fixture.program_id,
+        InitStrategyAccounts {
+            admin: fixture.admin.pubkey(),
+            gauntlet_state: fixture.gauntlet,
+            strategy_state: fixture.strategy,
+            strategy_token: fixture.strategy_token,
+            performance_fee_token: fixture.fee_account,
+            token_program: spl_token::id(),
+        },
+        StrategyType::RAYDIUM_LP,
+        5_000,
+        3,
+    );
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::InitStrategy {
+            strategy_type: StrategyType::RAYDIUM_LP,
+            deposit_cap: 5_000,
+            admin_nonce: 3,
+        })
+    ));
+
+    let instruction = reset_user_status(&fixture, user, 4);
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::ResetUserStatus { user: u, admin_nonce: 4 }) if u == user
+    ));
+    assert_eq!(instruction.accounts[3].pubkey, fixture.user_address(&user));
+
+    let instruction = instruction_builder::set_strategist_fee(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.strategy,
+        &fixture.fee_account,
+        &spl_token::id(),
+        2_500,
+        5,
+    );
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::SetStrategistFee {
+            strategist_fee_split_bps: 2_500,
+            admin_nonce: 5,
+        })
+    ));
+
+    let instruction = instruction_builder::set_vault_max_pending_reward(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        &fixture.vault,
+        1_000,
+        8,
+    );
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::SetVaultMaxPendingReward {
+            max_pending_reward: 1_000,
+            admin_nonce: 8,
+        })
+    ));
+
+    // grow / migrate 는 닫히는 account 의 rent 를 admin 에게 돌려주므로 admin 이 writable
+    let instruction = grow_vault_strategy(&fixture, Pubkey::new_unique(), 16, 6);
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::GrowVaultStrategy {
+            new_capacity: 16,
+            admin_nonce: 6,
+        })
+    ));
+    assert!(instruction.accounts[0].is_signer && instruction.accounts[0].is_writable);
+
+    let instruction = instruction_builder::get_harvest_preview(
+        &fixture.program_id,
+        &fixture.vault,
+        &fixture.vault_strategy,
+        7,
+    );
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::GetHarvestPreview { reward_amount: 7 })
+    ));
+}
@@ -0,0 +1,1188 @@
+//! program-test fixtures: gauntlet state accounts are written directly with Pack and the
+//! raydium staking program is replaced by a stub that only moves LP / reward tokens
+#![allow(dead_code)]
+pub mod v1;
+
+use gauntlet_program::{
+    instruction_builder::{AdminAccounts, VaultStrategyAccounts},
+    orca::whirlpool::{WHIRLPOOL_SQRT_PRICE_OFFSET, WHIRLPOOL_TOKEN_MINT_A_OFFSET},
+    processor::Processor,
+    state::{Gauntlet, Status, Strategy, User, Vault, VaultStrategy},
+    utils::{
+        find_authority_address, find_user_address, ED25519_PROGRAM_ID, JUPITER_PROGRAM_ID,
+        POOL_PROGRAM_ID, SERUM_PROGRAM_ID, STAKING_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+        WHIRLPOOL_PROGRAM_ID,
+    },
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use spl_token::instruction::TokenInstruction;
+use std::{
+    convert::TryInto,
+    sync::{Mutex, Once},
+};
+
+/// stub 의 pool 이 LP 와 reward 를 보관하는 pda seed
+pub const STUB_POOL_SEED: &[u8] = b"pool";
+/// stub amm 의 pool coin / pc account 를 소유하는 pda seed
+pub const STUB_AMM_SEED: &[u8] = b"amm";
+/// raydium 과 같은 swap fee
+pub const STUB_AMM_FEE_BPS: u64 = 25;
+/// stub whirlpool 의 token vault a / b 를 소유하는 pda seed
+pub const STUB_WHIRLPOOL_SEED: &[u8] = b"whirlpool";
+/// stub jupiter route 의 pool in / out account 를 소유하는 pda seed
+pub const STUB_JUPITER_SEED: &[u8] = b"jupiter";
+
+/// raydium staking stub
+/// deposit(1) 은 amount 만큼 user LP 를 pool 로 옮기고, withdraw(2) 는 반대로 돌려줌
+/// 두 경우 모두 pool_id data 에 적힌 reward 만큼을 user reward account 로 지급 (harvest)
+/// pool_id data 가 16 byte 면 뒤의 8 byte 만큼은 deposit 에서 stake 하지 않고 user LP account 에 남김
+pub fn process_stub_staking<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pool_id = next_account_info(account_info_iter)?;
+    let pool_authority = next_account_info(account_info_iter)?;
+    let _user_info_account = next_account_info(account_info_iter)?;
+    let user_owner = next_account_info(account_info_iter)?;
+    let user_lp_token_account = next_account_info(account_info_iter)?;
+    let pool_lp_token_account = next_account_info(account_info_iter)?;
+    let user_reward_token_account = next_account_info(account_info_iter)?;
+    let pool_reward_token_account = next_account_info(account_info_iter)?;
+    let _clock_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let (_pool_signer, bump) = Pubkey::find_program_address(&[STUB_POOL_SEED], program_id);
+    let bump = [bump];
+    let pool_seeds: &[&[u8]] = &[STUB_POOL_SEED, &bump];
+
+    let amount = u64::from_le_bytes(input[1..9].try_into().unwrap());
+    let reward = u64::from_le_bytes(pool_id.data.borrow()[0..8].try_into().unwrap());
+    stub_transfer(
+        token_program,
+        pool_reward_token_account,
+        user_reward_token_account,
+        pool_authority,
+        reward,
+        pool_seeds,
+    )?;
+    let unstaked = pool_id
+        .data
+        .borrow()
+        .get(8..16)
+        .map_or(0, |bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    match input[0] {
+        1 => stub_transfer(
+            token_program,
+            user_lp_token_account,
+            pool_lp_token_account,
+            user_owner,
+            amount - unstaked,
+            pool_seeds,
+        ),
+        2 => stub_transfer(
+            token_program,
+            pool_lp_token_account,
+            user_lp_token_account,
+            pool_authority,
+            amount,
+            pool_seeds,
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// raydium amm swap(9) stub, serum 없이 pool coin / pc reserve 로 constant product swap 만 함
+/// accounts 는 amm program 을 뺀 raydium swap 순서 (token program, amm id, amm authority, ...)
+/// add liquidity(3) 는 stub_add_liquidity 로 넘김
+pub fn process_stub_amm<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    if input[0] == 3 {
+        return stub_add_liquidity(program_id, accounts, input);
+    }
+    let token_program = &accounts[0];
+    let amm_authority = &accounts[2];
+    let pool_coin_token_account = &accounts[5];
+    let pool_pc_token_account = &accounts[6];
+    let user_source_token_account = &accounts[15];
+    let user_dest_token_account = &accounts[16];
+    let user_owner = &accounts[17];
+
+    let (_amm_signer, bump) = Pubkey::find_program_address(&[STUB_AMM_SEED], program_id);
+    let bump = [bump];
+    let amm_seeds: &[&[u8]] = &[STUB_AMM_SEED, &bump];
+
+    let amount_in = u64::from_le_bytes(input[1..9].try_into().unwrap());
+    let min_out = u64::from_le_bytes(input[9..17].try_into().unwrap());
+    let source_mint =
+        spl_token::state::Account::unpack(&user_source_token_account.data.borrow())?.mint;
+    let pool_coin = spl_token::state::Account::unpack(&pool_coin_token_account.data.borrow())?;
+    let pool_pc = spl_token::state::Account::unpack(&pool_pc_token_account.data.borrow())?;
+    let (pool_in, reserve_in, pool_out, reserve_out) = if pool_coin.mint == source_mint {
+        (
+            pool_coin_token_account,
+            pool_coin.amount,
+            pool_pc_token_account,
+            pool_pc.amount,
+        )
+    } else {
+        (
+            pool_pc_token_account,
+            pool_pc.amount,
+            pool_coin_token_account,
+            pool_coin.amount,
+        )
+    };
+    let amount_in_after_fee = amount_in as u128 * (10_000 - STUB_AMM_FEE_BPS) as u128 / 10_000;
+    let amount_out = (reserve_out as u128 * amount_in_after_fee
+        / (reserve_in as u128 + amount_in_after_fee)) as u64;
+    if amount_out < min_out {
+        return Err(solana_program::program_error::ProgramError::Custom(30));
+    }
+    stub_transfer(
+        token_program,
+        user_source_token_account,
+        pool_in,
+        user_owner,
+        amount_in,
+        amm_seeds,
+    )?;
+    stub_transfer(
+        token_program,
+        pool_out,
+        user_dest_token_account,
+        amm_authority,
+        amount_out,
+        amm_seeds,
+    )
+}
+
+/// raydium add liquidity(3) stub, max coin / pc 를 모두 pool 로 옮기고 coin 만큼 LP 를 mint 함
+/// (pool 비율은 caller 가 맞춘다고 보고 base side 는 보지 않음, LP mint authority 는 amm pda 여야 함)
+fn stub_add_liquidity<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let token_program = &accounts[0];
+    let amm_authority = &accounts[2];
+    let lp_mint = &accounts[5];
+    let pool_coin_token_account = &accounts[6];
+    let pool_pc_token_account = &accounts[7];
+    let user_coin_token_account = &accounts[9];
+    let user_pc_token_account = &accounts[10];
+    let user_lp_token_account = &accounts[11];
+    let user_owner = &accounts[12];
+
+    let (_amm_signer, bump) = Pubkey::find_program_address(&[STUB_AMM_SEED], program_id);
+    let bump = [bump];
+    let amm_seeds: &[&[u8]] = &[STUB_AMM_SEED, &bump];
+
+    let max_coin_amount = u64::from_le_bytes(input[1..9].try_into().unwrap());
+    let max_pc_amount = u64::from_le_bytes(input[9..17].try_into().unwrap());
+    stub_transfer(
+        token_program,
+        user_coin_token_account,
+        pool_coin_token_account,
+        user_owner,
+        max_coin_amount,
+        amm_seeds,
+    )?;
+    stub_transfer(
+        token_program,
+        user_pc_token_account,
+        pool_pc_token_account,
+        user_owner,
+        max_pc_amount,
+        amm_seeds,
+    )?;
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            lp_mint.key,
+            user_lp_token_account.key,
+            amm_authority.key,
+            &[],
+            max_coin_amount,
+        )?,
+        &[
+            lp_mint.clone(),
+            user_lp_token_account.clone(),
+            amm_authority.clone(),
+            token_program.clone(),
+        ],
+        &[amm_seeds],
+    )
+}
+
+/// orca whirlpool swap stub, tick array 없이 token vault a / b reserve 로 constant product swap 만 함
+/// accounts 는 whirlpool swap 순서 (token program, token authority, whirlpool, owner a, vault a, owner b, vault b, ...)
+pub fn process_stub_whirlpool<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let token_program = &accounts[0];
+    let token_authority = &accounts[1];
+    let whirlpool = &accounts[2];
+    let token_owner_account_a = &accounts[3];
+    let token_vault_a = &accounts[4];
+    let token_owner_account_b = &accounts[5];
+    let token_vault_b = &accounts[6];
+
+    // whirlpool 처럼 whirlpool account 가 token vault 의 authority
+    let (whirlpool_signer, bump) = Pubkey::find_program_address(&[STUB_WHIRLPOOL_SEED], program_id);
+    if *whirlpool.key != whirlpool_signer {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let bump = [bump];
+    let whirlpool_seeds: &[&[u8]] = &[STUB_WHIRLPOOL_SEED, &bump];
+
+    // discriminator(8) amount(8) other_amount_threshold(8) sqrt_price_limit(16) amount_specified_is_input(1) a_to_b(1)
+    let amount_in = u64::from_le_bytes(input[8..16].try_into().unwrap());
+    let min_out = u64::from_le_bytes(input[16..24].try_into().unwrap());
+    let a_to_b = input[41] == 1;
+    let (owner_in, vault_in, owner_out, vault_out) = match a_to_b {
+        true => (
+            token_owner_account_a,
+            token_vault_a,
+            token_owner_account_b,
+            token_vault_b,
+        ),
+        false => (
+            token_owner_account_b,
+            token_vault_b,
+            token_owner_account_a,
+            token_vault_a,
+        ),
+    };
+    let reserve_in = spl_token::state::Account::unpack(&vault_in.data.borrow())?.amount;
+    let vault_out_account = spl_token::state::Account::unpack(&vault_out.data.borrow())?;
+    let amount_out = (vault_out_account.amount as u128 * amount_in as u128
+        / (reserve_in as u128 + amount_in as u128)) as u64;
+    if amount_out < min_out {
+        return Err(ProgramError::Custom(0x1794));
+    }
+    stub_transfer(
+        token_program,
+        owner_in,
+        vault_in,
+        token_authority,
+        amount_in,
+        whirlpool_seeds,
+    )?;
+    stub_transfer(
+        token_program,
+        vault_out,
+        owner_out,
+        whirlpool,
+        amount_out,
+        whirlpool_seeds,
+    )
+}
+
+/// jupiter route stub, route data 의 amount in(8) amount out(8) 만큼 그대로 옮김
+/// accounts 는 token program, token authority, source, dest, pool in, pool out, pool signer
+pub fn process_stub_jupiter<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let token_program = &accounts[0];
+    let token_authority = &accounts[1];
+    let source = &accounts[2];
+    let dest = &accounts[3];
+    let pool_in = &accounts[4];
+    let pool_out = &accounts[5];
+    let pool_signer = &accounts[6];
+
+    let (_pool_signer, bump) = Pubkey::find_program_address(&[STUB_JUPITER_SEED], program_id);
+    let bump = [bump];
+    let pool_seeds: &[&[u8]] = &[STUB_JUPITER_SEED, &bump];
+
+    let amount_in = u64::from_le_bytes(input[0..8].try_into().unwrap());
+    let amount_out = u64::from_le_bytes(input[8..16].try_into().unwrap());
+    stub_transfer(
+        token_program,
+        source,
+        pool_in,
+        token_authority,
+        amount_in,
+        pool_seeds,
+    )?;
+    stub_transfer(
+        token_program,
+        pool_out,
+        dest,
+        pool_signer,
+        amount_out,
+        pool_seeds,
+    )
+}
+
+/// 1.7 runtime 에는 ed25519 precompile 이 없어서 같은 data layout 을 native program 으로 확인
+/// signature 가 맞지 않으면 precompile 처럼 transaction 을 실패시킴
+pub fn process_stub_ed25519(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let offset = |index: usize| {
+        u16::from_le_bytes(input[2 + index * 2..4 + index * 2].try_into().unwrap()) as usize
+    };
+    let (signature_offset, public_key_offset, message_offset, message_size) =
+        (offset(0), offset(2), offset(4), offset(5));
+    let signature = Signature::new(&input[signature_offset..signature_offset + 64]);
+    let verified = signature.verify(
+        &input[public_key_offset..public_key_offset + 32],
+        &input[message_offset..message_offset + message_size],
+    );
+    match verified {
+        true => Ok(()),
+        false => Err(ProgramError::InvalidArgument),
+    }
+}
+
+/// Token-2022 stub, transfer fee mint 의 TransferChecked 만 직접 처리하고 나머지는 legacy processor 로 넘김
+/// fee mint data 는 base mint 뒤 Account::LEN 까지 padding, account type 1, fee bps(u16) 이고
+/// 그 mint 의 token account 는 base account 뒤에 account type 2 가 붙음. fee 는 받는 쪽에서 빠짐
+pub fn process_stub_token_2022(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let account_len = spl_token::state::Account::LEN;
+    let fee_bps = match (TokenInstruction::unpack(input)?, accounts.get(1)) {
+        (TokenInstruction::TransferChecked { .. }, Some(mint)) if mint.data_len() > account_len => {
+            u16::from_le_bytes(
+                mint.data.borrow()[account_len + 1..account_len + 3]
+                    .try_into()
+                    .unwrap(),
+            )
+        }
+        _ => return spl_token::processor::Processor::process(program_id, accounts, input),
+    };
+    let (amount, decimals) = match TokenInstruction::unpack(input)? {
+        TokenInstruction::TransferChecked { amount, decimals } => (amount, decimals),
+        _ => unreachable!(),
+    };
+    let account_info_iter = &mut accounts.iter();
+    let source = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let mint_info =
+        spl_token::state::Mint::unpack(&mint.data.borrow()[..spl_token::state::Mint::LEN])?;
+    let mut source_info = spl_token::state::Account::unpack(&source.data.borrow()[..account_len])?;
+    let mut destination_info =
+        spl_token::state::Account::unpack(&destination.data.borrow()[..account_len])?;
+    if !authority.is_signer
+        || source_info.owner != *authority.key
+        || source_info.mint != *mint.key
+        || destination_info.mint != *mint.key
+        || mint_info.decimals != decimals
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let fee = amount * fee_bps as u64 / 10_000;
+    source_info.amount = source_info
+        .amount
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    destination_info.amount += amount - fee;
+    spl_token::state::Account::pack(source_info, &mut source.data.borrow_mut()[..account_len])?;
+    spl_token::state::Account::pack(
+        destination_info,
+        &mut destination.data.borrow_mut()[..account_len],
+    )?;
+    Ok(())
+}
+
+fn stub_transfer<'a>(
+    token_program: &AccountInfo<'a>,
+    from: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    pool_seeds: &[&[u8]],
+) -> ProgramResult {
+    if amount == 0 {
+        return Ok(());
+    }
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            from.key,
+            to.key,
+            authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            from.clone(),
+            to.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        &[pool_seeds],
+    )
+}
+
+/// native program-test 는 invoke 하는 program 의 AccountInfo 가 없으면 panic 하지만
+/// bpf runtime 은 transaction 에 있는 program account 를 그대로 씀 (utils::transfer_token 등은 넘기지 않음)
+/// bank 에서 읽어둔 program account 를 붙여서 bpf 와 같게 동작시킴
+struct ProgramAccountStubs {
+    inner: Box<dyn SyscallStubs>,
+    program_accounts: Vec<(Pubkey, Account)>,
+}
+
+/// program 이 남긴 모든 log, test 들이 병렬로 돌기 때문에 다른 test 의 log 도 섞여 있음
+static LOGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// `prefix` 로 시작하는 log 에서 prefix 를 뗀 나머지
+pub fn logged(prefix: &str) -> Vec<String> {
+    LOGS.lock()
+        .unwrap()
+        .iter()
+        .filter_map(|message| message.strip_prefix(prefix).map(str::to_string))
+        .collect()
+}
+
+/// query instruction 들이 log 로 남기는 hex 를 byte 로
+pub fn decode_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// 감싸는 동안 잠깐 설치되는 기본 stub
+struct DefaultStubs;
+impl SyscallStubs for DefaultStubs {}
+
+impl SyscallStubs for ProgramAccountStubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.lock().unwrap().push(message.to_string());
+        self.inner.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.inner.sol_log_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if account_infos
+            .iter()
+            .any(|account_info| *account_info.key == instruction.program_id)
+        {
+            return self
+                .inner
+                .sol_invoke_signed(instruction, account_infos, signers_seeds);
+        }
+        let (key, account) = self
+            .program_accounts
+            .iter()
+            .find(|(key, _)| *key == instruction.program_id)
+            .expect("program account not loaded by start");
+        let program_account_info = AccountInfo::new(
+            Box::leak(Box::new(*key)),
+            false,
+            false,
+            Box::leak(Box::new(account.lamports)),
+            Box::leak(account.data.clone().into_boxed_slice()),
+            Box::leak(Box::new(account.owner)),
+            true,
+            account.rent_epoch,
+        );
+        let mut account_infos = account_infos.to_vec();
+        account_infos.push(program_account_info);
+        self.inner
+            .sol_invoke_signed(instruction, &account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_rent_sysvar(var_addr)
+    }
+}
+
+/// one vault with one raydium (v1) pool routed to one RAY strategy
+pub struct Fixture {
+    pub program_id: Pubkey,
+    pub staking_program_id: Pubkey,
+    pub admin: Keypair,
+    pub gauntlet: Pubkey,
+    pub authority: Pubkey,
+    pub authority_bump: u8,
+    pub vault: Pubkey,
+    pub vault_strategy: Pubkey,
+    pub strategy: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub vault_lp: Pubkey,
+    pub vault_reward: Pubkey,
+    pub vault_usdc: Pubkey,
+    pub strategy_token: Pubkey,
+    pub pool_id: Pubkey,
+    pub pool_signer: Pubkey,
+    pub pool_lp: Pubkey,
+    pub pool_reward: Pubkey,
+    pub user_info: Pubkey,
+    pub fee_account: Pubkey,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let staking_program_id = STAKING_PROGRAM_ID[0];
+        let gauntlet = Pubkey::new_unique();
+        let (authority, authority_bump) = find_authority_address(&gauntlet, &program_id);
+        let (pool_signer, _) = Pubkey::find_program_address(&[STUB_POOL_SEED], &staking_program_id);
+        Fixture {
+            program_id,
+            staking_program_id,
+            admin: Keypair::new(),
+            gauntlet,
+            authority,
+            authority_bump,
+            vault: Pubkey::new_unique(),
+            vault_strategy: Pubkey::new_unique(),
+            strategy: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            reward_mint: Pubkey::new_unique(),
+            usdc_mint: Pubkey::new_unique(),
+            vault_lp: Pubkey::new_unique(),
+            vault_reward: Pubkey::new_unique(),
+            vault_usdc: Pubkey::new_unique(),
+            strategy_token: Pubkey::new_unique(),
+            pool_id: Pubkey::new_unique(),
+            pool_signer,
+            pool_lp: Pubkey::new_unique(),
+            pool_reward: Pubkey::new_unique(),
+            user_info: Pubkey::new_unique(),
+            fee_account: Pubkey::new_unique(),
+        }
+    }
+
+    pub fn gauntlet_state(&self) -> Gauntlet {
+        let mut gauntlet =
+            Gauntlet::init(self.admin.pubkey(), self.vault_usdc, self.authority_bump);
+        gauntlet.vaults_len = 1;
+        gauntlet.strategies_len = 1;
+        for mint in [self.usdc_mint, self.lp_mint, self.reward_mint] {
+            gauntlet.track_mint(mint).unwrap();
+        }
+        gauntlet
+    }
+
+    pub fn vault_state(&self) -> Vault {
+        let mut vault = Vault::unpack_unchecked(&vec![0; Vault::LEN]).unwrap();
+        vault.is_initialized = true;
+        vault.status = Status::NORMAL;
+        vault.gauntlet_state_account = self.gauntlet;
+        vault.deposit_token_account = self.vault_lp;
+        vault.reward_token_account = self.vault_reward;
+        vault.withdraw_fee_account = self.fee_account;
+        vault.deposit_fee_account = self.fee_account;
+        vault.deposit_token_mint = self.lp_mint;
+        vault.reward_token_mint = self.reward_mint;
+        vault.min_deposit = 1;
+        vault.deposits_enabled = true;
+        vault.withdrawals_enabled = true;
+        vault.token_program = spl_token::id();
+        vault
+    }
+
+    pub fn vault_strategy_state(&self) -> VaultStrategy {
+        let mut vault_strategy =
+            VaultStrategy::unpack_unchecked(&vec![0; VaultStrategy::LEN]).unwrap();
+        vault_strategy.is_initialized = true;
+        vault_strategy.vault_account = self.vault;
+        vault_strategy.availabilities[0] = true;
+        vault_strategy.accepting_deposits[0] = true;
+        vault_strategy
+    }
+
+    pub fn strategy_state(&self) -> Strategy {
+        let mut strategy = Strategy::unpack_unchecked(&vec![0; Strategy::LEN]).unwrap();
+        strategy.is_initialized = true;
+        strategy.gauntlet_state_account = self.gauntlet;
+        strategy.admin = self.admin.pubkey();
+        strategy.performance_fee_account = self.fee_account;
+        strategy.status = Status::NORMAL;
+        strategy.strategy_token_account = self.strategy_token;
+        strategy.strategy_token_mint = self.reward_mint;
+        strategy
+    }
+
+    /// gauntlet program, stub staking program and every fixture account with the given states
+    pub fn program_test(
+        &self,
+        vault: &Vault,
+        vault_strategy: &VaultStrategy,
+        strategy: &Strategy,
+    ) -> ProgramTest {
+        let mut program_test = ProgramTest::new(
+            "gauntlet_program",
+            self.program_id,
+            processor!(Processor::process),
+        );
+        program_test.add_program(
+            "stub_staking",
+            self.staking_program_id,
+            processor!(process_stub_staking),
+        );
+        program_test.add_program(
+            "stub_ed25519",
+            ED25519_PROGRAM_ID,
+            processor!(process_stub_ed25519),
+        );
+        // spl-token 도 native processor 로 올려서 bpf 실행 없이 돌게 함
+        program_test.add_program(
+            "spl_token",
+            spl_token::id(),
+            processor!(spl_token::processor::Processor::process),
+        );
+        // extension 없는 Token-2022 account 는 legacy 와 같은 layout 이라 같은 processor 로 흉내냄
+        // (transfer fee mint 만 stub 이 따로 처리)
+        program_test.add_program(
+            "spl_token_2022",
+            TOKEN_2022_PROGRAM_ID,
+            processor!(process_stub_token_2022),
+        );
+        add_packed(
+            &mut program_test,
+            self.gauntlet,
+            &self.gauntlet_state(),
+            &self.program_id,
+        );
+        add_packed(&mut program_test, self.vault, vault, &self.program_id);
+        add_packed(
+            &mut program_test,
+            self.vault_strategy,
+            vault_strategy,
+            &self.program_id,
+        );
+        add_packed(&mut program_test, self.strategy, strategy, &self.program_id);
+        for mint in [self.lp_mint, self.reward_mint, self.usdc_mint] {
+            add_mint(&mut program_test, mint);
+        }
+        add_token_account(
+            &mut program_test,
+            self.vault_lp,
+            self.lp_mint,
+            self.authority,
+            0,
+        );
+        add_token_account(
+            &mut program_test,
+            self.vault_reward,
+            self.reward_mint,
+            self.authority,
+            0,
+        );
+        add_token_account(
+            &mut program_test,
+            self.vault_usdc,
+            self.usdc_mint,
+            self.authority,
+            0,
+        );
+        add_token_account(
+            &mut program_test,
+            self.strategy_token,
+            self.reward_mint,
+            self.authority,
+            0,
+        );
+        add_token_account(
+            &mut program_test,
+            self.fee_account,
+            self.lp_mint,
+            self.admin.pubkey(),
+            0,
+        );
+        add_token_account(
+            &mut program_test,
+            self.pool_lp,
+            self.lp_mint,
+            self.pool_signer,
+            0,
+        );
+        add_token_account(
+            &mut program_test,
+            self.pool_reward,
+            self.reward_mint,
+            self.pool_signer,
+            u64::MAX / 2,
+        );
+        // pool_id data 의 첫 8 byte 가 stub 이 매번 지급하는 reward
+        program_test.add_account(
+            self.pool_id,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![0; 8],
+                owner: self.staking_program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        program_test
+    }
+
+    /// raydium (v1) deposit / withdraw / harvest accounts in the order Raydium reads them
+    pub fn staking_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.staking_program_id, false),
+            AccountMeta::new(self.pool_id, false),
+            AccountMeta::new_readonly(self.pool_signer, false),
+            AccountMeta::new(self.user_info, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(self.vault_lp, false),
+            AccountMeta::new(self.pool_lp, false),
+            AccountMeta::new(self.vault_reward, false),
+            AccountMeta::new(self.pool_reward, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]
+    }
+
+    /// staking accounts followed by the deposit LP mint, used to re-stake swapped LP
+    pub fn restake_accounts(&self) -> Vec<AccountMeta> {
+        let mut accounts = self.staking_accounts();
+        accounts.push(AccountMeta::new_readonly(self.lp_mint, false));
+        accounts
+    }
+
+    /// stub amm 과 `source_mint` / `dest_mint` pool 을 추가하고 source -> dest raydium swap accounts 를 돌려줌
+    pub fn add_stub_amm(
+        &self,
+        program_test: &mut ProgramTest,
+        source: Pubkey,
+        source_mint: Pubkey,
+        dest: Pubkey,
+        dest_mint: Pubkey,
+        reserve: u64,
+    ) -> Vec<AccountMeta> {
+        let amm_program_id = POOL_PROGRAM_ID[0];
+        program_test.add_program("stub_amm", amm_program_id, processor!(process_stub_amm));
+        self.stub_amm_accounts(
+            program_test,
+            amm_program_id,
+            source,
+            source_mint,
+            dest,
+            dest_mint,
+            reserve,
+        )
+    }
+
+    /// `amm_program_id` 에 올린 stub amm 의 pool 을 추가하고 source -> dest raydium swap accounts 를 돌려줌
+    pub fn stub_amm_accounts(
+        &self,
+        program_test: &mut ProgramTest,
+        amm_program_id: Pubkey,
+        source: Pubkey,
+        source_mint: Pubkey,
+        dest: Pubkey,
+        dest_mint: Pubkey,
+        reserve: u64,
+    ) -> Vec<AccountMeta> {
+        let (amm_signer, _) = Pubkey::find_program_address(&[STUB_AMM_SEED], &amm_program_id);
+        let pool_coin = Pubkey::new_unique();
+        let pool_pc = Pubkey::new_unique();
+        add_token_account(program_test, pool_coin, source_mint, amm_signer, reserve);
+        add_token_account(program_test, pool_pc, dest_mint, amm_signer, reserve);
+        let mut metas = vec![
+            AccountMeta::new_readonly(amm_program_id, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(amm_signer, false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(pool_coin, false),
+            AccountMeta::new(pool_pc, false),
+            AccountMeta::new_readonly(SERUM_PROGRAM_ID[0], false),
+        ];
+        // serum market, bids, asks, event queue, coin / pc vault, vault signer 는 stub 이 읽지 않음
+        for _ in 0..7 {
+            metas.push(AccountMeta::new(Pubkey::new_unique(), false));
+        }
+        metas.push(AccountMeta::new(source, false));
+        metas.push(AccountMeta::new(dest, false));
+        metas.push(AccountMeta::new_readonly(self.authority, false));
+        metas
+    }
+
+    /// stub whirlpool 과 `source_mint`(token a) / `dest_mint`(token b) pool 을 추가하고
+    /// source -> dest orca swap accounts 를 돌려줌
+    pub fn add_stub_whirlpool(
+        &self,
+        program_test: &mut ProgramTest,
+        source: Pubkey,
+        source_mint: Pubkey,
+        dest: Pubkey,
+        dest_mint: Pubkey,
+        reserve: u64,
+    ) -> Vec<AccountMeta> {
+        let whirlpool_program_id = WHIRLPOOL_PROGRAM_ID[0];
+        program_test.add_program(
+            "stub_whirlpool",
+            whirlpool_program_id,
+            processor!(process_stub_whirlpool),
+        );
+        let (whirlpool, _) =
+            Pubkey::find_program_address(&[STUB_WHIRLPOOL_SEED], &whirlpool_program_id);
+        let mut data = vec![0; WHIRLPOOL_TOKEN_MINT_A_OFFSET + 32];
+        // 가격 1, fee 0 인 pool 로 보이게 함, 실제 output 은 reserve 기준 constant product
+        data[WHIRLPOOL_SQRT_PRICE_OFFSET..WHIRLPOOL_SQRT_PRICE_OFFSET + 16]
+            .copy_from_slice(&(1u128 << 64).to_le_bytes());
+        data[WHIRLPOOL_TOKEN_MINT_A_OFFSET..].copy_from_slice(source_mint.as_ref());
+        program_test.add_account(
+            whirlpool,
+            Account {
+                lamports: 1_000_000_000,
+                data,
+                owner: whirlpool_program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        let token_vault_a = Pubkey::new_unique();
+        let token_vault_b = Pubkey::new_unique();
+        add_token_account(program_test, token_vault_a, source_mint, whirlpool, reserve);
+        add_token_account(program_test, token_vault_b, dest_mint, whirlpool, reserve);
+        let mut metas = vec![
+            AccountMeta::new_readonly(whirlpool_program_id, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(whirlpool, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(token_vault_a, false),
+            AccountMeta::new(dest, false),
+            AccountMeta::new(token_vault_b, false),
+        ];
+        // tick array 0 / 1 / 2 와 oracle 은 stub 이 읽지 않음
+        for _ in 0..4 {
+            metas.push(AccountMeta::new(Pubkey::new_unique(), false));
+        }
+        metas
+    }
+
+    /// stub jupiter 와 `source_mint` -> `dest_mint` route 를 추가하고 jupiter swap accounts 를 돌려줌
+    /// route data 는 `jupiter_route_data` 로 만듦
+    pub fn add_stub_jupiter(
+        &self,
+        program_test: &mut ProgramTest,
+        source: Pubkey,
+        source_mint: Pubkey,
+        dest: Pubkey,
+        dest_mint: Pubkey,
+        reserve: u64,
+    ) -> Vec<AccountMeta> {
+        let jupiter_program_id = JUPITER_PROGRAM_ID[0];
+        program_test.add_program(
+            "stub_jupiter",
+            jupiter_program_id,
+            processor!(process_stub_jupiter),
+        );
+        let (pool_signer, _) =
+            Pubkey::find_program_address(&[STUB_JUPITER_SEED], &jupiter_program_id);
+        let pool_in = Pubkey::new_unique();
+        let pool_out = Pubkey::new_unique();
+        add_token_account(program_test, pool_in, source_mint, pool_signer, 0);
+        add_token_account(program_test, pool_out, dest_mint, pool_signer, reserve);
+        vec![
+            AccountMeta::new_readonly(jupiter_program_id, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(dest, false),
+            // route accounts
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(dest, false),
+            AccountMeta::new(pool_in, false),
+            AccountMeta::new(pool_out, false),
+            AccountMeta::new_readonly(pool_signer, false),
+        ]
+    }
+
+    pub fn user_address(&self, user: &Pubkey) -> Pubkey {
+        find_user_address(&self.vault, user, &self.strategy, &self.program_id).0
+    }
+
+    pub fn admin_accounts(&self) -> AdminAccounts {
+        AdminAccounts {
+            admin: self.admin.pubkey(),
+            gauntlet_state: self.gauntlet,
+        }
+    }
+
+    pub fn vault_strategy_accounts(&self) -> VaultStrategyAccounts {
+        VaultStrategyAccounts {
+            vault_strategy_state: self.vault_strategy,
+            vault_state: self.vault,
+            strategy_state: self.strategy,
+        }
+    }
+
+    /// depositor with SOL for rent and an LP account holding `lp_amount`
+    pub fn add_depositor(
+        &self,
+        program_test: &mut ProgramTest,
+        lp_amount: u64,
+    ) -> (Keypair, Pubkey) {
+        let depositor = Keypair::new();
+        program_test.add_account(
+            depositor.pubkey(),
+            Account {
+                lamports: 10_000_000_000,
+                data: vec![],
+                owner: solana_program::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        let depositor_lp = Pubkey::new_unique();
+        add_token_account(
+            program_test,
+            depositor_lp,
+            self.lp_mint,
+            depositor.pubkey(),
+            lp_amount,
+        );
+        (depositor, depositor_lp)
+    }
+}
+
+/// stub jupiter route 가 source 에서 `amount_in` 을 가져가고 dest 로 `amount_out` 을 보내는 route data
+pub fn jupiter_route_data(amount_in: u64, amount_out: u64) -> Vec<u8> {
+    let mut data = amount_in.to_le_bytes().to_vec();
+    data.extend_from_slice(&amount_out.to_le_bytes());
+    data
+}
+
+/// ProgramTest::start 가 program-test 의 stub 을 설치한 뒤에 한번만 ProgramAccountStubs 로 감쌈
+pub async fn start(program_test: ProgramTest) -> (BanksClient, Keypair) {
+    let (mut banks_client, payer, _recent_blockhash) = program_test.start().await;
+    let mut program_accounts = vec![];
+    for program_id in [
+        spl_token::id(),
+        TOKEN_2022_PROGRAM_ID,
+        solana_program::system_program::id(),
+    ] {
+        let account = banks_client.get_account(program_id).await.unwrap().unwrap();
+        program_accounts.push((program_id, account));
+    }
+    static WRAP_STUBS: Once = Once::new();
+    WRAP_STUBS.call_once(|| {
+        let inner = program_stubs::set_syscall_stubs(Box::new(DefaultStubs));
+        program_stubs::set_syscall_stubs(Box::new(ProgramAccountStubs {
+            inner,
+            program_accounts,
+        }));
+    });
+    (banks_client, payer)
+}
+
+pub fn add_packed<T: Pack>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    state: &T,
+    owner: &Pubkey,
+) {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+pub fn add_mint(program_test: &mut ProgramTest, address: Pubkey) {
+    let mint = spl_token::state::Mint {
+        mint_authority: Some(Pubkey::new_unique()).into(),
+        supply: u64::MAX,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: None.into(),
+    };
+    add_packed(program_test, address, &mint, &spl_token::id());
+}
+
+/// Token-2022 transfer fee mint, process_stub_token_2022 가 읽는 layout
+pub fn add_token_2022_fee_mint(program_test: &mut ProgramTest, address: Pubkey, fee_bps: u16) {
+    let mint = spl_token::state::Mint {
+        mint_authority: Some(Pubkey::new_unique()).into(),
+        supply: u64::MAX,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: None.into(),
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    mint.pack_into_slice(&mut data[..spl_token::state::Mint::LEN]);
+    data.push(1);
+    data.extend_from_slice(&fee_bps.to_le_bytes());
+    add_raw(program_test, address, data, &TOKEN_2022_PROGRAM_ID);
+}
+
+/// base account 뒤에 account type 이 붙은 Token-2022 token account
+pub fn add_token_2022_account(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) {
+    let account = spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    account.pack_into_slice(&mut data);
+    data.push(2);
+    add_raw(program_test, address, data, &TOKEN_2022_PROGRAM_ID);
+}
+
+fn add_raw(program_test: &mut ProgramTest, address: Pubkey, data: Vec<u8>, owner: &Pubkey) {
+    program_test.add_account(
+        address,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+pub fn add_token_account(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) {
+    // wSOL 은 add_packed 의 lamports 중 amount 를 뺀 나머지를 rent reserve 로 둠
+    let is_native = match mint == spl_token::native_mint::id() {
+        true => Some(1_000_000_000 - amount),
+        false => None,
+    };
+    let account = spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: is_native.into(),
+        ..spl_token::state::Account::default()
+    };
+    add_packed(program_test, address, &account, &spl_token::id());
+}
+
+pub async fn get_packed<T: Pack>(banks_client: &mut BanksClient, address: Pubkey) -> T {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+    T::unpack_unchecked(&account.data).unwrap()
+}
+
+/// extension 을 건너뛰고 base account 만 읽음
+pub async fn get_token_2022_account(
+    banks_client: &mut BanksClient,
+    address: Pubkey,
+) -> spl_token::state::Account {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data[..spl_token::state::Account::LEN]).unwrap()
+}
+
+pub async fn get_user(banks_client: &mut BanksClient, address: Pubkey) -> User {
+    get_packed(banks_client, address).await
+}
+
+pub async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    process_all(banks_client, payer, &[instruction], signers).await
+}
+
+/// several instructions in one transaction, e.g. an ed25519 instruction before the one reading it
+pub async fn process_all(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+/// custom program error code of a failed transaction
+pub fn custom_error(result: Result<(), TransportError>) -> Option<u32> {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            solana_program::instruction::InstructionError::Custom(code),
+        ))) => Some(code),
+        _ => None,
+    }
+}
+
+/// instruction error of a failed transaction, for errors that are not GauntletError
+pub fn instruction_error(
+    result: Result<(), TransportError>,
+) -> Option<solana_program::instruction::InstructionError> {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(_, error))) => {
+            Some(error)
+        }
+        _ => None,
+    }
+}
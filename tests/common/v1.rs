@@ -0,0 +1,346 @@
+//! 처음 배포된 (v1) program 의 state layout, baseline 의 Pack 코드를 그대로 옮겨옴
+//! migration test 에서 실제 v1 account bytes 를 만드는 데만 씀
+use arrayref::{array_mut_ref, mut_array_refs};
+use solana_program::{
+    clock::UnixTimestamp,
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+pub const MAX_NUMBER_OF_STRATEGY: usize = 50;
+pub const MAX_NUMBER_OF_VAULTS: usize = 50;
+
+/// v1 Fees : performance / withdrawal 만 있음
+#[derive(Default)]
+pub struct Fees {
+    pub performance_fee_numerator: u64,
+    pub performance_fee_denominator: u64,
+    pub withdrawal_fee_numerator: u64,
+    pub withdrawal_fee_denominator: u64,
+}
+
+impl Fees {
+    const LEN: usize = 32;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 32];
+        let (
+            performance_fee_numerator,
+            performance_fee_denominator,
+            withdrawal_fee_numerator,
+            withdrawal_fee_denominator,
+        ) = mut_array_refs![output, 8, 8, 8, 8];
+        *performance_fee_numerator = self.performance_fee_numerator.to_le_bytes();
+        *performance_fee_denominator = self.performance_fee_denominator.to_le_bytes();
+        *withdrawal_fee_numerator = self.withdrawal_fee_numerator.to_le_bytes();
+        *withdrawal_fee_denominator = self.withdrawal_fee_denominator.to_le_bytes();
+    }
+}
+
+pub struct Gauntlet {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub strategies_len: u8,
+    pub vaults_len: u8,
+    pub usdc_token_account: Pubkey,
+}
+
+impl Sealed for Gauntlet {}
+impl Pack for Gauntlet {
+    const LEN: usize = 1 + 32 + 8 + 8 + 32; // 81
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, Gauntlet::LEN];
+        let (is_initialized, admin, strategies_len, vaults_len, usdc_token_account) =
+            mut_array_refs![output, 1, 32, 8, 8, 32];
+
+        is_initialized[0] = self.is_initialized as u8;
+        admin.copy_from_slice(self.admin.as_ref());
+        strategies_len[0] = self.strategies_len;
+        vaults_len[0] = self.vaults_len;
+        usdc_token_account.copy_from_slice(self.usdc_token_account.as_ref());
+    }
+
+    fn unpack_from_slice(_input: &[u8]) -> Result<Self, ProgramError> {
+        // v1 account 는 쓰기만 함
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+pub struct User {
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    pub vault_account: Pubkey,
+    pub strategy_account: Pubkey,
+    pub amount: u64,
+    pub reward: u64,
+    pub reward_debt: u64,
+    pub user_status: u8,
+    pub deadline: UnixTimestamp,
+}
+
+impl Sealed for User {}
+impl Pack for User {
+    const LEN: usize = 130;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, User::LEN];
+        let (
+            is_initialized,
+            user,
+            vault_account,
+            strategy_account,
+            amount,
+            reward,
+            reward_debt,
+            user_status,
+            deadline,
+        ) = mut_array_refs![output, 1, 32, 32, 32, 8, 8, 8, 1, 8];
+
+        is_initialized[0] = self.is_initialized as u8;
+        user.copy_from_slice(self.user.as_ref());
+        vault_account.copy_from_slice(self.vault_account.as_ref());
+        strategy_account.copy_from_slice(self.strategy_account.as_ref());
+        *amount = self.amount.to_le_bytes();
+        *reward = self.reward.to_le_bytes();
+        *reward_debt = self.reward_debt.to_le_bytes();
+        user_status[0] = self.user_status;
+        *deadline = self.deadline.to_le_bytes();
+    }
+
+    fn unpack_from_slice(_input: &[u8]) -> Result<Self, ProgramError> {
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+pub struct Vault {
+    pub is_initialized: bool,
+    pub index: u8,
+    /// 0 PAUSED, 1 NORMAL
+    pub status: u8,
+    pub fees: Fees,
+    pub gauntlet_state_account: Pubkey,
+    pub deposit_token_account: Pubkey,
+    pub reward_token_account: Pubkey,
+    pub reward_token_b_account: Pubkey,
+    pub withdraw_fee_account: Pubkey,
+    pub total_deposit_amount: u64,
+    pub deposit_amounts: Vec<u64>,
+    pub reward_token_remain_amounts: Vec<u64>,
+    pub reward_token_b_remain_amounts: Vec<u64>,
+    pub usdc_token_amounts: Vec<u64>,
+    pub accumulated_reward_per_shares: Vec<u128>,
+    pub last_reward_update_time: UnixTimestamp,
+    pub raydium_state_account: Pubkey,
+}
+
+impl Sealed for Vault {}
+impl Pack for Vault {
+    const LEN: usize = 1
+        + 1
+        + 1
+        + Fees::LEN
+        + 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 8
+        + 8 * 4 * MAX_NUMBER_OF_STRATEGY
+        + 16 * MAX_NUMBER_OF_STRATEGY
+        + 8
+        + 32;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, Vault::LEN];
+        let (
+            is_initialized,
+            index,
+            status,
+            fees,
+            gauntlet_state_account,
+            deposit_token_account,
+            reward_token_account,
+            reward_token_b_account,
+            withdraw_fee_account,
+            total_deposit_amount,
+            deposit_amounts,
+            reward_token_remain_amounts,
+            reward_token_b_remain_amounts,
+            usdc_token_amounts,
+            accumulated_reward_per_shares,
+            last_reward_update_time,
+            raydium_state_account,
+        ) = mut_array_refs![
+            output,
+            1,
+            1,
+            1,
+            Fees::LEN,
+            32,
+            32,
+            32,
+            32,
+            32,
+            8,
+            8 * MAX_NUMBER_OF_STRATEGY,
+            8 * MAX_NUMBER_OF_STRATEGY,
+            8 * MAX_NUMBER_OF_STRATEGY,
+            8 * MAX_NUMBER_OF_STRATEGY,
+            16 * MAX_NUMBER_OF_STRATEGY,
+            8,
+            32
+        ];
+        is_initialized[0] = self.is_initialized as u8;
+        index[0] = self.index;
+        status[0] = self.status;
+        self.fees.pack_into_slice(&mut fees[..]);
+        gauntlet_state_account.copy_from_slice(self.gauntlet_state_account.as_ref());
+        deposit_token_account.copy_from_slice(self.deposit_token_account.as_ref());
+        reward_token_account.copy_from_slice(self.reward_token_account.as_ref());
+        reward_token_b_account.copy_from_slice(self.reward_token_b_account.as_ref());
+        withdraw_fee_account.copy_from_slice(self.withdraw_fee_account.as_ref());
+        *total_deposit_amount = self.total_deposit_amount.to_le_bytes();
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![deposit_amounts, i * 8, 8];
+            *arr_ref = self.deposit_amounts[i].to_le_bytes();
+        }
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![reward_token_remain_amounts, i * 8, 8];
+            *arr_ref = self.reward_token_remain_amounts[i].to_le_bytes();
+        }
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![reward_token_b_remain_amounts, i * 8, 8];
+            *arr_ref = self.reward_token_b_remain_amounts[i].to_le_bytes();
+        }
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![usdc_token_amounts, i * 8, 8];
+            *arr_ref = self.usdc_token_amounts[i].to_le_bytes();
+        }
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![accumulated_reward_per_shares, i * 16, 16];
+            *arr_ref = self.accumulated_reward_per_shares[i].to_le_bytes();
+        }
+        *last_reward_update_time = self.last_reward_update_time.to_le_bytes();
+        raydium_state_account.copy_from_slice(self.raydium_state_account.as_ref());
+    }
+
+    fn unpack_from_slice(_input: &[u8]) -> Result<Self, ProgramError> {
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+pub struct Strategy {
+    pub is_initialized: bool,
+    pub index: u8,
+    pub gauntlet_state_account: Pubkey,
+    pub admin: Pubkey,
+    pub performance_fee_account: Pubkey,
+    /// 0 PAUSED, 1 NORMAL
+    pub status: u8,
+    pub last_reward_update_time: UnixTimestamp,
+    pub total_deposit_amount: u64,
+    pub deposit_amounts: Vec<u64>,
+    pub strategy_token_account: Pubkey,
+}
+
+impl Sealed for Strategy {}
+impl Pack for Strategy {
+    const LEN: usize = 1 + 1 + 32 + 32 + 32 + 1 + 8 + 8 + 8 * MAX_NUMBER_OF_VAULTS + 32;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, Strategy::LEN];
+        let (
+            is_initialized,
+            index,
+            gauntlet_state_account,
+            admin,
+            performance_fee_account,
+            status,
+            last_reward_update_time,
+            total_deposit_amount,
+            deposit_amounts,
+            strategy_token_account,
+        ) = mut_array_refs![
+            output,
+            1,
+            1,
+            32,
+            32,
+            32,
+            1,
+            8,
+            8,
+            8 * MAX_NUMBER_OF_VAULTS,
+            32
+        ];
+
+        is_initialized[0] = self.is_initialized as u8;
+        index[0] = self.index;
+        gauntlet_state_account.copy_from_slice(self.gauntlet_state_account.as_ref());
+        admin.copy_from_slice(self.admin.as_ref());
+        performance_fee_account.copy_from_slice(self.performance_fee_account.as_ref());
+        status[0] = self.status;
+        *last_reward_update_time = self.last_reward_update_time.to_le_bytes();
+        *total_deposit_amount = self.total_deposit_amount.to_le_bytes();
+        for i in 0..MAX_NUMBER_OF_VAULTS {
+            let strategy_deposit_amount = array_mut_ref![deposit_amounts, i * 8, 8];
+            *strategy_deposit_amount = self.deposit_amounts[i].to_le_bytes();
+        }
+        strategy_token_account.copy_from_slice(self.strategy_token_account.as_ref());
+    }
+
+    fn unpack_from_slice(_input: &[u8]) -> Result<Self, ProgramError> {
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+pub struct VaultStrategy {
+    pub is_initialized: bool,
+    pub vault_account: Pubkey,
+    pub needs_usdc_pools: Vec<bool>,
+    pub availabilities: Vec<bool>,
+    pub strategy_token_amounts: Vec<u64>,
+}
+
+impl Sealed for VaultStrategy {}
+impl Pack for VaultStrategy {
+    const LEN: usize =
+        1 + 32 + 8 * MAX_NUMBER_OF_STRATEGY + MAX_NUMBER_OF_STRATEGY + MAX_NUMBER_OF_STRATEGY;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, VaultStrategy::LEN];
+        let (
+            is_initialized,
+            vault_account,
+            needs_usdc_pools,
+            availabilities,
+            strategy_token_amounts,
+        ) = mut_array_refs![
+            output,
+            1,
+            32,
+            MAX_NUMBER_OF_STRATEGY,
+            MAX_NUMBER_OF_STRATEGY,
+            8 * MAX_NUMBER_OF_STRATEGY
+        ];
+
+        is_initialized[0] = self.is_initialized as u8;
+        vault_account.copy_from_slice(self.vault_account.as_ref());
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![needs_usdc_pools, i, 1];
+            arr_ref[0] = self.needs_usdc_pools[i] as u8;
+        }
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![availabilities, i, 1];
+            arr_ref[0] = self.availabilities[i] as u8;
+        }
+        for i in 0..MAX_NUMBER_OF_STRATEGY {
+            let arr_ref = array_mut_ref![strategy_token_amounts, i * 8, 8];
+            *arr_ref = self.strategy_token_amounts[i].to_le_bytes();
+        }
+    }
+
+    fn unpack_from_slice(_input: &[u8]) -> Result<Self, ProgramError> {
+        Err(ProgramError::InvalidAccountData)
+    }
+}
@@ -0,0 +1,859 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use common::*;
+use gauntlet_program::{
+    error::GauntletError,
+    instruction::{DepositType, GauntletInstruction},
+    instruction_builder::{self, DepositAccounts},
+    state::{User, Vault, VaultStrategy, USER_STATUS_IDLE},
+    utils::TOKEN_2022_PROGRAM_ID,
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+};
+
+fn deposit_accounts(
+    fixture: &Fixture,
+    depositor: &dyn Signer,
+    depositor_lp: Pubkey,
+    create_user: bool,
+) -> DepositAccounts {
+    DepositAccounts {
+        depositor: depositor.pubkey(),
+        depositor_user_state: fixture.user_address(&depositor.pubkey()),
+        depositor_deposit_token: depositor_lp,
+        gauntlet_state: fixture.gauntlet,
+        vault_state: fixture.vault,
+        vault_strategy_state: fixture.vault_strategy,
+        strategy_state: fixture.strategy,
+        deposit_accounts: fixture.staking_accounts(),
+        deposit_token_mint: fixture.lp_mint,
+        deposit_fee_token: None,
+        referral_token: None,
+        create_user,
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_new_user_skips_swap_steps() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    // native program-test 는 CPI 로 account 크기를 바꾸지 못하므로 deposit 이 만드는 것과 같은 user 를 미리 넣음
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 방금 만든 user (status IDLE) 는 정산할 reward 가 없으므로 swap 단계를 거치지 않고 바로 deposit
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            600,
+            DepositType::RAYDIUM,
+        ),
+        &[&depositor],
+    )
+    .await
+    .unwrap();
+
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 600);
+    assert_eq!(user.user_status, USER_STATUS_IDLE);
+    let pool_lp: spl_token::state::Account = get_packed(&mut banks_client, fixture.pool_lp).await;
+    assert_eq!(pool_lp.amount, 600);
+
+    // 이미 있는 user 는 harvest / swap 을 끝내 READY 가 되어야 다시 deposit 가능
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            400,
+            DepositType::RAYDIUM,
+        ),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::UserStatusError as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_rejects_user_state_at_another_address() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    // 내용은 depositor 의 user 와 같지만 PDA 가 아닌 주소
+    let foreign_user = Pubkey::new_unique();
+    add_packed(
+        &mut program_test,
+        foreign_user,
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, false);
+    accounts.depositor_user_state = foreign_user;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 600, DepositType::RAYDIUM),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_fails_when_raydium_stakes_less_than_received() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    // stub staking 이 100 LP 를 stake 하지 않고 vault LP account 에 남김
+    let mut pool_data = vec![0; 16];
+    pool_data[8..16].copy_from_slice(&100u64.to_le_bytes());
+    program_test.add_account(
+        fixture.pool_id,
+        Account {
+            lamports: 1_000_000_000,
+            data: pool_data,
+            owner: fixture.staking_program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            600,
+            DepositType::RAYDIUM,
+        ),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::AccountingDesync as u32)
+    );
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 0);
+}
+
+#[tokio::test]
+async fn test_deposit_splits_fee_with_referral() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.fees.deposit_fee_numerator = 1;
+    vault.fees.deposit_fee_denominator = 10;
+    vault.fees.referral_fee_numerator = 1;
+    vault.fees.referral_fee_denominator = 2;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let referrer = Pubkey::new_unique();
+    let referral_lp = Pubkey::new_unique();
+    add_token_account(&mut program_test, referral_lp, fixture.lp_mint, referrer, 0);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // referral 만 넘기면 fee account 로 읽지 않고, fee account 가 없으므로 거부
+    let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, false);
+    accounts.referral_token = Some(referral_lp);
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 1_000, DepositType::RAYDIUM),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+
+    let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, false);
+    accounts.deposit_fee_token = Some(fixture.fee_account);
+    accounts.referral_token = Some(referral_lp);
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 1_000, DepositType::RAYDIUM),
+        &[&depositor],
+    )
+    .await
+    .unwrap();
+
+    // fee 100 중 절반은 referral 에게, 나머지 900 을 stake
+    let referral: spl_token::state::Account = get_packed(&mut banks_client, referral_lp).await;
+    assert_eq!(referral.amount, 50);
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, fixture.fee_account).await;
+    assert_eq!(fee.amount, 50);
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 900);
+    assert_eq!(user.referrer, referrer);
+}
+
+#[tokio::test]
+async fn test_deposit_creates_user_only_when_flagged() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.fees.deposit_fee_numerator = 1;
+    vault.fees.deposit_fee_denominator = 10;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (new_depositor, new_depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // flag 가 없으면 아직 없는 user account 를 만들지 않음
+    let mut accounts = deposit_accounts(&fixture, &new_depositor, new_depositor_lp, false);
+    accounts.deposit_fee_token = Some(fixture.fee_account);
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 1_000, DepositType::RAYDIUM),
+        &[&new_depositor],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::IncorrectProgramId)
+    );
+
+    // system program 은 fee account 뒤에서 읽고, 이미 있는 user 는 그대로 씀
+    let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, true);
+    accounts.deposit_fee_token = Some(fixture.fee_account);
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 1_000, DepositType::RAYDIUM),
+        &[&depositor],
+    )
+    .await
+    .unwrap();
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, fixture.fee_account).await;
+    assert_eq!(fee.amount, 100);
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 900);
+}
+
+#[tokio::test]
+async fn test_deposit_fee_goes_to_deposit_fee_account() {
+    let fixture = Fixture::new();
+    let deposit_fee_lp = Pubkey::new_unique();
+    let mut vault = fixture.vault_state();
+    vault.fees.deposit_fee_numerator = 1;
+    vault.fees.deposit_fee_denominator = 10;
+    vault.deposit_fee_account = deposit_fee_lp;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_token_account(
+        &mut program_test,
+        deposit_fee_lp,
+        fixture.lp_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let deposit_with_fee_account = |deposit_fee_token| {
+        let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, false);
+        accounts.deposit_fee_token = Some(deposit_fee_token);
+        instruction_builder::deposit(&fixture.program_id, accounts, 1_000, DepositType::RAYDIUM)
+    };
+
+    // withdraw fee account 로는 deposit fee 를 받지 않음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        deposit_with_fee_account(vault.withdraw_fee_account),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        deposit_with_fee_account(deposit_fee_lp),
+        &[&depositor],
+    )
+    .await
+    .unwrap();
+    let fee: spl_token::state::Account = get_packed(&mut banks_client, deposit_fee_lp).await;
+    assert_eq!(fee.amount, 100);
+    let withdraw_fee: spl_token::state::Account =
+        get_packed(&mut banks_client, vault.withdraw_fee_account).await;
+    assert_eq!(withdraw_fee.amount, 0);
+}
+
+#[tokio::test]
+async fn test_deposit_rejected_while_strategy_closed_to_deposits() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.total_deposit_amount = 500;
+    vault.deposit_amounts[0] = 500;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::set_accepting_deposits(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault_strategy_accounts(),
+            false,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    // availability 와 기존 예치금은 그대로
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(vault_strategy.availabilities[0]);
+    assert!(!vault_strategy.accepting_deposits[0]);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.total_deposit_amount, 500);
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            600,
+            DepositType::RAYDIUM,
+        ),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::DepositsClosed as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_set_accepting_deposits_rejects_foreign_state_accounts() {
+    // 같은 layout 이라도 다른 program 이 소유한 state account 는 unpack 전에 거부
+    for foreign in 0..4 {
+        let fixture = Fixture::new();
+        let mut program_test = fixture.program_test(
+            &fixture.vault_state(),
+            &fixture.vault_strategy_state(),
+            &fixture.strategy_state(),
+        );
+        let other_program = Pubkey::new_unique();
+        match foreign {
+            0 => add_packed(
+                &mut program_test,
+                fixture.gauntlet,
+                &fixture.gauntlet_state(),
+                &other_program,
+            ),
+            1 => add_packed(
+                &mut program_test,
+                fixture.vault_strategy,
+                &fixture.vault_strategy_state(),
+                &other_program,
+            ),
+            2 => add_packed(
+                &mut program_test,
+                fixture.vault,
+                &fixture.vault_state(),
+                &other_program,
+            ),
+            _ => add_packed(
+                &mut program_test,
+                fixture.strategy,
+                &fixture.strategy_state(),
+                &other_program,
+            ),
+        }
+        let (mut banks_client, payer) = start(program_test).await;
+        let result = process(
+            &mut banks_client,
+            &payer,
+            instruction_builder::set_accepting_deposits(
+                &fixture.program_id,
+                &fixture.admin_accounts(),
+                &fixture.vault_strategy_accounts(),
+                false,
+                0,
+            ),
+            &[&fixture.admin],
+        )
+        .await;
+        assert_eq!(
+            instruction_error(result),
+            Some(InstructionError::IncorrectProgramId)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_rejected_while_vault_deposits_disabled() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // vault 단위로 deposit 만 막고 withdraw 는 열어둠
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::set_vault_flags(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault,
+            false,
+            true,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert!(!vault.deposits_enabled);
+    assert!(vault.withdrawals_enabled);
+
+    // strategy 는 여전히 deposit 을 받고 있어도 vault 에서 막힘
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            600,
+            DepositType::RAYDIUM,
+        ),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::DepositsClosed as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_below_min_deposit_rejected() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.min_deposit = 500;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let deposit = |amount| {
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            amount,
+            DepositType::RAYDIUM,
+        )
+    };
+
+    let result = process(&mut banks_client, &payer, deposit(499), &[&depositor]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::DepositTooSmall as u32)
+    );
+
+    process(&mut banks_client, &payer, deposit(500), &[&depositor])
+        .await
+        .unwrap();
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 500);
+}
+
+#[tokio::test]
+async fn test_deposit_type_must_match_vault_reward_count() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.has_second_reward = true;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // second reward 가 있는 vault 는 v4 staking 으로만 deposit 할 수 있음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &depositor, depositor_lp, false),
+            600,
+            DepositType::RAYDIUM,
+        ),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::DepositTypeError as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_keeps_first_referrer() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let first_referrer = Pubkey::new_unique();
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    let mut user = User::init(depositor.pubkey(), fixture.vault, fixture.strategy);
+    user.referrer = first_referrer;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &user,
+        &fixture.program_id,
+    );
+    let (self_referred, self_referred_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&self_referred.pubkey()),
+        &User::init(self_referred.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let other_referral_lp = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        other_referral_lp,
+        fixture.lp_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let self_referral_lp = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        self_referral_lp,
+        fixture.lp_mint,
+        self_referred.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let deposit_with_referral = |depositor: &dyn Signer, depositor_lp, referral_token| {
+        let mut accounts = deposit_accounts(&fixture, depositor, depositor_lp, false);
+        accounts.deposit_fee_token = Some(fixture.fee_account);
+        accounts.referral_token = Some(referral_token);
+        instruction_builder::deposit(&fixture.program_id, accounts, 1_000, DepositType::RAYDIUM)
+    };
+
+    // 자기 자신을 referrer 로 기록할 수 없음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        deposit_with_referral(&self_referred, self_referred_lp, self_referral_lp),
+        &[&self_referred],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidAccount as u32)
+    );
+
+    // 이미 기록된 referrer 는 다른 referral account 를 넘겨도 바뀌지 않음
+    process(
+        &mut banks_client,
+        &payer,
+        deposit_with_referral(&depositor, depositor_lp, other_referral_lp),
+        &[&depositor],
+    )
+    .await
+    .unwrap();
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.referrer, first_referrer);
+    assert_eq!(user.amount, 1_000);
+}
+
+fn create_user_account(fixture: &Fixture, depositor: Pubkey) -> Instruction {
+    instruction_builder::create_user_account(
+        &fixture.program_id,
+        &depositor,
+        &fixture.vault,
+        &fixture.strategy,
+    )
+}
+
+#[tokio::test]
+async fn test_create_user_account_initializes_once() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, _) = fixture.add_depositor(&mut program_test, 0);
+    let mut user = User::init(depositor.pubkey(), fixture.vault, fixture.strategy);
+    user.amount = 600;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &user,
+        &fixture.program_id,
+    );
+    // native program-test 는 CPI 로 account 를 만들지 못하므로 할당만 된 빈 user account 를 미리 넣음
+    let (new_depositor, new_depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    program_test.add_account(
+        fixture.user_address(&new_depositor.pubkey()),
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; User::LEN],
+            owner: fixture.program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 이미 있는 user 는 건드리지 않음
+    process(
+        &mut banks_client,
+        &payer,
+        create_user_account(&fixture, depositor.pubkey()),
+        &[&depositor],
+    )
+    .await
+    .unwrap();
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 600);
+
+    // deposit 은 초기화되지 않은 user account 를 쓰지 않음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(
+            &fixture.program_id,
+            deposit_accounts(&fixture, &new_depositor, new_depositor_lp, false),
+            600,
+            DepositType::RAYDIUM,
+        ),
+        &[&new_depositor],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::UninitializedAccount)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        create_user_account(&fixture, new_depositor.pubkey()),
+        &[&new_depositor],
+    )
+    .await
+    .unwrap();
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&new_depositor.pubkey()),
+    )
+    .await;
+    assert!(user.is_initialized);
+    assert_eq!(user.user, new_depositor.pubkey());
+    assert_eq!(user.vault_account, fixture.vault);
+    assert_eq!(user.strategy_account, fixture.strategy);
+    assert_eq!(user.amount, 0);
+}
+
+#[test]
+fn test_deposit_builder_matches_program_layout() {
+    let fixture = Fixture::new();
+    let depositor = Keypair::new();
+    let mut accounts = deposit_accounts(&fixture, &depositor, Pubkey::new_unique(), true);
+    accounts.deposit_fee_token = Some(fixture.fee_account);
+    let instruction =
+        instruction_builder::deposit(&fixture.program_id, accounts, 600, DepositType::RAYDIUM_V4);
+
+    assert!(matches!(
+        GauntletInstruction::unpack(&instruction.data),
+        Ok(GauntletInstruction::Deposit {
+            amount: 600,
+            deposit_type: DepositType::RAYDIUM_V4,
+            with_deposit_fee_account: true,
+            with_referral_account: false,
+            create_user: true,
+        })
+    ));
+    // 새 user 를 만들 때는 depositor 가 rent 를 내므로 writable signer, system program 이 마지막
+    assert!(instruction.accounts[0].is_signer && instruction.accounts[0].is_writable);
+    // deposit LP mint 다음에 fee account
+    let fee_index = 7 + fixture.staking_accounts().len() + 1;
+    assert_eq!(instruction.accounts[fee_index].pubkey, fixture.fee_account);
+    assert_eq!(
+        instruction.accounts.last().unwrap().pubkey,
+        solana_program::system_program::id()
+    );
+    assert_eq!(instruction.accounts.len(), fee_index + 2);
+}
+
+#[tokio::test]
+async fn test_deposit_checks_deposit_mint_and_token_program() {
+    let fixture = Fixture::new();
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let (depositor, depositor_lp) = fixture.add_depositor(&mut program_test, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&depositor.pubkey()),
+        &User::init(depositor.pubkey(), fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // LP 는 vault.deposit_token_mint 로 transfer_checked 하므로 다른 mint 는 거부
+    let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, false);
+    accounts.deposit_token_mint = fixture.reward_mint;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 600, DepositType::RAYDIUM),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+
+    // vault.token_program 이 아닌 token program 도 거부
+    let mut accounts = deposit_accounts(&fixture, &depositor, depositor_lp, false);
+    accounts.deposit_accounts[10].pubkey = TOKEN_2022_PROGRAM_ID;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::deposit(&fixture.program_id, accounts, 600, DepositType::RAYDIUM),
+        &[&depositor],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+    let user = get_user(&mut banks_client, fixture.user_address(&depositor.pubkey())).await;
+    assert_eq!(user.amount, 0);
+}
@@ -0,0 +1,184 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use common::*;
+use gauntlet_program::{
+    instruction_builder,
+    state::{
+        GauntletConfig, User, UserPosition, VaultTvl, GAUNTLET_CONFIG_VERSION,
+        MAX_NUMBER_OF_STRATEGY, REWARD_PRECISION_SHIFT, USER_POSITION_VERSION, VAULT_TVL_VERSION,
+    },
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::Signer;
+use std::convert::TryInto;
+
+#[tokio::test]
+async fn test_get_config_logs_packed_config() {
+    let fixture = Fixture::new();
+    let mut gauntlet = fixture.gauntlet_state();
+    gauntlet.keepers_len = 2;
+    gauntlet.admin_nonce = 7;
+    gauntlet.global_paused = true;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_packed(
+        &mut program_test,
+        fixture.gauntlet,
+        &gauntlet,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::get_config(&fixture.program_id, &fixture.gauntlet),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // 다른 test 의 log 도 섞여 있으므로 admin 으로 이 gauntlet 의 것을 찾음
+    let config = logged("gauntlet_config: ")
+        .iter()
+        .map(|hex| GauntletConfig::unpack_from_slice(&decode_hex(hex)).unwrap())
+        .find(|config| config.admin == fixture.admin.pubkey())
+        .unwrap();
+    assert_eq!(config.version, GAUNTLET_CONFIG_VERSION);
+    assert_eq!(config.usdc_token_account, gauntlet.usdc_token_account);
+    assert_eq!(config.strategies_len, gauntlet.strategies_len);
+    assert_eq!(config.vaults_len, gauntlet.vaults_len);
+    assert_eq!(config.keepers_len, 2);
+    assert_eq!(config.authority_bump, gauntlet.authority_bump);
+    assert_eq!(config.admin_nonce, 7);
+    assert!(config.global_paused);
+}
+
+#[tokio::test]
+async fn test_get_vault_tvl_logs_packed_tvl() {
+    let fixture = Fixture::new();
+    // 다른 test 의 log 와 구분되도록 흔하지 않은 값을 씀
+    let mut vault = fixture.vault_state();
+    vault.total_deposit_amount = 5_401_001;
+    vault.reward_token_remain_amounts[0] = 11;
+    vault.reward_token_remain_amounts[1] = 22;
+    vault.reward_token_b_remain_amounts[1] = 33;
+    vault.usdc_token_amounts[0] = 44;
+    vault.usdc_token_amounts[2] = 55;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.strategy_token_amounts[0] = 66;
+    vault_strategy.strategy_token_amounts[3] = 77;
+    let program_test = fixture.program_test(&vault, &vault_strategy, &fixture.strategy_state());
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::get_vault_tvl(
+            &fixture.program_id,
+            &fixture.vault,
+            &fixture.vault_strategy,
+        ),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let tvl = logged("vault_tvl: ")
+        .iter()
+        .map(|hex| VaultTvl::unpack_from_slice(&decode_hex(hex)).unwrap())
+        .find(|tvl| tvl.total_deposit_amount == 5_401_001)
+        .unwrap();
+    assert_eq!(tvl.version, VAULT_TVL_VERSION);
+    assert_eq!(tvl.reward_token_remain_amount, 33);
+    assert_eq!(tvl.reward_token_b_remain_amount, 33);
+    assert_eq!(tvl.usdc_token_amount, 99);
+    assert_eq!(tvl.strategy_token_amount, 143);
+}
+
+#[tokio::test]
+async fn test_get_user_position_logs_settled_position() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    // LP 1 당 reward 3, 재예치 LP 0.5
+    vault.accumulated_reward_per_shares[0] = 3 << REWARD_PRECISION_SHIFT;
+    vault.compounded_per_shares[0] = 1 << (REWARD_PRECISION_SHIFT - 1);
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let user_owner = Pubkey::new_unique();
+    let mut user = User::init(user_owner, fixture.vault, fixture.strategy);
+    user.amount = 1_000;
+    user.reward = 9_876_543;
+    let user_address = fixture.user_address(&user_owner);
+    add_packed(&mut program_test, user_address, &user, &fixture.program_id);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::get_user_position(
+            &fixture.program_id,
+            &user_address,
+            &fixture.vault_strategy_accounts(),
+        ),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let position = logged("user_position: ")
+        .iter()
+        .map(|hex| UserPosition::unpack_from_slice(&decode_hex(hex)).unwrap())
+        .find(|position| position.claimable_reward == 9_876_543 + 3_000)
+        .unwrap();
+    assert_eq!(position.version, USER_POSITION_VERSION);
+    assert_eq!(position.amount, 1_500);
+    assert_eq!(position.pending_reward, 3_000);
+}
+
+#[tokio::test]
+async fn test_get_harvest_preview_logs_reward_shares() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 300;
+    vault.deposit_amounts[1] = 100;
+    vault.deposit_amounts[2] = 600;
+    vault.total_deposit_amount = 1_000;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.availabilities[2] = true;
+    let program_test = fixture.program_test(&vault, &vault_strategy, &fixture.strategy_state());
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let instruction = instruction_builder::get_harvest_preview(
+        &fixture.program_id,
+        &fixture.vault,
+        &fixture.vault_strategy,
+        7_770_001,
+    );
+    process(&mut banks_client, &payer, instruction, &[])
+        .await
+        .unwrap();
+
+    let shares: Vec<u64> = logged("harvest_preview: ")
+        .iter()
+        .map(|hex| {
+            decode_hex(hex)
+                .chunks(8)
+                .map(|share| u64::from_le_bytes(share.try_into().unwrap()))
+                .collect::<Vec<u64>>()
+        })
+        .find(|shares| shares[0] == 2_331_000)
+        .unwrap();
+    // strategy index 순서, 내림하고 unavailable strategy 는 0
+    assert_eq!(shares.len(), MAX_NUMBER_OF_STRATEGY);
+    assert_eq!(shares[1], 0);
+    assert_eq!(shares[2], 4_662_000);
+    assert!(shares[3..].iter().all(|&share| share == 0));
+}
@@ -0,0 +1,2621 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use common::*;
+use gauntlet_program::{
+    error::GauntletError,
+    instruction::{DepositType, SwapType, WithdrawType},
+    instruction_builder::{
+        self, CompoundAccounts, HarvestAccounts, SwapAccounts, WithdrawAccounts,
+    },
+    oracle::{
+        PriceAttestation, PYTH_AGG_PRICE_OFFSET, PYTH_AGG_PUB_SLOT_OFFSET, PYTH_AGG_STATUS_OFFSET,
+        PYTH_EXPO_OFFSET, PYTH_MAGIC, PYTH_STATUS_TRADING,
+    },
+    state::{
+        Strategy, User, Vault, VaultStrategy, USER_STATUS_HARVESTED, USER_STATUS_READY,
+        USER_STATUS_REWARDS_SWAPPED,
+    },
+    utils::{OPENBOOK_PROGRAM_ID, POOL_PROGRAM_ID, PYTH_PROGRAM_ID, TOKEN_2022_PROGRAM_ID},
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+};
+use std::convert::TryInto;
+
+/// strategy token 이 vault 의 deposit LP 인 strategy 로 1_000 reward 를 swap (stub amm 으로 996 LP)
+/// 재예치 여부는 vault strategy 의 compounding 표시를 따름
+async fn swap_reward_to_deposit_lp(fixture: &Fixture, compounding: bool) -> (BanksClient, Pubkey) {
+    let lp_strategy_token = Pubkey::new_unique();
+    let mut strategy = fixture.strategy_state();
+    strategy.strategy_token_account = lp_strategy_token;
+    strategy.strategy_token_mint = fixture.lp_mint;
+    strategy.routed_deposit_amount = 10_000;
+    let mut vault = fixture.vault_state();
+    vault.compound_ratio_bps = 5_000;
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = 1_000;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.compounding[0] = compounding;
+    let mut program_test = fixture.program_test(&vault, &vault_strategy, &strategy);
+    add_token_account(
+        &mut program_test,
+        lp_strategy_token,
+        fixture.lp_mint,
+        fixture.authority,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        1_000,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        lp_strategy_token,
+        fixture.lp_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let swaper_user_state = fixture.user_address(&keeper);
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_HARVESTED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        swaper_user_state,
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::swap_farm_reward_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper,
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state,
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts,
+                restake_accounts: fixture.restake_accounts(),
+            },
+            SwapType::RAYDIUM,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    (banks_client, lp_strategy_token)
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_deposit_lp_restakes_compound_share() {
+    let fixture = Fixture::new();
+    let (mut banks_client, lp_strategy_token) = swap_reward_to_deposit_lp(&fixture, true).await;
+
+    // 1_000 reward -> 996 LP, 절반은 farm 에 다시 staking 하고 나머지는 claimable 로 strategy 에 남김
+    let restaked = 498;
+    let pool_lp: spl_token::state::Account = get_packed(&mut banks_client, fixture.pool_lp).await;
+    assert_eq!(pool_lp.amount, restaked);
+    let strategy_lp: spl_token::state::Account =
+        get_packed(&mut banks_client, lp_strategy_token).await;
+    assert_eq!(strategy_lp.amount, 996 - restaked);
+
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert_eq!(vault.deposit_amounts[0], 10_000 + restaked);
+    assert_eq!(vault.total_deposit_amount, 10_000 + restaked);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 996 - restaked);
+    // 재예치한 LP 도 deposit 과 같이 cap 기준 합계에 들어감
+    assert_eq!(strategy.routed_deposit_amount, 10_000 + restaked);
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&fixture.admin.pubkey()),
+    )
+    .await;
+    assert_eq!(user.user_status, USER_STATUS_READY);
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_deposit_lp_without_compounding_keeps_all_claimable() {
+    let fixture = Fixture::new();
+    let (mut banks_client, lp_strategy_token) = swap_reward_to_deposit_lp(&fixture, false).await;
+
+    // compounding 으로 표시되지 않은 strategy 는 compound_ratio_bps 와 상관없이 재예치하지 않음
+    let pool_lp: spl_token::state::Account = get_packed(&mut banks_client, fixture.pool_lp).await;
+    assert_eq!(pool_lp.amount, 0);
+    let strategy_lp: spl_token::state::Account =
+        get_packed(&mut banks_client, lp_strategy_token).await;
+    assert_eq!(strategy_lp.amount, 996);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.deposit_amounts[0], 10_000);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 996);
+    assert_eq!(strategy.routed_deposit_amount, 10_000);
+}
+
+/// compound(20) with swap reward -> usdc, reward / usdc add liquidity and restake accounts
+/// add liquidity 는 swap 과 같은 stub amm pool 로 보냄
+fn compound(fixture: &Fixture, swap_accounts: Vec<AccountMeta>) -> Instruction {
+    // add liquidity: program, token program, amm authority, LP mint, pool coin / pc,
+    // [10] coin, [11] pc, [12] LP 받는 account, owner 만 stub 과 program 이 읽음
+    let add_liquidity_accounts = (0..14)
+        .map(|i| {
+            let key = match i {
+                0 | 1 | 3 => swap_accounts[i].pubkey,
+                6 => fixture.lp_mint,
+                7 => swap_accounts[6].pubkey,
+                8 => swap_accounts[7].pubkey,
+                10 => fixture.vault_reward,
+                11 => fixture.vault_usdc,
+                12 => fixture.vault_lp,
+                13 => fixture.authority,
+                _ => Pubkey::new_unique(),
+            };
+            match i {
+                0 | 1 | 3 | 13 => AccountMeta::new_readonly(key, false),
+                _ => AccountMeta::new(key, false),
+            }
+        })
+        .collect();
+    instruction_builder::compound(
+        &fixture.program_id,
+        CompoundAccounts {
+            keeper: fixture.admin.pubkey(),
+            gauntlet_state: fixture.gauntlet,
+            vault_state: fixture.vault,
+            vault_strategy_state: fixture.vault_strategy,
+            strategy_state: fixture.strategy,
+            swap_accounts,
+            add_liquidity_accounts,
+            restake_accounts: fixture.staking_accounts(),
+        },
+        DepositType::RAYDIUM,
+    )
+}
+
+#[tokio::test]
+async fn test_compound_skips_strategy_without_deposit() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.compounding = true;
+    vault.reward_token_remain_amounts[0] = 1_000;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        compound(&fixture, swap_accounts),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    assert!(logged("compound skipped: ").contains(&"strategy 0 has no deposit".to_string()));
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 1_000);
+    assert_eq!(vault.total_deposit_amount, 0);
+}
+
+#[tokio::test]
+async fn test_compound_counts_restaked_lp_toward_deposit_cap_until_withdrawn() {
+    let fixture = Fixture::new();
+    // 2 의 거듭제곱이라 재예치 LP 가 반올림 없이 user 원금으로 배분됨
+    let deposit = 8_192;
+    let mut vault = fixture.vault_state();
+    vault.compounding = true;
+    vault.compound_ratio_bps = 10_000;
+    vault.deposit_amounts[0] = deposit;
+    vault.total_deposit_amount = deposit;
+    vault.reward_token_remain_amounts[0] = 1_000;
+    let mut strategy = fixture.strategy_state();
+    strategy.routed_deposit_amount = deposit;
+    strategy.deposit_cap = deposit + 600;
+    let mut program_test = fixture.program_test(&vault, &fixture.vault_strategy_state(), &strategy);
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        1_000,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        deposit,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    // stub amm 이 add liquidity 에서 LP 를 mint 할 수 있게 함
+    add_packed(
+        &mut program_test,
+        fixture.lp_mint,
+        &spl_token::state::Mint {
+            mint_authority: Some(swap_accounts[3].pubkey).into(),
+            supply: deposit,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        },
+        &spl_token::id(),
+    );
+    let (withdrawer, withdrawer_lp) = fixture.add_depositor(&mut program_test, 0);
+    let withdrawer_reward = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        withdrawer_reward,
+        fixture.reward_mint,
+        withdrawer.pubkey(),
+        0,
+    );
+    let withdrawer_state = fixture.user_address(&withdrawer.pubkey());
+    let mut user = User::init(withdrawer.pubkey(), fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_READY;
+    user.deadline = i64::MAX;
+    user.amount = deposit;
+    add_packed(
+        &mut program_test,
+        withdrawer_state,
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        compound(&fixture, swap_accounts),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // 1_000 reward 중 500 -> 498 usdc, 500 reward 만큼 LP 를 만들어 재예치
+    let lp_amount = 500;
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.deposit_amounts[0], deposit + lp_amount);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.routed_deposit_amount, deposit + lp_amount);
+    // 재예치한 LP 도 cap 을 채우므로 남은 cap 은 100
+    assert!(strategy.check_deposit_cap(100).is_ok());
+    assert!(matches!(
+        strategy.check_deposit_cap(101),
+        Err(GauntletError::DepositCapExceeded)
+    ));
+
+    // 재예치분까지 모두 빼면 routed_deposit_amount 도 정확히 0 이 됨
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            WithdrawAccounts {
+                withdrawer: withdrawer.pubkey(),
+                withdrawer_user_state: withdrawer_state,
+                withdrawer_deposit_token: withdrawer_lp,
+                withdrawer_reward_token: withdrawer_reward,
+                gauntlet_state: fixture.gauntlet,
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                strategy_token: fixture.strategy_token,
+                withdraw_fee_token: fixture.fee_account,
+                performance_fee_token: fixture.fee_account,
+                withdraw_accounts: fixture.staking_accounts(),
+                deposit_token_mint: fixture.lp_mint,
+                strategy_token_mint: fixture.reward_mint,
+                strategy_token_program: spl_token::id(),
+                gas_reimbursement_swap_accounts: vec![],
+                recipient_tokens: None,
+                strategist_fee_token: None,
+            },
+            deposit + lp_amount,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer],
+    )
+    .await
+    .unwrap();
+
+    let withdrawer_lp: spl_token::state::Account =
+        get_packed(&mut banks_client, withdrawer_lp).await;
+    assert_eq!(withdrawer_lp.amount, deposit + lp_amount);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.deposit_amounts[0], 0);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.routed_deposit_amount, 0);
+}
+
+/// harvest 한 `reward` 를 usdc 로 swap 할 차례인 vault, stub amm 은 vault reward -> gauntlet usdc
+fn usdc_swap_program_test(fixture: &Fixture, reward: u64) -> (ProgramTest, Vec<AccountMeta>) {
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.needs_usdc_pools[0] = true;
+    let mut program_test = fixture.program_test(&vault, &vault_strategy, &fixture.strategy_state());
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        reward,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_HARVESTED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    (program_test, swap_accounts)
+}
+
+fn swap_farm_reward_to_usdc(
+    fixture: &Fixture,
+    swap_accounts: &[AccountMeta],
+    oracle_accounts: Vec<AccountMeta>,
+    max_slippage_bps: u64,
+) -> Instruction {
+    swap_farm_reward_to_usdc_via(
+        fixture,
+        SwapType::RAYDIUM,
+        swap_accounts,
+        oracle_accounts,
+        max_slippage_bps,
+        &[],
+    )
+}
+
+fn swap_farm_reward_to_usdc_via(
+    fixture: &Fixture,
+    swap_type: SwapType,
+    swap_accounts: &[AccountMeta],
+    oracle_accounts: Vec<AccountMeta>,
+    max_slippage_bps: u64,
+    route_data: &[u8],
+) -> Instruction {
+    let keeper = fixture.admin.pubkey();
+    instruction_builder::swap_farm_reward_to_usdc(
+        &fixture.program_id,
+        SwapAccounts {
+            swaper: keeper,
+            gauntlet_state: fixture.gauntlet,
+            swaper_user_state: fixture.user_address(&keeper),
+            vault_state: fixture.vault,
+            vault_strategy_state: fixture.vault_strategy,
+            strategy_state: fixture.strategy,
+            oracle_accounts,
+            swap_accounts: swap_accounts.to_vec(),
+            restake_accounts: vec![],
+        },
+        swap_type,
+        max_slippage_bps,
+        route_data,
+    )
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_logs_realized_amounts() {
+    let fixture = Fixture::new();
+    // 다른 test 의 swap_result 와 구분되도록 흔하지 않은 양을 씀
+    let reward = 123_457;
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // log 의 in / out 이 state 와 token account 의 변화량과 같아야 함
+    let out: u64 = logged("swap_result: in ")
+        .iter()
+        .find_map(|amounts| amounts.strip_prefix(&format!("{} out ", reward)))
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(out > 0);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert_eq!(vault.usdc_token_amounts[0], out);
+    let usdc: spl_token::state::Account = get_packed(&mut banks_client, fixture.vault_usdc).await;
+    assert_eq!(usdc.amount, out);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, 0);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_readies_user_when_nothing_pending() {
+    let fixture = Fixture::new();
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, 0);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // swap 할 reward 도 usdc 도 없으면 나머지 swap 단계 없이 바로 READY
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&fixture.admin.pubkey()),
+    )
+    .await;
+    assert_eq!(user.user_status, USER_STATUS_READY);
+    let usdc: spl_token::state::Account = get_packed(&mut banks_client, fixture.vault_usdc).await;
+    assert_eq!(usdc.amount, 0);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_checks_price_attestation() {
+    let fixture = Fixture::new();
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, 10_000);
+    let (mut banks_client, payer) = start(program_test).await;
+    let oracle_accounts = vec![
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new_readonly(fixture.reward_mint, false),
+        AccountMeta::new_readonly(fixture.usdc_mint, false),
+    ];
+    // 같은 decimals 의 1:1 가격, 2% slippage 면 최소 9_800 usdc (stub amm 은 9_876 을 줌)
+    let attestation = |price, expires_at| PriceAttestation {
+        source_mint: fixture.reward_mint,
+        dest_mint: fixture.usdc_mint,
+        price,
+        expo: 0,
+        expires_at,
+    };
+    let sign = |signer: &Keypair, attestation: &PriceAttestation| {
+        let mut message = [0; PriceAttestation::LEN];
+        attestation.pack_into_slice(&mut message);
+        let signature: [u8; 64] = signer.sign_message(&message).as_ref().try_into().unwrap();
+        signature
+    };
+    let attested_swap = |signer: &Keypair, signature: [u8; 64], attestation: &PriceAttestation| {
+        [
+            instruction_builder::price_attestation(&signer.pubkey(), &signature, attestation),
+            swap_farm_reward_to_usdc(&fixture, &swap_accounts, oracle_accounts.clone(), 200),
+        ]
+    };
+
+    // instructions sysvar 를 넘겼는데 앞에 ed25519 instruction 이 없으면 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, oracle_accounts.clone(), 200),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidPriceAttestation as u32)
+    );
+
+    // 서명한 것과 다른 가격을 넘기면 precompile 이 transaction 을 실패시킴
+    let fair_price = attestation(1, i64::MAX);
+    let signature = sign(&fixture.admin, &attestation(2, i64::MAX));
+    let result = process_all(
+        &mut banks_client,
+        &payer,
+        &attested_swap(&fixture.admin, signature, &fair_price),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::InvalidArgument)
+    );
+
+    // keeper 가 아닌 key 의 서명은 거부
+    let outsider = Keypair::new();
+    let result = process_all(
+        &mut banks_client,
+        &payer,
+        &attested_swap(&outsider, sign(&outsider, &fair_price), &fair_price),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(custom_error(result), Some(GauntletError::NotKeeper as u32));
+
+    // 만료된 가격은 거부
+    let expired = attestation(1, 1);
+    let result = process_all(
+        &mut banks_client,
+        &payer,
+        &attested_swap(&fixture.admin, sign(&fixture.admin, &expired), &expired),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidPriceAttestation as u32)
+    );
+
+    // 서명된 가격 대비 realized output 이 부족하면 거부
+    let high_price = attestation(2, i64::MAX);
+    let result = process_all(
+        &mut banks_client,
+        &payer,
+        &attested_swap(
+            &fixture.admin,
+            sign(&fixture.admin, &high_price),
+            &high_price,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::PriceDeviation as u32)
+    );
+
+    process_all(
+        &mut banks_client,
+        &payer,
+        &attested_swap(
+            &fixture.admin,
+            sign(&fixture.admin, &fair_price),
+            &fair_price,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert_eq!(vault.usdc_token_amounts[0], 9_876);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_rejects_uninitialized_vault() {
+    let fixture = Fixture::new();
+    let (mut program_test, swap_accounts) = usdc_swap_program_test(&fixture, 10_000);
+    let mut vault = fixture.vault_state();
+    vault.is_initialized = false;
+    vault.reward_token_remain_amounts[0] = 10_000;
+    add_packed(
+        &mut program_test,
+        fixture.vault,
+        &vault,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongVaultStateAccount as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_single_reward_skips_reward_b_step() {
+    let fixture = Fixture::new();
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, 10_000);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    // reward token b 가 없는 vault 는 REWARD_A_SWAPPED 를 건너뜀
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&fixture.admin.pubkey()),
+    )
+    .await;
+    assert_eq!(user.user_status, USER_STATUS_REWARDS_SWAPPED);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_through_orca_whirlpool() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (mut program_test, _) = usdc_swap_program_test(&fixture, reward);
+    let swap_accounts = fixture.add_stub_whirlpool(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc_via(&fixture, SwapType::ORCA, &swap_accounts, vec![], 0, &[]),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // 1_000_000 * 10_000 / (1_000_000 + 10_000)
+    let usdc: spl_token::state::Account = get_packed(&mut banks_client, fixture.vault_usdc).await;
+    assert_eq!(usdc.amount, 9_900);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert_eq!(vault.usdc_token_amounts[0], 9_900);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, 0);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_rejects_short_orca_accounts() {
+    let fixture = Fixture::new();
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, 1_000);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // orca 는 12 개가 필요, 모자라면 panic 대신 에러
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc_via(
+            &fixture,
+            SwapType::ORCA,
+            &swap_accounts[..11],
+            vec![],
+            0,
+            &[],
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+/// RAY strategy 로 reward 를 옮기는 swap_farm_reward_to_strategy_token, swap accounts 는
+/// (vault reward, strategy token, authority, token program) 뿐
+fn swap_reward_to_ray_strategy(fixture: &Fixture, authority: Pubkey) -> Instruction {
+    let keeper = fixture.admin.pubkey();
+    instruction_builder::swap_farm_reward_to_strategy_token(
+        &fixture.program_id,
+        SwapAccounts {
+            swaper: keeper,
+            gauntlet_state: fixture.gauntlet,
+            swaper_user_state: fixture.user_address(&keeper),
+            vault_state: fixture.vault,
+            vault_strategy_state: fixture.vault_strategy,
+            strategy_state: fixture.strategy,
+            oracle_accounts: vec![],
+            swap_accounts: vec![
+                AccountMeta::new(fixture.vault_reward, false),
+                AccountMeta::new(fixture.strategy_token, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(fixture.reward_mint, false),
+            ],
+            restake_accounts: vec![],
+        },
+        SwapType::RAYDIUM,
+    )
+}
+
+/// fixture strategy 는 reward mint 를 strategy token 으로 쓰는 RAY strategy
+fn ray_strategy_program_test(fixture: &Fixture, reward: u64) -> ProgramTest {
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        reward,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_HARVESTED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    program_test
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_ray_strategy_moves_reward_without_swap() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    let (mut banks_client, payer) = start(ray_strategy_program_test(&fixture, reward)).await;
+
+    // swap accounts 없이 reward / strategy token account, authority, token program 만 넘김
+    process(
+        &mut banks_client,
+        &payer,
+        swap_reward_to_ray_strategy(&fixture, fixture.authority),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // swap fee 없이 reward 가 그대로 strategy token account 로 옮겨짐
+    let strategy_token: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.strategy_token).await;
+    assert_eq!(strategy_token.amount, reward);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], reward);
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&fixture.admin.pubkey()),
+    )
+    .await;
+    assert_eq!(user.user_status, USER_STATUS_READY);
+    // 성공하면 lock 이 풀려 있어야 다음 crank 가 진행됨
+    assert!(!user.locked);
+}
+
+#[tokio::test]
+async fn test_swap_rejects_user_locked_by_in_flight_instruction() {
+    let fixture = Fixture::new();
+    let mut program_test = ray_strategy_program_test(&fixture, 1_000);
+    // 진행 중인 instruction 이 cpi 로 다시 들어오면 user account 는 lock 이 걸린 상태
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_HARVESTED;
+    user.deadline = i64::MAX;
+    user.locked = true;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_reward_to_ray_strategy(&fixture, fixture.authority),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(custom_error(result), Some(GauntletError::UserBusy as u32));
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 1_000);
+    let user = get_user(&mut banks_client, fixture.user_address(&keeper)).await;
+    assert_eq!(user.user_status, USER_STATUS_HARVESTED);
+}
+
+#[tokio::test]
+async fn test_swap_checks_user_account_before_locking() {
+    let keeper_user = |fixture: &Fixture| {
+        let mut user = User::init(fixture.admin.pubkey(), fixture.vault, fixture.strategy);
+        user.user_status = USER_STATUS_HARVESTED;
+        user.deadline = i64::MAX;
+        user
+    };
+    // 다른 program 소유의 account 에는 lock 을 쓰지 않음
+    let fixture = Fixture::new();
+    let mut program_test = ray_strategy_program_test(&fixture, 1_000);
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&fixture.admin.pubkey()),
+        &keeper_user(&fixture),
+        &Pubkey::new_unique(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_reward_to_ray_strategy(&fixture, fixture.authority),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::IncorrectProgramId)
+    );
+
+    // user 의 (vault, user, strategy) seed 로 만든 PDA 가 아니면 거부
+    let fixture = Fixture::new();
+    let mut program_test = ray_strategy_program_test(&fixture, 1_000);
+    let mut user = keeper_user(&fixture);
+    user.strategy_account = Pubkey::new_unique();
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&fixture.admin.pubkey()),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_reward_to_ray_strategy(&fixture, fixture.authority),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_ray_strategy_checks_authority() {
+    let fixture = Fixture::new();
+    let (mut banks_client, payer) = start(ray_strategy_program_test(&fixture, 1_000)).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_reward_to_ray_strategy(&fixture, Pubkey::new_unique()),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidAccount as u32)
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 1_000);
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_token_2022_ray_strategy_counts_received_amount() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    // reward 와 strategy token 이 1% transfer fee 가 있는 Token-2022 mint
+    let fee_mint = Pubkey::new_unique();
+    let mut vault = fixture.vault_state();
+    vault.token_program = TOKEN_2022_PROGRAM_ID;
+    vault.reward_token_mint = fee_mint;
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    let mut strategy = fixture.strategy_state();
+    strategy.strategy_token_mint = fee_mint;
+    let mut program_test = fixture.program_test(&vault, &fixture.vault_strategy_state(), &strategy);
+    add_token_2022_fee_mint(&mut program_test, fee_mint, 100);
+    add_token_2022_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fee_mint,
+        fixture.authority,
+        reward,
+    );
+    add_token_2022_account(
+        &mut program_test,
+        fixture.strategy_token,
+        fee_mint,
+        fixture.authority,
+        0,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_HARVESTED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let swap = |token_program: Pubkey, mint: Pubkey| {
+        let mut instruction = swap_reward_to_ray_strategy(&fixture, fixture.authority);
+        let accounts = instruction.accounts.len();
+        instruction.accounts[accounts - 2].pubkey = token_program;
+        instruction.accounts[accounts - 1].pubkey = mint;
+        instruction
+    };
+
+    // vault.token_program 이 아닌 token program, reward mint 가 아닌 mint 는 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap(spl_token::id(), fee_mint),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap(TOKEN_2022_PROGRAM_ID, fixture.reward_mint),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        swap(TOKEN_2022_PROGRAM_ID, fee_mint),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // transfer fee 10 을 뺀, strategy token account 에 실제로 도착한 양만 분배함
+    let strategy_token = get_token_2022_account(&mut banks_client, fixture.strategy_token).await;
+    assert_eq!(strategy_token.amount, 990);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 990);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert_eq!(vault_strategy.strategy_token_amounts[0], 990);
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_strategy_token_through_orca_respects_slippage() {
+    // stub whirlpool 의 가격은 1 이므로 10_000 reward 의 예상 수령량은 10_000, 1% slippage 면 최소 9_900
+    // 실제 output 은 reserve 기준이라 reserve 가 작으면 price impact 로 최소 수령량에 못 미침
+    for (reserve, expect_ok) in [(100_000, false), (10_000_000, true)] {
+        let fixture = Fixture::new();
+        let reward = 10_000;
+        let usdc_strategy_token = Pubkey::new_unique();
+        let mut strategy = fixture.strategy_state();
+        strategy.strategy_token_account = usdc_strategy_token;
+        strategy.strategy_token_mint = fixture.usdc_mint;
+        let mut vault = fixture.vault_state();
+        vault.swap_slippage_bps = 100;
+        vault.deposit_amounts[0] = 10_000;
+        vault.total_deposit_amount = 10_000;
+        vault.reward_token_remain_amounts[0] = reward;
+        let mut program_test =
+            fixture.program_test(&vault, &fixture.vault_strategy_state(), &strategy);
+        add_token_account(
+            &mut program_test,
+            fixture.vault_reward,
+            fixture.reward_mint,
+            fixture.authority,
+            reward,
+        );
+        add_token_account(
+            &mut program_test,
+            usdc_strategy_token,
+            fixture.usdc_mint,
+            fixture.authority,
+            0,
+        );
+        let swap_accounts = fixture.add_stub_whirlpool(
+            &mut program_test,
+            fixture.vault_reward,
+            fixture.reward_mint,
+            usdc_strategy_token,
+            fixture.usdc_mint,
+            reserve,
+        );
+        let keeper = fixture.admin.pubkey();
+        let swaper_user_state = fixture.user_address(&keeper);
+        let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+        user.user_status = USER_STATUS_HARVESTED;
+        user.deadline = i64::MAX;
+        add_packed(
+            &mut program_test,
+            swaper_user_state,
+            &user,
+            &fixture.program_id,
+        );
+        let (mut banks_client, payer) = start(program_test).await;
+
+        let result = process(
+            &mut banks_client,
+            &payer,
+            instruction_builder::swap_farm_reward_to_strategy_token(
+                &fixture.program_id,
+                SwapAccounts {
+                    swaper: keeper,
+                    gauntlet_state: fixture.gauntlet,
+                    swaper_user_state,
+                    vault_state: fixture.vault,
+                    vault_strategy_state: fixture.vault_strategy,
+                    strategy_state: fixture.strategy,
+                    oracle_accounts: vec![],
+                    swap_accounts,
+                    restake_accounts: vec![],
+                },
+                SwapType::ORCA,
+            ),
+            &[&fixture.admin],
+        )
+        .await;
+        let strategy_token: spl_token::state::Account =
+            get_packed(&mut banks_client, usdc_strategy_token).await;
+        if expect_ok {
+            result.unwrap();
+            // 10_000_000 * 10_000 / (10_000_000 + 10_000)
+            assert_eq!(strategy_token.amount, 9_990);
+        } else {
+            // stub whirlpool 의 amount out below minimum
+            assert_eq!(custom_error(result), Some(0x1794));
+            assert_eq!(strategy_token.amount, 0);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_swap_usdc_to_deposit_lp_restakes_compound_share() {
+    let fixture = Fixture::new();
+    let lp_strategy_token = Pubkey::new_unique();
+    let mut strategy = fixture.strategy_state();
+    strategy.strategy_token_account = lp_strategy_token;
+    strategy.strategy_token_mint = fixture.lp_mint;
+    strategy.routed_deposit_amount = 10_000;
+    let mut vault = fixture.vault_state();
+    vault.compound_ratio_bps = 10_000;
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.usdc_token_amounts[0] = 1_000;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.needs_usdc_pools[0] = true;
+    vault_strategy.compounding[0] = true;
+    let mut program_test = fixture.program_test(&vault, &vault_strategy, &strategy);
+    add_token_account(
+        &mut program_test,
+        lp_strategy_token,
+        fixture.lp_mint,
+        fixture.authority,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.authority,
+        1_000,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        lp_strategy_token,
+        fixture.lp_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let swaper_user_state = fixture.user_address(&keeper);
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_REWARDS_SWAPPED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        swaper_user_state,
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::swap_usdc_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper,
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state,
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts,
+                restake_accounts: fixture.restake_accounts(),
+            },
+            SwapType::RAYDIUM,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // 1_000 usdc -> 996 LP, compound ratio 100% 이므로 전부 farm 에 다시 staking
+    let pool_lp: spl_token::state::Account = get_packed(&mut banks_client, fixture.pool_lp).await;
+    assert_eq!(pool_lp.amount, 996);
+    let strategy_lp: spl_token::state::Account =
+        get_packed(&mut banks_client, lp_strategy_token).await;
+    assert_eq!(strategy_lp.amount, 0);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.usdc_token_amounts[0], 0);
+    assert_eq!(vault.deposit_amounts[0], 10_000 + 996);
+    assert_eq!(vault.total_deposit_amount, 10_000 + 996);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 0);
+    assert_eq!(strategy.routed_deposit_amount, 10_000 + 996);
+}
+
+#[tokio::test]
+async fn test_swap_rejects_unknown_swap_type() {
+    let fixture = Fixture::new();
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, 10_000);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // SwapType 은 non_exhaustive, 이 program 이 모르는 backend tag 는 unpack 에서 거부
+    let mut instruction = swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0);
+    instruction.data[1] = 0xff;
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InstructionUnpackError as u32)
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 10_000);
+}
+
+/// pool 이 harvest 마다 `reward` 를 지급하고 admin 이 keeper 로 harvest 할 수 있는 `vault`
+fn harvest_program_test(fixture: &Fixture, vault: &Vault, reward: u64) -> ProgramTest {
+    let mut program_test = fixture.program_test(
+        vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    program_test.add_account(
+        fixture.pool_id,
+        Account {
+            lamports: 1_000_000_000,
+            data: reward.to_le_bytes().to_vec(),
+            owner: fixture.staking_program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let keeper = fixture.admin.pubkey();
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &User::init(keeper, fixture.vault, fixture.strategy),
+        &fixture.program_id,
+    );
+    program_test
+}
+
+fn harvest(fixture: &Fixture, max_strategies: u8) -> Instruction {
+    let keeper = fixture.admin.pubkey();
+    instruction_builder::harvest(
+        &fixture.program_id,
+        HarvestAccounts {
+            harvestor: keeper,
+            gauntlet_state: fixture.gauntlet,
+            harvestor_user_state: fixture.user_address(&keeper),
+            vault_state: fixture.vault,
+            vault_strategy_state: fixture.vault_strategy,
+            harvest_accounts: fixture.staking_accounts(),
+            keeper_reward_token: None,
+        },
+        DepositType::RAYDIUM,
+        max_strategies,
+    )
+}
+
+/// 연속 빈 harvest 가 `failures` 번인 vault 를 pool 이 `reward` 를 지급하는 상태로 한번 harvest
+async fn harvest_after_failures(fixture: &Fixture, failures: u8, reward: u64) -> Vault {
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.consecutive_harvest_failures = failures;
+    vault.max_pending_reward = u64::MAX;
+    let program_test = harvest_program_test(fixture, &vault, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(fixture, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    get_packed(&mut banks_client, fixture.vault).await
+}
+
+#[tokio::test]
+async fn test_harvest_counts_consecutive_empty_harvests() {
+    // 빈 harvest 는 세고, threshold 에 닿으면 vault 주소와 함께 경고를 남김
+    let fixture = Fixture::new();
+    let vault = harvest_after_failures(&fixture, 2, 0).await;
+    assert_eq!(vault.consecutive_harvest_failures, 3);
+    assert!(logged("Warning: vault ")
+        .iter()
+        .any(|message| *message == format!("{} had 3 consecutive empty harvests", fixture.vault)));
+
+    // reward 를 받으면 다시 0 부터
+    let fixture = Fixture::new();
+    let vault = harvest_after_failures(&fixture, 2, 500).await;
+    assert_eq!(vault.consecutive_harvest_failures, 0);
+    assert_eq!(vault.reward_token_remain_amounts[0], 500);
+}
+
+#[tokio::test]
+async fn test_harvest_rejects_short_staking_accounts() {
+    let fixture = Fixture::new();
+    let program_test = harvest_program_test(&fixture, &fixture.vault_state(), 0);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // staking account 가 모자라면 panic 하지 않고 거부
+    let mut instruction = harvest(&fixture, 0);
+    instruction.accounts.pop();
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn test_harvest_skips_farm_when_vault_has_no_deposits() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.max_pending_reward = u64::MAX;
+    let program_test = harvest_program_test(&fixture, &vault, 1_000);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // 배분할 곳이 없으므로 farm 의 reward 는 그대로 두고 실패로도 세지 않음
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.consecutive_harvest_failures, 0);
+    assert_eq!(vault.last_reward_update_time, 0);
+    assert_eq!(vault.harvest_pending_amount, 0);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, 0);
+}
+
+#[tokio::test]
+async fn test_harvest_rejects_pending_reward_over_vault_cap() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = 500;
+    vault.max_pending_reward = 1_000;
+    let program_test = harvest_program_test(&fixture, &vault, 600);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // swap 되지 않은 500 에 600 이 더해지면 cap 을 넘음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::PendingRewardOverflow as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_harvest_with_default_max_pending_reward() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = 500;
+    // init_vault 의 기본값 0 은 cap 없음
+    vault.max_pending_reward = 0;
+    let program_test = harvest_program_test(&fixture, &vault, 600);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 1_100);
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::set_vault_max_pending_reward(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault,
+            1_000,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.max_pending_reward, 1_000);
+}
+
+#[tokio::test]
+async fn test_harvest_pays_keeper_fee_before_distribution() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    vault.keeper_fee_numerator = 1;
+    vault.keeper_fee_denominator = 100;
+    let mut program_test = harvest_program_test(&fixture, &vault, 1_000);
+    let keeper_reward = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        keeper_reward,
+        fixture.reward_mint,
+        fixture.admin.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // keeper reward account 없이는 bounty 를 줄 수 없으므로 실패
+    let result = process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongFeeAccount as u32)
+    );
+
+    // transfer_checked 에 쓰는 reward mint 도 vault 의 reward mint 여야 함
+    let with_keeper_accounts = |accounts: Vec<AccountMeta>| {
+        let mut instruction = harvest(&fixture, 0);
+        instruction.accounts.extend(accounts);
+        instruction
+    };
+    for accounts in [
+        vec![AccountMeta::new(keeper_reward, false)],
+        vec![
+            AccountMeta::new(keeper_reward, false),
+            AccountMeta::new_readonly(fixture.usdc_mint, false),
+        ],
+    ] {
+        let result = process(
+            &mut banks_client,
+            &payer,
+            with_keeper_accounts(accounts),
+            &[&fixture.admin],
+        )
+        .await;
+        assert_eq!(
+            custom_error(result),
+            Some(GauntletError::WrongFeeAccount as u32)
+        );
+    }
+
+    process(
+        &mut banks_client,
+        &payer,
+        with_keeper_accounts(vec![
+            AccountMeta::new(keeper_reward, false),
+            AccountMeta::new_readonly(fixture.reward_mint, false),
+        ]),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let keeper: spl_token::state::Account = get_packed(&mut banks_client, keeper_reward).await;
+    assert_eq!(keeper.amount, 10);
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 990);
+}
+
+#[tokio::test]
+async fn test_swap_rejects_mismatched_routing_mode() {
+    let fixture = Fixture::new();
+    let (mut program_test, swap_accounts) = usdc_swap_program_test(&fixture, 10_000);
+    // usdc 를 거치지 않는 strategy
+    add_packed(
+        &mut program_test,
+        fixture.vault_strategy,
+        &fixture.vault_strategy_state(),
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongRoutingMode as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_rejects_unknown_pool_program() {
+    let fixture = Fixture::new();
+    let (program_test, mut swap_accounts) = usdc_swap_program_test(&fixture, 1_000);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // pool balance 를 읽기 전에 pool program 이 raydium 인지 확인해야 함
+    swap_accounts[0] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 1_000);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_validates_raydium_account_order() {
+    let fixture = Fixture::new();
+    let (program_test, swap_accounts) = usdc_swap_program_test(&fixture, 1_000);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // token program (1) 과 serum program (8) 자리가 틀리면 cpi 전에 거부
+    for index in [1, 8] {
+        let mut swap_accounts = swap_accounts.clone();
+        swap_accounts[index] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+        let result = process(
+            &mut banks_client,
+            &payer,
+            swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+            &[&fixture.admin],
+        )
+        .await;
+        assert_eq!(
+            custom_error(result),
+            Some(GauntletError::InvalidProgramId as u32)
+        );
+    }
+
+    // 19 개보다 적으면 account 를 읽기 전에 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts[..18], vec![], 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_accepts_openbook_market() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (program_test, mut swap_accounts) = usdc_swap_program_test(&fixture, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // serum 대신 openbook 으로 옮겨진 market 도 swap 할 수 있어야 함
+    swap_accounts[8] = AccountMeta::new_readonly(OPENBOOK_PROGRAM_ID[0], false);
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert!(vault.usdc_token_amounts[0] > 0);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_checks_source_against_reward_mint() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (mut program_test, swap_accounts) = usdc_swap_program_test(&fixture, reward);
+    // swap account 는 그대로 두고 vault 가 기록한 reward mint 만 다르게
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    vault.reward_token_mint = Pubkey::new_unique();
+    add_packed(
+        &mut program_test,
+        fixture.vault,
+        &vault,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::RewardTokenAccountError as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_estimates_from_amount_in() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (mut program_test, swap_accounts) = usdc_swap_program_test(&fixture, reward);
+    // vault reward account 에 기록되지 않은 잔액이 더 있어도 estimate 는 swap 할 양 기준
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        reward * 100,
+    );
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    vault.swap_slippage_bps = 100;
+    add_packed(
+        &mut program_test,
+        fixture.vault,
+        &vault,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert!(vault.usdc_token_amounts[0] > 0);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, reward * 99);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_leaves_output_below_min_swap_output() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (mut program_test, swap_accounts) = usdc_swap_program_test(&fixture, reward);
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    vault.min_swap_output = u64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.vault,
+        &vault,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 예상 수령량이 min_swap_output 보다 작으면 swap 하지 않고 다음 crank 로 넘김
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc(&fixture, &swap_accounts, vec![], 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], reward);
+    assert_eq!(vault.usdc_token_amounts[0], 0);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, reward);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_skips_orca_dust_below_min_swap_output() {
+    let fixture = Fixture::new();
+    let reward = 10;
+    let (mut program_test, _) = usdc_swap_program_test(&fixture, reward);
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = reward;
+    vault.min_swap_output = 100;
+    add_packed(
+        &mut program_test,
+        fixture.vault,
+        &vault,
+        &fixture.program_id,
+    );
+    let swap_accounts = fixture.add_stub_whirlpool(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // min_swap_output 은 min-out 이 아니라 skip 기준, dust 는 실패하지 않고 다음 crank 로 넘김
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc_via(&fixture, SwapType::ORCA, &swap_accounts, vec![], 0, &[]),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], reward);
+    assert_eq!(vault.usdc_token_amounts[0], 0);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, reward);
+}
+
+#[tokio::test]
+async fn test_harvest_skips_farm_within_min_harvest_interval() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    vault.min_harvest_interval = 3_600;
+    let program_test = harvest_program_test(&fixture, &vault, 500);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert!(vault.last_reward_update_time > 0);
+    assert_eq!(vault.reward_token_remain_amounts[0], 500);
+
+    let last_reward_update_time = vault.last_reward_update_time;
+
+    // interval 이 지나기 전에는 farm 에서 reward 를 받지 않고 harvestor 의 status 만 진행
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 500);
+    assert_eq!(vault.last_reward_update_time, last_reward_update_time);
+    assert_eq!(vault.consecutive_harvest_failures, 0);
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&fixture.admin.pubkey()),
+    )
+    .await;
+    assert_eq!(user.user_status, USER_STATUS_HARVESTED);
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_through_jupiter_route() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (mut program_test, _) = usdc_swap_program_test(&fixture, reward);
+    // 다른 strategy 몫으로 기록된 reward 가 같은 account 에 더 있음
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        reward * 2,
+    );
+    let swap_accounts = fixture.add_stub_jupiter(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let jupiter_swap = |amount_in| {
+        swap_farm_reward_to_usdc_via(
+            &fixture,
+            SwapType::JUPITER,
+            &swap_accounts,
+            vec![],
+            0,
+            &jupiter_route_data(amount_in, 9_000),
+        )
+    };
+
+    // route 가 이 strategy 의 reward 보다 많이 쓰면 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        jupiter_swap(reward + 1),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::RouteOverspent as u32)
+    );
+
+    // allow-list 에 없는 program 으로는 route 하지 않음
+    let mut unknown_program = swap_accounts.clone();
+    unknown_program[0] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc_via(
+            &fixture,
+            SwapType::JUPITER,
+            &unknown_program,
+            vec![],
+            0,
+            &jupiter_route_data(reward, 9_000),
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        jupiter_swap(reward),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert_eq!(vault.usdc_token_amounts[0], 9_000);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, reward);
+}
+
+#[tokio::test]
+async fn test_swap_through_jupiter_rejects_other_pda_token_accounts() {
+    let fixture = Fixture::new();
+    let reward = 10_000;
+    let (mut program_test, _) = usdc_swap_program_test(&fixture, reward);
+    let swap_accounts = fixture.add_stub_jupiter(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.vault_lp,
+        fixture.lp_mint,
+        fixture.authority,
+        reward,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 다른 vault 의 deposit LP 처럼 pda 가 가진 token account 를 route 에 끼워 넣으면 거부
+    let mut route_with_vault_lp = swap_accounts.clone();
+    route_with_vault_lp.push(AccountMeta::new(fixture.vault_lp, false));
+    let result = process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc_via(
+            &fixture,
+            SwapType::JUPITER,
+            &route_with_vault_lp,
+            vec![],
+            0,
+            &jupiter_route_data(reward, 9_000),
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidAccount as u32)
+    );
+    let vault_lp: spl_token::state::Account = get_packed(&mut banks_client, fixture.vault_lp).await;
+    assert_eq!(vault_lp.amount, reward);
+
+    // source / dest 만 pda 소유인 route 는 그대로 통과
+    process(
+        &mut banks_client,
+        &payer,
+        swap_farm_reward_to_usdc_via(
+            &fixture,
+            SwapType::JUPITER,
+            &swap_accounts,
+            vec![],
+            0,
+            &jupiter_route_data(reward, 9_000),
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.usdc_token_amounts[0], 9_000);
+}
+
+#[tokio::test]
+async fn test_harvest_requires_an_available_strategy() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    let mut program_test = harvest_program_test(&fixture, &vault, 500);
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.availabilities[0] = false;
+    add_packed(
+        &mut program_test,
+        fixture.vault_strategy,
+        &vault_strategy,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 나눠줄 strategy 가 없으면 reward 를 vault 에 묶어두지 않도록 harvest 하지 않음
+    let result = process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::NotRegisteredStrategy as u32)
+    );
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, 0);
+}
+
+fn set_global_pause(fixture: &Fixture, paused: bool, admin_nonce: u64) -> Instruction {
+    instruction_builder::set_global_pause(
+        &fixture.program_id,
+        &fixture.admin_accounts(),
+        paused,
+        admin_nonce,
+    )
+}
+
+#[tokio::test]
+async fn test_global_pause_stops_keeper_cranks() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    let mut program_test = harvest_program_test(&fixture, &vault, 500);
+    // strategy 의 cap 합계도 vault 에 들어온 deposit 과 맞춰 둠
+    let mut strategy = fixture.strategy_state();
+    strategy.routed_deposit_amount = 10_000;
+    add_packed(
+        &mut program_test,
+        fixture.strategy,
+        &strategy,
+        &fixture.program_id,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        10_000,
+    );
+    // harvest 를 받은 적 없는 (IDLE) user
+    let (withdrawer, withdrawer_lp) = fixture.add_depositor(&mut program_test, 0);
+    let withdrawer_reward = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        withdrawer_reward,
+        fixture.reward_mint,
+        withdrawer.pubkey(),
+        0,
+    );
+    let withdrawer_state = fixture.user_address(&withdrawer.pubkey());
+    let mut user = User::init(withdrawer.pubkey(), fixture.vault, fixture.strategy);
+    user.amount = 1_000;
+    add_packed(
+        &mut program_test,
+        withdrawer_state,
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        set_global_pause(&fixture, true, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let result = process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::VaultPaused as u32)
+    );
+
+    // harvest 가 막혀 READY 가 될 수 없어도 원금은 인출 가능
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            WithdrawAccounts {
+                withdrawer: withdrawer.pubkey(),
+                withdrawer_user_state: withdrawer_state,
+                withdrawer_deposit_token: withdrawer_lp,
+                withdrawer_reward_token: withdrawer_reward,
+                gauntlet_state: fixture.gauntlet,
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                strategy_token: fixture.strategy_token,
+                withdraw_fee_token: fixture.fee_account,
+                performance_fee_token: fixture.fee_account,
+                withdraw_accounts: fixture.staking_accounts(),
+                deposit_token_mint: fixture.lp_mint,
+                strategy_token_mint: fixture.reward_mint,
+                strategy_token_program: spl_token::id(),
+                gas_reimbursement_swap_accounts: vec![],
+                recipient_tokens: None,
+                strategist_fee_token: None,
+            },
+            1_000,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer],
+    )
+    .await
+    .unwrap();
+    let withdrawer_lp: spl_token::state::Account =
+        get_packed(&mut banks_client, withdrawer_lp).await;
+    assert_eq!(withdrawer_lp.amount, 1_000);
+    let user = get_user(&mut banks_client, withdrawer_state).await;
+    assert_eq!(user.amount, 0);
+
+    process(
+        &mut banks_client,
+        &payer,
+        set_global_pause(&fixture, false, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 500);
+}
+
+/// `price` * 10^`expo` USD 의 pyth aggregate price account
+fn pyth_price_account(price: i64, expo: i32, status: u32) -> Account {
+    let mut data = vec![0; PYTH_AGG_PUB_SLOT_OFFSET + 8];
+    data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[PYTH_EXPO_OFFSET..PYTH_EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+    data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+    data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: PYTH_PROGRAM_ID[0],
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_swap_farm_reward_to_usdc_checks_pyth_price() {
+    let fixture = Fixture::new();
+    let (mut program_test, swap_accounts) = usdc_swap_program_test(&fixture, 10_000);
+    let (fair, overpriced, halted) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    // 같은 decimals 의 1:1 가격, 2% slippage 면 최소 9_800 usdc (stub amm 은 9_876 을 줌)
+    program_test.add_account(fair, pyth_price_account(1, 0, PYTH_STATUS_TRADING));
+    program_test.add_account(overpriced, pyth_price_account(2, 0, PYTH_STATUS_TRADING));
+    program_test.add_account(halted, pyth_price_account(1, 0, 0));
+    let (mut banks_client, payer) = start(program_test).await;
+    let priced_swap_with_slippage = |price_account, max_slippage_bps| {
+        swap_farm_reward_to_usdc(
+            &fixture,
+            &swap_accounts,
+            vec![
+                AccountMeta::new_readonly(price_account, false),
+                AccountMeta::new_readonly(fixture.reward_mint, false),
+                AccountMeta::new_readonly(fixture.usdc_mint, false),
+            ],
+            max_slippage_bps,
+        )
+    };
+    let priced_swap = |price_account| priced_swap_with_slippage(price_account, 200);
+
+    // 100% 를 넘는 slippage 는 가격을 읽기 전에 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        priced_swap_with_slippage(fair, 10_001),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidSlippage as u32)
+    );
+
+    // oracle 가격 대비 realized output 이 부족하거나 거래 중이 아닌 가격이면 거부
+    for price_account in [overpriced, halted] {
+        let result = process(
+            &mut banks_client,
+            &payer,
+            priced_swap(price_account),
+            &[&fixture.admin],
+        )
+        .await;
+        assert_eq!(
+            custom_error(result),
+            Some(GauntletError::PriceDeviation as u32)
+        );
+    }
+
+    process(
+        &mut banks_client,
+        &payer,
+        priced_swap(fair),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 0);
+    assert_eq!(vault.usdc_token_amounts[0], 9_876);
+}
+
+#[tokio::test]
+async fn test_harvest_rejects_unknown_staking_program() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    let program_test = harvest_program_test(&fixture, &vault, 500);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // panic 이 아니라 InvalidProgramId 로 실패해야 함
+    let mut instruction = harvest(&fixture, 0);
+    instruction.accounts[5] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_swap_usdc_to_strategy_token_caps_usdc_per_crank() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.usdc_token_amounts[0] = 10_000;
+    vault.max_swap_usdc_per_tx = 4_000;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.needs_usdc_pools[0] = true;
+    let mut program_test = fixture.program_test(&vault, &vault_strategy, &fixture.strategy_state());
+    add_token_account(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.authority,
+        10_000,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_REWARDS_SWAPPED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::swap_usdc_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper,
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state: fixture.user_address(&keeper),
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts,
+                restake_accounts: vec![],
+            },
+            SwapType::RAYDIUM,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+
+    // cap 만큼만 swap 하고 나머지 usdc 는 다음 crank 로
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.usdc_token_amounts[0], 6_000);
+    let usdc: spl_token::state::Account = get_packed(&mut banks_client, fixture.vault_usdc).await;
+    assert_eq!(usdc.amount, 6_000);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert!(vault_strategy.strategy_token_amounts[0] > 0);
+}
+
+#[tokio::test]
+async fn test_swap_usdc_to_strategy_token_fails_overflowing_strategy_token_amount() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.usdc_token_amounts[0] = 1_000;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.needs_usdc_pools[0] = true;
+    // 마지막 합산인 vault strategy 의 strategy token 양이 넘치게 함
+    vault_strategy.strategy_token_amounts[0] = u64::MAX;
+    let mut program_test = fixture.program_test(&vault, &vault_strategy, &fixture.strategy_state());
+    add_token_account(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.authority,
+        1_000,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_REWARDS_SWAPPED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // panic 이 아니라 MathOverflow 로 실패해야 함
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::swap_usdc_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper,
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state: fixture.user_address(&keeper),
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts,
+                restake_accounts: vec![],
+            },
+            SwapType::RAYDIUM,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::MathOverflow as u32)
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.usdc_token_amounts[0], 1_000);
+}
+
+#[tokio::test]
+async fn test_swap_reward_to_strategy_token_fails_overflowing_strategy_deposit() {
+    let fixture = Fixture::new();
+    let lp_strategy_token = Pubkey::new_unique();
+    let mut strategy = fixture.strategy_state();
+    strategy.strategy_token_account = lp_strategy_token;
+    strategy.strategy_token_mint = fixture.lp_mint;
+    // swap 한 strategy token 을 strategy 합계에 더할 때 넘치게 함
+    strategy.total_deposit_amount = u64::MAX;
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.reward_token_remain_amounts[0] = 1_000;
+    let mut program_test = fixture.program_test(&vault, &fixture.vault_strategy_state(), &strategy);
+    add_token_account(
+        &mut program_test,
+        lp_strategy_token,
+        fixture.lp_mint,
+        fixture.authority,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.authority,
+        1_000,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        lp_strategy_token,
+        fixture.lp_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_HARVESTED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::swap_farm_reward_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper,
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state: fixture.user_address(&keeper),
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts,
+                restake_accounts: fixture.restake_accounts(),
+            },
+            SwapType::RAYDIUM,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::MathOverflow as u32)
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.reward_token_remain_amounts[0], 1_000);
+}
+
+/// 요청한 amount_in 보다 1 더 가져가는 stub amm
+fn process_greedy_stub_amm<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    input: &[u8],
+) -> ProgramResult {
+    let mut input = input.to_vec();
+    let amount_in = u64::from_le_bytes(input[1..9].try_into().unwrap());
+    input[1..9].copy_from_slice(&(amount_in + 1).to_le_bytes());
+    process_stub_amm(program_id, accounts, &input)
+}
+
+#[tokio::test]
+async fn test_swap_usdc_to_strategy_token_detects_overspent_usdc() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.usdc_token_amounts[0] = 10_000;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.needs_usdc_pools[0] = true;
+    let mut program_test = fixture.program_test(&vault, &vault_strategy, &fixture.strategy_state());
+    // 다른 strategy 몫의 usdc 1 이 같은 account 에 있음
+    add_token_account(
+        &mut program_test,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.authority,
+        10_001,
+    );
+    let amm_program_id = POOL_PROGRAM_ID[1];
+    program_test.add_program(
+        "greedy_stub_amm",
+        amm_program_id,
+        processor!(process_greedy_stub_amm),
+    );
+    let swap_accounts = fixture.stub_amm_accounts(
+        &mut program_test,
+        amm_program_id,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        1_000_000,
+    );
+    let keeper = fixture.admin.pubkey();
+    let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_REWARDS_SWAPPED;
+    user.deadline = i64::MAX;
+    add_packed(
+        &mut program_test,
+        fixture.user_address(&keeper),
+        &user,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::swap_usdc_to_strategy_token(
+            &fixture.program_id,
+            SwapAccounts {
+                swaper: keeper,
+                gauntlet_state: fixture.gauntlet,
+                swaper_user_state: fixture.user_address(&keeper),
+                vault_state: fixture.vault,
+                vault_strategy_state: fixture.vault_strategy,
+                strategy_state: fixture.strategy,
+                oracle_accounts: vec![],
+                swap_accounts,
+                restake_accounts: vec![],
+            },
+            SwapType::RAYDIUM,
+        ),
+        &[&fixture.admin],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::AccountingDesync as u32)
+    );
+}
+
+#[tokio::test]
+async fn test_harvest_distributes_across_calls_with_cursor() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 6_000;
+    vault.deposit_amounts[1] = 4_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    let mut program_test = harvest_program_test(&fixture, &vault, 1_000);
+    let mut gauntlet = fixture.gauntlet_state();
+    gauntlet.strategies_len = 2;
+    add_packed(
+        &mut program_test,
+        fixture.gauntlet,
+        &gauntlet,
+        &fixture.program_id,
+    );
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.availabilities[1] = true;
+    add_packed(
+        &mut program_test,
+        fixture.vault_strategy,
+        &vault_strategy,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 첫 strategy 에만 배분하고 cursor 를 남김
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 1),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.harvest_cursor, 1);
+    assert_eq!(vault.harvest_pending_amount, 1_000);
+    assert_eq!(vault.reward_token_remain_amounts[0], 600);
+    assert_eq!(vault.reward_token_remain_amounts[1], 0);
+
+    // 이어서 나머지를 배분, farm 에서 다시 harvest 하지 않음
+    process(
+        &mut banks_client,
+        &payer,
+        harvest(&fixture, 0),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.harvest_cursor, 0);
+    assert_eq!(vault.harvest_pending_amount, 0);
+    assert_eq!(vault.reward_token_remain_amounts[0], 600);
+    assert_eq!(vault.reward_token_remain_amounts[1], 400);
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, 1_000);
+}
+
+#[tokio::test]
+async fn test_harvest_requires_authority_as_raydium_owner() {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = 10_000;
+    vault.total_deposit_amount = 10_000;
+    vault.max_pending_reward = u64::MAX;
+    let program_test = harvest_program_test(&fixture, &vault, 500);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // raydium user owner 자리에 authority pda 가 아닌 account
+    let mut instruction = harvest(&fixture, 0);
+    let owner_index = 5 + 4;
+    assert_eq!(instruction.accounts[owner_index].pubkey, fixture.authority);
+    instruction.accounts[owner_index] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let result = process(&mut banks_client, &payer, instruction, &[&fixture.admin]).await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidAccount as u32)
+    );
+    let vault_reward: spl_token::state::Account =
+        get_packed(&mut banks_client, fixture.vault_reward).await;
+    assert_eq!(vault_reward.amount, 0);
+}
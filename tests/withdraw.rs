@@ -0,0 +1,1458 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use common::*;
+use gauntlet_program::{
+    error::GauntletError,
+    instruction::{DepositType, SwapType, WithdrawType},
+    instruction_builder::{self, HarvestAccounts, SwapAccounts, WithdrawAccounts},
+    state::{
+        Status, Strategy, User, Vault, VaultStrategy, MAX_NUMBER_OF_STRATEGY, MAX_NUMBER_OF_VAULTS,
+        USER_STATUS_READY,
+    },
+    utils::TOKEN_2022_PROGRAM_ID,
+};
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// READY user 가 `amount` LP 와 strategy token `reward` 를 claim 할 수 있는 상태
+struct Withdrawer {
+    keypair: Keypair,
+    deposit_token: Pubkey,
+    reward_token: Pubkey,
+}
+
+fn add_withdrawer(
+    fixture: &Fixture,
+    program_test: &mut ProgramTest,
+    amount: u64,
+    reward: u64,
+) -> Withdrawer {
+    let (keypair, deposit_token) = fixture.add_depositor(program_test, 0);
+    let reward_token = Pubkey::new_unique();
+    add_token_account(
+        program_test,
+        reward_token,
+        fixture.reward_mint,
+        keypair.pubkey(),
+        0,
+    );
+    let mut user = User::init(keypair.pubkey(), fixture.vault, fixture.strategy);
+    user.user_status = USER_STATUS_READY;
+    user.deadline = i64::MAX;
+    user.amount = amount;
+    user.reward = reward;
+    add_packed(
+        program_test,
+        fixture.user_address(&keypair.pubkey()),
+        &user,
+        &fixture.program_id,
+    );
+    // strategy token account 에 vault 몫의 reward 가 있어야 함
+    add_token_account(
+        program_test,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        fixture.authority,
+        reward,
+    );
+    Withdrawer {
+        keypair,
+        deposit_token,
+        reward_token,
+    }
+}
+
+fn vault_strategy_with_reward(fixture: &Fixture, reward: u64) -> VaultStrategy {
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.strategy_token_amounts[0] = reward;
+    vault_strategy
+}
+
+fn strategy_with_reward(fixture: &Fixture, reward: u64) -> Strategy {
+    let mut strategy = fixture.strategy_state();
+    strategy.deposit_amounts[0] = reward;
+    strategy.total_deposit_amount = reward;
+    strategy
+}
+
+/// vault 들에서 `amount` LP 가 들어와 있는 strategy
+fn strategy_with_routed_deposit(fixture: &Fixture, amount: u64) -> Strategy {
+    let mut strategy = fixture.strategy_state();
+    strategy.routed_deposit_amount = amount;
+    strategy
+}
+
+fn withdraw_accounts(fixture: &Fixture, withdrawer: &Withdrawer) -> WithdrawAccounts {
+    WithdrawAccounts {
+        withdrawer: withdrawer.keypair.pubkey(),
+        withdrawer_user_state: fixture.user_address(&withdrawer.keypair.pubkey()),
+        withdrawer_deposit_token: withdrawer.deposit_token,
+        withdrawer_reward_token: withdrawer.reward_token,
+        gauntlet_state: fixture.gauntlet,
+        vault_state: fixture.vault,
+        vault_strategy_state: fixture.vault_strategy,
+        strategy_state: fixture.strategy,
+        strategy_token: fixture.strategy_token,
+        withdraw_fee_token: fixture.fee_account,
+        performance_fee_token: fixture.fee_account,
+        withdraw_accounts: fixture.staking_accounts(),
+        deposit_token_mint: fixture.lp_mint,
+        strategy_token_mint: fixture.reward_mint,
+        strategy_token_program: spl_token::id(),
+        gas_reimbursement_swap_accounts: vec![],
+        recipient_tokens: None,
+        strategist_fee_token: None,
+    }
+}
+
+async fn token_amount(banks_client: &mut BanksClient, address: Pubkey) -> u64 {
+    get_packed::<spl_token::state::Account>(banks_client, address)
+        .await
+        .amount
+}
+
+#[tokio::test]
+async fn test_withdraw_splits_performance_fee_with_strategist() {
+    let fixture = Fixture::new();
+    let reward = 999;
+    let mut vault = fixture.vault_state();
+    vault.fees.performance_fee_numerator = 1;
+    vault.fees.performance_fee_denominator = 10;
+    let treasury = Pubkey::new_unique();
+    let strategist = Pubkey::new_unique();
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.performance_fee_account = treasury;
+    strategy.strategist_fee_account = strategist;
+    strategy.strategist_fee_split_bps = 2_500;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    for fee_account in [treasury, strategist] {
+        add_token_account(
+            &mut program_test,
+            fee_account,
+            fixture.reward_mint,
+            Pubkey::new_unique(),
+            0,
+        );
+    }
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.performance_fee_token = treasury;
+    accounts.strategist_fee_token = Some(strategist);
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+
+    // fee 99 중 strategist 몫 24.75 는 내림, 반올림 오차는 treasury 로
+    assert_eq!(token_amount(&mut banks_client, strategist).await, 24);
+    assert_eq!(token_amount(&mut banks_client, treasury).await, 75);
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.reward_token).await,
+        900
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.cumulative_performance_fees, 99);
+}
+
+#[tokio::test]
+async fn test_withdraw_clamps_performance_fee_to_reward() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    // numerator > denominator 로 잘못 설정된 vault
+    let mut vault = fixture.vault_state();
+    vault.fees.performance_fee_numerator = 2;
+    vault.fees.performance_fee_denominator = 1;
+    let treasury = Pubkey::new_unique();
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.performance_fee_account = treasury;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    add_token_account(
+        &mut program_test,
+        treasury,
+        fixture.reward_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.performance_fee_token = treasury;
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+
+    // fee 는 인출하는 reward 를 넘지 않음
+    assert_eq!(token_amount(&mut banks_client, treasury).await, reward);
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.reward_token).await,
+        0
+    );
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.cumulative_performance_fees, reward);
+}
+
+#[tokio::test]
+async fn test_withdraw_reimburses_gas_in_lamports() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy_with_reward(&fixture, reward),
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let wsol = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        wsol,
+        spl_token::native_mint::id(),
+        withdrawer.keypair.pubkey(),
+        0,
+    );
+    let gas_swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        wsol,
+        spl_token::native_mint::id(),
+        1_000_000,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let wsol_lamports = banks_client.get_balance(wsol).await.unwrap();
+    let withdrawer_lamports = banks_client
+        .get_balance(withdrawer.keypair.pubkey())
+        .await
+        .unwrap();
+
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.gas_reimbursement_swap_accounts = gas_swap_accounts;
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            500,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+
+    // 1_000 의 5% 인 50 을 wSOL 로 swap (fee 후 48 lamports), wSOL account 를 닫아 rent 까지 돌려받음
+    assert_eq!(
+        banks_client
+            .get_balance(withdrawer.keypair.pubkey())
+            .await
+            .unwrap(),
+        withdrawer_lamports + wsol_lamports + 48
+    );
+    assert!(banks_client.get_account(wsol).await.unwrap().is_none());
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.reward_token).await,
+        950
+    );
+}
+
+/// `reward` 를 claim 했을 때 performance fee account 가 받는 양
+async fn claimed_performance_fee(reward: u64, min_fee_enabled: bool) -> u64 {
+    let fixture = Fixture::new();
+    let mut vault = fixture.vault_state();
+    vault.fees.performance_fee_numerator = 1;
+    vault.fees.performance_fee_denominator = 100;
+    vault.fees.min_fee_enabled = min_fee_enabled;
+    let treasury = Pubkey::new_unique();
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.performance_fee_account = treasury;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    add_token_account(
+        &mut program_test,
+        treasury,
+        fixture.reward_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.performance_fee_token = treasury;
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+    token_amount(&mut banks_client, treasury).await
+}
+
+#[tokio::test]
+async fn test_withdraw_performance_fee_follows_min_fee_flag() {
+    // 1% 의 99 는 0 으로 내림, floor 가 켜져 있을 때만 1 token
+    assert_eq!(claimed_performance_fee(99, true).await, 1);
+    assert_eq!(claimed_performance_fee(99, false).await, 0);
+    // 내림하지 않아도 되는 양은 flag 와 무관
+    assert_eq!(claimed_performance_fee(100, true).await, 1);
+    assert_eq!(claimed_performance_fee(100, false).await, 1);
+}
+
+#[tokio::test]
+async fn test_withdraw_to_recipient_checks_recipient_accounts() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy_with_reward(&fixture, reward),
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let treasury = Pubkey::new_unique();
+    let (recipient_lp, recipient_reward, wrong_mint_reward, other_owner_lp) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_token_account(
+        &mut program_test,
+        recipient_lp,
+        fixture.lp_mint,
+        treasury,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        recipient_reward,
+        fixture.reward_mint,
+        treasury,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        wrong_mint_reward,
+        fixture.usdc_mint,
+        treasury,
+        0,
+    );
+    add_token_account(
+        &mut program_test,
+        other_owner_lp,
+        fixture.lp_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let withdraw_to = |recipient_tokens| {
+        let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+        accounts.recipient_tokens = Some(recipient_tokens);
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        )
+    };
+
+    // reward 가 strategy token 이 아닌 mint 로 가면 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        withdraw_to((recipient_lp, wrong_mint_reward)),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+    // 원금과 보상의 recipient owner 가 다르면 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        withdraw_to((other_owner_lp, recipient_reward)),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+
+    // signer 는 여전히 user owner, 보상은 제3자 recipient 로
+    process(
+        &mut banks_client,
+        &payer,
+        withdraw_to((recipient_lp, recipient_reward)),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_amount(&mut banks_client, recipient_reward).await,
+        reward
+    );
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.reward_token).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_frozen_reward_destination() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy_with_reward(&fixture, reward),
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let frozen_reward = spl_token::state::Account {
+        mint: fixture.reward_mint,
+        owner: withdrawer.keypair.pubkey(),
+        state: spl_token::state::AccountState::Frozen,
+        ..spl_token::state::Account::default()
+    };
+    add_packed(
+        &mut program_test,
+        withdrawer.reward_token,
+        &frozen_reward,
+        &spl_token::id(),
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // token program 의 transfer 에서 실패하기 전에 frozen account 를 먼저 거부
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::FrozenTokenAccount as u32)
+    );
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&withdrawer.keypair.pubkey()),
+    )
+    .await;
+    assert_eq!(user.reward, reward);
+}
+
+#[tokio::test]
+async fn test_withdraw_honors_requested_reward_amount() {
+    let fixture = Fixture::new();
+    let total = 1_300;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, total),
+        &strategy_with_reward(&fixture, total),
+    );
+    let partial = add_withdrawer(&fixture, &mut program_test, 0, 1_000);
+    let clamped = add_withdrawer(&fixture, &mut program_test, 0, 300);
+    add_token_account(
+        &mut program_test,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        fixture.authority,
+        total,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let withdraw_reward = |withdrawer: &Withdrawer, reward_amount| {
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, withdrawer),
+            0,
+            reward_amount,
+            WithdrawType::RAYDIUM,
+            0,
+        )
+    };
+
+    // 요청한 만큼만 인출하고 나머지는 user 에 남김
+    process(
+        &mut banks_client,
+        &payer,
+        withdraw_reward(&partial, 400),
+        &[&partial.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_amount(&mut banks_client, partial.reward_token).await,
+        400
+    );
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&partial.keypair.pubkey()),
+    )
+    .await;
+    assert_eq!(user.reward, 600);
+
+    // 보유량보다 많이 요청하면 보유 reward 전부를 인출
+    process(
+        &mut banks_client,
+        &payer,
+        withdraw_reward(&clamped, 5_000),
+        &[&clamped.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_amount(&mut banks_client, clamped.reward_token).await,
+        300
+    );
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&clamped.keypair.pubkey()),
+    )
+    .await;
+    assert_eq!(user.reward, 0);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 600);
+}
+
+#[tokio::test]
+async fn test_withdraw_reduces_strategy_deposit_by_redeemed_amount() {
+    let fixture = Fixture::new();
+    // strategy token 1 개가 2 만큼의 strategy 예치분으로 불어난 상태
+    let mut strategy = fixture.strategy_state();
+    strategy.deposit_amounts[0] = 2_000;
+    strategy.total_deposit_amount = 2_000;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, 1_000),
+        &strategy,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, 500);
+    add_token_account(
+        &mut program_test,
+        fixture.strategy_token,
+        fixture.reward_mint,
+        fixture.authority,
+        2_000,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            0,
+            500,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.reward_token).await,
+        1_000
+    );
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 1_000);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert_eq!(vault_strategy.strategy_token_amounts[0], 500);
+}
+
+#[tokio::test]
+async fn test_withdraw_everything_lets_strategy_retire() {
+    let fixture = Fixture::new();
+    let amount = 1_000;
+    let reward = 500;
+    // 한 user 가 deposit 하고 swap 까지 끝나 strategy token 이 쌓인 상태
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = amount;
+    vault.total_deposit_amount = amount;
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.routed_deposit_amount = amount;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        amount,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, amount, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            amount,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 0);
+    assert_eq!(strategy.total_deposit_amount, 0);
+
+    // 모두 빠져나갔으므로 retire 할 수 있음
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::retire_strategy(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.strategy,
+            &[fixture.vault],
+            &[fixture.vault_strategy],
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert!(strategy.status == Status::RETIRED);
+}
+
+#[tokio::test]
+async fn test_withdraw_waits_for_deposit_cooldown() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    // last_deposit_time 이 0 이어도 아직 지나지 않을 만큼 긴 cooldown
+    let mut vault = fixture.vault_state();
+    vault.withdraw_cooldown_secs = 1 << 40;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy_with_reward(&fixture, reward),
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+    let withdraw = |reward_amount| {
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            0,
+            reward_amount,
+            WithdrawType::RAYDIUM,
+            0,
+        )
+    };
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        withdraw(reward),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WithdrawCooldown as u32)
+    );
+
+    // cooldown 을 끄면 바로 인출 가능, 같은 transaction 이 되지 않도록 요청량만 다르게 (보유량으로 clamp 됨)
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::set_vault_withdraw_cooldown(
+            &fixture.program_id,
+            &fixture.admin_accounts(),
+            &fixture.vault,
+            0,
+            0,
+        ),
+        &[&fixture.admin],
+    )
+    .await
+    .unwrap();
+    process(
+        &mut banks_client,
+        &payer,
+        withdraw(reward + 1),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.reward_token).await,
+        reward
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_destination_owned_by_someone_else() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy_with_reward(&fixture, reward),
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let other_reward = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        other_reward,
+        fixture.reward_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // recipient 를 지정하지 않은 withdraw 는 withdrawer 자신의 account 로만 보냄
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.withdrawer_reward_token = other_reward;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+    assert_eq!(token_amount(&mut banks_client, other_reward).await, 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_checks_stored_strategy_token_mint() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    // strategy 에 기록된 mint 와 strategy token account 의 mint 가 다름
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.strategy_token_mint = fixture.usdc_mint;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+    assert_eq!(
+        token_amount(&mut banks_client, fixture.strategy_token).await,
+        reward
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_out_of_range_indexes() {
+    // 범위 밖 index 는 배열 접근에서 panic 하지 않고 error 로 거부
+    for (vault_index, strategy_index) in [
+        (0, MAX_NUMBER_OF_STRATEGY as u8),
+        (MAX_NUMBER_OF_VAULTS as u8, 0),
+    ] {
+        let fixture = Fixture::new();
+        let reward = 1_000;
+        let mut vault = fixture.vault_state();
+        vault.index = vault_index;
+        let mut strategy = strategy_with_reward(&fixture, reward);
+        strategy.index = strategy_index;
+        let mut program_test = fixture.program_test(
+            &vault,
+            &vault_strategy_with_reward(&fixture, reward),
+            &strategy,
+        );
+        let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+        let (mut banks_client, payer) = start(program_test).await;
+
+        let result = process(
+            &mut banks_client,
+            &payer,
+            instruction_builder::withdraw(
+                &fixture.program_id,
+                withdraw_accounts(&fixture, &withdrawer),
+                0,
+                reward,
+                WithdrawType::RAYDIUM,
+                0,
+            ),
+            &[&withdrawer.keypair],
+        )
+        .await;
+        assert_eq!(
+            custom_error(result),
+            Some(GauntletError::StrategyIdSizeError as u32)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_reward_before_any_strategy_token() {
+    let fixture = Fixture::new();
+    // swap 이 아직 한번도 끝나지 않아 vault strategy 에 strategy token 이 없음
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, 0),
+        &strategy_with_reward(&fixture, 500),
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, 500);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            0,
+            500,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidWithdrawAmount as u32)
+    );
+    let user = get_user(
+        &mut banks_client,
+        fixture.user_address(&withdrawer.keypair.pubkey()),
+    )
+    .await;
+    assert_eq!(user.reward, 500);
+}
+
+#[tokio::test]
+async fn test_withdraw_tracks_cumulative_withdrawal_fees() {
+    let fixture = Fixture::new();
+    let amount = 1_000;
+    let mut vault = fixture.vault_state();
+    vault.fees.withdrawal_fee_numerator = 1;
+    vault.fees.withdrawal_fee_denominator = 10;
+    vault.deposit_amounts[0] = amount;
+    vault.total_deposit_amount = amount;
+    vault.cumulative_withdrawal_fees = 7;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &strategy_with_routed_deposit(&fixture, amount),
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        amount,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, amount, 0);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            amount,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_amount(&mut banks_client, fixture.fee_account).await,
+        100
+    );
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.deposit_token).await,
+        900
+    );
+    // 이전까지 쌓인 fee 에 더해짐
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.cumulative_withdrawal_fees, 107);
+    assert_eq!(vault.cumulative_performance_fees, 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_principal_destination_owned_by_someone_else() {
+    let fixture = Fixture::new();
+    let amount = 1_000;
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = amount;
+    vault.total_deposit_amount = amount;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        amount,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, amount, 0);
+    let other_lp = Pubkey::new_unique();
+    add_token_account(
+        &mut program_test,
+        other_lp,
+        fixture.lp_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 원금도 withdrawer 자신의 LP account 로만 보냄
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.withdrawer_deposit_token = other_lp;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            amount,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+    assert_eq!(token_amount(&mut banks_client, other_lp).await, 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_for_each_user_within_min_harvest_interval() {
+    let fixture = Fixture::new();
+    let amount = 1_000;
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = amount * 2;
+    vault.total_deposit_amount = amount * 2;
+    vault.max_pending_reward = u64::MAX;
+    vault.min_harvest_interval = 3_600;
+    let mut vault_strategy = fixture.vault_strategy_state();
+    vault_strategy.needs_usdc_pools[0] = true;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &vault_strategy,
+        &strategy_with_routed_deposit(&fixture, amount * 2),
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        amount * 2,
+    );
+    let swap_accounts = fixture.add_stub_amm(
+        &mut program_test,
+        fixture.vault_reward,
+        fixture.reward_mint,
+        fixture.vault_usdc,
+        fixture.usdc_mint,
+        1_000_000,
+    );
+    // 두 user 모두 harvest 부터 직접 돌리는 keeper
+    let withdrawers: Vec<Withdrawer> = (0..2)
+        .map(|_| add_withdrawer(&fixture, &mut program_test, amount, 0))
+        .collect();
+    let mut gauntlet = fixture.gauntlet_state();
+    for withdrawer in &withdrawers {
+        let keeper = withdrawer.keypair.pubkey();
+        gauntlet.keepers[gauntlet.keepers_len as usize] = keeper;
+        gauntlet.keepers_len += 1;
+        let mut user = User::init(keeper, fixture.vault, fixture.strategy);
+        user.amount = amount;
+        add_packed(
+            &mut program_test,
+            fixture.user_address(&keeper),
+            &user,
+            &fixture.program_id,
+        );
+    }
+    add_packed(
+        &mut program_test,
+        fixture.gauntlet,
+        &gauntlet,
+        &fixture.program_id,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // 두번째 user 의 harvest 는 interval 안이라 farm cpi 없이 status 만 진행
+    for withdrawer in &withdrawers {
+        let keeper = withdrawer.keypair.pubkey();
+        let user_state = fixture.user_address(&keeper);
+        process(
+            &mut banks_client,
+            &payer,
+            instruction_builder::harvest(
+                &fixture.program_id,
+                HarvestAccounts {
+                    harvestor: keeper,
+                    gauntlet_state: fixture.gauntlet,
+                    harvestor_user_state: user_state,
+                    vault_state: fixture.vault,
+                    vault_strategy_state: fixture.vault_strategy,
+                    harvest_accounts: fixture.staking_accounts(),
+                    keeper_reward_token: None,
+                },
+                DepositType::RAYDIUM,
+                0,
+            ),
+            &[&withdrawer.keypair],
+        )
+        .await
+        .unwrap();
+        process(
+            &mut banks_client,
+            &payer,
+            instruction_builder::swap_farm_reward_to_usdc(
+                &fixture.program_id,
+                SwapAccounts {
+                    swaper: keeper,
+                    gauntlet_state: fixture.gauntlet,
+                    swaper_user_state: user_state,
+                    vault_state: fixture.vault,
+                    vault_strategy_state: fixture.vault_strategy,
+                    strategy_state: fixture.strategy,
+                    oracle_accounts: vec![],
+                    swap_accounts: swap_accounts.clone(),
+                    restake_accounts: vec![],
+                },
+                SwapType::RAYDIUM,
+                0,
+                &[],
+            ),
+            &[&withdrawer.keypair],
+        )
+        .await
+        .unwrap();
+        process(
+            &mut banks_client,
+            &payer,
+            instruction_builder::withdraw(
+                &fixture.program_id,
+                withdraw_accounts(&fixture, withdrawer),
+                amount,
+                0,
+                WithdrawType::RAYDIUM,
+                0,
+            ),
+            &[&withdrawer.keypair],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            token_amount(&mut banks_client, withdrawer.deposit_token).await,
+            amount
+        );
+    }
+
+    // farm harvest 는 첫 user 때 한번만 (빈 harvest 로 기록)
+    let vault: Vault = get_packed(&mut banks_client, fixture.vault).await;
+    assert_eq!(vault.consecutive_harvest_failures, 1);
+    assert_eq!(vault.total_deposit_amount, 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_short_accounts_and_desynced_principal() {
+    let fixture = Fixture::new();
+    let amount = 1_000;
+    // user 원금은 있지만 vault 의 strategy 몫은 0 으로 어긋난 상태
+    let mut vault = fixture.vault_state();
+    vault.total_deposit_amount = amount;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &fixture.strategy_state(),
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        amount,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, amount, 0);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    // staking account 가 모자라면 panic 하지 않고 거부
+    let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+    accounts.withdraw_accounts.truncate(10);
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            amount,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        instruction_error(result),
+        Some(InstructionError::NotEnoughAccountKeys)
+    );
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            amount,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::MathOverflow as u32)
+    );
+    assert_eq!(
+        token_amount(&mut banks_client, withdrawer.deposit_token).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_rejects_principal_missing_from_routed_deposit() {
+    let fixture = Fixture::new();
+    let amount = 1_000;
+    // vault 장부는 맞지만 strategy 의 routed_deposit_amount 에는 절반만 들어간 상태
+    let mut vault = fixture.vault_state();
+    vault.deposit_amounts[0] = amount;
+    vault.total_deposit_amount = amount;
+    let mut program_test = fixture.program_test(
+        &vault,
+        &fixture.vault_strategy_state(),
+        &strategy_with_routed_deposit(&fixture, amount / 2),
+    );
+    add_token_account(
+        &mut program_test,
+        fixture.pool_lp,
+        fixture.lp_mint,
+        fixture.pool_signer,
+        amount,
+    );
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, amount, 0);
+    let (mut banks_client, payer) = start(program_test).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            withdraw_accounts(&fixture, &withdrawer),
+            amount,
+            0,
+            WithdrawType::RAYDIUM,
+            0,
+        ),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::AccountingDesync as u32)
+    );
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.routed_deposit_amount, amount / 2);
+}
+
+#[tokio::test]
+async fn test_withdraw_token_2022_strategy_token_after_transfer_fee() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    // 1% transfer fee 가 있는 Token-2022 strategy token
+    let fee_mint = Pubkey::new_unique();
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.strategy_token_mint = fee_mint;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    add_token_2022_fee_mint(&mut program_test, fee_mint, 100);
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    add_token_2022_account(
+        &mut program_test,
+        fixture.strategy_token,
+        fee_mint,
+        fixture.authority,
+        reward,
+    );
+    add_token_2022_account(
+        &mut program_test,
+        withdrawer.reward_token,
+        fee_mint,
+        withdrawer.keypair.pubkey(),
+        0,
+    );
+    let (mut banks_client, payer) = start(program_test).await;
+    let accounts = || {
+        let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+        accounts.strategy_token_mint = fee_mint;
+        accounts.strategy_token_program = TOKEN_2022_PROGRAM_ID;
+        accounts
+    };
+    let withdraw = |accounts| {
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        )
+    };
+
+    // strategy token mint 와 token program 은 strategy token account 와 맞아야 함
+    let mut wrong_mint = accounts();
+    wrong_mint.strategy_token_mint = fixture.reward_mint;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        withdraw(wrong_mint),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::WrongTokenAccount as u32)
+    );
+    let mut wrong_program = accounts();
+    wrong_program.strategy_token_program = spl_token::id();
+    let result = process(
+        &mut banks_client,
+        &payer,
+        withdraw(wrong_program),
+        &[&withdrawer.keypair],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        Some(GauntletError::InvalidProgramId as u32)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        withdraw(accounts()),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+
+    // vault 회계에서는 claim 한 만큼 빠지고, transfer fee 는 받는 쪽에서 빠짐
+    let reward_token = get_token_2022_account(&mut banks_client, withdrawer.reward_token).await;
+    assert_eq!(reward_token.amount, 990);
+    let strategy_token = get_token_2022_account(&mut banks_client, fixture.strategy_token).await;
+    assert_eq!(strategy_token.amount, 0);
+    let vault_strategy: VaultStrategy = get_packed(&mut banks_client, fixture.vault_strategy).await;
+    assert_eq!(vault_strategy.strategy_token_amounts[0], 0);
+    let strategy: Strategy = get_packed(&mut banks_client, fixture.strategy).await;
+    assert_eq!(strategy.deposit_amounts[0], 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_to_recipient_checks_recipient_token_programs() {
+    let fixture = Fixture::new();
+    let reward = 1_000;
+    let fee_mint = Pubkey::new_unique();
+    let mut strategy = strategy_with_reward(&fixture, reward);
+    strategy.strategy_token_mint = fee_mint;
+    let mut program_test = fixture.program_test(
+        &fixture.vault_state(),
+        &vault_strategy_with_reward(&fixture, reward),
+        &strategy,
+    );
+    add_token_2022_fee_mint(&mut program_test, fee_mint, 100);
+    let withdrawer = add_withdrawer(&fixture, &mut program_test, 0, reward);
+    add_token_2022_account(
+        &mut program_test,
+        fixture.strategy_token,
+        fee_mint,
+        fixture.authority,
+        reward,
+    );
+    let treasury = Pubkey::new_unique();
+    let (recipient_lp, token_2022_lp, legacy_reward, recipient_reward) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    add_token_account(
+        &mut program_test,
+        recipient_lp,
+        fixture.lp_mint,
+        treasury,
+        0,
+    );
+    add_token_2022_account(
+        &mut program_test,
+        token_2022_lp,
+        fixture.lp_mint,
+        treasury,
+        0,
+    );
+    add_token_account(&mut program_test, legacy_reward, fee_mint, treasury, 0);
+    add_token_2022_account(&mut program_test, recipient_reward, fee_mint, treasury, 0);
+    let (mut banks_client, payer) = start(program_test).await;
+    let withdraw_to = |recipient_tokens| {
+        let mut accounts = withdraw_accounts(&fixture, &withdrawer);
+        accounts.strategy_token_mint = fee_mint;
+        accounts.strategy_token_program = TOKEN_2022_PROGRAM_ID;
+        accounts.recipient_tokens = Some(recipient_tokens);
+        instruction_builder::withdraw(
+            &fixture.program_id,
+            accounts,
+            0,
+            reward,
+            WithdrawType::RAYDIUM,
+            0,
+        )
+    };
+
+    // 원금 recipient 는 vault 의 token program, 보상 recipient 는 strategy token 의 token program 소유여야 함
+    for recipient_tokens in [
+        (token_2022_lp, recipient_reward),
+        (recipient_lp, legacy_reward),
+    ] {
+        let result = process(
+            &mut banks_client,
+            &payer,
+            withdraw_to(recipient_tokens),
+            &[&withdrawer.keypair],
+        )
+        .await;
+        assert_eq!(
+            custom_error(result),
+            Some(GauntletError::WrongTokenAccount as u32)
+        );
+    }
+
+    process(
+        &mut banks_client,
+        &payer,
+        withdraw_to((recipient_lp, recipient_reward)),
+        &[&withdrawer.keypair],
+    )
+    .await
+    .unwrap();
+    let recipient_reward = get_token_2022_account(&mut banks_client, recipient_reward).await;
+    assert_eq!(recipient_reward.amount, 990);
+}